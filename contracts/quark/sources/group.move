@@ -43,6 +43,7 @@ module quark::group {
     const EUSER_NOT_VOTED: u64 = 24;
     const ERESOURCE_ACCOUNT_NOT_EXISTS: u64 = 25;
     const EGROUP_ALREADY_MIGRATED: u64 = 26;
+    const EEND_CAN_ONLY_BE_EXTENDED: u64 = 27;
 
     struct Group has store, drop {
         group_id: String,
@@ -828,6 +829,82 @@ module quark::group {
         });
     }
 
+    public entry fun vote_group_dao_for_user_v1<CoinType>(admin: &signer, reviewer: &signer, user: address, group_id: String, dao_id: String, choice_id: u64) acquires GroupDaosV1, Groups {
+        let admin_address = signer::address_of(admin);
+        assert!(admin::is_admin(admin_address), EONLY_ADMIN_CAN_CALL);
+
+        let reviewer_address = signer::address_of(reviewer);
+        assert!(admin::is_reviewer(reviewer_address), EONLY_REVIEWER_CAN_CALL);
+
+        let resource_account = user::get_resource_signer(user);
+
+        vote_group_dao_v1<CoinType>(&resource_account, group_id, dao_id, choice_id);
+    }
+
+    public entry fun vote_group_dao_for_user_v2(admin: &signer, reviewer: &signer, user: address, group_id: String, dao_id: String, choice_id: u64, currency: address) acquires GroupDaosV2, Groups {
+        let admin_address = signer::address_of(admin);
+        assert!(admin::is_admin(admin_address), EONLY_ADMIN_CAN_CALL);
+
+        let reviewer_address = signer::address_of(reviewer);
+        assert!(admin::is_reviewer(reviewer_address), EONLY_REVIEWER_CAN_CALL);
+
+        let resource_account = user::get_resource_signer(user);
+
+        vote_group_dao_v2(&resource_account, group_id, dao_id, choice_id, currency);
+    }
+
+    public entry fun update_group_dao_end_v1(admin: &signer, reviewer: &signer, group_id: String, dao_id: String, to: u64) acquires GroupDaosV1, Groups {
+        let admin_address = signer::address_of(admin);
+        let reviewer_address = signer::address_of(reviewer);
+
+        assert!(admin::is_admin(admin_address), EONLY_ADMIN_CAN_CALL);
+        assert!(admin::is_reviewer(reviewer_address), EONLY_REVIEWER_CAN_CALL);
+
+        let group_account = get_group_account(group_id);
+
+        assert!(exists<GroupDaosV1>(group_account), EDAO_NOT_EXISTS);
+        let group_daos = borrow_global_mut<GroupDaosV1>(group_account);
+
+        let (exists_dao, dao_index) = vector::find<GroupDaoV1>(&group_daos.daos, |dao| dao.dao_id == dao_id);
+        assert!(exists_dao, EDAO_NOT_EXISTS);
+
+        let group_dao = vector::borrow_mut(&mut group_daos.daos, dao_index);
+
+        let now = timestamp::now_seconds();
+
+        assert!(group_dao.from <= to, EFROM_TO_NOT_VALID);
+        assert!(group_dao.to <= to, EEND_CAN_ONLY_BE_EXTENDED);
+        assert!(now <= group_dao.to && now <= to, ENOT_IN_TIME);
+
+        group_dao.to = to;
+    }
+
+    public entry fun update_group_dao_end_v2(admin: &signer, reviewer: &signer, group_id: String, dao_id: String, to: u64) acquires GroupDaosV2, Groups {
+        let admin_address = signer::address_of(admin);
+        let reviewer_address = signer::address_of(reviewer);
+
+        assert!(admin::is_admin(admin_address), EONLY_ADMIN_CAN_CALL);
+        assert!(admin::is_reviewer(reviewer_address), EONLY_REVIEWER_CAN_CALL);
+
+        let group_account = get_group_account(group_id);
+
+        assert!(exists<GroupDaosV2>(group_account), EDAO_NOT_EXISTS);
+        let group_daos = borrow_global_mut<GroupDaosV2>(group_account);
+
+        let (exists_dao, dao_index) = vector::find<GroupDaoV2>(&group_daos.daos, |dao| dao.dao_id == dao_id);
+        assert!(exists_dao, EDAO_NOT_EXISTS);
+
+        let group_dao = vector::borrow_mut(&mut group_daos.daos, dao_index);
+
+        let now = timestamp::now_seconds();
+
+        assert!(group_dao.from <= to, EFROM_TO_NOT_VALID);
+        assert!(group_dao.to <= to, EEND_CAN_ONLY_BE_EXTENDED);
+        assert!(now <= group_dao.to && now <= to, ENOT_IN_TIME);
+
+        group_dao.to = to;
+    }
+
     public entry fun migrate_group_id(admin: &signer, reviewer: &signer, group_id: String, new_group_id: String) acquires Groups {
         let admin_address = signer::address_of(admin);
         let reviewer_address = signer::address_of(reviewer);
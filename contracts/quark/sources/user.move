@@ -15,6 +15,8 @@ module quark::user {
     use sshift_gpt::fees;
     use quark::admin;
 
+    friend quark::group;
+
     const EONLY_ADMIN_CAN_CALL: u64 = 1;
     const EONLY_REVIEWER_CAN_CALL: u64 = 2;
     const ENOT_ENOUGH_FUNDS: u64 = 3;
@@ -295,6 +297,12 @@ module quark::user {
         });
     }
 
+    public(friend) fun get_resource_signer(user: address): signer acquires Account {
+        assert!(exists<Account>(user), ERESOURCE_ACCOUNT_NOT_EXISTS);
+        let user_account = borrow_global<Account>(user);
+        account::create_signer_with_capability(&user_account.signer_cap)
+    }
+
     #[view]
     public fun exists_resource_account(user: address): bool {
         exists<Account>(user)
@@ -852,6 +852,64 @@ module quark::group_test {
         group::create_group_dao_v1<AptosCoin>(quark, quark, group_id, dao_id, choices, from, to);
     }
 
+    #[test(aptos_framework = @0x1, quark = @quark)]
+    fun test_update_group_dao_end_v1_extends_voting(aptos_framework: &signer, quark: &signer) {
+        timestamp::set_time_has_started_for_testing(aptos_framework);
+        init_module(quark);
+        let group_id = string::utf8(TEST_GROUP_ID);
+        let dao_id = string::utf8(TEST_DAO_ID);
+        
+        group::create_group(quark, quark, group_id);
+        
+        let choices = get_test_choices();
+        let from = timestamp::now_seconds();
+        let to = from + 86400;
+        
+        group::create_group_dao_v1<AptosCoin>(quark, quark, group_id, dao_id, choices, from, to);
+
+        group::update_group_dao_end_v1(quark, quark, group_id, dao_id, to + 86400);
+    }
+
+    #[test(aptos_framework = @0x1, quark = @quark)]
+    #[expected_failure(abort_code = 19, location = quark::group)] // ENOT_IN_TIME
+    fun test_update_group_dao_end_v1_after_end(aptos_framework: &signer, quark: &signer) {
+        timestamp::set_time_has_started_for_testing(aptos_framework);
+        init_module(quark);
+        let group_id = string::utf8(TEST_GROUP_ID);
+        let dao_id = string::utf8(TEST_DAO_ID);
+        
+        group::create_group(quark, quark, group_id);
+        
+        let choices = get_test_choices();
+        let from = timestamp::now_seconds();
+        let to = from + 86400;
+        
+        group::create_group_dao_v1<AptosCoin>(quark, quark, group_id, dao_id, choices, from, to);
+
+        timestamp::update_global_time_for_test_secs(to + 1);
+
+        group::update_group_dao_end_v1(quark, quark, group_id, dao_id, to + 86400);
+    }
+
+    #[test(aptos_framework = @0x1, quark = @quark)]
+    #[expected_failure(abort_code = 27, location = quark::group)] // EEND_CAN_ONLY_BE_EXTENDED
+    fun test_update_group_dao_end_v1_cannot_shorten(aptos_framework: &signer, quark: &signer) {
+        timestamp::set_time_has_started_for_testing(aptos_framework);
+        init_module(quark);
+        let group_id = string::utf8(TEST_GROUP_ID);
+        let dao_id = string::utf8(TEST_DAO_ID);
+        
+        group::create_group(quark, quark, group_id);
+        
+        let choices = get_test_choices();
+        let from = timestamp::now_seconds();
+        let to = from + 86400;
+        
+        group::create_group_dao_v1<AptosCoin>(quark, quark, group_id, dao_id, choices, from, to);
+
+        group::update_group_dao_end_v1(quark, quark, group_id, dao_id, to - 3600);
+    }
+
     // ==================== MOCK CLAIM REWARD TESTS ====================
 
     #[test(aptos_framework = @0x1, quark = @quark, user = @0x5)]
@@ -1088,6 +1146,70 @@ module quark::group_test {
         coin::destroy_mint_cap(mint_cap);
     }
 
+    #[test(aptos_framework = @0x1, quark = @quark, voter = @0x5)]
+    fun test_vote_group_dao_for_user_v1_success(aptos_framework: &signer, quark: &signer, voter: &signer) {
+        let (burn_cap, mint_cap) = aptos_coin::initialize_for_test(aptos_framework);
+        timestamp::set_time_has_started_for_testing(aptos_framework);
+        init_module(quark);
+        let group_id = string::utf8(TEST_GROUP_ID);
+        let dao_id = string::utf8(TEST_DAO_ID);
+        
+        group::create_group(quark, quark, group_id);
+        
+        let choices = get_test_choices();
+        let from = timestamp::now_seconds();
+        let to = from + 86400;
+        
+        group::create_group_dao_v1<AptosCoin>(quark, quark, group_id, dao_id, choices, from, to);
+        
+        let voter_addr = signer::address_of(voter);
+        
+        account::create_account_for_test(voter_addr);
+        coin::register<AptosCoin>(voter);
+        aptos_coin::mint(aptos_framework, voter_addr, TEST_AMOUNT);
+        
+        user::create_account(voter, string::utf8(b"1234567890"));
+        let resource_addr = user::get_resource_account(voter_addr);
+        aptos_account::transfer_coins<AptosCoin>(voter, resource_addr, TEST_AMOUNT);
+        
+        // The admin votes with the user's resource account
+        group::vote_group_dao_for_user_v1<AptosCoin>(quark, quark, voter_addr, group_id, dao_id, 1);
+        
+        assert!(group::exist_group_user_choice_v1(group_id, dao_id, resource_addr), 0);
+        assert!(!group::exist_group_user_choice_v1(group_id, dao_id, voter_addr), 1);
+        
+        coin::destroy_burn_cap(burn_cap);
+        coin::destroy_mint_cap(mint_cap);
+    }
+
+    #[test(aptos_framework = @0x1, quark = @quark, voter = @0x5)]
+    #[expected_failure(abort_code = 1, location = quark::group)] // EONLY_ADMIN_CAN_CALL
+    fun test_vote_group_dao_for_user_v1_not_admin(aptos_framework: &signer, quark: &signer, voter: &signer) {
+        let (burn_cap, mint_cap) = aptos_coin::initialize_for_test(aptos_framework);
+        timestamp::set_time_has_started_for_testing(aptos_framework);
+        init_module(quark);
+        let group_id = string::utf8(TEST_GROUP_ID);
+        let dao_id = string::utf8(TEST_DAO_ID);
+        
+        group::create_group(quark, quark, group_id);
+        
+        let choices = get_test_choices();
+        let from = timestamp::now_seconds();
+        let to = from + 86400;
+        
+        group::create_group_dao_v1<AptosCoin>(quark, quark, group_id, dao_id, choices, from, to);
+        
+        let voter_addr = signer::address_of(voter);
+        account::create_account_for_test(voter_addr);
+        user::create_account(voter, string::utf8(b"1234567890"));
+        
+        // Voters can't use the admin path themselves
+        group::vote_group_dao_for_user_v1<AptosCoin>(voter, quark, voter_addr, group_id, dao_id, 0);
+        
+        coin::destroy_burn_cap(burn_cap);
+        coin::destroy_mint_cap(mint_cap);
+    }
+
     #[test(aptos_framework = @0x1, quark = @quark, voter = @0x5)]
     fun test_vote_group_dao_v2_success(aptos_framework: &signer, quark: &signer, voter: &signer) acquires FAController {
         timestamp::set_time_has_started_for_testing(aptos_framework);
@@ -1069,4 +1069,112 @@ impl AI {
 
         Ok((ai_resp, new_response_id))
     }
+
+    /// One-shot generation for AI filters: no conversation thread and no custom
+    /// tools, only the group's document library when one exists.
+    pub async fn generate_filter_response(
+        &self,
+        prompt: &str,
+        trigger_context: &str,
+        model: Model,
+        max_tokens: u32,
+        bot_deps: BotDependencies,
+        group_id: String,
+    ) -> Result<AIResponse, anyhow::Error> {
+        let group_chat_id = teloxide::types::ChatId(group_id.parse().unwrap_or(0));
+        let group_credentials = bot_deps
+            .group
+            .get_credentials(group_chat_id)
+            .ok_or_else(|| anyhow::anyhow!("Group credentials not found"))?;
+
+        let default_payment_prefs = bot_deps.default_payment_prefs.clone();
+        let coin = bot_deps
+            .payment
+            .get_payment_token(group_id.clone(), &bot_deps)
+            .await
+            .unwrap_or(PaymentPrefs::from((
+                default_payment_prefs.label,
+                default_payment_prefs.currency,
+                default_payment_prefs.version,
+            )));
+
+        let token = bot_deps.panora.get_token_by_symbol(&coin.label).await?;
+        let token_price = token
+            .usd_price
+            .ok_or_else(|| anyhow::anyhow!("Token price not found"))?
+            .parse::<f64>()?;
+        let min_deposit = bot_deps.panora.min_deposit / token_price;
+        let min_deposit = (min_deposit * 10_f64.powi(token.decimals as i32)) as u64;
+        let group_balance = bot_deps
+            .panora
+            .aptos
+            .get_account_balance(&group_credentials.resource_account_address, &coin.currency)
+            .await?;
+        if group_balance < min_deposit as i64 {
+            return Err(anyhow::anyhow!(
+                "Group balance is below the minimum deposit for AI filters"
+            ));
+        }
+
+        let vector_store_id = bot_deps
+            .group_docs
+            .get_group_vector_store_id(group_id.clone())
+            .filter(|vs_id| !vs_id.is_empty());
+
+        let mut tools = vec![];
+        if let Some(vs_id) = vector_store_id.clone() {
+            tools.push(Tool::file_search(vec![vs_id]));
+        }
+
+        let instructions = format!(
+            "{}\n\nYou are replying automatically to a group message that matched a filter. Follow the admin's instructions, answer the message directly and keep the reply short.",
+            self.system_prompt
+        );
+        let input = format!(
+            "Admin instructions:\n{}\n\nMessage that triggered the filter:\n{}",
+            prompt, trigger_context
+        );
+
+        let mut request_builder = Request::builder()
+            .model(model.clone())
+            .instructions(instructions)
+            .max_output_tokens(max_tokens)
+            .user(&format!("filter-{}", group_id))
+            .store(false)
+            .input(input);
+
+        if !tools.is_empty() {
+            request_builder = request_builder
+                .tools(tools)
+                .include(vec![Include::FileSearchResults]);
+        }
+
+        if let Model::GPT5 | Model::GPT5Mini = model {
+            request_builder = request_builder.reasoning_effort(ReasoningEffort::Minimal);
+        }
+
+        let response = self
+            .openai_client
+            .responses
+            .create(request_builder.build())
+            .await?;
+
+        let total_tokens = response.usage.as_ref().map(|u| u.total_tokens).unwrap_or(0);
+        let reply = response.output_text().replace("\\n", "\n");
+
+        let (web_search_count, file_search_count, image_generation_count, code_interpreter_count) =
+            AIResponse::calculate_tool_usage(&response);
+
+        Ok(AIResponse::from((
+            reply,
+            model,
+            None,
+            None,
+            total_tokens,
+            web_search_count,
+            file_search_count,
+            image_generation_count,
+            code_interpreter_count,
+        )))
+    }
 }
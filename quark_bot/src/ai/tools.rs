@@ -321,6 +321,60 @@ pub fn create_proposal() -> Tool {
                 "symbol": {
                     "type": "string",
                     "description": "The symbol of the currency of the proposal. Optional - if not provided, will use the saved DAO token preference for this group."
+                },
+                "quorum": {
+                    "type": "number",
+                    "description": "Minimum total tokens that must vote for the proposal to count. Optional - only set when the user asks for a quorum; otherwise the group's default applies."
+                },
+                "approval_threshold": {
+                    "type": "integer",
+                    "description": "Percentage (1-100) of the voted tokens the leading option needs to pass. Optional - only set when the user asks for it; otherwise the group's default applies."
+                },
+                "ballot_type": {
+                    "type": "string",
+                    "enum": ["single", "multi_select", "ranked_choice"],
+                    "description": "How members vote. 'single' (default): one option each. 'multi_select': pick up to max_choices options, e.g. 'pick up to 3'. 'ranked_choice': rank the options, counted by instant runoff - use for elections when the user asks for ranked voting."
+                },
+                "max_choices": {
+                    "type": "integer",
+                    "description": "For multi_select only: how many options each member may pick."
+                },
+                "actions": {
+                    "type": "array",
+                    "description": "Treasury payouts to prepare if an option wins, e.g. 'pay 500 APT to @dev if Yes passes'. Optional - only set when the user describes a payment. Admins still confirm the payment after the vote.",
+                    "items": {
+                        "type": "object",
+                        "properties": {
+                            "option": {
+                                "type": "string",
+                                "description": "The option (exactly as listed in options) that triggers the payout"
+                            },
+                            "symbol": {
+                                "type": "string",
+                                "description": "The symbol of the token to pay, e.g. 'APT'"
+                            },
+                            "recipients": {
+                                "type": "array",
+                                "items": {
+                                    "type": "object",
+                                    "properties": {
+                                        "username": {
+                                            "type": "string",
+                                            "description": "telegram username without @"
+                                        },
+                                        "amount": {
+                                            "type": "number",
+                                            "description": "Amount of the token this user receives"
+                                        }
+                                    },
+                                    "required": ["username", "amount"],
+                                    "additionalProperties": false
+                                }
+                            }
+                        },
+                        "required": ["option", "symbol", "recipients"],
+                        "additionalProperties": false
+                    }
                 }
             },
             "required": ["name", "description", "start_date", "end_date", "options"],
@@ -3,16 +3,41 @@ use aptos_rust_sdk::client::{
     builder::AptosClientBuilder, config::AptosNetwork, rest_api::AptosFullnodeClient,
 };
 use aptos_rust_sdk_types::api_types::{chain_id::ChainId, view::ViewRequest};
-use quark_core::helpers::dto::TokenAddress;
+use quark_core::helpers::dto::{CoinVersion, TokenAddress};
+
+use crate::dao::dto::{ChainVote, ProposalTally};
+
+// u64 values come back from view functions as strings
+fn parse_u64(value: &serde_json::Value) -> Option<u64> {
+    value.as_str().and_then(|v| v.parse::<u64>().ok())
+}
 
 #[derive(Clone)]
 pub struct Aptos {
     pub node: AptosFullnodeClient,
     pub contract_address: String,
+    indexer_url: String,
+    api_key: String,
+    http: reqwest::Client,
 }
 
 impl Aptos {
-    pub fn new(network: String, contract_address: String, api_key: String) -> Self {
+    pub fn new(
+        network: String,
+        contract_address: String,
+        api_key: String,
+        indexer_url: String,
+    ) -> Self {
+        let indexer_url = if indexer_url.is_empty() {
+            let indexer_network = match network.as_str() {
+                "mainnet" | "devnet" => network.as_str(),
+                _ => "testnet",
+            };
+            format!("https://api.{}.aptoslabs.com/v1/graphql", indexer_network)
+        } else {
+            indexer_url
+        };
+
         let (builder, _chain_id) = match network.as_str() {
             "mainnet" => (
                 AptosClientBuilder::new(AptosNetwork::mainnet()),
@@ -43,6 +68,9 @@ impl Aptos {
         Self {
             node,
             contract_address,
+            indexer_url,
+            api_key,
+            http: reqwest::Client::new(),
         }
     }
 
@@ -98,6 +126,122 @@ impl Aptos {
         Ok(fees_currency_payment_list[0].clone())
     }
 
+    /// Whether `voter` already has a choice recorded on the group proposal.
+    pub async fn has_voted(
+        &self,
+        group_id: &str,
+        proposal_id: &str,
+        version: &CoinVersion,
+        voter: &str,
+    ) -> Result<bool> {
+        let function = match version {
+            CoinVersion::V1 => "exist_group_user_choice_v1",
+            CoinVersion::V2 => "exist_group_user_choice_v2",
+        };
+
+        let voted = self
+            .node
+            .view_function(ViewRequest {
+                function: format!("{}::group::{}", self.contract_address, function),
+                type_arguments: vec![],
+                arguments: vec![
+                    serde_json::Value::String(group_id.to_string()),
+                    serde_json::Value::String(proposal_id.to_string()),
+                    serde_json::Value::String(voter.to_string()),
+                ],
+            })
+            .await?
+            .into_inner();
+
+        let voted = serde_json::from_value::<Vec<bool>>(voted)?;
+
+        Ok(voted.first().copied().unwrap_or(false))
+    }
+
+    /// Group proposal as returned by the contract's `get_group_dao_*` view.
+    async fn get_group_dao(
+        &self,
+        group_id: &str,
+        proposal_id: &str,
+        version: &CoinVersion,
+    ) -> Result<serde_json::Value> {
+        let function = match version {
+            CoinVersion::V1 => "get_group_dao_v1",
+            CoinVersion::V2 => "get_group_dao_v2",
+        };
+
+        let response = self
+            .node
+            .view_function(ViewRequest {
+                function: format!("{}::group::{}", self.contract_address, function),
+                type_arguments: vec![],
+                arguments: vec![
+                    serde_json::Value::String(group_id.to_string()),
+                    serde_json::Value::String(proposal_id.to_string()),
+                ],
+            })
+            .await?
+            .into_inner();
+
+        response
+            .as_array()
+            .and_then(|values| values.first())
+            .cloned()
+            .ok_or_else(|| anyhow::anyhow!("Proposal {} not found on-chain", proposal_id))
+    }
+
+    /// Votes and token-weighted totals per choice of a group proposal.
+    pub async fn get_proposal_tally(
+        &self,
+        group_id: &str,
+        proposal_id: &str,
+        version: &CoinVersion,
+    ) -> Result<ProposalTally> {
+        let dao = self.get_group_dao(group_id, proposal_id, version).await?;
+
+        let weights: Vec<u64> = dao["choices_weights"]
+            .as_array()
+            .map(|weights| weights.iter().map(|w| parse_u64(w).unwrap_or(0)).collect())
+            .unwrap_or_default();
+
+        let mut votes = vec![0; weights.len()];
+        for choice in dao["user_choices"].as_array().into_iter().flatten() {
+            let Some(choice_id) = parse_u64(&choice["choice_id"]) else {
+                continue;
+            };
+            if let Some(count) = votes.get_mut(choice_id as usize) {
+                *count += 1;
+            }
+        }
+
+        Ok(ProposalTally { votes, weights })
+    }
+
+    /// Every wallet's on-chain vote on a group proposal.
+    pub async fn get_proposal_votes(
+        &self,
+        group_id: &str,
+        proposal_id: &str,
+        version: &CoinVersion,
+    ) -> Result<Vec<ChainVote>> {
+        let dao = self.get_group_dao(group_id, proposal_id, version).await?;
+
+        let votes = dao["user_choices"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .filter_map(|choice| {
+                Some(ChainVote {
+                    voter: normalize_address(choice["user"].as_str()?),
+                    choice_id: parse_u64(&choice["choice_id"])?,
+                    weight: parse_u64(&choice["vote_weight"]).unwrap_or(0),
+                })
+            })
+            .collect();
+
+        Ok(votes)
+    }
+
     async fn get_token_address_internal(&self) -> Result<String> {
         let coin_address_value = self
             .node
@@ -140,4 +284,102 @@ impl Aptos {
 
         Ok(balance.unwrap())
     }
+
+    /// Count the digital assets from `collection_id` that `owner` holds, stopping at `limit`.
+    pub async fn count_collection_tokens(
+        &self,
+        owner: &str,
+        collection_id: &str,
+        limit: u64,
+    ) -> Result<u64> {
+        let query = r#"query CollectionTokens($owner: String!, $collection: String!, $limit: Int!) {
+  current_token_ownerships_v2(
+    where: {owner_address: {_eq: $owner}, amount: {_gt: "0"}, current_token_data: {collection_id: {_eq: $collection}}}
+    limit: $limit
+  ) {
+    token_data_id
+  }
+}"#;
+
+        let data = self
+            .indexer_query(
+                query,
+                serde_json::json!({
+                    "owner": normalize_address(owner),
+                    "collection": normalize_address(collection_id),
+                    "limit": limit,
+                }),
+            )
+            .await?;
+
+        let tokens = data["current_token_ownerships_v2"]
+            .as_array()
+            .ok_or_else(|| anyhow::anyhow!("Unexpected indexer response"))?;
+
+        Ok(tokens.len() as u64)
+    }
+
+    /// Name of a digital asset collection, or `None` when it doesn't exist.
+    pub async fn get_collection_name(&self, collection_id: &str) -> Result<Option<String>> {
+        let query = r#"query CollectionName($collection: String!) {
+  current_collections_v2(where: {collection_id: {_eq: $collection}}, limit: 1) {
+    collection_name
+  }
+}"#;
+
+        let data = self
+            .indexer_query(
+                query,
+                serde_json::json!({ "collection": normalize_address(collection_id) }),
+            )
+            .await?;
+
+        Ok(data["current_collections_v2"][0]["collection_name"]
+            .as_str()
+            .map(|name| name.to_string()))
+    }
+
+    async fn indexer_query(
+        &self,
+        query: &str,
+        variables: serde_json::Value,
+    ) -> Result<serde_json::Value> {
+        let mut request = self
+            .http
+            .post(&self.indexer_url)
+            .json(&serde_json::json!({ "query": query, "variables": variables }));
+        if !self.api_key.is_empty() {
+            request = request.bearer_auth(&self.api_key);
+        }
+
+        let response: serde_json::Value = request.send().await?.error_for_status()?.json().await?;
+
+        if let Some(errors) = response.get("errors") {
+            return Err(anyhow::anyhow!("Indexer query failed: {}", errors));
+        }
+
+        Ok(response["data"].clone())
+    }
+}
+
+/// The indexer stores addresses lowercase and zero-padded to 64 hex digits.
+pub fn normalize_address(address: &str) -> String {
+    let hex = address.trim().trim_start_matches("0x").to_lowercase();
+    format!("0x{:0>64}", hex)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn normalizes_short_and_mixed_case_addresses() {
+        assert_eq!(normalize_address("0x1"), format!("0x{}1", "0".repeat(63)));
+        assert_eq!(
+            normalize_address(" 0xAB "),
+            format!("0x{}ab", "0".repeat(62))
+        );
+        let full = format!("0x{}", "f".repeat(64));
+        assert_eq!(normalize_address(&full), full);
+    }
 }
@@ -18,7 +18,12 @@ use crate::bot::handler::{
     handle_aptos_connect, handle_balance, handle_group_balance, handle_group_wallet_address,
     handle_wallet_address,
 };
+use crate::dao::archive::handle_proposals_command;
+use crate::dao::lifecycle::handle_manage_proposals;
+use crate::dao::wizard::handle_new_proposal_command;
 use crate::dependencies::BotDependencies;
+use crate::nft_gate::dto::MemberRole;
+use crate::nft_gate::handler::handle_roles;
 use crate::scheduled_payments::handler::{
     handle_listscheduledpayments_command, handle_schedulepayment_command,
 };
@@ -128,11 +133,14 @@ pub async fn answers(
 
             let is_admin = users_admin.iter().any(|member| member.user.id == user.id);
 
-            let sponsor = bot_deps
-                .sponsor
-                .can_make_request(msg.chat.id.to_string(), user.id.to_string());
-
-            let is_sponsor = sponsor.is_ok() && sponsor.unwrap();
+            // Sponsored AI holders are paid for by the group without using up the quota
+            let is_sponsor = bot_deps
+                .nft_gate
+                .has_role(msg.chat.id, user.id, MemberRole::SponsoredAi)
+                || bot_deps
+                    .sponsor
+                    .can_make_request(msg.chat.id.to_string(), user.id.to_string())
+                    .unwrap_or(false);
 
             if !is_admin && !is_sponsor {
                 send_message(msg, bot, "Only group admins can use this command or requests allowed to members reached the limit.".to_string())
@@ -157,6 +165,18 @@ pub async fn answers(
             }
         }
 
+        Command::Roles => {
+            handle_roles(bot, msg, bot_deps.clone()).await?;
+        }
+        Command::ManageProposals => {
+            handle_manage_proposals(bot, msg, bot_deps.clone()).await?;
+        }
+        Command::Proposals => {
+            handle_proposals_command(bot, msg, bot_deps.clone()).await?;
+        }
+        Command::NewProposal => {
+            handle_new_proposal_command(bot, msg, bot_deps.clone()).await?;
+        }
         Command::Report => {
             handle_mod(bot, msg, bot_deps.clone()).await?;
         }
@@ -6,7 +6,10 @@ use crate::{
     assets::handler::{handle_file_upload, handle_group_file_upload},
     bot::hooks::{fund_account_hook, pay_users_hook, withdraw_funds_hook},
     credentials::dto::CredentialsPayload,
-    dao::handler::handle_message_dao,
+    dao::{
+        handler::handle_message_dao, lifecycle::handle_description_edit_message,
+        wizard::handle_message_proposal_wizard,
+    },
     dependencies::BotDependencies,
     filters::handler::{handle_message_filters, process_message_for_filters},
     group::dto::GroupCredentials,
@@ -1086,6 +1089,30 @@ pub async fn handle_message(bot: Bot, msg: Message, bot_deps: BotDependencies) -
         // Try to find the pending token input with the formatted group ID
         let formatted_group_id = format!("{}-{}", group_id, bot_deps.group.account_seed);
 
+        if handle_description_edit_message(
+            bot.clone(),
+            msg.clone(),
+            bot_deps.clone(),
+            &user_id,
+            &formatted_group_id,
+        )
+        .await?
+        {
+            return Ok(());
+        }
+
+        if handle_message_proposal_wizard(
+            bot.clone(),
+            msg.clone(),
+            bot_deps.clone(),
+            &user_id,
+            &formatted_group_id,
+        )
+        .await?
+        {
+            return Ok(());
+        }
+
         let dao_executed = handle_message_dao(
             bot.clone(),
             msg.clone(),
@@ -1716,6 +1743,14 @@ pub async fn handle_rules(bot: Bot, msg: Message, bot_deps: BotDependencies) ->
         return Ok(());
     }
 
+    let text = group_rules_html(&bot_deps, msg.chat.id);
+
+    send_html_message(msg, bot, text).await?;
+    Ok(())
+}
+
+/// Core and custom rules for a group as HTML, shared by /rules and the welcome flow.
+pub fn group_rules_html(bot_deps: &BotDependencies, chat_id: ChatId) -> String {
     let core = r#"
 <b>🛡️ Core Moderation Rules</b>
 
@@ -1745,7 +1780,7 @@ To avoid being muted or banned, please follow these rules:
 
     let settings = bot_deps
         .moderation
-        .get_moderation_settings(msg.chat.id.to_string())
+        .get_moderation_settings(chat_id.to_string())
         .unwrap_or(crate::ai::moderation::dto::ModerationSettings::from((
             vec![],
             vec![],
@@ -1772,13 +1807,10 @@ To avoid being muted or banned, please follow these rules:
         }
     }
 
-    let text = format!(
+    format!(
         "{}\n\n{}\n\n<i>Ask an admin if unclear.</i>",
         core, custom_section
-    );
-
-    send_html_message(msg, bot, text).await?;
-    Ok(())
+    )
 }
 
 async fn check_group_resource_account_address(
@@ -12,6 +12,7 @@ use crate::{
     bot::{answers::answers, handler::handle_message, handler::handle_web_app_data},
     callbacks::handle_callback_query,
     message_history::handler::{store_message, MessageEntry},
+    welcome::handler::handle_pending_member_message,
 };
 
 async fn handle_unauthenticated(bot: Bot, msg: Message) -> Result<()> {
@@ -33,29 +34,34 @@ async fn handle_chat_member_update(
     update: ChatMemberUpdated,
     bot_deps: BotDependencies,
 ) -> Result<()> {
-    // Only handle new chat members joining
-    if let teloxide::types::ChatMemberStatus::Member = update.new_chat_member.status() {
-        // Check if this is a new member (not a status change)
-        if let teloxide::types::ChatMemberStatus::Left = update.old_chat_member.status() {
-            // New member joined
-            log::info!("Chat member update: new member {} joined chat {}", update.new_chat_member.user.id.0, update.chat.id.0);
-            let welcome_service = bot_deps.welcome_service.clone();
-            
-            if welcome_service.is_enabled(update.chat.id) {
-                let user = &update.new_chat_member.user;
-                let username = user.username.clone();
-                let first_name = user.first_name.clone();
-                
-                if let Err(e) = welcome_service
-                    .handle_new_member(&bot, update.chat.id, user.id, username, first_name)
-                    .await
-                {
-                    log::error!("Failed to handle new member: {}", e);
-                }
-            }
+    let was_present = update.old_chat_member.is_present();
+    let is_present = update.new_chat_member.is_present();
+
+    // Member left or was removed
+    if was_present && !is_present {
+        log::info!("Chat member update: member {} left chat {}", update.new_chat_member.user.id.0, update.chat.id.0);
+        if let Err(e) = bot_deps
+            .welcome_service
+            .handle_member_left(&bot, update.chat.id, &update.new_chat_member.user, Some(update.from.id))
+            .await
+        {
+            log::error!("Failed to handle member leaving: {}", e);
         }
+        return Ok(());
     }
-    
+
+    // New member joined
+    if !was_present && is_present {
+        log::info!("Chat member update: new member {} joined chat {}", update.new_chat_member.user.id.0, update.chat.id.0);
+        if let Err(e) = bot_deps
+            .welcome_service
+            .handle_member_joined(&bot, update.chat.id, &update.new_chat_member.user)
+            .await
+        {
+            log::error!("Failed to handle new member: {}", e);
+        }
+    }
+
     Ok(())
 }
 
@@ -84,26 +90,52 @@ pub fn handler_tree() -> Handler<'static, Result<()>, DpHandlerDescription> {
                         .filter(|msg: Message| msg.new_chat_members().map(|m| !m.is_empty()).unwrap_or(false))
                         .endpoint(|bot: Bot, msg: Message, bot_deps: BotDependencies| async move {
                             log::info!("Service message: new members detected in chat {}", msg.chat.id.0);
-                            let welcome_service = bot_deps.welcome_service.clone();
-
-                            if welcome_service.is_enabled(msg.chat.id) {
-                                if let Some(members) = msg.new_chat_members() {
-                                    for user in members {
-                                        log::info!("Service message: processing new member {} in chat {}", user.id.0, msg.chat.id.0);
-                                        let username = user.username.clone();
-                                        let first_name = user.first_name.clone();
-                                        if let Err(e) = welcome_service
-                                            .handle_new_member(&bot, msg.chat.id, user.id, username, first_name)
-                                            .await
-                                        {
-                                            log::error!("Failed to handle new member (message event): {}", e);
-                                        }
-                                    }
+                            bot_deps.welcome_service.schedule_service_message_deletion(msg.chat.id, msg.id);
+                            for user in msg.new_chat_members().unwrap_or_default() {
+                                log::info!("Service message: processing new member {} in chat {}", user.id.0, msg.chat.id.0);
+                                if let Err(e) = bot_deps
+                                    .welcome_service
+                                    .handle_member_joined(&bot, msg.chat.id, user)
+                                    .await
+                                {
+                                    log::error!("Failed to handle new member (message event): {}", e);
                                 }
                             }
 
                             Ok(())
                         }))
+                // Fallback: handle members leaving via service messages
+                .branch(
+                    dptree::entry()
+                        .filter(|msg: Message| msg.left_chat_member().is_some())
+                        .endpoint(|bot: Bot, msg: Message, bot_deps: BotDependencies| async move {
+                            bot_deps.welcome_service.schedule_service_message_deletion(msg.chat.id, msg.id);
+                            if let Some(user) = msg.left_chat_member() {
+                                let actor_id = msg.from.as_ref().map(|from| from.id);
+                                if let Err(e) = bot_deps
+                                    .welcome_service
+                                    .handle_member_left(&bot, msg.chat.id, user, actor_id)
+                                    .await
+                                {
+                                    log::error!("Failed to handle member leaving (message event): {}", e);
+                                }
+                            }
+                            Ok(())
+                        }))
+                // Members still verifying may only type their word challenge answer
+                .branch(
+                    dptree::entry()
+                        .filter(|msg: Message, bot_deps: BotDependencies| {
+                            !msg.chat.is_private()
+                                && msg.from.as_ref().is_some_and(|user| {
+                                    bot_deps
+                                        .welcome_service
+                                        .get_pending_verification(msg.chat.id, user.id)
+                                        .is_some()
+                                })
+                        })
+                        .endpoint(handle_pending_member_message),
+                )
                 .branch(
                     dptree::entry()
                         .filter(|msg: Message| {
@@ -177,7 +209,7 @@ pub fn handler_tree() -> Handler<'static, Result<()>, DpHandlerDescription> {
                             matches!(
                                 cmd,
                                 Command::G(_) | Command::Groupsettings
-                                    | Command::Report | Command::GroupBalance(_) | Command::GroupWalletAddress | Command::Rules | Command::SchedulePrompt | Command::ListScheduled | Command::SchedulePayment | Command::ListScheduledPayments
+                                    | Command::Report | Command::GroupBalance(_) | Command::GroupWalletAddress | Command::Rules | Command::Roles | Command::ManageProposals | Command::Proposals | Command::NewProposal | Command::SchedulePrompt | Command::ListScheduled | Command::SchedulePayment | Command::ListScheduledPayments
                             )
                         })
                         .filter_async(|msg: Message, bot_deps: BotDependencies| async move {
@@ -7,15 +7,32 @@ use crate::ai::moderation::dto::{ModerationSettings, ModerationState};
 use crate::ai::vector_store::{
     delete_file_from_vector_store, delete_vector_store, list_user_files_with_names,
 };
-use crate::dao::handler::{handle_dao_preference_callback, handle_disable_notifications_callback};
+use crate::dao::handler::{
+    handle_dao_preference_callback, handle_dao_vote_callback, handle_dao_vote_confirm_callback,
+    handle_disable_notifications_callback,
+};
+use crate::dao::helpers::{describe_approval_threshold, describe_quorum};
+use crate::dao::archive::handle_proposals_callback;
+use crate::dao::wizard::handle_proposal_wizard_callback;
+use crate::dao::ballot::handle_ballot_callback;
+use crate::dao::lifecycle::handle_proposal_lifecycle_callback;
+use crate::dao::payouts::handle_payout_callback;
 use crate::dependencies::BotDependencies;
 use crate::filters::handler::handle_filters_callback;
 use crate::scheduled_payments::callbacks::handle_scheduled_payments_callback;
 use crate::scheduled_prompts::callbacks::handle_scheduled_prompts_callback;
 use crate::sponsor::handler::handle_sponsor_settings_callback;
+use crate::treasury::approvals::{
+    ad_hoc_payment, approval_gate, handle_treasury_approval_callback, request_approval,
+};
+use crate::treasury::settings::handle_treasury_settings_callback;
 use crate::user_model_preferences::callbacks::handle_model_preferences_callback;
 use crate::utils::{self, send_html_message};
+use crate::welcome::dto::{
+    CaptchaAnswer, SpammerSource, VerificationOutcome, VerificationStage, WelcomeSettings,
+};
 use crate::welcome::handler::handle_welcome_settings_callback;
+use crate::welcome::token_gate::{TokenGateStatus, check_holdings};
 use anyhow::Result;
 use teloxide::sugar::request::RequestReplyExt;
 use teloxide::{
@@ -276,6 +293,15 @@ pub async fn handle_callback_query(
                             .await?;
 
                         log::info!("Admin {} banned user {}", requester_id, target_user_id);
+
+                        if let Err(e) = bot_deps.welcome_service.add_known_spammer(
+                            teloxide::types::UserId(target_user_id as u64),
+                            SpammerSource::Banned {
+                                chat_id: message.chat.id,
+                            },
+                        ) {
+                            log::error!("Failed to add user {} to spammer list: {}", target_user_id, e);
+                        }
                     }
                     Err(e) => {
                         log::error!("Failed to ban user {}: {}", target_user_id, e);
@@ -903,6 +929,10 @@ pub async fn handle_callback_query(
                             "💳 Choose Group Payment Token",
                             "payment_selected",
                         )],
+                        vec![InlineKeyboardButton::callback(
+                            "🔐 Treasury Approvals",
+                            "trsy_policy",
+                        )],
                         vec![InlineKeyboardButton::callback(
                             "↩️ Back",
                             "back_to_group_settings",
@@ -954,6 +984,8 @@ pub async fn handle_callback_query(
                                 interval_dao_results_notifications: 3600,
                                 default_dao_token: None,
                                 vote_duration: Some(24 * 60 * 60), // Default to 24 hours
+                                quorum: None,
+                                approval_threshold: None,
                             };
 
                             // Save default preferences
@@ -1006,6 +1038,26 @@ pub async fn handle_callback_query(
                             ),
                             format!("dao_set_vote_duration_{}", group_id_formatted),
                         )],
+                        vec![InlineKeyboardButton::callback(
+                            format!(
+                                "📉 Quorum: {}",
+                                describe_quorum(current_prefs.quorum)
+                            ),
+                            format!("dao_set_quorum_{}", group_id_formatted),
+                        )],
+                        vec![InlineKeyboardButton::callback(
+                            format!(
+                                "✅ Approval Threshold: {}",
+                                describe_approval_threshold(
+                                    current_prefs.approval_threshold
+                                )
+                            ),
+                            format!("dao_set_approval_{}", group_id_formatted),
+                        )],
+                        vec![InlineKeyboardButton::callback(
+                            "📋 Manage Proposals",
+                            "daop_list",
+                        )],
                         vec![InlineKeyboardButton::callback(
                             "↩️ Back",
                             "back_to_group_settings",
@@ -1246,6 +1298,10 @@ pub async fn handle_callback_query(
             || data.starts_with("dao_notif_")
             || data.starts_with("dao_res_notif_")
             || data.starts_with("dao_vote_duration_")
+            || data.starts_with("dao_set_quorum_")
+            || data.starts_with("dao_set_approval_")
+            || data.starts_with("dao_quorum_")
+            || data.starts_with("dao_approval_")
             || data == "dao_preferences_back"
         {
             // Handle DAO preferences callbacks
@@ -1274,9 +1330,11 @@ pub async fn handle_callback_query(
             let parts: Vec<&str> = data.split(':').collect();
             log::info!("Callback parts: {:?}", parts);
 
-            if parts.len() == 3 {
+            // welcome_verify:{chat}:{user}[:{option index}]
+            if parts.len() == 3 || parts.len() == 4 {
                 let chat_id = parts[1].parse::<i64>().unwrap_or(0);
                 let user_id = parts[2].parse::<u64>().unwrap_or(0);
+                let answer = parts.get(3).and_then(|index| index.parse::<usize>().ok());
                 log::info!("Parsed chat_id: {}, user_id: {}", chat_id, user_id);
 
                 // Accept negative chat IDs (Telegram supergroups use negative IDs)
@@ -1291,11 +1349,53 @@ pub async fn handle_callback_query(
                         query.from.id.0
                     );
                     let welcome_service = bot_deps.welcome_service.clone();
+
+                    // Holder-gated groups end with a balance check instead of a plain answer
+                    let at_holdings_check = welcome_service
+                        .get_pending_verification(chat_id, user_id)
+                        .is_some_and(|verification| {
+                            verification.stage == VerificationStage::TokenGate
+                                && query.from.id == user_id
+                        });
+                    if at_holdings_check {
+                        let settings = welcome_service.get_settings(chat_id);
+                        return handle_token_gate_check(
+                            bot, query, bot_deps, chat_id, user_id, settings,
+                        )
+                        .await;
+                    }
+
+                    let rules_html = crate::bot::handler::group_rules_html(&bot_deps, chat_id);
                     match welcome_service
-                        .handle_verification(&bot, chat_id, user_id, query.from.id)
+                        .handle_verification(
+                            &bot,
+                            chat_id,
+                            user_id,
+                            query.from.id,
+                            CaptchaAnswer::Button(answer),
+                            &rules_html,
+                        )
                         .await
                     {
-                        Ok(_) => {
+                        Ok(VerificationOutcome::NextStep) => {
+                            bot.answer_callback_query(query.id)
+                                .text("✅ Done! Please complete the next step.")
+                                .await?;
+                        }
+                        Ok(VerificationOutcome::WrongAnswer { attempts_left }) => {
+                            bot.answer_callback_query(query.id)
+                                .text(format!(
+                                    "❌ Wrong answer. {} attempt(s) left.",
+                                    attempts_left
+                                ))
+                                .await?;
+                        }
+                        Ok(VerificationOutcome::Rejected) => {
+                            bot.answer_callback_query(query.id)
+                                .text("❌ Too many wrong answers. You have been removed from the group.")
+                                .await?;
+                        }
+                        Ok(VerificationOutcome::Verified) => {
                             log::info!(
                                 "Verification successful for user {} in chat {}",
                                 user_id.0,
@@ -1341,7 +1441,7 @@ pub async fn handle_callback_query(
                 }
             } else {
                 log::error!(
-                    "Invalid callback format: expected 3 parts, got {}",
+                    "Invalid callback format: expected 3 or 4 parts, got {}",
                     parts.len()
                 );
             }
@@ -1875,15 +1975,38 @@ If you have questions, ask an admin before posting.
         } else if data == "disable_notifications" {
             // Handle disable notifications callback
             handle_disable_notifications_callback(bot, query, bot_deps).await?;
+        } else if data.starts_with("props:") || data.starts_with("props_csv:") {
+            handle_proposals_callback(bot, query, bot_deps).await?;
+        } else if data.starts_with("dao_ballot") {
+            handle_ballot_callback(bot, query, bot_deps).await?;
+        } else if data.starts_with("dao_payout:") {
+            handle_payout_callback(bot, query, bot_deps).await?;
+        } else if data.starts_with("daonew_") {
+            handle_proposal_wizard_callback(bot, query, bot_deps).await?;
+        } else if data.starts_with("daop_") {
+            handle_proposal_lifecycle_callback(bot, query, bot_deps).await?;
+        } else if data.starts_with("dao_vote:") {
+            handle_dao_vote_callback(bot, query, bot_deps).await?;
+        } else if data.starts_with("dao_vote_confirm:") || data == "dao_vote_cancel" {
+            handle_dao_vote_confirm_callback(bot, query, bot_deps).await?;
         } else if data == "voting_help" {
             // Handle voting help callback
             bot.answer_callback_query(query.id)
-                .text("📱 Mini App: Opens voting interface inside Telegram\n🌐 Browser: Opens voting page in external browser\n\nBoth options work the same way!")
+                .text("🗳️ Vote: Opens the voting page in your browser\n⚡ Vote here: Votes with your linked wallet after a confirmation in DM (link it with /loginuser)\n\nOne vote per wallet!")
                 .show_alert(true)
                 .await?;
         } else if data.starts_with("pay_accept:") || data.starts_with("pay_reject:") {
             // Handle payment confirmation callbacks
             handle_payment_callback(bot, query, bot_deps).await?;
+        } else if data.starts_with("trsy_ok:")
+            || data.starts_with("trsy_no:")
+            || data.starts_with("trsy_retry:")
+        {
+            // Handle admin answers to treasury payment requests
+            handle_treasury_approval_callback(bot, query, bot_deps).await?;
+        } else if data.starts_with("trsy_") {
+            // Handle treasury approval policy settings
+            handle_treasury_settings_callback(bot, query, bot_deps).await?;
         } else if data.starts_with("sched_") {
             // Handle scheduled prompts wizard and management callbacks
             handle_scheduled_prompts_callback(bot, query, bot_deps).await?;
@@ -1911,6 +2034,54 @@ If you have questions, ask an admin before posting.
     Ok(())
 }
 
+async fn handle_token_gate_check(
+    bot: Bot,
+    query: teloxide::types::CallbackQuery,
+    bot_deps: BotDependencies,
+    chat_id: ChatId,
+    user_id: UserId,
+    settings: WelcomeSettings,
+) -> Result<()> {
+    let status = check_holdings(
+        &bot_deps.auth,
+        &bot_deps.panora.aptos,
+        &settings,
+        user_id,
+        query.from.username.as_deref(),
+    )
+    .await;
+
+    let text = match status {
+        TokenGateStatus::NotLinked => {
+            "🔗 No linked wallet found. Send /loginuser to me in a private chat, then tap Check again."
+                .to_string()
+        }
+        TokenGateStatus::Insufficient { detail } => format!("❌ {}", detail),
+        TokenGateStatus::Unavailable => {
+            "⚠️ Couldn't read your balance right now. Please try again in a moment.".to_string()
+        }
+        TokenGateStatus::Qualified => match bot_deps
+            .welcome_service
+            .pass_token_gate(&bot, chat_id, user_id, query.from.id)
+            .await
+        {
+            Ok(_) => "✅ Verification successful! You can now participate in the group.".to_string(),
+            Err(e) => {
+                log::error!(
+                    "Token gate verification failed for user {} in chat {}: {}",
+                    user_id.0,
+                    chat_id.0,
+                    e
+                );
+                "❌ Verification failed. Please contact an administrator.".to_string()
+            }
+        },
+    };
+
+    bot.answer_callback_query(query.id).text(text).await?;
+    Ok(())
+}
+
 pub async fn handle_payment_callback(
     bot: Bot,
     query: teloxide::types::CallbackQuery,
@@ -2043,6 +2214,45 @@ pub async fn handle_payment_callback(
 
     match action {
         "pay_accept" => {
+            // Large group payments wait for the admins the group's policy asks for
+            if pending_transaction.is_group_transfer {
+                let payment = ad_hoc_payment(&bot_deps, &pending_transaction).await;
+                let chat_id = ChatId(pending_transaction.chat_id);
+
+                if let Some(gate) = approval_gate(&bot_deps, chat_id, &payment).await {
+                    if let Err(e) = bot_deps
+                        .pending_transactions
+                        .delete_pending_transaction(user_id, group_id_opt)
+                    {
+                        log::warn!(
+                            "Failed to delete pending transaction after approval request: {}",
+                            e
+                        );
+                    }
+
+                    let message_id = match &query.message {
+                        Some(MaybeInaccessibleMessage::Regular(msg)) => Some(msg.id),
+                        _ => None,
+                    };
+
+                    request_approval(
+                        &bot,
+                        &bot_deps,
+                        chat_id,
+                        payment,
+                        gate,
+                        Some(&query.from),
+                        message_id,
+                    )
+                    .await?;
+
+                    bot.answer_callback_query(query.id)
+                        .text("🔐 Sent for admin approval")
+                        .await?;
+                    return Ok(());
+                }
+            }
+
             // Execute the transaction
             let pay_request =
                 crate::pending_transactions::handler::PendingTransactions::to_pay_users_request(
@@ -1,7 +1,7 @@
 use serde::{Deserialize, Serialize};
 use teloxide::types::UserId;
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Credentials {
     pub jwt: String,
     pub user_id: UserId,
@@ -29,6 +29,46 @@ impl Auth {
         }
     }
 
+    /// Nova wallet (resource account) linked to this Telegram user via /loginuser.
+    /// Credentials are stored by username, so the user id must match as well.
+    pub fn get_linked_address(&self, user_id: UserId, username: Option<&str>) -> Option<String> {
+        let credentials = self.get_credentials(username?)?;
+        (credentials.user_id == user_id).then_some(credentials.resource_account_address)
+    }
+
+    /// Credentials linked to this Telegram user, with the JWT renewed (and saved)
+    /// when it has expired so it can be used for a backend request right away.
+    pub fn get_valid_credentials(
+        &self,
+        user_id: UserId,
+        username: Option<&str>,
+    ) -> Option<Credentials> {
+        let username = username?;
+        let mut credentials = self.get_credentials(username)?;
+
+        if credentials.user_id != user_id {
+            return None;
+        }
+
+        let jwt = self
+            .jwt_manager
+            .validate_and_update_jwt(
+                credentials.jwt.clone(),
+                user_id,
+                credentials.account_address.clone(),
+            )
+            .ok()?;
+
+        if jwt != credentials.jwt {
+            credentials.jwt = jwt;
+            if let Err(e) = self.save_credentials(username, credentials.clone()) {
+                log::warn!("AUTH: Failed to save refreshed JWT: {}", e);
+            }
+        }
+
+        Some(credentials)
+    }
+
     pub fn save_credentials(&self, username: &str, credentials: Credentials) -> Result<()> {
         let bytes = serde_json::to_vec(&credentials).unwrap();
         self.db
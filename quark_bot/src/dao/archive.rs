@@ -0,0 +1,282 @@
+use anyhow::Result;
+use chrono::Utc;
+use teloxide::{
+    prelude::*,
+    sugar::request::RequestReplyExt,
+    types::{
+        InlineKeyboardButton, InlineKeyboardMarkup, InputFile, MaybeInaccessibleMessage, ParseMode,
+    },
+    utils::html,
+};
+
+use crate::{
+    dao::{
+        dto::{ProposalArchive, ProposalOutcome},
+        helpers::{archive_to_csv, describe_outcome},
+    },
+    dependencies::BotDependencies,
+    utils::{format_timestamp, send_message},
+};
+
+const PAGE_SIZE: usize = 5;
+
+#[derive(Clone, Copy, PartialEq)]
+enum ProposalFilter {
+    Active,
+    Archive,
+    Passed,
+    Rejected,
+}
+
+impl ProposalFilter {
+    const ALL: [ProposalFilter; 4] = [Self::Active, Self::Archive, Self::Passed, Self::Rejected];
+
+    fn parse(value: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|filter| filter.key() == value)
+    }
+
+    fn key(self) -> &'static str {
+        match self {
+            Self::Active => "active",
+            Self::Archive => "archive",
+            Self::Passed => "passed",
+            Self::Rejected => "rejected",
+        }
+    }
+
+    fn label(self) -> &'static str {
+        match self {
+            Self::Active => "🗳️ Active",
+            Self::Archive => "📚 Archive",
+            Self::Passed => "✅ Passed",
+            Self::Rejected => "❌ Rejected",
+        }
+    }
+
+    /// Archived proposals shown under this filter; rejected includes failed quorum.
+    fn matches(self, outcome: &ProposalOutcome) -> bool {
+        match self {
+            Self::Active => false,
+            Self::Archive => true,
+            Self::Passed => matches!(outcome, ProposalOutcome::Passed { .. }),
+            Self::Rejected => !matches!(outcome, ProposalOutcome::Passed { .. }),
+        }
+    }
+}
+
+fn archived_line(proposal: &ProposalArchive) -> String {
+    let icon = match proposal.outcome {
+        ProposalOutcome::Passed { .. } => "✅",
+        ProposalOutcome::Rejected { .. } => "❌",
+        ProposalOutcome::FailedQuorum { .. } => "⚠️",
+    };
+
+    format!(
+        "🏛️ <b>{}</b>\n{} {} · {:.2} {} · ended {}",
+        html::escape(&proposal.name),
+        icon,
+        html::escape(&describe_outcome(&proposal.outcome, &proposal.options)),
+        proposal.weights.iter().sum::<f64>(),
+        html::escape(&proposal.symbol),
+        format_timestamp(proposal.end_date)
+    )
+}
+
+fn archived(
+    bot_deps: &BotDependencies,
+    group_id: &str,
+    filter: ProposalFilter,
+) -> Result<Vec<ProposalArchive>> {
+    Ok(bot_deps
+        .dao
+        .get_group_archive(group_id)?
+        .into_iter()
+        .filter(|proposal| filter.matches(&proposal.outcome))
+        .collect())
+}
+
+fn render_page(
+    bot_deps: &BotDependencies,
+    group_id: &str,
+    filter: ProposalFilter,
+    page: usize,
+) -> Result<(String, InlineKeyboardMarkup)> {
+    let now = Utc::now().timestamp() as u64;
+
+    let lines: Vec<String> = if filter == ProposalFilter::Active {
+        bot_deps
+            .dao
+            .get_open_group_daos(group_id)?
+            .iter()
+            .map(|proposal| {
+                let when = if proposal.start_date > now {
+                    format!("⏳ starts {}", format_timestamp(proposal.start_date))
+                } else {
+                    format!("⏰ ends {}", format_timestamp(proposal.end_date))
+                };
+                format!("🏛️ <b>{}</b>\n{}", html::escape(&proposal.name), when)
+            })
+            .collect()
+    } else {
+        archived(bot_deps, group_id, filter)?
+            .iter()
+            .map(archived_line)
+            .collect()
+    };
+
+    let pages = lines.len().div_ceil(PAGE_SIZE).max(1);
+    let page = page.min(pages - 1);
+
+    let mut text = format!("📋 <b>Proposals</b> · {}\n\n", filter.label());
+
+    if lines.is_empty() {
+        text.push_str("No proposals here yet.");
+    } else {
+        text.push_str(
+            &lines
+                .iter()
+                .skip(page * PAGE_SIZE)
+                .take(PAGE_SIZE)
+                .cloned()
+                .collect::<Vec<_>>()
+                .join("\n\n"),
+        );
+        text.push_str(&format!("\n\n📄 Page {}/{}", page + 1, pages));
+    }
+
+    let mut rows = vec![
+        ProposalFilter::ALL
+            .iter()
+            .map(|option| {
+                let label = if *option == filter {
+                    format!("• {}", option.label())
+                } else {
+                    option.label().to_string()
+                };
+                InlineKeyboardButton::callback(label, format!("props:{}:0", option.key()))
+            })
+            .collect::<Vec<_>>(),
+    ];
+
+    let mut navigation = Vec::new();
+    if page > 0 {
+        navigation.push(InlineKeyboardButton::callback(
+            "◀️ Prev",
+            format!("props:{}:{}", filter.key(), page - 1),
+        ));
+    }
+    if page + 1 < pages {
+        navigation.push(InlineKeyboardButton::callback(
+            "Next ▶️",
+            format!("props:{}:{}", filter.key(), page + 1),
+        ));
+    }
+    if !navigation.is_empty() {
+        rows.push(navigation);
+    }
+
+    if filter != ProposalFilter::Active && !lines.is_empty() {
+        rows.push(vec![InlineKeyboardButton::callback(
+            "📤 Export CSV",
+            format!("props_csv:{}", filter.key()),
+        )]);
+    }
+
+    Ok((text, InlineKeyboardMarkup::new(rows)))
+}
+
+/// /proposals: browse the group's active and archived proposals.
+pub async fn handle_proposals_command(
+    bot: Bot,
+    msg: Message,
+    bot_deps: BotDependencies,
+) -> Result<()> {
+    if !msg.chat.is_group() && !msg.chat.is_supergroup() {
+        send_message(
+            msg,
+            bot,
+            "❌ This command can only be used in a group".to_string(),
+        )
+        .await?;
+        return Ok(());
+    }
+
+    let group_id = format!("{}-{}", msg.chat.id, bot_deps.group.account_seed);
+    let (text, keyboard) = render_page(&bot_deps, &group_id, ProposalFilter::Active, 0)?;
+
+    bot.send_message(msg.chat.id, text)
+        .parse_mode(ParseMode::Html)
+        .reply_markup(keyboard)
+        .reply_to(msg.id)
+        .await?;
+
+    Ok(())
+}
+
+/// Filter, paging (`props:{filter}:{page}`) and export (`props_csv:{filter}`) buttons.
+pub async fn handle_proposals_callback(
+    bot: Bot,
+    query: CallbackQuery,
+    bot_deps: BotDependencies,
+) -> Result<()> {
+    let data = query.data.clone().unwrap_or_default();
+    let msg = match &query.message {
+        Some(MaybeInaccessibleMessage::Regular(message)) => message.clone(),
+        _ => return Ok(()),
+    };
+
+    let group_id = format!("{}-{}", msg.chat.id, bot_deps.group.account_seed);
+
+    if let Some(filter) = data.strip_prefix("props_csv:") {
+        let filter = ProposalFilter::parse(filter).unwrap_or(ProposalFilter::Archive);
+        let proposals = archived(&bot_deps, &group_id, filter)?;
+
+        if proposals.is_empty() {
+            bot.answer_callback_query(query.id)
+                .text("No proposals to export")
+                .await?;
+            return Ok(());
+        }
+
+        let file_name = format!(
+            "proposals_{}_{}.csv",
+            filter.key(),
+            Utc::now().format("%Y-%m-%d")
+        );
+        let document =
+            InputFile::memory(archive_to_csv(&proposals).into_bytes()).file_name(file_name);
+
+        let mut request = bot
+            .send_document(msg.chat.id, document)
+            .caption("📤 Proposal archive export (one row per option, UTC)");
+        if let Some(thread_id) = msg.thread_id {
+            request = request.message_thread_id(thread_id);
+        }
+        request.await?;
+
+        bot.answer_callback_query(query.id)
+            .text("✅ Export sent")
+            .await?;
+        return Ok(());
+    }
+
+    let mut parts = data.trim_start_matches("props:").split(':');
+    let filter = parts
+        .next()
+        .and_then(ProposalFilter::parse)
+        .unwrap_or(ProposalFilter::Active);
+    let page = parts
+        .next()
+        .and_then(|page| page.parse::<usize>().ok())
+        .unwrap_or(0);
+
+    let (text, keyboard) = render_page(&bot_deps, &group_id, filter, page)?;
+
+    bot.edit_message_text(msg.chat.id, msg.id, text)
+        .parse_mode(ParseMode::Html)
+        .reply_markup(keyboard)
+        .await?;
+    bot.answer_callback_query(query.id).await?;
+
+    Ok(())
+}
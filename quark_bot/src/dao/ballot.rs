@@ -0,0 +1,383 @@
+use anyhow::Result;
+use chrono::Utc;
+use teloxide::{
+    prelude::*,
+    types::{InlineKeyboardButton, InlineKeyboardMarkup, MaybeInaccessibleMessage, ParseMode},
+    utils::html,
+};
+
+use quark_core::helpers::dto::VoteProposalRequest;
+
+use crate::{
+    dao::dto::{BallotType, ProposalEntry, ProposalStatus, VoteRecord},
+    dependencies::BotDependencies,
+};
+
+/// Multi-select or ranked-choice proposal that is open for voting, or the
+/// reason it isn't.
+fn open_ballot(
+    bot_deps: &BotDependencies,
+    proposal_id: &str,
+) -> Result<ProposalEntry, &'static str> {
+    let proposal = match bot_deps.dao.get_dao(proposal_id) {
+        Ok(Some(proposal)) => proposal,
+        Ok(None) => return Err("❌ Proposal not found"),
+        Err(e) => {
+            log::error!("Failed to get proposal {}: {}", proposal_id, e);
+            return Err("❌ Error retrieving proposal");
+        }
+    };
+
+    if proposal.status == ProposalStatus::Cancelled {
+        return Err("🛑 This proposal was cancelled");
+    }
+
+    if proposal.ballot == BallotType::Single {
+        return Err("❌ This proposal uses single-choice voting");
+    }
+
+    let now = Utc::now().timestamp() as u64;
+    if now < proposal.start_date || now > proposal.end_date {
+        return Err("⏰ Voting is not open for this proposal");
+    }
+
+    Ok(proposal)
+}
+
+/// Picked options, numbered by rank for ranked-choice ballots.
+fn picked_lines(proposal: &ProposalEntry, draft: &[usize]) -> String {
+    if draft.is_empty() {
+        return "—".to_string();
+    }
+
+    draft
+        .iter()
+        .enumerate()
+        .map(|(rank, &index)| {
+            let option = html::escape(
+                proposal
+                    .options
+                    .get(index)
+                    .map(String::as_str)
+                    .unwrap_or("Unknown"),
+            );
+            match proposal.ballot {
+                BallotType::RankedChoice => format!("{}. {}", rank + 1, option),
+                _ => format!("• {}", option),
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+fn ballot_message(proposal: &ProposalEntry, draft: &[usize]) -> (String, InlineKeyboardMarkup) {
+    let instructions = match proposal.ballot {
+        BallotType::RankedChoice => {
+            "Tap the options in order of preference. You don't have to rank all of them."
+                .to_string()
+        }
+        BallotType::MultiSelect { max_choices } => format!("Pick up to {} options.", max_choices),
+        BallotType::Single => String::new(),
+    };
+
+    let text = format!(
+        "🗳️ <b>{}</b>\n\n{}\n\n<b>Your ballot:</b>\n{}\n\n<i>Submitting casts your first pick on-chain from your wallet, with your token balance as voting power. Ballots can't be changed afterwards.</i>",
+        html::escape(&proposal.name),
+        instructions,
+        picked_lines(proposal, draft)
+    );
+
+    let mut rows = Vec::new();
+
+    for (index, name) in proposal.options.iter().enumerate() {
+        let selected = draft.contains(&index);
+        let label = match proposal.ballot {
+            // Ranked options leave the list once they have a rank
+            BallotType::RankedChoice if selected => continue,
+            BallotType::RankedChoice => format!("➕ {}", name),
+            _ if selected => format!("☑️ {}", name),
+            _ => format!("⬜ {}", name),
+        };
+        rows.push(vec![InlineKeyboardButton::callback(
+            label,
+            format!("dao_ballot_pick:{}:{}", proposal.proposal_id, index),
+        )]);
+    }
+
+    rows.push(vec![
+        InlineKeyboardButton::callback(
+            "↩️ Reset",
+            format!("dao_ballot_reset:{}", proposal.proposal_id),
+        ),
+        InlineKeyboardButton::callback(
+            "✅ Submit",
+            format!("dao_ballot_submit:{}", proposal.proposal_id),
+        ),
+    ]);
+
+    (text, InlineKeyboardMarkup::new(rows))
+}
+
+/// Buttons of multi-select and ranked-choice ballots. "🗳️ Open ballot" in the
+/// group sends the ballot to the voter's private chat, where it's filled in.
+pub async fn handle_ballot_callback(
+    bot: Bot,
+    query: CallbackQuery,
+    bot_deps: BotDependencies,
+) -> Result<()> {
+    let data = query.data.clone().unwrap_or_default();
+    let Some((action, rest)) = data.split_once(':') else {
+        bot.answer_callback_query(query.id).await?;
+        return Ok(());
+    };
+    let (proposal_id, argument) = match rest.split_once(':') {
+        Some((proposal_id, argument)) => (proposal_id, Some(argument)),
+        None => (rest, None),
+    };
+
+    let proposal = match open_ballot(&bot_deps, proposal_id) {
+        Ok(proposal) => proposal,
+        Err(reason) => {
+            bot.answer_callback_query(query.id)
+                .text(reason)
+                .show_alert(true)
+                .await?;
+            return Ok(());
+        }
+    };
+
+    let Some(credentials) = bot_deps
+        .auth
+        .get_valid_credentials(query.from.id, query.from.username.as_deref())
+    else {
+        bot.answer_callback_query(query.id)
+            .text("🔗 Link your wallet first: open a chat with me and send /loginuser, then tap 🗳️ Open ballot again.")
+            .show_alert(true)
+            .await?;
+        return Ok(());
+    };
+
+    let wallet = credentials.resource_account_address;
+
+    if let Ok(Some(_)) = bot_deps.dao.get_vote(&proposal.proposal_id, &wallet) {
+        bot.answer_callback_query(query.id)
+            .text("✅ Your wallet already voted on this proposal")
+            .show_alert(true)
+            .await?;
+        return Ok(());
+    }
+
+    let user_id = query.from.id.0;
+    let mut draft = bot_deps
+        .dao
+        .get_ballot_draft(user_id, &proposal.proposal_id)?;
+
+    if action == "dao_ballot" {
+        let (text, keyboard) = ballot_message(&proposal, &draft);
+        let sent = bot
+            .send_message(ChatId::from(query.from.id), text)
+            .parse_mode(ParseMode::Html)
+            .reply_markup(keyboard)
+            .await;
+
+        if sent.is_err() {
+            bot.answer_callback_query(query.id)
+                .text("❌ I couldn't message you. Open a chat with me first, then tap 🗳️ Open ballot again.")
+                .show_alert(true)
+                .await?;
+            return Ok(());
+        }
+
+        bot.answer_callback_query(query.id)
+            .text("📩 Check your private chat with me to fill in your ballot")
+            .await?;
+        return Ok(());
+    }
+
+    let msg = match &query.message {
+        Some(MaybeInaccessibleMessage::Regular(message)) => message.clone(),
+        _ => return Ok(()),
+    };
+
+    match action {
+        "dao_ballot_pick" => {
+            let Some(index) = argument
+                .and_then(|index| index.parse::<usize>().ok())
+                .filter(|&index| index < proposal.options.len())
+            else {
+                bot.answer_callback_query(query.id)
+                    .text("❌ Unknown voting option")
+                    .await?;
+                return Ok(());
+            };
+
+            if let Some(position) = draft.iter().position(|&picked| picked == index) {
+                if proposal.ballot != BallotType::RankedChoice {
+                    draft.remove(position);
+                }
+            } else {
+                match proposal.ballot {
+                    BallotType::MultiSelect { max_choices }
+                        if draft.len() >= max_choices as usize =>
+                    {
+                        bot.answer_callback_query(query.id)
+                            .text(format!("You can pick up to {} options", max_choices))
+                            .await?;
+                        return Ok(());
+                    }
+                    _ => draft.push(index),
+                }
+            }
+
+            bot_deps
+                .dao
+                .save_ballot_draft(user_id, &proposal.proposal_id, &draft)?;
+        }
+        "dao_ballot_reset" => {
+            draft.clear();
+            bot_deps
+                .dao
+                .remove_ballot_draft(user_id, &proposal.proposal_id)?;
+        }
+        "dao_ballot_submit" => {
+            if draft.is_empty() {
+                bot.answer_callback_query(query.id)
+                    .text("Pick at least one option first")
+                    .await?;
+                return Ok(());
+            }
+
+            let weight = match bot_deps
+                .panora
+                .aptos
+                .get_account_balance(&wallet, &proposal.coin_type)
+                .await
+            {
+                Ok(balance) if balance > 0 => balance as u64,
+                Ok(_) => {
+                    bot.answer_callback_query(query.id)
+                        .text("❌ Your wallet doesn't hold the voting token")
+                        .show_alert(true)
+                        .await?;
+                    return Ok(());
+                }
+                Err(e) => {
+                    log::error!("Failed to get voting power of {}: {}", wallet, e);
+                    bot.answer_callback_query(query.id)
+                        .text("❌ Couldn't check your voting power, please try again")
+                        .await?;
+                    return Ok(());
+                }
+            };
+
+            let mut vote = VoteRecord {
+                proposal_id: proposal.proposal_id.clone(),
+                wallet_address: wallet.clone(),
+                user_id,
+                choice_id: draft[0] as u64,
+                transaction_hash: None,
+                voted_at: Utc::now().timestamp() as u64,
+                choices: draft.iter().map(|&index| index as u64).collect(),
+                weight,
+            };
+
+            if !bot_deps.dao.reserve_vote(&vote)? {
+                bot.answer_callback_query(query.id)
+                    .text("✅ Your wallet already voted on this proposal")
+                    .await?;
+                return Ok(());
+            }
+
+            bot.answer_callback_query(query.id)
+                .text("⏳ Submitting your ballot...")
+                .await?;
+            bot.edit_message_text(msg.chat.id, msg.id, "⏳ Submitting your ballot...")
+                .await?;
+
+            // The wallet votes its first pick on-chain; the stored ballot only
+            // counts alongside that vote
+            let request = VoteProposalRequest {
+                group_id: proposal.group_id.clone(),
+                proposal_id: proposal.proposal_id.clone(),
+                choice_id: vote.choice_id,
+                version: proposal.version.clone(),
+                currency: proposal.coin_type.clone(),
+            };
+
+            let response = match bot_deps
+                .service
+                .vote_proposal(credentials.jwt, request)
+                .await
+            {
+                Ok(response) => response,
+                Err(e) => {
+                    log::error!(
+                        "Failed to submit ballot on {} for {}: {}",
+                        proposal.proposal_id,
+                        wallet,
+                        e
+                    );
+
+                    if let Err(e) = bot_deps.dao.remove_vote(&proposal.proposal_id, &wallet) {
+                        log::error!("Failed to release vote reservation for {}: {}", wallet, e);
+                    }
+
+                    let (text, keyboard) = ballot_message(&proposal, &draft);
+                    bot.edit_message_text(
+                        msg.chat.id,
+                        msg.id,
+                        format!(
+                            "❌ Your ballot couldn't be submitted, please try again.\n\n{}",
+                            text
+                        ),
+                    )
+                    .parse_mode(ParseMode::Html)
+                    .reply_markup(keyboard)
+                    .await?;
+                    return Ok(());
+                }
+            };
+
+            vote.transaction_hash = Some(response.hash.clone());
+            if let Err(e) = bot_deps.dao.save_vote(&vote) {
+                log::error!("Failed to save ballot hash for {}: {}", wallet, e);
+            }
+
+            if let Err(e) = bot_deps
+                .dao
+                .remove_ballot_draft(user_id, &proposal.proposal_id)
+            {
+                log::warn!("Failed to remove ballot draft of {}: {}", user_id, e);
+            }
+
+            let network = std::env::var("APTOS_NETWORK")
+                .unwrap_or("mainnet".to_string())
+                .to_lowercase();
+
+            bot.edit_message_text(
+                msg.chat.id,
+                msg.id,
+                format!(
+                    "✅ <b>Ballot recorded!</b>\n\n🏛️ {}\n\n{}\n\n🔗 <a href=\"https://explorer.aptoslabs.com/txn/{}?network={}\">View transaction</a>",
+                    html::escape(&proposal.name),
+                    picked_lines(&proposal, &draft),
+                    response.hash,
+                    network
+                ),
+            )
+            .parse_mode(ParseMode::Html)
+            .await?;
+            return Ok(());
+        }
+        _ => {}
+    }
+
+    let (text, keyboard) = ballot_message(&proposal, &draft);
+    bot.edit_message_text(msg.chat.id, msg.id, text)
+        .parse_mode(ParseMode::Html)
+        .reply_markup(keyboard)
+        .await?;
+    bot.answer_callback_query(query.id).await?;
+
+    Ok(())
+}
@@ -2,7 +2,10 @@ use anyhow::Result;
 use chrono::Utc;
 use sled::Tree;
 
-use crate::dao::dto::{DaoAdminPreferences, ProposalEntry, ProposalStatus};
+use crate::dao::dto::{
+    DaoAdminPreferences, PayoutStatus, ProposalArchive, ProposalEntry, ProposalPayout,
+    ProposalStatus, ProposalWizardState, VoteRecord,
+};
 
 #[derive(Clone)]
 pub struct Dao {
@@ -50,6 +53,9 @@ impl Dao {
                                 admin_preferences[index].default_dao_token.clone()
                             };
                         admin_preferences[index].vote_duration = preferences.vote_duration;
+                        admin_preferences[index].quorum = preferences.quorum;
+                        admin_preferences[index].approval_threshold =
+                            preferences.approval_threshold;
                     } else {
                         // Add new preference with uppercase token
                         let mut new_prefs = preferences.clone();
@@ -87,6 +93,8 @@ impl Dao {
                 interval_dao_results_notifications: 3600,
                 default_dao_token: None,
                 vote_duration: Some(24 * 60 * 60), // Default to 24 hours
+                quorum: None,
+                approval_threshold: None,
             });
         }
 
@@ -207,7 +215,11 @@ impl Dao {
 
         Ok(daos
             .into_iter()
-            .filter(|dao| dao.start_date <= now && dao.end_date >= now)
+            .filter(|dao| {
+                dao.start_date <= now
+                    && dao.end_date >= now
+                    && dao.status != ProposalStatus::Cancelled
+            })
             .collect())
     }
 
@@ -237,8 +249,10 @@ impl Dao {
                 let initial_count = daos.len();
                 if let Some(admin_preference) = admin_preference {
                     daos.retain(|dao| {
-                        // Keep proposals that are not completed
-                        if dao.status != ProposalStatus::Completed {
+                        // Keep proposals that are not completed or cancelled
+                        if dao.status != ProposalStatus::Completed
+                            && dao.status != ProposalStatus::Cancelled
+                        {
                             return true;
                         }
                         // For completed proposals, only remove if they've passed expiration time
@@ -251,8 +265,10 @@ impl Dao {
                     });
                 } else {
                     daos.retain(|dao| {
-                        // Keep proposals that are not completed
-                        if dao.status != ProposalStatus::Completed {
+                        // Keep proposals that are not completed or cancelled
+                        if dao.status != ProposalStatus::Completed
+                            && dao.status != ProposalStatus::Cancelled
+                        {
                             return true;
                         }
                         // For completed proposals, only remove if they've passed expiration time (default 7 days)
@@ -323,7 +339,9 @@ impl Dao {
 
         let dao_results = dao_results
             .into_iter()
-            .filter(|dao_result| dao_result.end_date < now)
+            .filter(|dao_result| {
+                dao_result.end_date < now && dao_result.status != ProposalStatus::Cancelled
+            })
             .collect::<Vec<ProposalEntry>>();
 
         Ok(dao_results)
@@ -387,6 +405,37 @@ impl Dao {
         Ok(())
     }
 
+    pub fn update_reminder_message_id(
+        &self,
+        proposal_id: String,
+        message_id: Option<i32>,
+    ) -> Result<()> {
+        self.db.fetch_and_update("daos", |entries| {
+            if let Some(daos) = entries {
+                let daos_result: Result<Vec<ProposalEntry>, serde_json::Error> =
+                    serde_json::from_slice(daos);
+
+                if daos_result.is_err() {
+                    return None;
+                }
+
+                let mut daos = daos_result.unwrap();
+
+                let dao = daos.iter_mut().find(|dao| dao.proposal_id == proposal_id);
+
+                if let Some(dao) = dao {
+                    dao.reminder_message_id = message_id;
+                }
+
+                Some(serde_json::to_vec(&daos).unwrap())
+            } else {
+                None
+            }
+        })?;
+
+        Ok(())
+    }
+
     pub fn update_disabled_notifications(&self, proposal_id: String, disabled: bool) -> Result<()> {
         self.db.fetch_and_update("daos", |entries| {
             if let Some(daos) = entries {
@@ -414,6 +463,340 @@ impl Dao {
         Ok(())
     }
 
+    pub fn get_dao(&self, proposal_id: &str) -> Result<Option<ProposalEntry>> {
+        let Some(daos) = self.db.get("daos")? else {
+            return Ok(None);
+        };
+
+        let daos: Vec<ProposalEntry> = serde_json::from_slice(&daos)?;
+
+        Ok(daos.into_iter().find(|dao| dao.proposal_id == proposal_id))
+    }
+
+    /// Proposals of a group that haven't ended or been cancelled, oldest first.
+    pub fn get_open_group_daos(&self, group_id: &str) -> Result<Vec<ProposalEntry>> {
+        let now = Utc::now().timestamp() as u64;
+
+        let Some(daos) = self.db.get("daos")? else {
+            return Ok(vec![]);
+        };
+
+        let daos: Vec<ProposalEntry> = serde_json::from_slice(&daos)?;
+
+        Ok(daos
+            .into_iter()
+            .filter(|dao| {
+                dao.group_id == group_id
+                    && dao.end_date >= now
+                    && dao.status != ProposalStatus::Cancelled
+            })
+            .collect())
+    }
+
+    /// Apply `update` to a stored proposal and return the updated entry.
+    pub fn update_dao<F>(&self, proposal_id: &str, update: F) -> Result<Option<ProposalEntry>>
+    where
+        F: Fn(&mut ProposalEntry),
+    {
+        let daos = self.db.update_and_fetch("daos", |entries| {
+            let entries = entries?;
+
+            // Leave the list untouched rather than dropping it if it can't be parsed
+            let Ok(mut daos) = serde_json::from_slice::<Vec<ProposalEntry>>(entries) else {
+                return Some(entries.to_vec());
+            };
+
+            if let Some(dao) = daos.iter_mut().find(|dao| dao.proposal_id == proposal_id) {
+                update(dao);
+            }
+
+            Some(serde_json::to_vec(&daos).unwrap_or_else(|_| entries.to_vec()))
+        })?;
+
+        let Some(daos) = daos else {
+            return Ok(None);
+        };
+
+        let daos: Vec<ProposalEntry> = serde_json::from_slice(&daos)?;
+
+        Ok(daos.into_iter().find(|dao| dao.proposal_id == proposal_id))
+    }
+
+    fn description_edit_key(user_id: &str, group_id: &str) -> String {
+        format!("proposal_edit_{}_{}", user_id, group_id)
+    }
+
+    /// Remember that the admin's next message in the group is a new description.
+    pub fn set_pending_description_edit(
+        &self,
+        user_id: &str,
+        group_id: &str,
+        proposal_id: &str,
+    ) -> Result<()> {
+        let key = Self::description_edit_key(user_id, group_id);
+        self.db.insert(key.as_bytes(), proposal_id.as_bytes())?;
+
+        Ok(())
+    }
+
+    pub fn get_pending_description_edit(
+        &self,
+        user_id: &str,
+        group_id: &str,
+    ) -> Result<Option<String>> {
+        let key = Self::description_edit_key(user_id, group_id);
+
+        Ok(self
+            .db
+            .get(key.as_bytes())?
+            .map(|value| String::from_utf8_lossy(&value).to_string()))
+    }
+
+    pub fn remove_pending_description_edit(&self, user_id: &str, group_id: &str) -> Result<()> {
+        let key = Self::description_edit_key(user_id, group_id);
+        self.db.remove(key.as_bytes())?;
+
+        Ok(())
+    }
+
+    fn proposal_wizard_key(user_id: &str, group_id: &str) -> String {
+        format!("proposal_wizard_{}_{}", user_id, group_id)
+    }
+
+    pub fn get_proposal_wizard(
+        &self,
+        user_id: &str,
+        group_id: &str,
+    ) -> Result<Option<ProposalWizardState>> {
+        let key = Self::proposal_wizard_key(user_id, group_id);
+
+        Ok(self
+            .db
+            .get(key.as_bytes())?
+            .map(|value| serde_json::from_slice(&value))
+            .transpose()?)
+    }
+
+    pub fn save_proposal_wizard(&self, user_id: &str, state: &ProposalWizardState) -> Result<()> {
+        let key = Self::proposal_wizard_key(user_id, &state.group_id);
+        self.db.insert(key.as_bytes(), serde_json::to_vec(state)?)?;
+
+        Ok(())
+    }
+
+    /// Remove the draft and return it. Only one caller gets the draft back, so
+    /// this claims it for work that must not run twice.
+    pub fn take_proposal_wizard(
+        &self,
+        user_id: &str,
+        group_id: &str,
+    ) -> Result<Option<ProposalWizardState>> {
+        let key = Self::proposal_wizard_key(user_id, group_id);
+
+        Ok(self
+            .db
+            .remove(key.as_bytes())?
+            .map(|value| serde_json::from_slice(&value))
+            .transpose()?)
+    }
+
+    pub fn remove_proposal_wizard(&self, user_id: &str, group_id: &str) -> Result<()> {
+        let key = Self::proposal_wizard_key(user_id, group_id);
+        self.db.remove(key.as_bytes())?;
+
+        Ok(())
+    }
+
+    fn vote_key(proposal_id: &str, wallet_address: &str) -> String {
+        format!("vote:{}:{}", proposal_id, wallet_address)
+    }
+
+    pub fn get_vote(&self, proposal_id: &str, wallet_address: &str) -> Result<Option<VoteRecord>> {
+        let key = Self::vote_key(proposal_id, wallet_address);
+
+        match self.db.get(key.as_bytes())? {
+            Some(bytes) => Ok(Some(serde_json::from_slice(&bytes)?)),
+            None => Ok(None),
+        }
+    }
+
+    /// Store the vote only if this wallet hasn't voted on the proposal yet.
+    /// Returns false when a vote already exists.
+    pub fn reserve_vote(&self, vote: &VoteRecord) -> Result<bool> {
+        let key = Self::vote_key(&vote.proposal_id, &vote.wallet_address);
+        let value = serde_json::to_vec(vote)?;
+
+        let reserved = self
+            .db
+            .compare_and_swap(key.as_bytes(), None as Option<&[u8]>, Some(value))?
+            .is_ok();
+
+        Ok(reserved)
+    }
+
+    pub fn save_vote(&self, vote: &VoteRecord) -> Result<()> {
+        let key = Self::vote_key(&vote.proposal_id, &vote.wallet_address);
+        self.db.insert(key.as_bytes(), serde_json::to_vec(vote)?)?;
+
+        Ok(())
+    }
+
+    pub fn remove_vote(&self, proposal_id: &str, wallet_address: &str) -> Result<()> {
+        let key = Self::vote_key(proposal_id, wallet_address);
+        self.db.remove(key.as_bytes())?;
+
+        Ok(())
+    }
+
+    /// Every vote recorded from Telegram for a proposal.
+    pub fn get_proposal_votes(&self, proposal_id: &str) -> Result<Vec<VoteRecord>> {
+        let prefix = format!("vote:{}:", proposal_id);
+        let mut votes = Vec::new();
+
+        for entry in self.db.scan_prefix(prefix.as_bytes()) {
+            let (_, value) = entry?;
+            votes.push(serde_json::from_slice(&value)?);
+        }
+
+        Ok(votes)
+    }
+
+    fn ballot_draft_key(user_id: u64, proposal_id: &str) -> String {
+        format!("ballot_draft:{}:{}", user_id, proposal_id)
+    }
+
+    /// Options picked so far in a ballot that hasn't been submitted.
+    pub fn get_ballot_draft(&self, user_id: u64, proposal_id: &str) -> Result<Vec<usize>> {
+        let draft = self
+            .db
+            .get(Self::ballot_draft_key(user_id, proposal_id).as_bytes())?
+            .map(|value| serde_json::from_slice(&value))
+            .transpose()?
+            .unwrap_or_default();
+
+        Ok(draft)
+    }
+
+    pub fn save_ballot_draft(
+        &self,
+        user_id: u64,
+        proposal_id: &str,
+        choices: &[usize],
+    ) -> Result<()> {
+        let key = Self::ballot_draft_key(user_id, proposal_id);
+        self.db.insert(key.as_bytes(), serde_json::to_vec(choices)?)?;
+
+        Ok(())
+    }
+
+    pub fn remove_ballot_draft(&self, user_id: u64, proposal_id: &str) -> Result<()> {
+        self.db
+            .remove(Self::ballot_draft_key(user_id, proposal_id).as_bytes())?;
+
+        Ok(())
+    }
+
+    /// Archive keys sort by end date within a group.
+    fn archive_key(archive: &ProposalArchive) -> String {
+        format!(
+            "archive:{}:{:020}:{}",
+            archive.group_id, archive.end_date, archive.proposal_id
+        )
+    }
+
+    pub fn archive_proposal(&self, archive: &ProposalArchive) -> Result<()> {
+        let key = Self::archive_key(archive);
+        self.db.insert(key.as_bytes(), serde_json::to_vec(archive)?)?;
+
+        Ok(())
+    }
+
+    /// Archived proposals of a group, most recent first.
+    pub fn get_group_archive(&self, group_id: &str) -> Result<Vec<ProposalArchive>> {
+        let prefix = format!("archive:{}:", group_id);
+        let mut archive = Vec::new();
+
+        for entry in self.db.scan_prefix(prefix.as_bytes()).rev() {
+            let (_, value) = entry?;
+            archive.push(serde_json::from_slice(&value)?);
+        }
+
+        Ok(archive)
+    }
+
+    fn payout_key(proposal_id: &str) -> String {
+        format!("payout:{}", proposal_id)
+    }
+
+    pub fn get_payout(&self, proposal_id: &str) -> Result<Option<ProposalPayout>> {
+        let payout = self
+            .db
+            .get(Self::payout_key(proposal_id).as_bytes())?
+            .map(|value| serde_json::from_slice(&value))
+            .transpose()?;
+
+        Ok(payout)
+    }
+
+    /// Store the payout unless one was already prepared for this proposal.
+    /// Returns false when it already exists.
+    pub fn create_payout(&self, payout: &ProposalPayout) -> Result<bool> {
+        let key = Self::payout_key(&payout.proposal_id);
+        let value = serde_json::to_vec(payout)?;
+
+        let created = self
+            .db
+            .compare_and_swap(key.as_bytes(), None as Option<&[u8]>, Some(value))?
+            .is_ok();
+
+        Ok(created)
+    }
+
+    /// Move a payout awaiting approval (or a failed one) to `status`, so two
+    /// admins answering at once can't both act on it.
+    pub fn resolve_payout(
+        &self,
+        proposal_id: &str,
+        admin_id: u64,
+        status: PayoutStatus,
+    ) -> Result<Option<ProposalPayout>> {
+        let key = Self::payout_key(proposal_id);
+
+        let Some(current) = self.db.get(key.as_bytes())? else {
+            return Ok(None);
+        };
+
+        let mut payout: ProposalPayout = serde_json::from_slice(&current)?;
+
+        if !matches!(
+            payout.status,
+            PayoutStatus::AwaitingApproval | PayoutStatus::Failed
+        ) {
+            return Ok(None);
+        }
+
+        payout.status = status;
+        payout.resolved_by = Some(admin_id);
+
+        let claimed = self
+            .db
+            .compare_and_swap(
+                key.as_bytes(),
+                Some(current),
+                Some(serde_json::to_vec(&payout)?),
+            )?
+            .is_ok();
+
+        Ok(claimed.then_some(payout))
+    }
+
+    pub fn save_payout(&self, payout: &ProposalPayout) -> Result<()> {
+        let key = Self::payout_key(&payout.proposal_id);
+        self.db.insert(key.as_bytes(), serde_json::to_vec(payout)?)?;
+
+        Ok(())
+    }
+
     pub fn insert_pending_tokens(&self, key: String, value: String) -> Result<()> {
         self.db.insert(key.as_bytes(), value.as_bytes())?;
 
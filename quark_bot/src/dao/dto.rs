@@ -7,6 +7,21 @@ pub enum ProposalStatus {
     Pending,
     Active,
     Completed,
+    /// Cancelled by an admin before voting started.
+    Cancelled,
+}
+
+/// How members fill in their ballot. `Single` is tallied on-chain. The other
+/// types are collected by the bot; the voter's wallet also casts the first pick
+/// on-chain and only ballots backed by that vote are counted.
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Debug, Default)]
+pub enum BallotType {
+    #[default]
+    Single,
+    /// Pick up to `max_choices` options; each one gets the voter's full weight.
+    MultiSelect { max_choices: u8 },
+    /// Rank the options; counted by instant runoff.
+    RankedChoice,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug)]
@@ -17,6 +32,12 @@ pub struct DaoAdminPreferences {
     pub interval_dao_results_notifications: u64,
     pub default_dao_token: Option<String>,
     pub vote_duration: Option<u64>, // Duration in seconds for how long votes are open
+    /// Minimum token-weighted participation for a proposal to count; `None` disables it.
+    #[serde(default)]
+    pub quorum: Option<f64>,
+    /// Share of the voted tokens (percent) the leading option needs to pass.
+    #[serde(default)]
+    pub approval_threshold: Option<u8>,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug)]
@@ -35,6 +56,147 @@ pub struct ProposalEntry {
     pub last_result_notification: u64,
     pub disabled_notifications: bool,
     pub thread_id: Option<i32>,
+    /// Last reminder posted to the group; edited in place with the live tally.
+    #[serde(default)]
+    pub reminder_message_id: Option<i32>,
+    /// Overrides the group's `DaoAdminPreferences::quorum`.
+    #[serde(default)]
+    pub quorum: Option<f64>,
+    /// Overrides the group's `DaoAdminPreferences::approval_threshold`.
+    #[serde(default)]
+    pub approval_threshold: Option<u8>,
+    /// Treasury payouts prepared when the matching option wins.
+    #[serde(default)]
+    pub actions: Vec<ProposalAction>,
+    #[serde(default)]
+    pub ballot: BallotType,
+}
+
+/// Payment from the group treasury attached to one of the proposal options.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ProposalAction {
+    pub option_index: usize,
+    pub symbol: String,
+    pub coin_type: String,
+    pub version: CoinVersion,
+    pub decimals: u8,
+    pub recipients: Vec<PayoutRecipient>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct PayoutRecipient {
+    pub username: String,
+    pub address: String,
+    /// Amount in the token's smallest units.
+    pub amount: u64,
+    /// Set once this recipient has been paid, so a retry skips them.
+    #[serde(default)]
+    pub transaction_hash: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+pub enum PayoutStatus {
+    AwaitingApproval,
+    Executing,
+    Paid,
+    /// Some transfers failed; an admin can retry the unpaid recipients.
+    Failed,
+    Declined,
+}
+
+/// Payout prepared after a proposal passed, waiting for an admin to confirm it.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ProposalPayout {
+    pub proposal_id: String,
+    pub group_id: String,
+    pub proposal_name: String,
+    pub option: String,
+    pub action: ProposalAction,
+    pub status: PayoutStatus,
+    pub created_at: u64,
+    pub resolved_by: Option<u64>,
+}
+
+/// One instant-runoff count. Eliminated options keep a zero weight.
+#[derive(Clone, Debug, PartialEq)]
+pub struct RunoffRound {
+    pub votes: Vec<u64>,
+    pub weights: Vec<f64>,
+    pub eliminated: Option<usize>,
+}
+
+/// Live vote counts read from `get_group_dao_v1/v2`, indexed by choice.
+/// `weights` are raw token amounts (not scaled by decimals).
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ProposalTally {
+    pub votes: Vec<u64>,
+    pub weights: Vec<u64>,
+}
+
+/// A wallet's vote as recorded by the contract.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ChainVote {
+    pub voter: String,
+    pub choice_id: u64,
+    /// Token balance (smallest units) the contract counted for the vote.
+    pub weight: u64,
+}
+
+/// How a finished proposal is announced.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub enum ProposalOutcome {
+    Passed {
+        winner: usize,
+        approval: f64,
+    },
+    /// Quorum was met but no option reached the approval threshold (or the lead is tied).
+    Rejected {
+        leader: Option<usize>,
+        approval: f64,
+    },
+    FailedQuorum {
+        participation: f64,
+    },
+}
+
+/// Final state of a finished proposal, kept after `remove_expired_daos` drops
+/// the proposal itself.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ProposalArchive {
+    pub proposal_id: String,
+    pub group_id: String,
+    pub name: String,
+    pub description: String,
+    pub options: Vec<String>,
+    pub start_date: u64,
+    pub end_date: u64,
+    pub symbol: String,
+    /// Number of voters per option.
+    pub votes: Vec<u64>,
+    /// Tokens per option, scaled by the token decimals.
+    pub weights: Vec<f64>,
+    pub outcome: ProposalOutcome,
+    pub archived_at: u64,
+}
+
+/// Vote cast from Telegram on behalf of a linked wallet. Stored before the
+/// transaction is sent so a wallet can't vote twice on the same proposal.
+/// For multi-select and ranked ballots it holds the full ballot, counted only
+/// when the wallet's on-chain vote matches its first pick.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct VoteRecord {
+    pub proposal_id: String,
+    pub wallet_address: String,
+    pub user_id: u64,
+    pub choice_id: u64,
+    pub transaction_hash: Option<String>,
+    pub voted_at: u64,
+    /// Selected options, in order of preference for ranked ballots.
+    #[serde(default)]
+    pub choices: Vec<u64>,
+    /// Voting token balance (smallest units) when an off-chain ballot was cast.
+    #[serde(default)]
+    pub weight: u64,
 }
 
 impl From<(&CreateProposalRequest, String)> for ProposalEntry {
@@ -56,6 +218,45 @@ impl From<(&CreateProposalRequest, String)> for ProposalEntry {
             last_result_notification: now,
             disabled_notifications: false,
             thread_id: request.thread_id,
+            reminder_message_id: None,
+            quorum: None,
+            approval_threshold: None,
+            actions: Vec::new(),
+            ballot: BallotType::Single,
         }
     }
 }
+
+/// Steps of the /newproposal wizard, in order.
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, PartialOrd, Debug)]
+#[allow(clippy::enum_variant_names)]
+pub enum ProposalWizardStep {
+    AwaitingTitle,
+    AwaitingDescription,
+    AwaitingOptions,
+    AwaitingToken,
+    AwaitingTimezone,
+    AwaitingStart,
+    AwaitingEnd,
+    AwaitingConfirm,
+}
+
+/// Draft of a proposal being built with /newproposal.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ProposalWizardState {
+    pub group_id: String,
+    pub creator_user_id: u64,
+    pub step: ProposalWizardStep,
+    pub name: Option<String>,
+    pub description: Option<String>,
+    pub options: Vec<String>,
+    pub symbol: Option<String>,
+    /// IANA timezone name the start and end times are entered in.
+    #[serde(default)]
+    pub timezone: Option<String>,
+    pub start_date: Option<u64>,
+    pub end_date: Option<u64>,
+    pub thread_id: Option<i32>,
+    pub current_bot_message_id: Option<i32>,
+    pub user_message_ids: Vec<i32>,
+}
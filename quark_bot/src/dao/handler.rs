@@ -1,6 +1,6 @@
 use anyhow::Result as AnyResult;
 use chrono::Utc;
-use quark_core::helpers::dto::{CoinVersion, CreateProposalRequest};
+use quark_core::helpers::dto::{CoinVersion, CreateProposalRequest, VoteProposalRequest};
 use reqwest::Url;
 use teloxide::{
     prelude::*,
@@ -13,7 +13,11 @@ use teloxide::{
 use uuid::Uuid;
 
 use crate::{
-    dao::dto::ProposalEntry,
+    dao::{
+        dto::{BallotType, ProposalEntry, ProposalStatus, VoteRecord},
+        helpers::{describe_approval_threshold, describe_quorum},
+        payouts::resolve_actions,
+    },
     dependencies::BotDependencies,
     utils::{format_time_duration, send_html_message, send_message},
 };
@@ -187,6 +191,43 @@ pub async fn execute_create_proposal(
         return "❌ Start date cannot be more than 30 days in the future".to_string();
     }
 
+    let quorum = arguments["quorum"].as_f64();
+
+    if quorum.is_some_and(|quorum| quorum < 0.0) {
+        return "❌ Quorum can't be negative".to_string();
+    }
+
+    let approval_threshold = match arguments["approval_threshold"].as_u64() {
+        Some(threshold) if (1..=100).contains(&threshold) => Some(threshold as u8),
+        Some(_) => return "❌ Approval threshold must be between 1 and 100".to_string(),
+        None => None,
+    };
+
+    let ballot = match arguments["ballot_type"].as_str().unwrap_or("single") {
+        "single" => BallotType::Single,
+        "ranked_choice" if options.len() < 2 => {
+            return "❌ Ranked-choice proposals need at least 2 options".to_string();
+        }
+        "ranked_choice" => BallotType::RankedChoice,
+        "multi_select" => match arguments["max_choices"].as_u64() {
+            Some(max_choices) if (1..=options.len() as u64).contains(&max_choices) => {
+                BallotType::MultiSelect {
+                    max_choices: max_choices as u8,
+                }
+            }
+            Some(_) => {
+                return format!("❌ Max choices must be between 1 and {}", options.len());
+            }
+            None => return "❌ Max choices is required for multi-select proposals".to_string(),
+        },
+        _ => return "❌ Unknown ballot type".to_string(),
+    };
+
+    let actions = match resolve_actions(arguments, &options, &bot_deps).await {
+        Ok(actions) => actions,
+        Err(e) => return e,
+    };
+
     let token = bot_deps.panora.get_token_by_symbol(&symbol).await;
 
     if token.is_err() {
@@ -229,7 +270,11 @@ pub async fn execute_create_proposal(
 
     log::info!("Creating proposal with request: {:?}", request);
 
-    let proposal_entry = ProposalEntry::from((&request, group_id_formatted));
+    let mut proposal_entry = ProposalEntry::from((&request, group_id_formatted));
+    proposal_entry.quorum = quorum;
+    proposal_entry.approval_threshold = approval_threshold;
+    proposal_entry.actions = actions;
+    proposal_entry.ballot = ballot;
 
     let response = bot_deps.service.create_proposal(auth.jwt, request).await;
 
@@ -237,12 +282,22 @@ pub async fn execute_create_proposal(
         return "❌ Error creating proposal".to_string();
     }
 
+    let payout_options = proposal_entry.actions.len();
+
     let proposal_result = bot_deps.dao.create_dao(proposal_entry);
 
     if proposal_result.is_err() {
         return "❌ Error creating proposal".to_string();
     }
 
+    if payout_options > 0 {
+        return format!(
+            "Proposal created successfully: {}. Treasury payouts are attached to {} option(s); an admin confirms the payment once the vote passes.",
+            response.unwrap().hash,
+            payout_options
+        );
+    }
+
     return format!("Proposal created successfully: {}", response.unwrap().hash);
 }
 
@@ -933,6 +988,23 @@ pub async fn handle_dao_preference_callback(
                         group_id_formatted
                     )),
                 )],
+                vec![InlineKeyboardButton::new(
+                    format!("📉 Quorum: {}", describe_quorum(current_prefs.quorum)),
+                    InlineKeyboardButtonKind::CallbackData(format!(
+                        "dao_set_quorum_{}",
+                        group_id_formatted
+                    )),
+                )],
+                vec![InlineKeyboardButton::new(
+                    format!(
+                        "✅ Approval Threshold: {}",
+                        describe_approval_threshold(current_prefs.approval_threshold)
+                    ),
+                    InlineKeyboardButtonKind::CallbackData(format!(
+                        "dao_set_approval_{}",
+                        group_id_formatted
+                    )),
+                )],
                 vec![InlineKeyboardButton::new(
                     "↩️ Back",
                     InlineKeyboardButtonKind::CallbackData("back_to_group_settings".to_string()),
@@ -1036,6 +1108,23 @@ pub async fn handle_dao_preference_callback(
                         group_id_formatted
                     )),
                 )],
+                vec![InlineKeyboardButton::new(
+                    format!("📉 Quorum: {}", describe_quorum(current_prefs.quorum)),
+                    InlineKeyboardButtonKind::CallbackData(format!(
+                        "dao_set_quorum_{}",
+                        group_id_formatted
+                    )),
+                )],
+                vec![InlineKeyboardButton::new(
+                    format!(
+                        "✅ Approval Threshold: {}",
+                        describe_approval_threshold(current_prefs.approval_threshold)
+                    ),
+                    InlineKeyboardButtonKind::CallbackData(format!(
+                        "dao_set_approval_{}",
+                        group_id_formatted
+                    )),
+                )],
                 vec![InlineKeyboardButton::new(
                     "↩️ Back",
                     InlineKeyboardButtonKind::CallbackData("back_to_group_settings".to_string()),
@@ -1139,6 +1228,23 @@ pub async fn handle_dao_preference_callback(
                         group_id_formatted
                     )),
                 )],
+                vec![InlineKeyboardButton::new(
+                    format!("📉 Quorum: {}", describe_quorum(current_prefs.quorum)),
+                    InlineKeyboardButtonKind::CallbackData(format!(
+                        "dao_set_quorum_{}",
+                        group_id_formatted
+                    )),
+                )],
+                vec![InlineKeyboardButton::new(
+                    format!(
+                        "✅ Approval Threshold: {}",
+                        describe_approval_threshold(current_prefs.approval_threshold)
+                    ),
+                    InlineKeyboardButtonKind::CallbackData(format!(
+                        "dao_set_approval_{}",
+                        group_id_formatted
+                    )),
+                )],
                 vec![InlineKeyboardButton::new(
                     "↩️ Back",
                     InlineKeyboardButtonKind::CallbackData("back_to_group_settings".to_string()),
@@ -1242,6 +1348,23 @@ pub async fn handle_dao_preference_callback(
                         group_id_formatted
                     )),
                 )],
+                vec![InlineKeyboardButton::new(
+                    format!("📉 Quorum: {}", describe_quorum(current_prefs.quorum)),
+                    InlineKeyboardButtonKind::CallbackData(format!(
+                        "dao_set_quorum_{}",
+                        group_id_formatted
+                    )),
+                )],
+                vec![InlineKeyboardButton::new(
+                    format!(
+                        "✅ Approval Threshold: {}",
+                        describe_approval_threshold(current_prefs.approval_threshold)
+                    ),
+                    InlineKeyboardButtonKind::CallbackData(format!(
+                        "dao_set_approval_{}",
+                        group_id_formatted
+                    )),
+                )],
                 vec![InlineKeyboardButton::new(
                     "↩️ Back",
                     InlineKeyboardButtonKind::CallbackData("back_to_group_settings".to_string()),
@@ -1257,114 +1380,280 @@ pub async fn handle_dao_preference_callback(
             .reply_markup(keyboard)
             .await?;
         }
-    } else if data == "dao_preferences_back" {
-        // Go back to main preferences menu - just edit the message back to the main menu
-        let group_id = msg.chat.id.to_string();
-        let group_id_formatted = format!("{}-{}", group_id, bot_deps.group.account_seed);
+    } else if data.starts_with("dao_set_quorum_") {
+        let group_id = data.strip_prefix("dao_set_quorum_").unwrap();
 
-        // Clear any pending token input state
-        let user_id = query.from.id.0.to_string();
-        let key = format!("{}_{}", user_id, group_id_formatted);
-        bot_deps.dao.remove_pending_tokens(key).unwrap();
-        let current_prefs = match bot_deps
-            .dao
-            .get_dao_admin_preferences(group_id_formatted.clone())
-        {
-            Ok(prefs) => prefs,
-            Err(_) => return Ok(()),
+        let option = |label: &str, value: &str| {
+            InlineKeyboardButton::new(
+                label,
+                InlineKeyboardButtonKind::CallbackData(format!(
+                    "dao_quorum_{}_{}",
+                    group_id, value
+                )),
+            )
         };
 
         let keyboard = InlineKeyboardMarkup::new(vec![
+            vec![
+                option("Off", "off"),
+                option("100", "100"),
+                option("1K", "1000"),
+            ],
+            vec![
+                option("10K", "10000"),
+                option("100K", "100000"),
+                option("1M", "1000000"),
+            ],
             vec![InlineKeyboardButton::new(
-                format!(
-                    "🗑️ Deletion After Conclusion Duration: {}",
-                    format_time_duration(current_prefs.expiration_time)
-                ),
-                InlineKeyboardButtonKind::CallbackData(format!(
-                    "dao_set_expiration_{}",
-                    group_id_formatted
-                )),
-            )],
-            vec![InlineKeyboardButton::new(
-                format!(
-                    "🔔 Notification Interval: {}",
-                    format_time_duration(current_prefs.interval_active_proposal_notifications)
-                ),
-                InlineKeyboardButtonKind::CallbackData(format!(
-                    "dao_set_notifications_{}",
-                    group_id_formatted
-                )),
-            )],
-            vec![InlineKeyboardButton::new(
-                format!(
-                    "🔔 Results Notification Interval: {}",
-                    format_time_duration(current_prefs.interval_dao_results_notifications)
-                ),
-                InlineKeyboardButtonKind::CallbackData(format!(
-                    "dao_set_results_notifications_{}",
-                    group_id_formatted
-                )),
-            )],
-            vec![InlineKeyboardButton::new(
-                format!(
-                    "💰 DAO Token: {}",
-                    current_prefs
-                        .default_dao_token
-                        .as_ref()
-                        .unwrap_or(&"".to_string())
-                ),
-                InlineKeyboardButtonKind::CallbackData(format!(
-                    "dao_set_token_{}",
-                    group_id_formatted
-                )),
-            )],
-            vec![InlineKeyboardButton::new(
-                format!(
-                    "🗳️ Vote Duration: {}",
-                    format_time_duration(current_prefs.vote_duration.unwrap_or(24 * 60 * 60))
-                ),
-                InlineKeyboardButtonKind::CallbackData(format!(
-                    "dao_set_vote_duration_{}",
-                    group_id_formatted
-                )),
+                "🔙 Back",
+                InlineKeyboardButtonKind::CallbackData("dao_preferences_back".to_string()),
             )],
-            vec![InlineKeyboardButton::new(
-                "🔕 Manage Disabled Notifications",
+        ]);
+
+        bot.edit_message_text(
+            msg.chat.id,
+            msg.id,
+            "📉 <b>Select Quorum</b>\n\n\
+            Minimum number of tokens that must vote for a proposal to count. \
+            Proposals below it are announced as <b>Failed quorum</b>.",
+        )
+        .parse_mode(ParseMode::Html)
+        .reply_markup(keyboard)
+        .await?;
+    } else if data.starts_with("dao_set_approval_") {
+        let group_id = data.strip_prefix("dao_set_approval_").unwrap();
+
+        let option = |label: &str, value: &str| {
+            InlineKeyboardButton::new(
+                label,
                 InlineKeyboardButtonKind::CallbackData(format!(
-                    "dao_manage_disabled_{}",
-                    group_id_formatted
+                    "dao_approval_{}_{}",
+                    group_id, value
                 )),
-            )],
+            )
+        };
+
+        let keyboard = InlineKeyboardMarkup::new(vec![
+            vec![
+                option("Most votes", "off"),
+                option("50%", "50"),
+                option("60%", "60"),
+            ],
+            vec![
+                option("66%", "66"),
+                option("75%", "75"),
+                option("90%", "90"),
+            ],
             vec![InlineKeyboardButton::new(
-                "✅ Done",
-                InlineKeyboardButtonKind::CallbackData("dao_preferences_done".to_string()),
+                "🔙 Back",
+                InlineKeyboardButtonKind::CallbackData("dao_preferences_back".to_string()),
             )],
         ]);
 
-        let message_text = format!(
-            "🏛️ <b>DAO Admin Preferences</b>\n\n\
-            📊 <b>Current Settings:</b>\n\
-            🗑️ <b>Deletion After Conclusion Duration:</b> {}\n\
-            🔔 <b>Notification Interval:</b> {}\n\
-            💰 <b>DAO Token:</b> {}\n\
-            🗳️ <b>Vote Duration:</b> {}\n\n\
-            💡 <i>Click the buttons below to modify these settings</i>",
-            format_time_duration(current_prefs.expiration_time),
-            format_time_duration(current_prefs.interval_active_proposal_notifications),
-            current_prefs.default_dao_token.unwrap_or("".to_string()),
-            format_time_duration(current_prefs.vote_duration.unwrap_or(24 * 60 * 60))
-        );
+        bot.edit_message_text(
+            msg.chat.id,
+            msg.id,
+            "✅ <b>Select Approval Threshold</b>\n\n\
+            Share of the voted tokens the leading option needs to pass. \
+            Below it the proposal is announced as <b>Rejected</b>.",
+        )
+        .parse_mode(ParseMode::Html)
+        .reply_markup(keyboard)
+        .await?;
+    } else if data.starts_with("dao_quorum_") || data.starts_with("dao_approval_") {
+        let is_quorum = data.starts_with("dao_quorum_");
+        let rest = data
+            .strip_prefix("dao_quorum_")
+            .or_else(|| data.strip_prefix("dao_approval_"))
+            .unwrap();
 
-        bot.edit_message_text(msg.chat.id, msg.id, message_text)
-            .parse_mode(ParseMode::Html)
-            .reply_markup(keyboard)
+        let Some((group_id, value)) = rest.rsplit_once('_') else {
+            bot.answer_callback_query(query.id)
+                .text("❌ Invalid selection")
+                .await?;
+            return Ok(());
+        };
+
+        let mut prefs = match bot_deps.dao.get_dao_admin_preferences(group_id.to_string()) {
+            Ok(prefs) => prefs,
+            Err(_) => {
+                bot.answer_callback_query(query.id)
+                    .text("❌ Error: No admin preferences found for this group")
+                    .await?;
+                return Ok(());
+            }
+        };
+
+        let confirmation = if is_quorum {
+            prefs.quorum = value.parse::<f64>().ok();
+            format!("✅ Quorum updated to {}", describe_quorum(prefs.quorum))
+        } else {
+            prefs.approval_threshold = value.parse::<u8>().ok();
+            format!(
+                "✅ Approval threshold updated to {}",
+                describe_approval_threshold(prefs.approval_threshold)
+            )
+        };
+
+        if bot_deps
+            .dao
+            .set_dao_admin_preferences(group_id.to_string(), prefs)
+            .is_err()
+        {
+            bot.answer_callback_query(query.id)
+                .text("❌ Error updating preferences")
+                .await?;
+            return Ok(());
+        }
+
+        bot.answer_callback_query(query.id)
+            .text(confirmation)
             .await?;
+
+        show_dao_preferences_menu(&bot, msg, group_id, &bot_deps).await?;
+        return Ok(());
+    } else if data == "dao_preferences_back" {
+        // Go back to main preferences menu - just edit the message back to the main menu
+        let group_id = msg.chat.id.to_string();
+        let group_id_formatted = format!("{}-{}", group_id, bot_deps.group.account_seed);
+
+        // Clear any pending token input state
+        let user_id = query.from.id.0.to_string();
+        let key = format!("{}_{}", user_id, group_id_formatted);
+        bot_deps.dao.remove_pending_tokens(key).unwrap();
+        show_dao_preferences_menu(&bot, msg, &group_id_formatted, &bot_deps).await?;
     }
 
     bot.answer_callback_query(query.id).await?;
     Ok(())
 }
 
+/// Main DAO preferences menu with the current settings.
+async fn show_dao_preferences_menu(
+    bot: &Bot,
+    msg: &Message,
+    group_id_formatted: &str,
+    bot_deps: &BotDependencies,
+) -> AnyResult<()> {
+    let current_prefs = match bot_deps
+        .dao
+        .get_dao_admin_preferences(group_id_formatted.to_string())
+    {
+        Ok(prefs) => prefs,
+        Err(_) => return Ok(()),
+    };
+
+    let keyboard = InlineKeyboardMarkup::new(vec![
+        vec![InlineKeyboardButton::new(
+            format!(
+                "🗑️ Deletion After Conclusion Duration: {}",
+                format_time_duration(current_prefs.expiration_time)
+            ),
+            InlineKeyboardButtonKind::CallbackData(format!(
+                "dao_set_expiration_{}",
+                group_id_formatted
+            )),
+        )],
+        vec![InlineKeyboardButton::new(
+            format!(
+                "🔔 Notification Interval: {}",
+                format_time_duration(current_prefs.interval_active_proposal_notifications)
+            ),
+            InlineKeyboardButtonKind::CallbackData(format!(
+                "dao_set_notifications_{}",
+                group_id_formatted
+            )),
+        )],
+        vec![InlineKeyboardButton::new(
+            format!(
+                "🔔 Results Notification Interval: {}",
+                format_time_duration(current_prefs.interval_dao_results_notifications)
+            ),
+            InlineKeyboardButtonKind::CallbackData(format!(
+                "dao_set_results_notifications_{}",
+                group_id_formatted
+            )),
+        )],
+        vec![InlineKeyboardButton::new(
+            format!(
+                "💰 DAO Token: {}",
+                current_prefs
+                    .default_dao_token
+                    .as_ref()
+                    .unwrap_or(&"".to_string())
+            ),
+            InlineKeyboardButtonKind::CallbackData(format!("dao_set_token_{}", group_id_formatted)),
+        )],
+        vec![InlineKeyboardButton::new(
+            format!(
+                "🗳️ Vote Duration: {}",
+                format_time_duration(current_prefs.vote_duration.unwrap_or(24 * 60 * 60))
+            ),
+            InlineKeyboardButtonKind::CallbackData(format!(
+                "dao_set_vote_duration_{}",
+                group_id_formatted
+            )),
+        )],
+        vec![InlineKeyboardButton::new(
+            format!("📉 Quorum: {}", describe_quorum(current_prefs.quorum)),
+            InlineKeyboardButtonKind::CallbackData(format!(
+                "dao_set_quorum_{}",
+                group_id_formatted
+            )),
+        )],
+        vec![InlineKeyboardButton::new(
+            format!(
+                "✅ Approval Threshold: {}",
+                describe_approval_threshold(current_prefs.approval_threshold)
+            ),
+            InlineKeyboardButtonKind::CallbackData(format!(
+                "dao_set_approval_{}",
+                group_id_formatted
+            )),
+        )],
+        vec![InlineKeyboardButton::new(
+            "🔕 Manage Disabled Notifications",
+            InlineKeyboardButtonKind::CallbackData(format!(
+                "dao_manage_disabled_{}",
+                group_id_formatted
+            )),
+        )],
+        vec![InlineKeyboardButton::new(
+            "📋 Manage Proposals",
+            InlineKeyboardButtonKind::CallbackData("daop_list".to_string()),
+        )],
+        vec![InlineKeyboardButton::new(
+            "✅ Done",
+            InlineKeyboardButtonKind::CallbackData("dao_preferences_done".to_string()),
+        )],
+    ]);
+
+    let message_text = format!(
+        "🏛️ <b>DAO Admin Preferences</b>\n\n\
+        📊 <b>Current Settings:</b>\n\
+        🗑️ <b>Deletion After Conclusion Duration:</b> {}\n\
+        🔔 <b>Notification Interval:</b> {}\n\
+        💰 <b>DAO Token:</b> {}\n\
+        🗳️ <b>Vote Duration:</b> {}\n\
+        📉 <b>Quorum:</b> {}\n\
+        ✅ <b>Approval Threshold:</b> {}\n\n\
+        💡 <i>Click the buttons below to modify these settings</i>",
+        format_time_duration(current_prefs.expiration_time),
+        format_time_duration(current_prefs.interval_active_proposal_notifications),
+        current_prefs.default_dao_token.unwrap_or("".to_string()),
+        format_time_duration(current_prefs.vote_duration.unwrap_or(24 * 60 * 60)),
+        describe_quorum(current_prefs.quorum),
+        describe_approval_threshold(current_prefs.approval_threshold)
+    );
+
+    bot.edit_message_text(msg.chat.id, msg.id, message_text)
+        .parse_mode(ParseMode::Html)
+        .reply_markup(keyboard)
+        .await?;
+
+    Ok(())
+}
+
 pub async fn handle_disable_notifications_callback(
     bot: Bot,
     query: CallbackQuery,
@@ -1428,8 +1717,18 @@ pub async fn handle_disable_notifications_callback(
             // Remove the disable button from the keyboard
             let mut keyboard_buttons = Vec::new();
 
-            // Recreate the voting options
-            for (index, option) in proposal.options.iter().enumerate() {
+            // Recreate the voting options; off-chain ballots open from a private chat
+            let vote_options: &[String] = if proposal.ballot == BallotType::Single {
+                &proposal.options
+            } else {
+                keyboard_buttons.push(vec![InlineKeyboardButton::callback(
+                    "🗳️ Open ballot",
+                    format!("dao_ballot:{}", proposal.proposal_id),
+                )]);
+                &[]
+            };
+
+            for (index, option) in vote_options.iter().enumerate() {
                 let base_url = format!(
                     "{}/dao?group_id={}&proposal_id={}&choice_id={}&coin_type={}&coin_version={}&dao_name={}&dao_description={}",
                     app_url,
@@ -1458,10 +1757,13 @@ pub async fn handle_disable_notifications_callback(
                     }
                 };
 
-                keyboard_buttons.push(vec![InlineKeyboardButton::url(
-                    format!("🗳️ Vote: {}", option),
-                    parsed_url,
-                )]);
+                keyboard_buttons.push(vec![
+                    InlineKeyboardButton::url(format!("🗳️ Vote: {}", option), parsed_url),
+                    InlineKeyboardButton::callback(
+                        "⚡ Vote here",
+                        format!("dao_vote:{}:{}", proposal.proposal_id, index),
+                    ),
+                ]);
             }
 
             // Add voting help button
@@ -1562,3 +1864,296 @@ pub async fn handle_message_dao(
         return Ok(false);
     }
 }
+
+fn parse_vote_callback<'a>(data: &'a str, prefix: &str) -> Option<(&'a str, usize)> {
+    let (proposal_id, index) = data.strip_prefix(prefix)?.rsplit_once(':')?;
+    Some((proposal_id, index.parse().ok()?))
+}
+
+/// Proposal and option for a vote callback, or the reason voting isn't possible.
+fn votable_proposal(
+    bot_deps: &BotDependencies,
+    proposal_id: &str,
+    index: usize,
+) -> Result<(ProposalEntry, String), &'static str> {
+    let proposal = match bot_deps.dao.get_dao(proposal_id) {
+        Ok(Some(proposal)) => proposal,
+        Ok(None) => return Err("❌ Proposal not found"),
+        Err(e) => {
+            log::error!("Failed to get proposal {}: {}", proposal_id, e);
+            return Err("❌ Error retrieving proposal");
+        }
+    };
+
+    if proposal.status == ProposalStatus::Cancelled {
+        return Err("🛑 This proposal was cancelled");
+    }
+
+    let now = Utc::now().timestamp() as u64;
+    if now < proposal.start_date || now > proposal.end_date {
+        return Err("⏰ Voting is not open for this proposal");
+    }
+
+    let Some(option) = proposal.options.get(index).cloned() else {
+        return Err("❌ Unknown voting option");
+    };
+
+    Ok((proposal, option))
+}
+
+/// "⚡ Vote here" in the group: ask the voter to confirm in a private chat.
+pub async fn handle_dao_vote_callback(
+    bot: Bot,
+    query: CallbackQuery,
+    bot_deps: BotDependencies,
+) -> AnyResult<()> {
+    let data = query.data.clone().unwrap_or_default();
+
+    let Some((proposal_id, index)) = parse_vote_callback(&data, "dao_vote:") else {
+        bot.answer_callback_query(query.id)
+            .text("❌ Invalid vote")
+            .await?;
+        return Ok(());
+    };
+
+    let (proposal, option) = match votable_proposal(&bot_deps, proposal_id, index) {
+        Ok(found) => found,
+        Err(reason) => {
+            bot.answer_callback_query(query.id)
+                .text(reason)
+                .show_alert(true)
+                .await?;
+            return Ok(());
+        }
+    };
+
+    let Some(credentials) = bot_deps
+        .auth
+        .get_valid_credentials(query.from.id, query.from.username.as_deref())
+    else {
+        bot.answer_callback_query(query.id)
+            .text("🔗 Link your wallet first: open a chat with me and send /loginuser, then tap ⚡ Vote here again.")
+            .show_alert(true)
+            .await?;
+        return Ok(());
+    };
+
+    if let Ok(Some(_)) = bot_deps
+        .dao
+        .get_vote(&proposal.proposal_id, &credentials.resource_account_address)
+    {
+        bot.answer_callback_query(query.id)
+            .text("✅ Your wallet already voted on this proposal")
+            .show_alert(true)
+            .await?;
+        return Ok(());
+    }
+
+    let text = format!(
+        "🗳️ <b>Confirm your vote</b>\n\n\
+        🏛️ {}\n\
+        ✅ Choice: <b>{}</b>\n\
+        👛 Wallet: <code>{}</code>\n\n\
+        <i>Each wallet can vote once and votes can't be changed.</i>",
+        teloxide::utils::html::escape(&proposal.name),
+        teloxide::utils::html::escape(&option),
+        credentials.resource_account_address
+    );
+
+    let keyboard = InlineKeyboardMarkup::new(vec![vec![
+        InlineKeyboardButton::callback(
+            "✅ Confirm",
+            format!("dao_vote_confirm:{}:{}", proposal.proposal_id, index),
+        ),
+        InlineKeyboardButton::callback("❌ Cancel", "dao_vote_cancel"),
+    ]]);
+
+    let sent = bot
+        .send_message(ChatId::from(query.from.id), text)
+        .parse_mode(ParseMode::Html)
+        .reply_markup(keyboard)
+        .await;
+
+    if sent.is_err() {
+        bot.answer_callback_query(query.id)
+            .text("❌ I couldn't message you. Open a chat with me first, then tap ⚡ Vote here again.")
+            .show_alert(true)
+            .await?;
+        return Ok(());
+    }
+
+    bot.answer_callback_query(query.id)
+        .text("📩 Check your private chat with me to confirm your vote")
+        .await?;
+
+    Ok(())
+}
+
+/// Confirm/cancel buttons of the private vote confirmation.
+pub async fn handle_dao_vote_confirm_callback(
+    bot: Bot,
+    query: CallbackQuery,
+    bot_deps: BotDependencies,
+) -> AnyResult<()> {
+    let data = query.data.clone().unwrap_or_default();
+    let msg = match &query.message {
+        Some(MaybeInaccessibleMessage::Regular(message)) => message.clone(),
+        _ => return Ok(()),
+    };
+
+    if data == "dao_vote_cancel" {
+        bot.edit_message_text(msg.chat.id, msg.id, "❌ Vote cancelled")
+            .await?;
+        bot.answer_callback_query(query.id).await?;
+        return Ok(());
+    }
+
+    let Some((proposal_id, index)) = parse_vote_callback(&data, "dao_vote_confirm:") else {
+        bot.answer_callback_query(query.id)
+            .text("❌ Invalid vote")
+            .await?;
+        return Ok(());
+    };
+
+    let (proposal, option) = match votable_proposal(&bot_deps, proposal_id, index) {
+        Ok(found) => found,
+        Err(reason) => {
+            bot.edit_message_text(msg.chat.id, msg.id, reason).await?;
+            bot.answer_callback_query(query.id).await?;
+            return Ok(());
+        }
+    };
+
+    let Some(credentials) = bot_deps
+        .auth
+        .get_valid_credentials(query.from.id, query.from.username.as_deref())
+    else {
+        bot.edit_message_text(
+            msg.chat.id,
+            msg.id,
+            "🔗 Your wallet isn't linked anymore. Send /loginuser and try again.",
+        )
+        .await?;
+        bot.answer_callback_query(query.id).await?;
+        return Ok(());
+    };
+
+    let wallet = credentials.resource_account_address.clone();
+    let already_voted_text = format!(
+        "ℹ️ Your wallet already voted on <b>{}</b>.",
+        teloxide::utils::html::escape(&proposal.name)
+    );
+
+    match bot_deps
+        .panora
+        .aptos
+        .has_voted(
+            &proposal.group_id,
+            &proposal.proposal_id,
+            &proposal.version,
+            &wallet,
+        )
+        .await
+    {
+        Ok(true) => {
+            bot.edit_message_text(msg.chat.id, msg.id, already_voted_text)
+                .parse_mode(ParseMode::Html)
+                .await?;
+            bot.answer_callback_query(query.id).await?;
+            return Ok(());
+        }
+        Ok(false) => {}
+        // The contract rejects a second vote anyway, so don't block on the view call.
+        Err(e) => log::warn!(
+            "Failed to check vote of {} on {}: {}",
+            wallet,
+            proposal.proposal_id,
+            e
+        ),
+    }
+
+    let mut vote = VoteRecord {
+        proposal_id: proposal.proposal_id.clone(),
+        wallet_address: wallet.clone(),
+        user_id: query.from.id.0,
+        choice_id: index as u64,
+        transaction_hash: None,
+        voted_at: Utc::now().timestamp() as u64,
+        choices: Vec::new(),
+        weight: 0,
+    };
+
+    if !bot_deps.dao.reserve_vote(&vote)? {
+        bot.edit_message_text(msg.chat.id, msg.id, already_voted_text)
+            .parse_mode(ParseMode::Html)
+            .await?;
+        bot.answer_callback_query(query.id).await?;
+        return Ok(());
+    }
+
+    bot.answer_callback_query(query.id)
+        .text("⏳ Submitting your vote...")
+        .await?;
+    bot.edit_message_text(msg.chat.id, msg.id, "⏳ Submitting your vote...")
+        .await?;
+
+    let request = VoteProposalRequest {
+        group_id: proposal.group_id.clone(),
+        proposal_id: proposal.proposal_id.clone(),
+        choice_id: index as u64,
+        version: proposal.version.clone(),
+        currency: proposal.coin_type.clone(),
+    };
+
+    match bot_deps
+        .service
+        .vote_proposal(credentials.jwt, request)
+        .await
+    {
+        Ok(response) => {
+            vote.transaction_hash = Some(response.hash.clone());
+            if let Err(e) = bot_deps.dao.save_vote(&vote) {
+                log::error!("Failed to save vote hash for {}: {}", wallet, e);
+            }
+
+            let network = std::env::var("APTOS_NETWORK")
+                .unwrap_or("mainnet".to_string())
+                .to_lowercase();
+
+            bot.edit_message_text(
+                msg.chat.id,
+                msg.id,
+                format!(
+                    "✅ <b>Vote recorded!</b>\n\n🏛️ {}\n✅ Choice: <b>{}</b>\n\n🔗 <a href=\"https://explorer.aptoslabs.com/txn/{}?network={}\">View transaction</a>",
+                    teloxide::utils::html::escape(&proposal.name),
+                    teloxide::utils::html::escape(&option),
+                    response.hash,
+                    network
+                ),
+            )
+            .parse_mode(ParseMode::Html)
+            .await?;
+        }
+        Err(e) => {
+            log::error!(
+                "Failed to vote on {} for {}: {}",
+                proposal.proposal_id,
+                wallet,
+                e
+            );
+
+            if let Err(e) = bot_deps.dao.remove_vote(&proposal.proposal_id, &wallet) {
+                log::error!("Failed to release vote reservation for {}: {}", wallet, e);
+            }
+
+            bot.edit_message_text(
+                msg.chat.id,
+                msg.id,
+                "❌ Your vote couldn't be submitted. Make sure your wallet holds the voting token and try again.",
+            )
+            .await?;
+        }
+    }
+
+    Ok(())
+}
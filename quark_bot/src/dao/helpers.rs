@@ -0,0 +1,551 @@
+use chrono::{DateTime, NaiveDateTime, TimeZone};
+use chrono_tz::Tz;
+use teloxide::utils::html;
+
+use crate::aptos::handler::normalize_address;
+use crate::dao::dto::{
+    BallotType, ChainVote, ProposalArchive, ProposalOutcome, ProposalTally, RunoffRound, VoteRecord,
+};
+use crate::utils::csv_field;
+
+/// HTML block with votes and token-weighted totals for each option.
+pub fn format_tally(
+    options: &[String],
+    tally: &ProposalTally,
+    decimals: u8,
+    symbol: &str,
+) -> String {
+    let scale = 10_f64.powi(decimals as i32);
+    let total_votes: u64 = tally.votes.iter().sum();
+    let total_weight = tally.weights.iter().sum::<u64>() as f64 / scale;
+    let symbol = html::escape(symbol);
+
+    let mut text = String::from("📊 <b>Live tally</b>\n");
+
+    for (index, option) in options.iter().enumerate() {
+        let votes = tally.votes.get(index).copied().unwrap_or(0);
+        let weight = tally.weights.get(index).copied().unwrap_or(0) as f64 / scale;
+        let percentage = if total_weight > 0.0 {
+            weight / total_weight * 100.0
+        } else {
+            0.0
+        };
+
+        text.push_str(&format!(
+            "• {}: {} vote{} · {:.2} {} ({:.1}%)\n",
+            html::escape(option),
+            votes,
+            if votes == 1 { "" } else { "s" },
+            weight,
+            symbol,
+            percentage
+        ));
+    }
+
+    text.push_str(&format!(
+        "🗳️ Total: {} vote{} · {:.2} {}",
+        total_votes,
+        if total_votes == 1 { "" } else { "s" },
+        total_weight,
+        symbol
+    ));
+
+    text
+}
+
+/// Decide the outcome from per-option weights (in token units). Without a
+/// quorum any participation counts; without an approval threshold the
+/// leading option passes unless the lead is tied.
+pub fn evaluate_outcome(
+    weights: &[f64],
+    quorum: Option<f64>,
+    approval_threshold: Option<u8>,
+) -> ProposalOutcome {
+    let participation: f64 = weights.iter().sum();
+
+    evaluate_ballot(
+        weights,
+        participation,
+        participation,
+        quorum,
+        approval_threshold,
+    )
+}
+
+/// Like `evaluate_outcome` for ballots where the option weights don't add up
+/// to the participation: quorum is checked against `participation` and
+/// approval is the leader's share of `approval_base` (all voters for
+/// multi-select, the final round for ranked-choice).
+pub fn evaluate_ballot(
+    weights: &[f64],
+    participation: f64,
+    approval_base: f64,
+    quorum: Option<f64>,
+    approval_threshold: Option<u8>,
+) -> ProposalOutcome {
+    if participation <= 0.0 || quorum.is_some_and(|quorum| participation < quorum) {
+        return ProposalOutcome::FailedQuorum { participation };
+    }
+
+    let max = weights.iter().cloned().fold(0.0, f64::max);
+    let leaders: Vec<usize> = (0..weights.len()).filter(|&i| weights[i] == max).collect();
+    let approval = if approval_base > 0.0 {
+        max / approval_base * 100.0
+    } else {
+        0.0
+    };
+
+    if leaders.len() != 1 {
+        return ProposalOutcome::Rejected {
+            leader: None,
+            approval,
+        };
+    }
+
+    let leader = leaders[0];
+
+    if approval_threshold.is_some_and(|threshold| approval < threshold as f64) {
+        return ProposalOutcome::Rejected {
+            leader: Some(leader),
+            approval,
+        };
+    }
+
+    ProposalOutcome::Passed {
+        winner: leader,
+        approval,
+    }
+}
+
+/// Stored ballots backed by the wallet's own on-chain vote for their first
+/// pick, weighted by the balance the contract counted. Anything else is dropped.
+pub fn verified_ballots(votes: &[VoteRecord], chain_votes: &[ChainVote]) -> Vec<VoteRecord> {
+    votes
+        .iter()
+        .filter_map(|vote| {
+            let wallet = normalize_address(&vote.wallet_address);
+            let chain_vote = chain_votes
+                .iter()
+                .find(|chain_vote| chain_vote.voter == wallet)?;
+            if vote.choices.first() != Some(&chain_vote.choice_id) {
+                return None;
+            }
+            Some(VoteRecord {
+                weight: chain_vote.weight,
+                ..vote.clone()
+            })
+        })
+        .collect()
+}
+
+/// Live tally of off-chain ballots: every pick for multi-select, first
+/// preferences for ranked-choice.
+pub fn ballot_tally(ballot: BallotType, options_len: usize, votes: &[VoteRecord]) -> ProposalTally {
+    let mut tally = ProposalTally {
+        votes: vec![0; options_len],
+        weights: vec![0; options_len],
+    };
+
+    for vote in votes {
+        let counted = match ballot {
+            BallotType::RankedChoice => &vote.choices[..vote.choices.len().min(1)],
+            _ => &vote.choices[..],
+        };
+
+        for &choice in counted {
+            if let Some(count) = tally.votes.get_mut(choice as usize) {
+                *count += 1;
+                tally.weights[choice as usize] += vote.weight;
+            }
+        }
+    }
+
+    tally
+}
+
+/// Votes and weight per option when each pick gets the voter's full weight.
+pub fn approval_weights(options_len: usize, ballots: &[(Vec<usize>, f64)]) -> (Vec<u64>, Vec<f64>) {
+    let mut votes = vec![0; options_len];
+    let mut weights = vec![0.0; options_len];
+
+    for (choices, weight) in ballots {
+        for &choice in choices {
+            if choice < options_len {
+                votes[choice] += 1;
+                weights[choice] += weight;
+            }
+        }
+    }
+
+    (votes, weights)
+}
+
+/// Instant-runoff count over ranked ballots. Each round gives every ballot to
+/// its highest-ranked remaining option and drops the weakest one, until an
+/// option holds a majority of the counted weight, two options remain or all
+/// remaining options are tied. Ties for last place drop the option listed last.
+pub fn instant_runoff(options_len: usize, ballots: &[(Vec<usize>, f64)]) -> Vec<RunoffRound> {
+    let mut eliminated = vec![false; options_len];
+    let mut rounds = Vec::new();
+
+    loop {
+        let mut votes = vec![0; options_len];
+        let mut weights = vec![0.0; options_len];
+
+        for (ranking, weight) in ballots {
+            let preferred = ranking
+                .iter()
+                .copied()
+                .find(|&choice| choice < options_len && !eliminated[choice]);
+            if let Some(choice) = preferred {
+                votes[choice] += 1;
+                weights[choice] += weight;
+            }
+        }
+
+        let counted: f64 = weights.iter().sum();
+        let remaining: Vec<usize> = (0..options_len).filter(|&i| !eliminated[i]).collect();
+        let max = remaining.iter().map(|&i| weights[i]).fold(0.0, f64::max);
+        let min = remaining
+            .iter()
+            .map(|&i| weights[i])
+            .fold(f64::INFINITY, f64::min);
+
+        let loser = remaining.iter().copied().rfind(|&i| weights[i] == min);
+        let finished = (counted > 0.0 && max * 2.0 > counted) || remaining.len() <= 2 || min == max;
+
+        match loser {
+            Some(loser) if !finished => {
+                eliminated[loser] = true;
+                rounds.push(RunoffRound {
+                    votes,
+                    weights,
+                    eliminated: Some(loser),
+                });
+            }
+            _ => {
+                rounds.push(RunoffRound {
+                    votes,
+                    weights,
+                    eliminated: None,
+                });
+                return rounds;
+            }
+        }
+    }
+}
+
+pub fn describe_quorum(quorum: Option<f64>) -> String {
+    match quorum {
+        Some(quorum) => format!("{} tokens", quorum),
+        None => "Off".to_string(),
+    }
+}
+
+pub fn describe_approval_threshold(threshold: Option<u8>) -> String {
+    match threshold {
+        Some(threshold) => format!("{}%", threshold),
+        None => "Most votes".to_string(),
+    }
+}
+
+/// Short plain-text outcome, e.g. "Passed: Yes (62.5%)".
+pub fn describe_outcome(outcome: &ProposalOutcome, options: &[String]) -> String {
+    let option = |index: usize| options.get(index).map(String::as_str).unwrap_or("Unknown");
+
+    match outcome {
+        ProposalOutcome::Passed { winner, approval } => {
+            format!("Passed: {} ({:.1}%)", option(*winner), approval)
+        }
+        ProposalOutcome::Rejected {
+            leader: Some(leader),
+            approval,
+        } => format!("Rejected: {} led with {:.1}%", option(*leader), approval),
+        ProposalOutcome::Rejected { leader: None, .. } => "Rejected: tie".to_string(),
+        ProposalOutcome::FailedQuorum { .. } => "Failed quorum".to_string(),
+    }
+}
+
+fn format_csv_timestamp(timestamp: u64) -> String {
+    DateTime::from_timestamp(timestamp as i64, 0)
+        .map(|dt| dt.format("%Y-%m-%d %H:%M:%S").to_string())
+        .unwrap_or_default()
+}
+
+/// One row per archived proposal and option, so results can be pivoted in a
+/// spreadsheet.
+pub fn archive_to_csv(archive: &[ProposalArchive]) -> String {
+    let mut csv = String::from(
+        "proposal_id,name,description,start_date,end_date,token,outcome,option,votes,tokens,share\n",
+    );
+
+    for proposal in archive {
+        let total: f64 = proposal.weights.iter().sum();
+        let prefix = [
+            csv_field(&proposal.proposal_id),
+            csv_field(&proposal.name),
+            csv_field(&proposal.description),
+            format_csv_timestamp(proposal.start_date),
+            format_csv_timestamp(proposal.end_date),
+            csv_field(&proposal.symbol),
+            csv_field(&describe_outcome(&proposal.outcome, &proposal.options)),
+        ]
+        .join(",");
+
+        for (index, option) in proposal.options.iter().enumerate() {
+            let weight = proposal.weights.get(index).copied().unwrap_or(0.0);
+            let share = if total > 0.0 {
+                weight / total * 100.0
+            } else {
+                0.0
+            };
+
+            csv.push_str(&format!(
+                "{},{},{},{:.2},{:.2}\n",
+                prefix,
+                csv_field(option),
+                proposal.votes.get(index).copied().unwrap_or(0),
+                weight,
+                share
+            ));
+        }
+    }
+
+    csv
+}
+
+/// Unix timestamp of a `YYYY-MM-DD HH:MM` time entered in the given timezone.
+/// Times skipped by a daylight saving change don't exist and are rejected.
+pub fn parse_local_datetime(text: &str, tz: Tz) -> Option<u64> {
+    let naive = NaiveDateTime::parse_from_str(text.trim(), "%Y-%m-%d %H:%M").ok()?;
+    let timestamp = tz.from_local_datetime(&naive).earliest()?.timestamp();
+
+    u64::try_from(timestamp).ok()
+}
+
+/// `YYYY-MM-DD HH:MM (Zone/Name)` of a timestamp in the given timezone.
+pub fn format_local_datetime(timestamp: u64, tz: Tz) -> String {
+    DateTime::from_timestamp(timestamp as i64, 0)
+        .map(|dt| {
+            format!(
+                "{} ({})",
+                dt.with_timezone(&tz).format("%Y-%m-%d %H:%M"),
+                tz.name()
+            )
+        })
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn formats_votes_weights_and_shares() {
+        let options = vec!["Yes".to_string(), "No <maybe>".to_string()];
+        let tally = ProposalTally {
+            votes: vec![3, 1],
+            weights: vec![300_000_000, 100_000_000],
+        };
+
+        let text = format_tally(&options, &tally, 8, "APT");
+
+        assert!(text.contains("• Yes: 3 votes · 3.00 APT (75.0%)"));
+        assert!(text.contains("• No &lt;maybe&gt;: 1 vote · 1.00 APT (25.0%)"));
+        assert!(text.ends_with("🗳️ Total: 4 votes · 4.00 APT"));
+    }
+
+    #[test]
+    fn handles_no_votes_and_missing_choices() {
+        let options = vec!["A".to_string(), "B".to_string()];
+
+        let text = format_tally(&options, &ProposalTally::default(), 6, "USDC");
+
+        assert!(text.contains("• A: 0 votes · 0.00 USDC (0.0%)"));
+        assert!(text.contains("• B: 0 votes · 0.00 USDC (0.0%)"));
+    }
+
+    #[test]
+    fn passes_when_quorum_and_approval_are_met() {
+        assert_eq!(
+            evaluate_outcome(&[70.0, 30.0], Some(100.0), Some(60)),
+            ProposalOutcome::Passed {
+                winner: 0,
+                approval: 70.0
+            }
+        );
+    }
+
+    #[test]
+    fn fails_quorum_below_minimum_participation() {
+        assert_eq!(
+            evaluate_outcome(&[50.0, 10.0], Some(100.0), None),
+            ProposalOutcome::FailedQuorum {
+                participation: 60.0
+            }
+        );
+        assert_eq!(
+            evaluate_outcome(&[0.0, 0.0], None, None),
+            ProposalOutcome::FailedQuorum { participation: 0.0 }
+        );
+    }
+
+    #[test]
+    fn rejects_below_approval_or_on_tie() {
+        assert_eq!(
+            evaluate_outcome(&[50.0, 30.0, 20.0], None, Some(60)),
+            ProposalOutcome::Rejected {
+                leader: Some(0),
+                approval: 50.0
+            }
+        );
+        assert_eq!(
+            evaluate_outcome(&[40.0, 40.0, 20.0], None, None),
+            ProposalOutcome::Rejected {
+                leader: None,
+                approval: 40.0
+            }
+        );
+    }
+
+    #[test]
+    fn exports_one_row_per_option() {
+        let archive = ProposalArchive {
+            proposal_id: "p1".to_string(),
+            group_id: "-100-seed".to_string(),
+            name: "Audit, phase 2".to_string(),
+            description: "Pay the \"auditor\"".to_string(),
+            options: vec!["Yes".to_string(), "No".to_string()],
+            start_date: 0,
+            end_date: 86400,
+            symbol: "APT".to_string(),
+            votes: vec![3, 1],
+            weights: vec![75.0, 25.0],
+            outcome: ProposalOutcome::Passed {
+                winner: 0,
+                approval: 75.0,
+            },
+            archived_at: 86400,
+        };
+
+        let csv = archive_to_csv(&[archive]);
+        let rows: Vec<&str> = csv.lines().collect();
+
+        assert_eq!(rows.len(), 3);
+        assert_eq!(
+            rows[1],
+            "p1,\"Audit, phase 2\",\"Pay the \"\"auditor\"\"\",1970-01-01 00:00:00,1970-01-02 00:00:00,APT,Passed: Yes (75.0%),Yes,3,75.00,75.00"
+        );
+        assert!(rows[2].ends_with(",No,1,25.00,25.00"));
+    }
+
+    #[test]
+    fn multi_select_gives_each_pick_the_full_weight() {
+        let ballots = vec![(vec![0, 1], 10.0), (vec![1], 5.0)];
+
+        let (votes, weights) = approval_weights(3, &ballots);
+
+        assert_eq!(votes, vec![1, 2, 0]);
+        assert_eq!(weights, vec![10.0, 15.0, 0.0]);
+        assert_eq!(
+            evaluate_ballot(&weights, 15.0, 15.0, None, Some(60)),
+            ProposalOutcome::Passed {
+                winner: 1,
+                approval: 100.0
+            }
+        );
+    }
+
+    #[test]
+    fn instant_runoff_transfers_eliminated_ballots() {
+        // A leads first preferences, but C's voters prefer B
+        let ballots = vec![(vec![0], 40.0), (vec![1, 0], 35.0), (vec![2, 1], 25.0)];
+
+        let rounds = instant_runoff(3, &ballots);
+
+        assert_eq!(rounds.len(), 2);
+        assert_eq!(rounds[0].weights, vec![40.0, 35.0, 25.0]);
+        assert_eq!(rounds[0].eliminated, Some(2));
+        assert_eq!(rounds[1].weights, vec![40.0, 60.0, 0.0]);
+        assert_eq!(rounds[1].votes, vec![1, 2, 0]);
+        assert_eq!(rounds[1].eliminated, None);
+    }
+
+    #[test]
+    fn instant_runoff_stops_on_first_round_majority() {
+        let ballots = vec![(vec![0, 1], 60.0), (vec![1, 2], 30.0), (vec![2], 10.0)];
+
+        let rounds = instant_runoff(3, &ballots);
+
+        assert_eq!(rounds.len(), 1);
+        assert_eq!(rounds[0].eliminated, None);
+    }
+
+    #[test]
+    fn parses_and_formats_times_in_timezones() {
+        let timestamp = parse_local_datetime("2025-03-01 14:30", Tz::Asia__Kolkata).unwrap();
+
+        assert_eq!(timestamp, 1_740_819_600);
+        assert_eq!(
+            parse_local_datetime("2025-03-01 09:00", Tz::UTC),
+            Some(timestamp)
+        );
+        assert_eq!(
+            format_local_datetime(timestamp, Tz::America__Sao_Paulo),
+            "2025-03-01 06:00 (America/Sao_Paulo)"
+        );
+        assert_eq!(parse_local_datetime("01/03/2025 14:30", Tz::UTC), None);
+    }
+
+    #[test]
+    fn local_times_follow_daylight_saving() {
+        assert_eq!(
+            parse_local_datetime("2025-07-01 12:00", Tz::Europe__London),
+            Some(1_751_367_600)
+        );
+        // Clocks jump from 01:00 to 02:00 on the last Sunday of March
+        assert_eq!(
+            parse_local_datetime("2025-03-30 01:30", Tz::Europe__London),
+            None
+        );
+    }
+
+    #[test]
+    fn counts_only_ballots_backed_by_an_onchain_vote() {
+        let ballot = |wallet: &str, choices: Vec<u64>| VoteRecord {
+            proposal_id: "p".to_string(),
+            wallet_address: wallet.to_string(),
+            user_id: 1,
+            choice_id: choices[0],
+            transaction_hash: None,
+            voted_at: 0,
+            choices,
+            weight: 999,
+        };
+        let chain_votes = vec![
+            ChainVote {
+                voter: normalize_address("0xa"),
+                choice_id: 1,
+                weight: 40,
+            },
+            ChainVote {
+                voter: normalize_address("0xb"),
+                choice_id: 0,
+                weight: 25,
+            },
+        ];
+        let votes = vec![
+            ballot("0xA", vec![1, 2]),
+            // On-chain vote went to a different option
+            ballot("0xb", vec![2, 0]),
+            // Never voted on-chain
+            ballot("0xc", vec![0]),
+        ];
+
+        let verified = verified_ballots(&votes, &chain_votes);
+
+        assert_eq!(verified.len(), 1);
+        assert_eq!(verified[0].wallet_address, "0xA");
+        assert_eq!(verified[0].weight, 40);
+    }
+}
@@ -0,0 +1,476 @@
+use anyhow::Result;
+use chrono::Utc;
+use quark_core::helpers::dto::ExtendProposalRequest;
+use teloxide::{
+    prelude::*,
+    sugar::request::RequestReplyExt,
+    types::{InlineKeyboardButton, InlineKeyboardMarkup, MaybeInaccessibleMessage, ParseMode},
+    utils::html,
+};
+
+use crate::{
+    dao::dto::{ProposalEntry, ProposalStatus},
+    dependencies::BotDependencies,
+    utils::{
+        format_time_duration, format_timestamp, is_admin, send_message, send_scheduled_message,
+    },
+};
+
+/// Extensions offered for the end date, in seconds.
+const EXTEND_OPTIONS: [u64; 5] = [3600, 6 * 3600, 24 * 3600, 3 * 24 * 3600, 7 * 24 * 3600];
+
+fn is_pending(proposal: &ProposalEntry) -> bool {
+    (Utc::now().timestamp() as u64) < proposal.start_date
+}
+
+fn proposal_list(proposals: &[ProposalEntry]) -> (String, InlineKeyboardMarkup) {
+    if proposals.is_empty() {
+        return (
+            "📋 <b>Proposals</b>\n\nThere are no pending or active proposals in this group."
+                .to_string(),
+            InlineKeyboardMarkup::new(Vec::<Vec<InlineKeyboardButton>>::new()),
+        );
+    }
+
+    let rows = proposals
+        .iter()
+        .map(|proposal| {
+            let icon = if is_pending(proposal) {
+                "⏳"
+            } else {
+                "🗳️"
+            };
+            vec![InlineKeyboardButton::callback(
+                format!("{} {}", icon, proposal.name),
+                format!("daop_view:{}", proposal.proposal_id),
+            )]
+        })
+        .collect::<Vec<_>>();
+
+    (
+        "📋 <b>Proposals</b>\n\n⏳ pending · 🗳️ voting open\n\nSelect a proposal to manage it:"
+            .to_string(),
+        InlineKeyboardMarkup::new(rows),
+    )
+}
+
+fn proposal_details(proposal: &ProposalEntry) -> (String, InlineKeyboardMarkup) {
+    let pending = is_pending(proposal);
+
+    let text = format!(
+        "🏛️ <b>{}</b>\n\n📝 {}\n\n🔘 Options: {}\n🗓️ Starts: {}\n⏰ Ends: {}\n📊 Status: {}",
+        html::escape(&proposal.name),
+        html::escape(&proposal.description),
+        html::escape(&proposal.options.join(", ")),
+        format_timestamp(proposal.start_date),
+        format_timestamp(proposal.end_date),
+        if pending { "Pending" } else { "Voting open" }
+    );
+
+    let mut rows = Vec::new();
+
+    if pending {
+        rows.push(vec![
+            InlineKeyboardButton::callback(
+                "✏️ Edit description",
+                format!("daop_edit:{}", proposal.proposal_id),
+            ),
+            InlineKeyboardButton::callback(
+                "🛑 Cancel proposal",
+                format!("daop_cancel:{}", proposal.proposal_id),
+            ),
+        ]);
+    }
+
+    rows.push(vec![InlineKeyboardButton::callback(
+        "⏳ Extend voting",
+        format!("daop_extend:{}", proposal.proposal_id),
+    )]);
+    rows.push(vec![InlineKeyboardButton::callback("↩️ Back", "daop_list")]);
+
+    (text, InlineKeyboardMarkup::new(rows))
+}
+
+/// /manageproposals: list the group's pending and active proposals for admins.
+pub async fn handle_manage_proposals(
+    bot: Bot,
+    msg: Message,
+    bot_deps: BotDependencies,
+) -> Result<()> {
+    if !msg.chat.is_group() && !msg.chat.is_supergroup() {
+        send_message(
+            msg,
+            bot,
+            "❌ This command can only be used in a group".to_string(),
+        )
+        .await?;
+        return Ok(());
+    }
+
+    let Some(user) = msg.from.clone() else {
+        send_message(msg, bot, "❌ User not found".to_string()).await?;
+        return Ok(());
+    };
+
+    if !is_admin(&bot, msg.chat.id, user.id).await {
+        send_message(
+            msg,
+            bot,
+            "❌ Only group admins can manage proposals".to_string(),
+        )
+        .await?;
+        return Ok(());
+    }
+
+    let group_id = format!("{}-{}", msg.chat.id, bot_deps.group.account_seed);
+    let proposals = bot_deps.dao.get_open_group_daos(&group_id)?;
+    let (text, keyboard) = proposal_list(&proposals);
+
+    bot.send_message(msg.chat.id, text)
+        .parse_mode(ParseMode::Html)
+        .reply_markup(keyboard)
+        .reply_to(msg.id)
+        .await?;
+
+    Ok(())
+}
+
+/// Buttons of the proposal management menu (`daop_*`).
+pub async fn handle_proposal_lifecycle_callback(
+    bot: Bot,
+    query: CallbackQuery,
+    bot_deps: BotDependencies,
+) -> Result<()> {
+    let data = query.data.clone().unwrap_or_default();
+    let msg = match &query.message {
+        Some(MaybeInaccessibleMessage::Regular(message)) => message.clone(),
+        _ => return Ok(()),
+    };
+
+    if !is_admin(&bot, msg.chat.id, query.from.id).await {
+        bot.answer_callback_query(query.id)
+            .text("❌ Only group admins can manage proposals")
+            .await?;
+        return Ok(());
+    }
+
+    let group_id = format!("{}-{}", msg.chat.id, bot_deps.group.account_seed);
+
+    if data == "daop_list" {
+        let proposals = bot_deps.dao.get_open_group_daos(&group_id)?;
+        let (text, keyboard) = proposal_list(&proposals);
+        bot.edit_message_text(msg.chat.id, msg.id, text)
+            .parse_mode(ParseMode::Html)
+            .reply_markup(keyboard)
+            .await?;
+        bot.answer_callback_query(query.id).await?;
+        return Ok(());
+    }
+
+    let Some((action, rest)) = data.split_once(':') else {
+        bot.answer_callback_query(query.id).await?;
+        return Ok(());
+    };
+    let (proposal_id, argument) = match rest.split_once(':') {
+        Some((proposal_id, argument)) => (proposal_id, Some(argument)),
+        None => (rest, None),
+    };
+
+    let proposal = bot_deps.dao.get_dao(proposal_id)?.filter(|proposal| {
+        proposal.group_id == group_id && proposal.status != ProposalStatus::Cancelled
+    });
+
+    let Some(proposal) = proposal else {
+        bot.answer_callback_query(query.id)
+            .text("❌ Proposal not found or already cancelled")
+            .await?;
+        return Ok(());
+    };
+
+    let now = Utc::now().timestamp() as u64;
+    if proposal.end_date < now {
+        bot.answer_callback_query(query.id)
+            .text("⏰ Voting on this proposal has already ended")
+            .await?;
+        return Ok(());
+    }
+
+    match action {
+        "daop_view" => {
+            let (text, keyboard) = proposal_details(&proposal);
+            bot.edit_message_text(msg.chat.id, msg.id, text)
+                .parse_mode(ParseMode::Html)
+                .reply_markup(keyboard)
+                .await?;
+        }
+        "daop_cancel" => {
+            if !is_pending(&proposal) {
+                bot.answer_callback_query(query.id)
+                    .text("❌ Only proposals that haven't started can be cancelled")
+                    .await?;
+                return Ok(());
+            }
+
+            let keyboard = InlineKeyboardMarkup::new(vec![vec![
+                InlineKeyboardButton::callback(
+                    "🛑 Yes, cancel it",
+                    format!("daop_cancel_yes:{}", proposal.proposal_id),
+                ),
+                InlineKeyboardButton::callback(
+                    "↩️ Keep it",
+                    format!("daop_view:{}", proposal.proposal_id),
+                ),
+            ]]);
+            bot.edit_message_text(
+                msg.chat.id,
+                msg.id,
+                format!(
+                    "🛑 Cancel <b>{}</b>? Members will be told it won't go to a vote.",
+                    html::escape(&proposal.name)
+                ),
+            )
+            .parse_mode(ParseMode::Html)
+            .reply_markup(keyboard)
+            .await?;
+        }
+        "daop_cancel_yes" => {
+            if !is_pending(&proposal) {
+                bot.answer_callback_query(query.id)
+                    .text("❌ Voting has already started")
+                    .await?;
+                return Ok(());
+            }
+
+            bot_deps.dao.update_dao(&proposal.proposal_id, |dao| {
+                dao.status = ProposalStatus::Cancelled;
+                dao.disabled_notifications = true;
+            })?;
+
+            bot.edit_message_text(
+                msg.chat.id,
+                msg.id,
+                format!("🛑 <b>{}</b> was cancelled.", html::escape(&proposal.name)),
+            )
+            .parse_mode(ParseMode::Html)
+            .await?;
+
+            announce(
+                &bot,
+                msg.chat.id,
+                &proposal,
+                format!(
+                    "🛑 The proposal <b>{}</b> was cancelled by an admin and won't go to a vote.",
+                    html::escape(&proposal.name)
+                ),
+            )
+            .await;
+        }
+        "daop_extend" => match argument.and_then(|seconds| seconds.parse::<u64>().ok()) {
+            None => {
+                let rows = EXTEND_OPTIONS
+                    .chunks(3)
+                    .map(|chunk| {
+                        chunk
+                            .iter()
+                            .map(|seconds| {
+                                InlineKeyboardButton::callback(
+                                    format!("+{}", format_time_duration(*seconds)),
+                                    format!("daop_extend:{}:{}", proposal.proposal_id, seconds),
+                                )
+                            })
+                            .collect::<Vec<_>>()
+                    })
+                    .chain(std::iter::once(vec![InlineKeyboardButton::callback(
+                        "↩️ Back",
+                        format!("daop_view:{}", proposal.proposal_id),
+                    )]))
+                    .collect::<Vec<_>>();
+
+                bot.edit_message_text(
+                    msg.chat.id,
+                    msg.id,
+                    format!(
+                        "⏳ <b>Extend voting</b>\n\n🏛️ {}\n⏰ Currently ends: {}\n\nHow much longer should voting stay open?",
+                        html::escape(&proposal.name),
+                        format_timestamp(proposal.end_date)
+                    ),
+                )
+                .parse_mode(ParseMode::Html)
+                .reply_markup(InlineKeyboardMarkup::new(rows))
+                .await?;
+            }
+            Some(seconds) => {
+                let Some(credentials) = bot_deps.group.get_credentials(msg.chat.id) else {
+                    bot.answer_callback_query(query.id)
+                        .text("❌ Group not logged in")
+                        .await?;
+                    return Ok(());
+                };
+
+                let end_date = proposal.end_date + seconds;
+
+                bot.answer_callback_query(query.id.clone())
+                    .text("⏳ Updating the proposal on-chain...")
+                    .await?;
+
+                let request = ExtendProposalRequest {
+                    proposal_id: proposal.proposal_id.clone(),
+                    end_date,
+                    version: proposal.version.clone(),
+                };
+
+                if let Err(e) = bot_deps
+                    .service
+                    .extend_proposal(credentials.jwt, request)
+                    .await
+                {
+                    log::error!("Failed to extend proposal {}: {}", proposal.proposal_id, e);
+                    bot.edit_message_text(
+                        msg.chat.id,
+                        msg.id,
+                        "❌ The proposal couldn't be extended. Please try again later.",
+                    )
+                    .await?;
+                    return Ok(());
+                }
+
+                let Some(updated) = bot_deps.dao.update_dao(&proposal.proposal_id, |dao| {
+                    dao.end_date = end_date;
+                })?
+                else {
+                    return Ok(());
+                };
+
+                let (text, keyboard) = proposal_details(&updated);
+                bot.edit_message_text(msg.chat.id, msg.id, text)
+                    .parse_mode(ParseMode::Html)
+                    .reply_markup(keyboard)
+                    .await?;
+
+                announce(
+                    &bot,
+                    msg.chat.id,
+                    &updated,
+                    format!(
+                        "⏳ Voting on <b>{}</b> was extended until {}.",
+                        html::escape(&updated.name),
+                        format_timestamp(updated.end_date)
+                    ),
+                )
+                .await;
+                return Ok(());
+            }
+        },
+        "daop_edit" => {
+            if !is_pending(&proposal) {
+                bot.answer_callback_query(query.id)
+                    .text("❌ The description can only be edited before voting starts")
+                    .await?;
+                return Ok(());
+            }
+
+            bot_deps.dao.set_pending_description_edit(
+                &query.from.id.0.to_string(),
+                &group_id,
+                &proposal.proposal_id,
+            )?;
+
+            bot.edit_message_text(
+                msg.chat.id,
+                msg.id,
+                format!(
+                    "✏️ Send the new description for <b>{}</b> as your next message in this group.",
+                    html::escape(&proposal.name)
+                ),
+            )
+            .parse_mode(ParseMode::Html)
+            .reply_markup(InlineKeyboardMarkup::new(vec![vec![
+                InlineKeyboardButton::callback(
+                    "↩️ Back",
+                    format!("daop_view:{}", proposal.proposal_id),
+                ),
+            ]]))
+            .await?;
+        }
+        _ => {}
+    }
+
+    bot.answer_callback_query(query.id).await?;
+    Ok(())
+}
+
+/// Apply a pending description edit from the admin's message. Returns true when
+/// the message was consumed.
+pub async fn handle_description_edit_message(
+    bot: Bot,
+    msg: Message,
+    bot_deps: BotDependencies,
+    user_id: &str,
+    group_id: &str,
+) -> Result<bool> {
+    let Some(proposal_id) = bot_deps
+        .dao
+        .get_pending_description_edit(user_id, group_id)?
+    else {
+        return Ok(false);
+    };
+
+    let Some(description) = msg.text().map(str::trim).filter(|text| !text.is_empty()) else {
+        send_message(
+            msg,
+            bot,
+            "❌ Please send the new description as text".to_string(),
+        )
+        .await?;
+        return Ok(true);
+    };
+    let description = description.to_string();
+
+    bot_deps
+        .dao
+        .remove_pending_description_edit(user_id, group_id)?;
+
+    let proposal = bot_deps
+        .dao
+        .get_dao(&proposal_id)?
+        .filter(|proposal| proposal.status != ProposalStatus::Cancelled && is_pending(proposal));
+
+    let Some(proposal) = proposal else {
+        send_message(
+            msg,
+            bot,
+            "❌ Voting has already started, so the description can't be changed anymore"
+                .to_string(),
+        )
+        .await?;
+        return Ok(true);
+    };
+
+    bot_deps.dao.update_dao(&proposal.proposal_id, |dao| {
+        dao.description = description.clone();
+    })?;
+
+    announce(
+        &bot,
+        msg.chat.id,
+        &proposal,
+        format!(
+            "✏️ The description of <b>{}</b> was updated:\n\n{}",
+            html::escape(&proposal.name),
+            html::escape(&description)
+        ),
+    )
+    .await;
+
+    Ok(true)
+}
+
+async fn announce(bot: &Bot, chat_id: ChatId, proposal: &ProposalEntry, text: String) {
+    if let Err(e) = send_scheduled_message(bot, chat_id, &text, proposal.thread_id).await {
+        log::error!(
+            "Failed to announce change to proposal {}: {}",
+            proposal.proposal_id,
+            e
+        );
+    }
+}
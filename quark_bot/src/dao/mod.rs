@@ -1,3 +1,9 @@
+pub mod archive;
+pub mod ballot;
 pub mod dao;
 pub mod dto;
+pub mod helpers;
+pub mod lifecycle;
+pub mod payouts;
+pub mod wizard;
 pub mod handler;
@@ -0,0 +1,513 @@
+use anyhow::Result;
+use chrono::Utc;
+use quark_core::helpers::dto::{CoinVersion, PayUsersRequest};
+use teloxide::{
+    prelude::*,
+    types::{InlineKeyboardButton, InlineKeyboardMarkup, MaybeInaccessibleMessage, ParseMode},
+    utils::html,
+};
+
+use crate::{
+    dao::{
+        dao::Dao,
+        dto::{PayoutRecipient, PayoutStatus, ProposalAction, ProposalEntry, ProposalPayout},
+    },
+    dependencies::BotDependencies,
+    treasury::{
+        approvals::{approval_gate, request_approval},
+        dto::{PaymentSource, TreasuryPayment, TreasuryTransfer},
+    },
+    utils::send_scheduled_message_with_keyboard,
+};
+
+/// Parse the optional `actions` argument of the create_proposal tool. Usernames
+/// are resolved to wallets and amounts to smallest units now, so the payout
+/// can't change after members have voted.
+pub async fn resolve_actions(
+    arguments: &serde_json::Value,
+    options: &[String],
+    bot_deps: &BotDependencies,
+) -> Result<Vec<ProposalAction>, String> {
+    let Some(actions) = arguments["actions"].as_array() else {
+        return Ok(Vec::new());
+    };
+
+    let mut resolved: Vec<ProposalAction> = Vec::new();
+
+    for action in actions {
+        let option = action["option"].as_str().unwrap_or_default();
+        let Some(option_index) = options
+            .iter()
+            .position(|candidate| candidate.eq_ignore_ascii_case(option))
+        else {
+            return Err(format!(
+                "❌ Payout option \"{}\" is not one of the options",
+                option
+            ));
+        };
+
+        if resolved
+            .iter()
+            .any(|action| action.option_index == option_index)
+        {
+            return Err(format!("❌ Option \"{}\" has more than one payout", option));
+        }
+
+        let symbol = action["symbol"].as_str().unwrap_or("APT");
+
+        let (coin_type, version, decimals) =
+            if symbol.eq_ignore_ascii_case("apt") || symbol.eq_ignore_ascii_case("aptos") {
+                (
+                    "0x1::aptos_coin::AptosCoin".to_string(),
+                    CoinVersion::V1,
+                    8u8,
+                )
+            } else {
+                let token = bot_deps
+                    .panora
+                    .get_token_by_symbol(symbol)
+                    .await
+                    .map_err(|e| format!("❌ Error getting token {}: {}", symbol, e))?;
+
+                match token.token_address {
+                    Some(token_address) => (token_address, CoinVersion::V1, token.decimals),
+                    None => (token.fa_address, CoinVersion::V2, token.decimals),
+                }
+            };
+
+        let empty_vec = Vec::new();
+        let recipients = action["recipients"].as_array().unwrap_or(&empty_vec);
+
+        if recipients.is_empty() {
+            return Err(format!(
+                "❌ The payout for \"{}\" has no recipients",
+                option
+            ));
+        }
+
+        let mut payout_recipients = Vec::new();
+
+        for recipient in recipients {
+            let username = recipient["username"]
+                .as_str()
+                .unwrap_or_default()
+                .trim_start_matches('@');
+            let amount = recipient["amount"].as_f64().unwrap_or(0.0);
+
+            if amount <= 0.0 {
+                return Err(format!("❌ The payout to @{} must be above zero", username));
+            }
+
+            let Some(units) = smallest_units(amount, decimals) else {
+                return Err(format!(
+                    "❌ The payout to @{} is too small or not a valid amount",
+                    username
+                ));
+            };
+
+            let Some(credentials) = bot_deps.auth.get_credentials(username) else {
+                return Err(format!(
+                    "❌ @{} hasn't linked a wallet, so they can't be paid",
+                    username
+                ));
+            };
+
+            payout_recipients.push(PayoutRecipient {
+                username: username.to_string(),
+                address: credentials.resource_account_address,
+                amount: units,
+                transaction_hash: None,
+            });
+        }
+
+        resolved.push(ProposalAction {
+            option_index,
+            symbol: symbol.to_uppercase(),
+            coin_type,
+            version,
+            decimals,
+            recipients: payout_recipients,
+        });
+    }
+
+    Ok(resolved)
+}
+
+/// Converts a token amount to smallest units, rounding so 0.29 doesn't turn into
+/// 0.28999999. None when the result is zero, non-finite or out of range.
+pub fn smallest_units(amount: f64, decimals: u8) -> Option<u64> {
+    let units = (amount * 10_f64.powi(decimals as i32)).round();
+    (units.is_finite() && units >= 1.0 && units <= u64::MAX as f64).then_some(units as u64)
+}
+
+fn format_amount(amount: u64, decimals: u8) -> String {
+    format!("{:.2}", amount as f64 / 10_f64.powi(decimals as i32))
+}
+
+fn recipient_lines(action: &ProposalAction) -> String {
+    action
+        .recipients
+        .iter()
+        .map(|recipient| {
+            let status = if recipient.transaction_hash.is_some() {
+                " ✅"
+            } else {
+                ""
+            };
+            format!(
+                "• @{}: {} {}{}",
+                html::escape(&recipient.username),
+                format_amount(recipient.amount, action.decimals),
+                html::escape(&action.symbol),
+                status
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+fn treasury_payment(payout: &ProposalPayout) -> TreasuryPayment {
+    TreasuryPayment {
+        source: PaymentSource::DaoPayout {
+            proposal_id: payout.proposal_id.clone(),
+            proposal_name: payout.proposal_name.clone(),
+        },
+        symbol: payout.action.symbol.clone(),
+        coin_type: payout.action.coin_type.clone(),
+        version: payout.action.version.clone(),
+        decimals: payout.action.decimals,
+        transfers: payout
+            .action
+            .recipients
+            .iter()
+            .map(|recipient| TreasuryTransfer {
+                username: recipient.username.clone(),
+                address: recipient.address.clone(),
+                amount: recipient.amount,
+                transaction_hash: recipient.transaction_hash.clone(),
+            })
+            .collect(),
+    }
+}
+
+fn payout_keyboard(proposal_id: &str, confirm_label: &str) -> InlineKeyboardMarkup {
+    InlineKeyboardMarkup::new(vec![vec![
+        InlineKeyboardButton::callback(confirm_label, format!("dao_payout:{}:ok", proposal_id)),
+        InlineKeyboardButton::callback("❌ Decline", format!("dao_payout:{}:no", proposal_id)),
+    ]])
+}
+
+/// Post the payout attached to the winning option for admins to confirm.
+/// Runs once per proposal; later result notifications are no-ops.
+pub async fn prepare_payout(
+    bot: &Bot,
+    dao: &Dao,
+    chat_id: ChatId,
+    proposal: &ProposalEntry,
+    winner: usize,
+) -> Result<()> {
+    let Some(action) = proposal
+        .actions
+        .iter()
+        .find(|action| action.option_index == winner)
+    else {
+        return Ok(());
+    };
+
+    let payout = ProposalPayout {
+        proposal_id: proposal.proposal_id.clone(),
+        group_id: proposal.group_id.clone(),
+        proposal_name: proposal.name.clone(),
+        option: proposal.options.get(winner).cloned().unwrap_or_default(),
+        action: action.clone(),
+        status: PayoutStatus::AwaitingApproval,
+        created_at: Utc::now().timestamp() as u64,
+        resolved_by: None,
+    };
+
+    if !dao.create_payout(&payout)? {
+        return Ok(());
+    }
+
+    let text = format!(
+        "💸 <b>Treasury payout ready</b>\n\n🏛️ <b>{}</b> passed with \"{}\".\n\n{}\n\n🔐 An admin must confirm before anything is sent from the group wallet.",
+        html::escape(&payout.proposal_name),
+        html::escape(&payout.option),
+        recipient_lines(&payout.action)
+    );
+
+    send_scheduled_message_with_keyboard(
+        bot,
+        chat_id,
+        &text,
+        proposal.thread_id,
+        payout_keyboard(&payout.proposal_id, "✅ Confirm payment"),
+    )
+    .await?;
+
+    Ok(())
+}
+
+/// Admin answer to a prepared payout (`dao_payout:{proposal_id}:{ok|no}`).
+pub async fn handle_payout_callback(
+    bot: Bot,
+    query: CallbackQuery,
+    bot_deps: BotDependencies,
+) -> Result<()> {
+    let data = query.data.clone().unwrap_or_default();
+    let msg = match &query.message {
+        Some(MaybeInaccessibleMessage::Regular(message)) => message.clone(),
+        _ => return Ok(()),
+    };
+
+    let Some((proposal_id, answer)) = data
+        .strip_prefix("dao_payout:")
+        .and_then(|rest| rest.rsplit_once(':'))
+    else {
+        bot.answer_callback_query(query.id).await?;
+        return Ok(());
+    };
+
+    let admins = bot.get_chat_administrators(msg.chat.id).await?;
+    if !admins.iter().any(|admin| admin.user.id == query.from.id) {
+        bot.answer_callback_query(query.id)
+            .text("❌ Only group admins can approve payouts")
+            .await?;
+        return Ok(());
+    }
+
+    let group_id = format!("{}-{}", msg.chat.id, bot_deps.group.account_seed);
+    let is_group_payout = bot_deps
+        .dao
+        .get_payout(proposal_id)?
+        .is_some_and(|payout| payout.group_id == group_id);
+
+    if !is_group_payout {
+        bot.answer_callback_query(query.id)
+            .text("❌ Payout not found")
+            .await?;
+        return Ok(());
+    }
+
+    let status = if answer == "ok" {
+        PayoutStatus::Executing
+    } else {
+        PayoutStatus::Declined
+    };
+
+    let Some(mut payout) =
+        bot_deps
+            .dao
+            .resolve_payout(proposal_id, query.from.id.0, status.clone())?
+    else {
+        bot.answer_callback_query(query.id)
+            .text("ℹ️ This payout was already handled")
+            .await?;
+        return Ok(());
+    };
+
+    let admin = query
+        .from
+        .username
+        .clone()
+        .map(|username| format!("@{}", username))
+        .unwrap_or_else(|| query.from.first_name.clone());
+
+    if status == PayoutStatus::Declined {
+        bot.edit_message_text(
+            msg.chat.id,
+            msg.id,
+            format!(
+                "🚫 <b>Treasury payout declined</b>\n\n🏛️ <b>{}</b>\n\n{}\n\nDeclined by {}.",
+                html::escape(&payout.proposal_name),
+                recipient_lines(&payout.action),
+                html::escape(&admin)
+            ),
+        )
+        .parse_mode(ParseMode::Html)
+        .await?;
+        bot.answer_callback_query(query.id).await?;
+        return Ok(());
+    }
+
+    // Above the group's threshold, the payout also needs other admins
+    let payment = treasury_payment(&payout);
+    if let Some(gate) = approval_gate(&bot_deps, msg.chat.id, &payment).await {
+        if let Err(e) = request_approval(
+            &bot,
+            &bot_deps,
+            msg.chat.id,
+            payment,
+            gate,
+            Some(&query.from),
+            Some(msg.id),
+        )
+        .await
+        {
+            // Let admins try again from the payout message
+            payout.status = PayoutStatus::Failed;
+            bot_deps.dao.save_payout(&payout)?;
+            return Err(e);
+        }
+        bot.answer_callback_query(query.id)
+            .text("🔐 Sent for admin approval")
+            .await?;
+        return Ok(());
+    }
+
+    let Some(credentials) = bot_deps.group.get_credentials(msg.chat.id) else {
+        payout.status = PayoutStatus::Failed;
+        bot_deps.dao.save_payout(&payout)?;
+        bot.answer_callback_query(query.id)
+            .text("❌ Group not logged in")
+            .await?;
+        return Ok(());
+    };
+
+    bot.answer_callback_query(query.id)
+        .text("💸 Sending payment...")
+        .await?;
+
+    let mut failures = Vec::new();
+
+    for recipient in payout
+        .action
+        .recipients
+        .iter_mut()
+        .filter(|recipient| recipient.transaction_hash.is_none())
+    {
+        let request = PayUsersRequest {
+            amount: recipient.amount,
+            users: vec![recipient.address.clone()],
+            coin_type: payout.action.coin_type.clone(),
+            version: payout.action.version.clone(),
+        };
+
+        match bot_deps
+            .service
+            .pay_members(credentials.jwt.clone(), request)
+            .await
+        {
+            Ok(response) => recipient.transaction_hash = Some(response.hash),
+            Err(e) => {
+                log::error!(
+                    "Failed to pay @{} for proposal {}: {}",
+                    recipient.username,
+                    payout.proposal_id,
+                    e
+                );
+                failures.push(recipient.username.clone());
+            }
+        }
+    }
+
+    payout.status = if failures.is_empty() {
+        PayoutStatus::Paid
+    } else {
+        PayoutStatus::Failed
+    };
+    bot_deps.dao.save_payout(&payout)?;
+
+    let network = std::env::var("APTOS_NETWORK")
+        .unwrap_or("mainnet".to_string())
+        .to_lowercase();
+
+    let transactions = payout
+        .action
+        .recipients
+        .iter()
+        .filter_map(|recipient| {
+            recipient.transaction_hash.as_ref().map(|hash| {
+                format!(
+                    "🔗 @{}: <a href=\"https://explorer.aptoslabs.com/txn/{}?network={}\">{}</a>",
+                    html::escape(&recipient.username),
+                    hash,
+                    network,
+                    hash
+                )
+            })
+        })
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    if failures.is_empty() {
+        bot.edit_message_text(
+            msg.chat.id,
+            msg.id,
+            format!(
+                "✅ <b>Treasury payout sent</b>\n\n🏛️ <b>{}</b>\n\n{}\n\n{}\n\nApproved by {}.",
+                html::escape(&payout.proposal_name),
+                recipient_lines(&payout.action),
+                transactions,
+                html::escape(&admin)
+            ),
+        )
+        .parse_mode(ParseMode::Html)
+        .await?;
+    } else {
+        bot.edit_message_text(
+            msg.chat.id,
+            msg.id,
+            format!(
+                "⚠️ <b>Treasury payout incomplete</b>\n\n🏛️ <b>{}</b>\n\n{}\n\n{}\n\n❌ Couldn't pay: {}\n\nRetrying only pays the recipients without a ✅.",
+                html::escape(&payout.proposal_name),
+                recipient_lines(&payout.action),
+                transactions,
+                failures
+                    .iter()
+                    .map(|username| format!("@{}", html::escape(username)))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
+        )
+        .parse_mode(ParseMode::Html)
+        .reply_markup(payout_keyboard(&payout.proposal_id, "🔁 Retry payment"))
+        .await?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn amounts_round_to_smallest_units() {
+        assert_eq!(smallest_units(0.29, 8), Some(29_000_000));
+        assert_eq!(smallest_units(1.5, 6), Some(1_500_000));
+        assert_eq!(smallest_units(0.000_000_001, 8), None);
+        assert_eq!(smallest_units(f64::INFINITY, 8), None);
+        assert_eq!(smallest_units(f64::NAN, 8), None);
+    }
+
+    #[test]
+    fn lists_recipients_and_marks_paid_ones() {
+        let action = ProposalAction {
+            option_index: 0,
+            symbol: "APT".to_string(),
+            coin_type: "0x1::aptos_coin::AptosCoin".to_string(),
+            version: CoinVersion::V1,
+            decimals: 8,
+            recipients: vec![
+                PayoutRecipient {
+                    username: "dev".to_string(),
+                    address: "0xa".to_string(),
+                    amount: 50_000_000_000,
+                    transaction_hash: Some("0xhash".to_string()),
+                },
+                PayoutRecipient {
+                    username: "auditor".to_string(),
+                    address: "0xb".to_string(),
+                    amount: 12_500_000,
+                    transaction_hash: None,
+                },
+            ],
+        };
+
+        assert_eq!(
+            recipient_lines(&action),
+            "• @dev: 500.00 APT ✅\n• @auditor: 0.12 APT"
+        );
+    }
+}
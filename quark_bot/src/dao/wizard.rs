@@ -0,0 +1,716 @@
+use anyhow::Result;
+use chrono::Utc;
+use chrono_tz::Tz;
+use quark_core::helpers::dto::{CoinVersion, CreateProposalRequest};
+use teloxide::{
+    prelude::*,
+    types::{
+        InlineKeyboardButton, InlineKeyboardMarkup, MaybeInaccessibleMessage, MessageId, ParseMode,
+        ThreadId,
+    },
+    utils::html,
+};
+use uuid::Uuid;
+
+use crate::{
+    dao::{
+        dto::{ProposalEntry, ProposalWizardState, ProposalWizardStep},
+        helpers::{format_local_datetime, parse_local_datetime},
+    },
+    dependencies::BotDependencies,
+    utils::{WizardCleanupTargets, cleanup_and_transition, delete_message_safe, send_message},
+};
+
+/// Timezones offered by the picker; any other IANA name can be typed in.
+const TIMEZONES: [&str; 18] = [
+    "UTC",
+    "America/Los_Angeles",
+    "America/Denver",
+    "America/Chicago",
+    "America/New_York",
+    "America/Sao_Paulo",
+    "Europe/London",
+    "Europe/Berlin",
+    "Europe/Istanbul",
+    "Africa/Lagos",
+    "Asia/Dubai",
+    "Asia/Kolkata",
+    "Asia/Bangkok",
+    "Asia/Singapore",
+    "Asia/Shanghai",
+    "Asia/Tokyo",
+    "Australia/Sydney",
+    "Pacific/Auckland",
+];
+
+/// Voting lengths offered at the end time step, in seconds.
+const DURATIONS: [(&str, u64); 4] = [
+    ("24h", 24 * 3600),
+    ("48h", 48 * 3600),
+    ("72h", 72 * 3600),
+    ("1 week", 7 * 24 * 3600),
+];
+
+/// Same limit as proposals created through the AI tool.
+const MAX_START_DELAY: u64 = 30 * 24 * 60 * 60;
+
+fn nav_row(back_enabled: bool) -> Vec<InlineKeyboardButton> {
+    let mut row = Vec::new();
+    if back_enabled {
+        row.push(InlineKeyboardButton::callback("↩️ Back", "daonew_back"));
+    }
+    row.push(InlineKeyboardButton::callback("❌ Cancel", "daonew_cancel"));
+    row
+}
+
+fn previous_step(step: ProposalWizardStep) -> Option<ProposalWizardStep> {
+    match step {
+        ProposalWizardStep::AwaitingTitle => None,
+        ProposalWizardStep::AwaitingDescription => Some(ProposalWizardStep::AwaitingTitle),
+        ProposalWizardStep::AwaitingOptions => Some(ProposalWizardStep::AwaitingDescription),
+        ProposalWizardStep::AwaitingToken => Some(ProposalWizardStep::AwaitingOptions),
+        ProposalWizardStep::AwaitingTimezone => Some(ProposalWizardStep::AwaitingToken),
+        ProposalWizardStep::AwaitingStart => Some(ProposalWizardStep::AwaitingTimezone),
+        ProposalWizardStep::AwaitingEnd => Some(ProposalWizardStep::AwaitingStart),
+        ProposalWizardStep::AwaitingConfirm => Some(ProposalWizardStep::AwaitingEnd),
+    }
+}
+
+/// Clear the answers of `step` and every step after it.
+fn reset_from_step(state: &mut ProposalWizardState, step: ProposalWizardStep) {
+    if step <= ProposalWizardStep::AwaitingTitle {
+        state.name = None;
+    }
+    if step <= ProposalWizardStep::AwaitingDescription {
+        state.description = None;
+    }
+    if step <= ProposalWizardStep::AwaitingOptions {
+        state.options.clear();
+    }
+    if step <= ProposalWizardStep::AwaitingToken {
+        state.symbol = None;
+    }
+    if step <= ProposalWizardStep::AwaitingTimezone {
+        state.timezone = None;
+    }
+    if step <= ProposalWizardStep::AwaitingStart {
+        state.start_date = None;
+    }
+    if step <= ProposalWizardStep::AwaitingEnd {
+        state.end_date = None;
+    }
+}
+
+fn extract_cleanup_targets(state: &mut ProposalWizardState) -> WizardCleanupTargets {
+    WizardCleanupTargets {
+        user_message_ids: std::mem::take(&mut state.user_message_ids),
+        bot_message_id: state.current_bot_message_id.take(),
+    }
+}
+
+fn default_token(bot_deps: &BotDependencies, group_id: &str) -> Option<String> {
+    bot_deps
+        .dao
+        .get_dao_admin_preferences(group_id.to_string())
+        .ok()
+        .and_then(|preferences| preferences.default_dao_token)
+}
+
+/// Timezone picked for the draft, UTC until one is chosen.
+fn draft_timezone(state: &ProposalWizardState) -> Tz {
+    state
+        .timezone
+        .as_deref()
+        .and_then(|name| name.parse().ok())
+        .unwrap_or(Tz::UTC)
+}
+
+fn preview(state: &ProposalWizardState) -> String {
+    let tz = draft_timezone(state);
+    let when = |timestamp: Option<u64>| {
+        timestamp
+            .map(|timestamp| format_local_datetime(timestamp, tz))
+            .unwrap_or_else(|| "—".to_string())
+    };
+    let options = state
+        .options
+        .iter()
+        .map(|option| format!("• {}", html::escape(option)))
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    format!(
+        "👀 <b>Preview</b>\n\n🏛️ <b>{}</b>\n\n{}\n\n<b>Options:</b>\n{}\n\n💳 Token: {}\n⏰ Starts: {}\n⌛ Ends: {}\n\n<i>Check the details, then create the proposal.</i>",
+        html::escape(state.name.as_deref().unwrap_or("")),
+        html::escape(state.description.as_deref().unwrap_or("")),
+        options,
+        html::escape(state.symbol.as_deref().unwrap_or("")),
+        when(state.start_date),
+        when(state.end_date)
+    )
+}
+
+fn step_message(
+    state: &ProposalWizardState,
+    default_token: Option<&str>,
+) -> (String, InlineKeyboardMarkup) {
+    let timezone = draft_timezone(state).name();
+
+    match state.step {
+        ProposalWizardStep::AwaitingTitle => (
+            "🏛️ <b>New proposal</b>\n\nSend the proposal title.".to_string(),
+            InlineKeyboardMarkup::new(vec![nav_row(false)]),
+        ),
+        ProposalWizardStep::AwaitingDescription => (
+            "📝 Send the proposal description.".to_string(),
+            InlineKeyboardMarkup::new(vec![nav_row(true)]),
+        ),
+        ProposalWizardStep::AwaitingOptions => (
+            "🔢 Send the voting options, one per line (at least 2).".to_string(),
+            InlineKeyboardMarkup::new(vec![nav_row(true)]),
+        ),
+        ProposalWizardStep::AwaitingToken => {
+            let mut rows = Vec::new();
+            if let Some(symbol) = default_token {
+                rows.push(vec![InlineKeyboardButton::callback(
+                    format!("Use {}", symbol),
+                    "daonew_token_default",
+                )]);
+            }
+            rows.push(nav_row(true));
+            (
+                "💳 Send the symbol of the token that weighs the votes (e.g. APT, USDC)."
+                    .to_string(),
+                InlineKeyboardMarkup::new(rows),
+            )
+        }
+        ProposalWizardStep::AwaitingTimezone => {
+            let mut rows: Vec<Vec<InlineKeyboardButton>> = TIMEZONES
+                .chunks(2)
+                .map(|chunk| {
+                    chunk
+                        .iter()
+                        .map(|&name| {
+                            InlineKeyboardButton::callback(name, format!("daonew_tz:{}", name))
+                        })
+                        .collect()
+                })
+                .collect();
+            rows.push(nav_row(true));
+            (
+                "🌍 Pick the timezone you'll enter the start and end times in, or send its name (e.g. <code>Europe/Madrid</code>).".to_string(),
+                InlineKeyboardMarkup::new(rows),
+            )
+        }
+        ProposalWizardStep::AwaitingStart => (
+            format!(
+                "⏰ Send the start time as <code>YYYY-MM-DD HH:MM</code> ({}), or start voting right away.",
+                timezone
+            ),
+            InlineKeyboardMarkup::new(vec![
+                vec![InlineKeyboardButton::callback(
+                    "▶️ Start now",
+                    "daonew_start_now",
+                )],
+                nav_row(true),
+            ]),
+        ),
+        ProposalWizardStep::AwaitingEnd => (
+            format!(
+                "⌛ Send the end time as <code>YYYY-MM-DD HH:MM</code> ({}), or pick how long voting stays open.",
+                timezone
+            ),
+            InlineKeyboardMarkup::new(vec![
+                DURATIONS
+                    .iter()
+                    .map(|(label, seconds)| {
+                        InlineKeyboardButton::callback(*label, format!("daonew_end:{}", seconds))
+                    })
+                    .collect(),
+                nav_row(true),
+            ]),
+        ),
+        ProposalWizardStep::AwaitingConfirm => (
+            preview(state),
+            InlineKeyboardMarkup::new(vec![
+                vec![InlineKeyboardButton::callback(
+                    "✅ Create proposal",
+                    "daonew_confirm",
+                )],
+                nav_row(true),
+            ]),
+        ),
+    }
+}
+
+/// Send the prompt of the current step and save the state.
+async fn send_step(
+    bot: &Bot,
+    bot_deps: &BotDependencies,
+    chat_id: ChatId,
+    user_id: &str,
+    state: &mut ProposalWizardState,
+) -> Result<()> {
+    let default_token = if state.step == ProposalWizardStep::AwaitingToken {
+        default_token(bot_deps, &state.group_id)
+    } else {
+        None
+    };
+    let (text, keyboard) = step_message(state, default_token.as_deref());
+
+    let mut request = bot
+        .send_message(chat_id, text)
+        .parse_mode(ParseMode::Html)
+        .reply_markup(keyboard);
+    if let Some(thread_id) = state.thread_id {
+        request = request.message_thread_id(ThreadId(MessageId(thread_id)));
+    }
+
+    match request.await {
+        Ok(sent) => state.current_bot_message_id = Some(sent.id.0),
+        Err(e) => log::error!("Failed to send proposal wizard step: {}", e),
+    }
+
+    bot_deps.dao.save_proposal_wizard(user_id, state)?;
+
+    Ok(())
+}
+
+/// Symbol of a token known to Panora.
+async fn resolve_symbol(bot_deps: &BotDependencies, input: &str) -> Option<String> {
+    let symbol = if input.chars().any(|c| c.is_ascii_alphabetic()) {
+        input.to_uppercase()
+    } else {
+        input.to_string()
+    };
+
+    match bot_deps.panora.get_token_by_symbol(&symbol).await {
+        Ok(_) => Some(symbol),
+        Err(e) => {
+            log::warn!("Unknown proposal token {}: {}", symbol, e);
+            None
+        }
+    }
+}
+
+/// /newproposal: walk an admin through creating a proposal.
+pub async fn handle_new_proposal_command(
+    bot: Bot,
+    msg: Message,
+    bot_deps: BotDependencies,
+) -> Result<()> {
+    if !msg.chat.is_group() && !msg.chat.is_supergroup() {
+        send_message(
+            msg,
+            bot,
+            "❌ This command can only be used in a group".to_string(),
+        )
+        .await?;
+        return Ok(());
+    }
+
+    let Some(user) = msg.from.clone() else {
+        return Ok(());
+    };
+
+    let admins = bot.get_chat_administrators(msg.chat.id).await?;
+    if !admins.iter().any(|admin| admin.user.id == user.id) {
+        send_message(
+            msg,
+            bot,
+            "❌ Only group admins can create proposals.".to_string(),
+        )
+        .await?;
+        return Ok(());
+    }
+
+    if bot_deps.group.get_credentials(msg.chat.id).is_none() {
+        send_message(
+            msg,
+            bot,
+            "❌ Error getting credentials, maybe the group is not logged in".to_string(),
+        )
+        .await?;
+        return Ok(());
+    }
+
+    let user_id = user.id.0.to_string();
+    let group_id = format!("{}-{}", msg.chat.id, bot_deps.group.account_seed);
+
+    // Starting over replaces an unfinished draft
+    if let Some(mut previous) = bot_deps.dao.get_proposal_wizard(&user_id, &group_id)? {
+        cleanup_and_transition(
+            &bot,
+            msg.chat.id,
+            None,
+            &mut previous,
+            extract_cleanup_targets,
+        )
+        .await;
+    }
+
+    let mut state = ProposalWizardState {
+        group_id,
+        creator_user_id: user.id.0,
+        step: ProposalWizardStep::AwaitingTitle,
+        name: None,
+        description: None,
+        options: Vec::new(),
+        symbol: None,
+        timezone: None,
+        start_date: None,
+        end_date: None,
+        thread_id: msg.thread_id.map(|thread_id| thread_id.0.0),
+        current_bot_message_id: None,
+        user_message_ids: Vec::new(),
+    };
+
+    send_step(&bot, &bot_deps, msg.chat.id, &user_id, &mut state).await
+}
+
+/// Text answers to the wizard's steps. Returns false when the user has no
+/// draft or the current step expects a button.
+pub async fn handle_message_proposal_wizard(
+    bot: Bot,
+    msg: Message,
+    bot_deps: BotDependencies,
+    user_id: &str,
+    group_id: &str,
+) -> Result<bool> {
+    let Some(mut state) = bot_deps.dao.get_proposal_wizard(user_id, group_id)? else {
+        return Ok(false);
+    };
+
+    let text = msg.text().unwrap_or("").trim().to_string();
+    if text.is_empty() || text.starts_with('/') {
+        return Ok(true);
+    }
+
+    let now = Utc::now().timestamp() as u64;
+    let tz = draft_timezone(&state);
+
+    let outcome: Result<(), String> = match state.step {
+        ProposalWizardStep::AwaitingTitle => {
+            state.name = Some(text);
+            state.step = ProposalWizardStep::AwaitingDescription;
+            Ok(())
+        }
+        ProposalWizardStep::AwaitingDescription => {
+            state.description = Some(text);
+            state.step = ProposalWizardStep::AwaitingOptions;
+            Ok(())
+        }
+        ProposalWizardStep::AwaitingOptions => {
+            let mut options: Vec<String> = Vec::new();
+            for option in text.lines().map(str::trim).filter(|line| !line.is_empty()) {
+                if !options.iter().any(|existing| existing == option) {
+                    options.push(option.to_string());
+                }
+            }
+
+            if options.len() < 2 {
+                Err("❌ Send at least 2 different options, one per line.".to_string())
+            } else {
+                state.options = options;
+                state.step = ProposalWizardStep::AwaitingToken;
+                Ok(())
+            }
+        }
+        ProposalWizardStep::AwaitingToken => match resolve_symbol(&bot_deps, &text).await {
+            Some(symbol) => {
+                state.symbol = Some(symbol);
+                state.step = ProposalWizardStep::AwaitingTimezone;
+                Ok(())
+            }
+            None => Err("❌ Token not found. Try again (e.g., APT, USDC)".to_string()),
+        },
+        ProposalWizardStep::AwaitingTimezone => match text.parse::<Tz>() {
+            Ok(tz) => {
+                state.timezone = Some(tz.name().to_string());
+                state.step = ProposalWizardStep::AwaitingStart;
+                Ok(())
+            }
+            Err(_) => Err(
+                "❌ Unknown timezone. Send a name like Europe/Madrid or pick one above."
+                    .to_string(),
+            ),
+        },
+        ProposalWizardStep::AwaitingStart => match parse_local_datetime(&text, tz) {
+            None => Err("❌ Invalid time. Use YYYY-MM-DD HH:MM.".to_string()),
+            Some(start) if start < now => Err(
+                "❌ That time has already passed. Send a later time or tap ▶️ Start now."
+                    .to_string(),
+            ),
+            Some(start) if start > now + MAX_START_DELAY => {
+                Err("❌ Start date cannot be more than 30 days in the future".to_string())
+            }
+            Some(start) => {
+                state.start_date = Some(start);
+                state.step = ProposalWizardStep::AwaitingEnd;
+                Ok(())
+            }
+        },
+        ProposalWizardStep::AwaitingEnd => match parse_local_datetime(&text, tz) {
+            None => Err("❌ Invalid time. Use YYYY-MM-DD HH:MM.".to_string()),
+            Some(end) if end <= state.start_date.unwrap_or(now) => {
+                Err("❌ The end time must be after the start time.".to_string())
+            }
+            Some(end) => {
+                state.end_date = Some(end);
+                state.step = ProposalWizardStep::AwaitingConfirm;
+                Ok(())
+            }
+        },
+        ProposalWizardStep::AwaitingConfirm => {
+            return Ok(false);
+        }
+    };
+
+    if let Err(reason) = outcome {
+        send_message(msg, bot, reason).await?;
+        return Ok(true);
+    }
+
+    cleanup_and_transition(
+        &bot,
+        msg.chat.id,
+        Some(msg.id.0),
+        &mut state,
+        extract_cleanup_targets,
+    )
+    .await;
+    send_step(&bot, &bot_deps, msg.chat.id, user_id, &mut state).await?;
+
+    Ok(true)
+}
+
+/// Create the proposal on chain and store it, returning the confirmation text.
+async fn create_proposal(
+    bot_deps: &BotDependencies,
+    chat_id: ChatId,
+    state: &ProposalWizardState,
+) -> Result<String, &'static str> {
+    let (Some(name), Some(description), Some(symbol), Some(start_date), Some(end_date)) = (
+        state.name.clone(),
+        state.description.clone(),
+        state.symbol.clone(),
+        state.start_date,
+        state.end_date,
+    ) else {
+        return Err("❌ The proposal is incomplete, go back and fill in every step");
+    };
+
+    if end_date <= Utc::now().timestamp() as u64 {
+        return Err("❌ The end time has passed, go back and pick a new one");
+    }
+
+    let Some(auth) = bot_deps.group.get_credentials(chat_id) else {
+        return Err("❌ Group not logged in");
+    };
+
+    let token = match bot_deps.panora.get_token_by_symbol(&symbol).await {
+        Ok(token) => token,
+        Err(e) => {
+            log::error!("Failed to get proposal token {}: {}", symbol, e);
+            return Err("❌ Error getting token address");
+        }
+    };
+
+    let (version, currency) = match token.token_address {
+        Some(token_address) => (CoinVersion::V1, token_address),
+        None => (CoinVersion::V2, token.fa_address),
+    };
+
+    let request = CreateProposalRequest {
+        name,
+        description,
+        options: state.options.clone(),
+        start_date,
+        end_date,
+        proposal_id: Uuid::new_v4().to_string(),
+        version,
+        currency,
+        thread_id: state.thread_id,
+    };
+
+    let proposal = ProposalEntry::from((&request, state.group_id.clone()));
+
+    let response = match bot_deps.service.create_proposal(auth.jwt, request).await {
+        Ok(response) => response,
+        Err(e) => {
+            log::error!("Failed to create proposal from wizard: {}", e);
+            return Err("❌ Error creating proposal");
+        }
+    };
+
+    if let Err(e) = bot_deps.dao.create_dao(proposal.clone()) {
+        log::error!("Failed to store proposal {}: {}", proposal.proposal_id, e);
+        return Err("❌ Error creating proposal");
+    }
+
+    let tz = draft_timezone(state);
+
+    Ok(format!(
+        "✅ <b>Proposal created!</b>\n\n🏛️ {}\n⏰ {} → {}\n\n🔗 Transaction: <code>{}</code>",
+        html::escape(&proposal.name),
+        format_local_datetime(proposal.start_date, tz),
+        format_local_datetime(proposal.end_date, tz),
+        response.hash
+    ))
+}
+
+/// Buttons of the /newproposal wizard (`daonew_*`).
+pub async fn handle_proposal_wizard_callback(
+    bot: Bot,
+    query: CallbackQuery,
+    bot_deps: BotDependencies,
+) -> Result<()> {
+    let data = query.data.clone().unwrap_or_default();
+    let msg = match &query.message {
+        Some(MaybeInaccessibleMessage::Regular(message)) => message.clone(),
+        _ => return Ok(()),
+    };
+
+    let admins = bot.get_chat_administrators(msg.chat.id).await?;
+    if !admins.iter().any(|admin| admin.user.id == query.from.id) {
+        bot.answer_callback_query(query.id)
+            .text("❌ Admins only")
+            .await?;
+        return Ok(());
+    }
+
+    let user_id = query.from.id.0.to_string();
+    let group_id = format!("{}-{}", msg.chat.id, bot_deps.group.account_seed);
+
+    let Some(mut state) = bot_deps.dao.get_proposal_wizard(&user_id, &group_id)? else {
+        bot.answer_callback_query(query.id)
+            .text("ℹ️ No proposal draft in progress")
+            .await?;
+        return Ok(());
+    };
+
+    let (action, argument) = data.split_once(':').unwrap_or((data.as_str(), ""));
+
+    let expected_step = match action {
+        "daonew_token_default" => Some(ProposalWizardStep::AwaitingToken),
+        "daonew_tz" => Some(ProposalWizardStep::AwaitingTimezone),
+        "daonew_start_now" => Some(ProposalWizardStep::AwaitingStart),
+        "daonew_end" => Some(ProposalWizardStep::AwaitingEnd),
+        "daonew_confirm" => Some(ProposalWizardStep::AwaitingConfirm),
+        _ => None,
+    };
+    if expected_step.is_some_and(|step| step != state.step) {
+        bot.answer_callback_query(query.id)
+            .text("ℹ️ This step is no longer active")
+            .await?;
+        return Ok(());
+    }
+
+    match action {
+        "daonew_cancel" => {
+            cleanup_and_transition(&bot, msg.chat.id, None, &mut state, extract_cleanup_targets)
+                .await;
+            bot_deps.dao.remove_proposal_wizard(&user_id, &group_id)?;
+            bot.answer_callback_query(query.id)
+                .text("✅ Cancelled")
+                .await?;
+            return Ok(());
+        }
+        "daonew_back" => {
+            let Some(previous) = previous_step(state.step) else {
+                bot.answer_callback_query(query.id)
+                    .text("ℹ️ Already at first step")
+                    .await?;
+                return Ok(());
+            };
+            reset_from_step(&mut state, previous);
+            state.step = previous;
+        }
+        "daonew_token_default" => {
+            let symbol = match default_token(&bot_deps, &group_id) {
+                Some(symbol) => resolve_symbol(&bot_deps, &symbol).await,
+                None => None,
+            };
+            let Some(symbol) = symbol else {
+                bot.answer_callback_query(query.id)
+                    .text("❌ Token not found, send a symbol instead")
+                    .await?;
+                return Ok(());
+            };
+            state.symbol = Some(symbol);
+            state.step = ProposalWizardStep::AwaitingTimezone;
+        }
+        "daonew_tz" => {
+            let Ok(tz) = argument.parse::<Tz>() else {
+                bot.answer_callback_query(query.id)
+                    .text("❌ Unknown timezone")
+                    .await?;
+                return Ok(());
+            };
+            state.timezone = Some(tz.name().to_string());
+            state.step = ProposalWizardStep::AwaitingStart;
+        }
+        "daonew_start_now" => {
+            state.start_date = Some(Utc::now().timestamp() as u64);
+            state.step = ProposalWizardStep::AwaitingEnd;
+        }
+        "daonew_end" => {
+            let Some(seconds) = argument
+                .parse::<u64>()
+                .ok()
+                .filter(|seconds| DURATIONS.iter().any(|(_, duration)| duration == seconds))
+            else {
+                bot.answer_callback_query(query.id)
+                    .text("❌ Unknown duration")
+                    .await?;
+                return Ok(());
+            };
+            let start = state
+                .start_date
+                .unwrap_or_else(|| Utc::now().timestamp() as u64);
+            state.end_date = Some(start + seconds);
+            state.step = ProposalWizardStep::AwaitingConfirm;
+        }
+        "daonew_confirm" => {
+            // Claim the draft so a second tap can't create the proposal twice
+            let Some(state) = bot_deps.dao.take_proposal_wizard(&user_id, &group_id)? else {
+                bot.answer_callback_query(query.id)
+                    .text("ℹ️ The proposal is already being created")
+                    .await?;
+                return Ok(());
+            };
+            match create_proposal(&bot_deps, msg.chat.id, &state).await {
+                Ok(text) => {
+                    for message_id in state.user_message_ids {
+                        delete_message_safe(&bot, msg.chat.id, message_id).await;
+                    }
+                    bot.edit_message_text(msg.chat.id, msg.id, text)
+                        .parse_mode(ParseMode::Html)
+                        .await?;
+                    bot.answer_callback_query(query.id)
+                        .text("✅ Proposal created")
+                        .await?;
+                }
+                Err(reason) => {
+                    // Put the draft back so the admin can fix it and retry
+                    bot_deps.dao.save_proposal_wizard(&user_id, &state)?;
+                    bot.answer_callback_query(query.id)
+                        .text(reason)
+                        .show_alert(true)
+                        .await?;
+                }
+            }
+            return Ok(());
+        }
+        _ => {
+            bot.answer_callback_query(query.id).await?;
+            return Ok(());
+        }
+    }
+
+    cleanup_and_transition(&bot, msg.chat.id, None, &mut state, extract_cleanup_targets).await;
+    bot.answer_callback_query(query.id).await?;
+    send_step(&bot, &bot_deps, msg.chat.id, &user_id, &mut state).await?;
+
+    Ok(())
+}
@@ -15,6 +15,7 @@ use crate::{
     filters::filters::Filters,
     group::{document_library::GroupDocuments, handler::Group},
     message_history::handler::HistoryStorage,
+    nft_gate::nft_gate::NftGate,
     panora::handler::Panora,
     payment::dto::PaymentPrefs,
     payment::payment::Payment,
@@ -24,6 +25,7 @@ use crate::{
     services::handler::Services,
     sponsor::sponsor::Sponsor,
     summarization_settings::SummarizationSettings,
+    treasury::handler::TreasuryApprovals,
     user_conversation::handler::UserConversations,
     welcome::welcome_service::WelcomeService,
     yield_ai::yield_ai::YieldAI,
@@ -53,6 +55,7 @@ pub struct BotDependencies {
     pub media_aggregator: Arc<MediaGroupAggregator>,
     pub history_storage: HistoryStorage,
     pub pending_transactions: PendingTransactions,
+    pub treasury: TreasuryApprovals,
     pub yield_ai: YieldAI,
     pub scheduler: JobScheduler,
     pub payment: Payment,
@@ -63,5 +66,6 @@ pub struct BotDependencies {
     pub sponsor: Sponsor,
     pub summarization_settings: SummarizationSettings,
     pub welcome_service: WelcomeService,
+    pub nft_gate: NftGate,
     pub summarizer: SummarizerService,
 }
@@ -1,3 +1,5 @@
+use std::collections::BTreeMap;
+
 use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -11,6 +13,31 @@ pub struct FilterDefinition {
     pub match_type: MatchType,
     pub response_type: ResponseType,
     pub id: String,
+    #[serde(default)]
+    pub active_window: Option<FilterActiveWindow>,
+    /// How long a generated AI reply is reused, in seconds. 0 disables caching.
+    #[serde(default)]
+    pub cache_ttl_seconds: u64,
+    /// Only members holding the group's VIP NFT role trigger this filter.
+    #[serde(default)]
+    pub vip_only: bool,
+}
+
+/// Restricts when a filter answers. All set constraints must hold.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct FilterActiveWindow {
+    /// Days of week the filter answers on, 0 = Monday .. 6 = Sunday. Empty means every day.
+    #[serde(default)]
+    pub days: Vec<u8>,
+    /// Daily window in minutes after local midnight. An end before the start
+    /// spans midnight and belongs to the day it starts on.
+    pub start_minute: Option<u16>,
+    pub end_minute: Option<u16>,
+    /// IANA timezone name used for days and hours
+    pub timezone: String,
+    /// Absolute bounds as unix timestamps
+    pub starts_at: Option<i64>,
+    pub ends_at: Option<i64>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
@@ -25,6 +52,14 @@ pub enum MatchType {
 pub enum ResponseType {
     Text,
     Markdown,
+    /// The stored response is a prompt; the reply is generated by the group's AI
+    Ai,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct CachedAiResponse {
+    pub text: String,
+    pub created_at: i64,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -45,6 +80,9 @@ pub struct FilterStats {
     pub usage_count: u64,
     pub last_triggered: Option<i64>,
     pub last_triggered_by: Option<i64>,
+    /// Triggers per UTC day, keyed by `YYYY-MM-DD`, trimmed to the retention window
+    #[serde(default)]
+    pub daily_usage: BTreeMap<String, u64>,
 }
 
 #[derive(Debug, Clone)]
@@ -78,9 +116,11 @@ pub struct ValidationResult {
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[allow(clippy::enum_variant_names)]
 pub enum PendingFilterStep {
     AwaitingTrigger,
     AwaitingResponse,
+    AwaitingActiveWindow,
     AwaitingConfirm,
 }
 
@@ -93,6 +133,12 @@ pub struct PendingFilterWizardState {
     pub response: Option<String>,
     pub match_type: MatchType,
     pub response_type: ResponseType,
+    #[serde(default)]
+    pub active_window: Option<FilterActiveWindow>,
+    #[serde(default)]
+    pub cache_ttl_seconds: u64,
+    #[serde(default)]
+    pub vip_only: bool,
 }
 
 #[derive(Debug, Clone)]
@@ -139,6 +185,9 @@ impl From<(String, String, String, i64, MatchType, ResponseType)> for FilterDefi
             match_type,
             response_type,
             id,
+            active_window: None,
+            cache_ttl_seconds: 0,
+            vip_only: false,
         }
     }
 }
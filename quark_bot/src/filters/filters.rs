@@ -1,10 +1,24 @@
+use std::sync::Arc;
+
 use anyhow::Result;
+use dashmap::DashMap;
 use sled::{Db, Tree};
 
 use crate::filters::dto::{
-    FilterDefinition, FilterError, FilterMatch, FilterMetadata, FilterStats, MatchType,
+    CachedAiResponse, FilterDefinition, FilterError, FilterMatch, FilterMetadata, FilterStats,
     PendingFilterWizardState, ValidationResult,
 };
+use crate::filters::helpers::{is_window_open, record_daily_usage};
+use crate::filters::matcher::GroupMatcher;
+
+/// A group's cached matcher, tagged with how many times its filters changed.
+/// The generation outlives the matcher so a build that raced a change can't
+/// be cached over it.
+#[derive(Default)]
+struct CachedMatcher {
+    generation: u64,
+    matcher: Option<Arc<GroupMatcher>>,
+}
 
 #[derive(Clone)]
 pub struct Filters {
@@ -12,7 +26,9 @@ pub struct Filters {
     pub metadata_db: Tree,
     pub stats_db: Tree,
     pub settings_db: Tree,
+    pub ai_cache_db: Tree,
     pub account_seed: String,
+    matchers: Arc<DashMap<String, CachedMatcher>>,
 }
 
 impl Filters {
@@ -29,6 +45,9 @@ impl Filters {
         let settings_db = db
             .open_tree("filter_settings")
             .expect("Failed to open filter settings tree");
+        let ai_cache_db = db
+            .open_tree("filter_ai_cache")
+            .expect("Failed to open filter AI cache tree");
 
         let account_seed =
             std::env::var("ACCOUNT_SEED").expect("ACCOUNT_SEED environment variable not found");
@@ -38,7 +57,9 @@ impl Filters {
             metadata_db,
             stats_db,
             settings_db,
+            ai_cache_db,
             account_seed,
+            matchers: Arc::new(DashMap::new()),
         }
     }
 
@@ -86,6 +107,7 @@ impl Filters {
             usage_count: 0,
             last_triggered: None,
             last_triggered_by: None,
+            daily_usage: Default::default(),
         };
         let stats_key = self.format_key(&filter.group_id, &filter.id);
         let stats_bytes =
@@ -95,6 +117,8 @@ impl Filters {
             .insert(&stats_key, stats_bytes)
             .map_err(|e| FilterError::DatabaseError(e.to_string()))?;
 
+        self.rebuild_matcher(&filter.group_id)?;
+
         Ok(())
     }
 
@@ -141,6 +165,12 @@ impl Filters {
             .remove(&key)
             .map_err(|e| FilterError::DatabaseError(e.to_string()))?;
 
+        self.ai_cache_db
+            .remove(&key)
+            .map_err(|e| FilterError::DatabaseError(e.to_string()))?;
+
+        self.rebuild_matcher(group_id)?;
+
         Ok(())
     }
 
@@ -168,9 +198,14 @@ impl Filters {
             self.stats_db
                 .remove(&key)
                 .map_err(|e| FilterError::DatabaseError(e.to_string()))?;
+            self.ai_cache_db
+                .remove(&key)
+                .map_err(|e| FilterError::DatabaseError(e.to_string()))?;
             removed_count += 1;
         }
 
+        self.invalidate_matcher(group_id);
+
         Ok(removed_count)
     }
 
@@ -179,17 +214,54 @@ impl Filters {
         group_id: &str,
         text: &str,
     ) -> Result<Vec<FilterMatch>, FilterError> {
-        let filters = self.get_group_filters(group_id)?;
-        let mut matches = Vec::new();
-        let text_lower = text.to_lowercase();
+        let matcher = self.group_matcher(group_id)?;
+        let now = chrono::Utc::now();
+
+        Ok(matcher
+            .find_matches(text)
+            .into_iter()
+            .filter(|m| {
+                m.filter
+                    .active_window
+                    .as_ref()
+                    .is_none_or(|window| is_window_open(window, now))
+            })
+            .collect())
+    }
 
-        for filter in filters {
-            if let Some(filter_match) = self.check_filter_match(&filter, &text_lower) {
-                matches.push(filter_match);
-            }
+    /// Returns the cached matcher for a group, building it from sled on first use.
+    fn group_matcher(&self, group_id: &str) -> Result<Arc<GroupMatcher>, FilterError> {
+        let generation = match self.matchers.get(group_id) {
+            Some(cached) => match &cached.matcher {
+                Some(matcher) => return Ok(matcher.clone()),
+                None => cached.generation,
+            },
+            None => 0,
+        };
+
+        let matcher = Arc::new(GroupMatcher::new(self.get_group_filters(group_id)?));
+
+        // Only cache the build if no filter change landed while reading sled
+        let mut cached = self.matchers.entry(group_id.to_string()).or_default();
+        if cached.generation == generation && cached.matcher.is_none() {
+            cached.matcher = Some(matcher.clone());
         }
 
-        Ok(matches)
+        Ok(matcher)
+    }
+
+    /// Drops a group's matcher and rebuilds it from the updated filters.
+    /// Must be called whenever the group's filters change.
+    fn rebuild_matcher(&self, group_id: &str) -> Result<Arc<GroupMatcher>, FilterError> {
+        self.invalidate_matcher(group_id);
+        self.group_matcher(group_id)
+    }
+
+    /// Bumps the group's generation so in-flight builds from older data are discarded.
+    fn invalidate_matcher(&self, group_id: &str) {
+        let mut cached = self.matchers.entry(group_id.to_string()).or_default();
+        cached.generation += 1;
+        cached.matcher = None;
     }
 
     pub fn put_pending_settings(
@@ -244,12 +316,15 @@ impl Filters {
                 usage_count: 0,
                 last_triggered: None,
                 last_triggered_by: None,
+                daily_usage: Default::default(),
             }
         };
 
+        let now = chrono::Utc::now();
         stats.usage_count += 1;
-        stats.last_triggered = Some(chrono::Utc::now().timestamp());
+        stats.last_triggered = Some(now.timestamp());
         stats.last_triggered_by = Some(user_id);
+        record_daily_usage(&mut stats.daily_usage, now.date_naive());
 
         let stats_bytes =
             serde_json::to_vec(&stats).map_err(|e| FilterError::InternalError(e.to_string()))?;
@@ -261,6 +336,78 @@ impl Filters {
         Ok(())
     }
 
+    /// Returns the last generated reply for an AI filter while it is younger than
+    /// the filter's cache TTL.
+    pub fn get_cached_ai_response(&self, filter: &FilterDefinition) -> Option<String> {
+        if filter.cache_ttl_seconds == 0 {
+            return None;
+        }
+
+        let key = self.format_key(&filter.group_id, &filter.id);
+        let cached: CachedAiResponse = self
+            .ai_cache_db
+            .get(&key)
+            .ok()
+            .flatten()
+            .and_then(|data| serde_json::from_slice(&data).ok())?;
+
+        let age = chrono::Utc::now().timestamp() - cached.created_at;
+        if age >= 0 && (age as u64) < filter.cache_ttl_seconds {
+            Some(cached.text)
+        } else {
+            None
+        }
+    }
+
+    pub fn cache_ai_response(
+        &self,
+        filter: &FilterDefinition,
+        text: &str,
+    ) -> Result<(), FilterError> {
+        if filter.cache_ttl_seconds == 0 {
+            return Ok(());
+        }
+
+        let key = self.format_key(&filter.group_id, &filter.id);
+        let cached = CachedAiResponse {
+            text: text.to_string(),
+            created_at: chrono::Utc::now().timestamp(),
+        };
+        let cached_bytes =
+            serde_json::to_vec(&cached).map_err(|e| FilterError::InternalError(e.to_string()))?;
+
+        self.ai_cache_db
+            .insert(&key, cached_bytes)
+            .map_err(|e| FilterError::DatabaseError(e.to_string()))?;
+
+        Ok(())
+    }
+
+    /// Active filters of a group paired with their stats, for analytics views and exports.
+    pub fn get_group_filter_stats(
+        &self,
+        group_id: &str,
+    ) -> Result<Vec<(FilterDefinition, FilterStats)>, FilterError> {
+        let filters = self.get_group_filters(group_id)?;
+
+        Ok(filters
+            .into_iter()
+            .map(|filter| {
+                let stats = self
+                    .get_filter_stats(group_id, &filter.id)
+                    .unwrap_or_else(|_| FilterStats {
+                        group_id: group_id.to_string(),
+                        filter_id: filter.id.clone(),
+                        usage_count: 0,
+                        last_triggered: None,
+                        last_triggered_by: None,
+                        daily_usage: Default::default(),
+                    });
+                (filter, stats)
+            })
+            .collect())
+    }
+
     pub fn get_filter_stats(
         &self,
         group_id: &str,
@@ -366,74 +513,4 @@ impl Filters {
             s.to_string()
         }
     }
-
-    fn check_filter_match(&self, filter: &FilterDefinition, text: &str) -> Option<FilterMatch> {
-        let trigger_lower = filter.trigger.to_lowercase();
-
-        let (matched, position) = match filter.match_type {
-            MatchType::Exact => {
-                let words: Vec<&str> = text.split_whitespace().collect();
-                for (i, word) in words.iter().enumerate() {
-                    if word.trim_matches(|c: char| !c.is_alphanumeric()) == trigger_lower {
-                        return Some(FilterMatch {
-                            filter: filter.clone(),
-                            matched_text: word.to_string(),
-                            match_position: i,
-                        });
-                    }
-                }
-                (false, 0)
-            }
-            MatchType::Contains => {
-                // Unified logic for both single-word and multi-word triggers
-                let text_lower = text.to_lowercase();
-                
-                if let Some(pos) = text_lower.find(&trigger_lower) {
-                    // Verify word boundary match for proper contains behavior
-                    let start_pos = pos;
-                    let end_pos = pos + trigger_lower.len();
-                    
-                    let start_ok = start_pos == 0 || 
-                        !text_lower.chars().nth(start_pos - 1).unwrap_or(' ').is_alphanumeric();
-                    let end_ok = end_pos >= text_lower.len() || 
-                        !text_lower.chars().nth(end_pos).unwrap_or(' ').is_alphanumeric();
-                    
-                    if start_ok && end_ok {
-                        // Extract the actual matched text from the original input (preserving case)
-                        let matched_text = text.chars().skip(start_pos).take(trigger_lower.len()).collect::<String>();
-                        return Some(FilterMatch {
-                            filter: filter.clone(),
-                            matched_text,
-                            match_position: start_pos,
-                        });
-                    }
-                }
-                (false, 0)
-            }
-            MatchType::StartsWith => {
-                if text.starts_with(&trigger_lower) {
-                    (true, 0)
-                } else {
-                    (false, 0)
-                }
-            }
-            MatchType::EndsWith => {
-                if text.ends_with(&trigger_lower) {
-                    (true, text.len() - trigger_lower.len())
-                } else {
-                    (false, 0)
-                }
-            }
-        };
-
-        if matched {
-            Some(FilterMatch {
-                filter: filter.clone(),
-                matched_text: trigger_lower,
-                match_position: position,
-            })
-        } else {
-            None
-        }
-    }
 }
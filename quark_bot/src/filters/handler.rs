@@ -5,15 +5,26 @@ use teloxide::{
     utils::render::RenderMessageTextHelper,
 };
 
-use crate::filters::helpers::{parse_triggers, replace_filter_placeholders};
-use crate::utils::{self, KeyboardMarkupType, send_markdown_message_with_keyboard, escape_for_markdown_v2};
+use crate::filters::helpers::{
+    DEFAULT_AI_CACHE_TTL, daily_counts, describe_active_window, describe_cache_ttl,
+    filters_to_csv, is_dead_filter, is_window_open, next_cache_ttl, parse_active_window,
+    parse_triggers, replace_filter_placeholders, sparkline, usage_in_last_days,
+};
+use crate::nft_gate::dto::MemberRole;
+use crate::template::{TemplateContext, template_uses};
+use crate::user_model_preferences::dto::ModelPreferences;
+use crate::utils::{
+    self, KeyboardMarkupType, create_purchase_request, send_markdown_message_with_keyboard,
+    unescape_markdown,
+};
 use crate::{
     dependencies::BotDependencies,
     utils::{send_markdown_message, send_message},
 };
 use crate::{
     filters::dto::{
-        FilterError, MatchType, PendingFilterStep, PendingFilterWizardState, ResponseType,
+        FilterDefinition, FilterError, MatchType, PendingFilterStep, PendingFilterWizardState,
+        ResponseType,
     },
     utils::send_html_message,
 };
@@ -47,6 +58,15 @@ pub async fn handle_filters_callback(
                     "filters_view" => {
                         show_view_filters_menu(&bot, &query, &bot_deps, m.chat.id).await?;
                     }
+                    "filters_top" => {
+                        show_top_filters(&bot, &query, &bot_deps, m.chat.id).await?;
+                    }
+                    "filters_dead" => {
+                        show_dead_filters(&bot, &query, &bot_deps, m.chat.id, 30).await?;
+                    }
+                    "filters_export" => {
+                        export_filters_csv(&bot, &query, &bot_deps, m.chat.id).await?;
+                    }
                     "filters_reset_confirm" => {
                         show_reset_confirmation(&bot, &query, m.chat.id).await?;
                     }
@@ -63,6 +83,82 @@ pub async fn handle_filters_callback(
                     "filters_cancel" => {
                         cancel_filter_wizard(&bot, &query, &bot_deps, m.chat.id, user_id).await?;
                     }
+                    "filters_set_window" => {
+                        start_active_window_step(&bot, &query, &bot_deps, m.chat.id, user_id)
+                            .await?;
+                    }
+                    "filters_toggle_ai" => {
+                        update_wizard_response_options(
+                            &bot,
+                            &query,
+                            &bot_deps,
+                            m.chat.id,
+                            user_id,
+                            |state| {
+                                if state.response_type == ResponseType::Ai {
+                                    state.response_type = ResponseType::Markdown;
+                                    state.cache_ttl_seconds = 0;
+                                } else {
+                                    state.response_type = ResponseType::Ai;
+                                    state.cache_ttl_seconds = DEFAULT_AI_CACHE_TTL;
+                                }
+                            },
+                        )
+                        .await?;
+                    }
+                    "filters_toggle_vip" => {
+                        update_wizard_response_options(
+                            &bot,
+                            &query,
+                            &bot_deps,
+                            m.chat.id,
+                            user_id,
+                            |state| {
+                                state.vip_only = !state.vip_only;
+                            },
+                        )
+                        .await?;
+                    }
+                    "filters_cycle_cache" => {
+                        update_wizard_response_options(
+                            &bot,
+                            &query,
+                            &bot_deps,
+                            m.chat.id,
+                            user_id,
+                            |state| {
+                                state.cache_ttl_seconds = next_cache_ttl(state.cache_ttl_seconds);
+                            },
+                        )
+                        .await?;
+                    }
+                    _ if data.starts_with("filters_dead:") => {
+                        let days = data
+                            .strip_prefix("filters_dead:")
+                            .and_then(|d| d.parse::<i64>().ok())
+                            .unwrap_or(30);
+                        show_dead_filters(&bot, &query, &bot_deps, m.chat.id, days).await?;
+                    }
+                    _ if data.starts_with("filters_dead_remove:") => {
+                        let rest = data.strip_prefix("filters_dead_remove:").unwrap();
+                        let (days, filter_id) = rest.split_once(':').unwrap_or(("30", rest));
+                        let days = days.parse::<i64>().unwrap_or(30);
+                        match bot_deps
+                            .filters
+                            .remove_filter(&m.chat.id.to_string(), filter_id)
+                        {
+                            Ok(_) => {
+                                show_dead_filters(&bot, &query, &bot_deps, m.chat.id, days)
+                                    .await?;
+                            }
+                            Err(e) => {
+                                log::error!("Failed to remove filter: {}", e);
+                                bot.answer_callback_query(query.id)
+                                    .text("❌ Failed to remove filter")
+                                    .await?;
+                            }
+                        }
+                    }
                     _ if data.starts_with("filters_remove:") => {
                         let filter_id = data.strip_prefix("filters_remove:").unwrap();
                         remove_filter(&bot, &query, &bot_deps, m.chat.id, filter_id).await?;
@@ -94,28 +190,55 @@ pub async fn process_message_for_filters(
 
         match bot_deps.filters.find_matching_filters(&group_id, text) {
             Ok(matches) => {
-                if let Some(filter_match) = matches.first() {
-                    // Extract user info for placeholders
-                    let group_name = msg.chat.title().unwrap_or("Group").to_string();
-                    let trigger = &filter_match.matched_text;
-
-                    // Replace placeholders in the response
-                    // Build a safe MarkdownV2 tg://user mention for the sender
-                    let mention_markup = msg.from.as_ref().map(|u| {
-                        let display = if let Some(un) = &u.username {
-                            format!("@{}", un)
-                        } else {
-                            u.first_name.clone()
-                        };
-                        let escaped = escape_for_markdown_v2(&display);
-                        format!("[{}](tg://user?id={})", escaped, u.id.0)
-                    });
+                // VIP-only filters are skipped for members without the NFT role
+                let is_vip = |user: Option<&User>| {
+                    user.is_some_and(|user| {
+                        bot_deps
+                            .nft_gate
+                            .has_role(msg.chat.id, user.id, MemberRole::Vip)
+                    })
+                };
+                let filter_match = matches
+                    .iter()
+                    .find(|m| !m.filter.vip_only || is_vip(msg.from.as_ref()));
+                if let Some(filter_match) = filter_match {
+                    if filter_match.filter.response_type == ResponseType::Ai {
+                        send_ai_filter_response(&bot, &msg, &bot_deps, &filter_match.filter)
+                            .await;
+
+                        if let Some(user) = &msg.from {
+                            let _ = bot_deps.filters.record_filter_usage(
+                                &group_id,
+                                &filter_match.filter.id,
+                                user.id.0 as i64,
+                            );
+                        }
+
+                        return Ok(true);
+                    }
+
+                    // Extract user and chat info for placeholders
+                    let member_count = if template_uses(
+                        &unescape_markdown(&filter_match.filter.response),
+                        "member_count",
+                    ) {
+                        bot.get_chat_member_count(msg.chat.id).await.ok()
+                    } else {
+                        None
+                    };
+                    let template_ctx = TemplateContext {
+                        user_id: msg.from.as_ref().map(|u| u.id.0 as i64),
+                        username: msg.from.as_ref().and_then(|u| u.username.clone()),
+                        first_name: msg.from.as_ref().map(|u| u.first_name.clone()),
+                        group_name: Some(msg.chat.title().unwrap_or("Group").to_string()),
+                        trigger: Some(filter_match.matched_text.clone()),
+                        member_count,
+                        ..Default::default()
+                    };
 
                     let personalized_response = replace_filter_placeholders(
                         &filter_match.filter.response,
-                        mention_markup.as_deref(),
-                        &group_name,
-                        trigger,
+                        &template_ctx,
                         filter_match.filter.response_type.clone(),
                     );
 
@@ -130,7 +253,7 @@ pub async fn process_message_for_filters(
                             )
                             .await
                         }
-                        ResponseType::Text => {
+                        ResponseType::Text | ResponseType::Ai => {
                             // For text responses, send as plain text without parse mode
                             send_message(msg.clone(), bot.clone(), personalized_response.clone())
                                 .await
@@ -184,6 +307,9 @@ async fn start_filter_wizard(
         response: None,
         match_type: MatchType::Contains,       // Default
         response_type: ResponseType::Markdown, // Default
+        active_window: None,
+        cache_ttl_seconds: 0,
+        vip_only: false,
     };
 
     if let Err(e) = bot_deps
@@ -236,6 +362,14 @@ async fn show_filters_main_menu(
             format!("📋 View Filters ({})", filter_count),
             "filters_view",
         )],
+        vec![
+            InlineKeyboardButton::callback("📈 Top This Week", "filters_top"),
+            InlineKeyboardButton::callback("🧹 Dead Filters", "filters_dead"),
+        ],
+        vec![InlineKeyboardButton::callback(
+            "📤 Export Usage (CSV)",
+            "filters_export",
+        )],
         vec![InlineKeyboardButton::callback(
             "🗑️ Reset All Filters",
             "filters_reset_confirm",
@@ -247,7 +381,7 @@ async fn show_filters_main_menu(
     ]);
 
     let text = format!(
-        "🔍 <b>Filters</b>\n\nMake your chat more lively with filters! The bot will reply to certain words.\n\nFilters are case insensitive; every time someone says your trigger words, Nova will reply something else! Can be used to create your own commands, if desired.\n\n✨ <b>Personalization:</b> Use placeholders like {{username}}, {{group_name}}, and {{trigger}} in your responses to make them personal!\n\n<b>Current filters:</b> {} active",
+        "🔍 <b>Filters</b>\n\nMake your chat more lively with filters! The bot will reply to certain words.\n\nFilters are case insensitive; every time someone says your trigger words, Nova will reply something else! Can be used to create your own commands, if desired.\n\n✨ <b>Personalization:</b> Use placeholders like {{username}}, {{first_name}}, {{group_name}}, {{member_count}} and {{trigger}}, random choices like {{Hi|Hello}}, and conditions like {{if username}}...{{end}} in your responses to make them personal!\n\n<b>Current filters:</b> {} active",
         filter_count
     );
 
@@ -262,6 +396,186 @@ async fn show_filters_main_menu(
     Ok(())
 }
 
+async fn show_top_filters(
+    bot: &Bot,
+    query: &teloxide::types::CallbackQuery,
+    bot_deps: &BotDependencies,
+    chat_id: teloxide::types::ChatId,
+) -> Result<()> {
+    let group_id = chat_id.to_string();
+    let today = chrono::Utc::now().date_naive();
+
+    let mut ranked: Vec<_> = bot_deps
+        .filters
+        .get_group_filter_stats(&group_id)
+        .unwrap_or_default()
+        .into_iter()
+        .map(|(filter, stats)| {
+            let weekly = usage_in_last_days(&stats, 7, today);
+            (filter, stats, weekly)
+        })
+        .filter(|(_, _, weekly)| *weekly > 0)
+        .collect();
+    ranked.sort_by(|a, b| b.2.cmp(&a.2).then_with(|| a.0.trigger.cmp(&b.0.trigger)));
+
+    let mut text = String::from("📈 <b>Top Filters This Week</b>\n\n");
+    if ranked.is_empty() {
+        text.push_str("<i>No filter has triggered in the last 7 days.</i>");
+    } else {
+        for (rank, (filter, stats, weekly)) in ranked.iter().take(10).enumerate() {
+            text.push_str(&format!(
+                "{}. <b>{}</b>: {} uses\n<code>{}</code> (last 7 days, oldest first)\n\n",
+                rank + 1,
+                teloxide::utils::html::escape(&filter.trigger),
+                weekly,
+                sparkline(&daily_counts(stats, 7, today))
+            ));
+        }
+        text.push_str("💡 <i>Spikes right after an announcement usually point to a question worth answering in the announcement itself.</i>");
+    }
+
+    let keyboard = InlineKeyboardMarkup::new(vec![vec![InlineKeyboardButton::callback(
+        "↩️ Back to Filters",
+        "filters_main",
+    )]]);
+
+    if let Some(teloxide::types::MaybeInaccessibleMessage::Regular(message)) = &query.message {
+        bot.edit_message_text(message.chat.id, message.id, text)
+            .parse_mode(ParseMode::Html)
+            .reply_markup(keyboard)
+            .await?;
+    }
+
+    bot.answer_callback_query(query.id.clone()).await?;
+    Ok(())
+}
+
+async fn show_dead_filters(
+    bot: &Bot,
+    query: &teloxide::types::CallbackQuery,
+    bot_deps: &BotDependencies,
+    chat_id: teloxide::types::ChatId,
+    days: i64,
+) -> Result<()> {
+    let group_id = chat_id.to_string();
+    let now = chrono::Utc::now().timestamp();
+
+    let dead: Vec<_> = bot_deps
+        .filters
+        .get_group_filter_stats(&group_id)
+        .unwrap_or_default()
+        .into_iter()
+        .filter(|(filter, stats)| is_dead_filter(filter, stats, days, now))
+        .collect();
+
+    let mut keyboard_rows = Vec::new();
+    let mut text = format!("🧹 <b>Dead Filters</b> (no triggers in {} days)\n\n", days);
+
+    if dead.is_empty() {
+        text.push_str("<i>Every filter has triggered recently. Nothing to clean up!</i>");
+    } else {
+        for (filter, stats) in &dead {
+            let last_seen = match stats.last_triggered {
+                Some(ts) => format!("last used {} days ago", (now - ts) / 86_400),
+                None => "never used".to_string(),
+            };
+            text.push_str(&format!(
+                "• <b>{}</b> ({})\n",
+                teloxide::utils::html::escape(&filter.trigger),
+                last_seen
+            ));
+
+            let display_trigger = if filter.trigger.chars().count() > 20 {
+                format!("{}...", filter.trigger.chars().take(17).collect::<String>())
+            } else {
+                filter.trigger.clone()
+            };
+            keyboard_rows.push(vec![InlineKeyboardButton::callback(
+                format!("🗑️ Remove {}", display_trigger),
+                format!("filters_dead_remove:{}:{}", days, filter.id),
+            )]);
+        }
+        text.push_str("\n💡 <i>Consider removing these filters to keep the list focused.</i>");
+    }
+
+    keyboard_rows.push(
+        [7, 30, 90]
+            .into_iter()
+            .map(|d| {
+                let label = if d == days {
+                    format!("• {}d •", d)
+                } else {
+                    format!("{}d", d)
+                };
+                InlineKeyboardButton::callback(label, format!("filters_dead:{}", d))
+            })
+            .collect(),
+    );
+    keyboard_rows.push(vec![InlineKeyboardButton::callback(
+        "↩️ Back to Filters",
+        "filters_main",
+    )]);
+
+    if let Some(teloxide::types::MaybeInaccessibleMessage::Regular(message)) = &query.message {
+        bot.edit_message_text(message.chat.id, message.id, text)
+            .parse_mode(ParseMode::Html)
+            .reply_markup(InlineKeyboardMarkup::new(keyboard_rows))
+            .await?;
+    }
+
+    bot.answer_callback_query(query.id.clone()).await?;
+    Ok(())
+}
+
+async fn export_filters_csv(
+    bot: &Bot,
+    query: &teloxide::types::CallbackQuery,
+    bot_deps: &BotDependencies,
+    chat_id: teloxide::types::ChatId,
+) -> Result<()> {
+    let rows = match bot_deps.filters.get_group_filter_stats(&chat_id.to_string()) {
+        Ok(rows) => rows,
+        Err(e) => {
+            log::error!("Failed to load filter stats for export: {}", e);
+            bot.answer_callback_query(query.id.clone())
+                .text("❌ Failed to export filters")
+                .await?;
+            return Ok(());
+        }
+    };
+
+    if rows.is_empty() {
+        bot.answer_callback_query(query.id.clone())
+            .text("No filters to export")
+            .await?;
+        return Ok(());
+    }
+
+    let file_name = format!(
+        "filters_usage_{}.csv",
+        chrono::Utc::now().format("%Y-%m-%d")
+    );
+    let document =
+        teloxide::types::InputFile::memory(filters_to_csv(&rows).into_bytes()).file_name(file_name);
+
+    let mut request = bot
+        .send_document(chat_id, document)
+        .caption("📤 Filter usage export (daily buckets, UTC)");
+    let thread_id = match &query.message {
+        Some(teloxide::types::MaybeInaccessibleMessage::Regular(message)) => message.thread_id,
+        _ => None,
+    };
+    if let Some(thread_id) = thread_id {
+        request = request.message_thread_id(thread_id);
+    }
+    request.await?;
+
+    bot.answer_callback_query(query.id.clone())
+        .text("✅ Export sent")
+        .await?;
+    Ok(())
+}
+
 async fn show_view_filters_menu(
     bot: &Bot,
     query: &teloxide::types::CallbackQuery,
@@ -296,6 +610,7 @@ async fn show_view_filters_menu(
         }
     } else {
         let mut keyboard_rows = Vec::new();
+        let now = chrono::Utc::now();
 
         for filter in &filters {
             let stats = bot_deps
@@ -307,6 +622,7 @@ async fn show_view_filters_menu(
                     usage_count: 0,
                     last_triggered: None,
                     last_triggered_by: None,
+                    daily_usage: Default::default(),
                 });
 
             let display_trigger = if filter.trigger.len() > 20 {
@@ -315,7 +631,17 @@ async fn show_view_filters_menu(
                 filter.trigger.clone()
             };
 
-            let button_text = format!("🗑️ {} ({}x)", display_trigger, stats.usage_count);
+            let paused = filter
+                .active_window
+                .as_ref()
+                .is_some_and(|window| !is_window_open(window, now));
+            let button_text = format!(
+                "🗑️ {}{}{} ({}x)",
+                if paused { "⏸️ " } else { "" },
+                if filter.vip_only { "⭐ " } else { "" },
+                display_trigger,
+                stats.usage_count
+            );
             let remove_button = InlineKeyboardButton::callback(
                 button_text,
                 format!("filters_remove:{}", filter.id),
@@ -335,6 +661,7 @@ async fn show_view_filters_menu(
         let keyboard = InlineKeyboardMarkup::new(keyboard_rows);
 
         let mut text = format!("📋 <b>Active Filters ({})</b>\n\n", filters.len());
+        let now = chrono::Utc::now();
 
         for filter in &filters {
             let stats = bot_deps
@@ -346,6 +673,7 @@ async fn show_view_filters_menu(
                     usage_count: 0,
                     last_triggered: None,
                     last_triggered_by: None,
+                    daily_usage: Default::default(),
                 });
 
             let response_preview = if filter.response.len() > 50 {
@@ -354,9 +682,31 @@ async fn show_view_filters_menu(
                 filter.response.clone()
             };
 
+            let availability = match &filter.active_window {
+                Some(window) if is_window_open(window, now) => {
+                    format!("🟢 Active now · {}", describe_active_window(window))
+                }
+                Some(window) => format!("⏸️ Inactive now · {}", describe_active_window(window)),
+                None => "🟢 Always active".to_string(),
+            };
+
+            let response_label = if filter.response_type == ResponseType::Ai {
+                format!(
+                    "🤖 AI prompt (cache: {})",
+                    describe_cache_ttl(filter.cache_ttl_seconds)
+                )
+            } else {
+                "Response".to_string()
+            };
+
             text.push_str(&format!(
-                "🔹 <b>{}</b>\nResponse: \"{}\"\nUsed: {} times\n\n",
-                filter.trigger, response_preview, stats.usage_count
+                "🔹 <b>{}</b>{}\n{}: \"{}\"\nUsed: {} times\n{}\n\n",
+                filter.trigger,
+                if filter.vip_only { " ⭐ VIP only" } else { "" },
+                response_label,
+                response_preview,
+                stats.usage_count,
+                availability
             ));
         }
 
@@ -564,6 +914,9 @@ async fn confirm_and_create_filter(
                     match_type: wizard_state.match_type.clone(),
                     response_type: wizard_state.response_type.clone(),
                     id: uuid::Uuid::new_v4().to_string(),
+                    active_window: wizard_state.active_window.clone(),
+                    cache_ttl_seconds: wizard_state.cache_ttl_seconds,
+                    vip_only: wizard_state.vip_only,
                 };
 
                 match bot_deps.filters.create_filter(filter) {
@@ -646,6 +999,62 @@ async fn confirm_and_create_filter(
     Ok(())
 }
 
+async fn start_active_window_step(
+    bot: &Bot,
+    query: &teloxide::types::CallbackQuery,
+    bot_deps: &BotDependencies,
+    chat_id: teloxide::types::ChatId,
+    user_id: teloxide::types::UserId,
+) -> Result<()> {
+    let wizard_key = format!(
+        "filter_{}-{}:{}",
+        chat_id.0, bot_deps.filters.account_seed, user_id.0
+    );
+
+    let Some(mut wizard_state) = bot_deps.filters.get_pending_settings(&wizard_key) else {
+        bot.answer_callback_query(query.id.clone())
+            .text("❌ No active filter wizard found")
+            .await?;
+        return Ok(());
+    };
+
+    if wizard_state.step != PendingFilterStep::AwaitingConfirm {
+        bot.answer_callback_query(query.id.clone())
+            .text("❌ Invalid wizard state")
+            .await?;
+        return Ok(());
+    }
+
+    wizard_state.step = PendingFilterStep::AwaitingActiveWindow;
+    if let Err(e) = bot_deps
+        .filters
+        .put_pending_settings(wizard_key, &wizard_state)
+    {
+        log::error!("Failed to save wizard state: {}", e);
+        bot.answer_callback_query(query.id.clone())
+            .text("❌ Failed to update filter wizard")
+            .await?;
+        return Ok(());
+    }
+
+    let keyboard = InlineKeyboardMarkup::new(vec![vec![InlineKeyboardButton::callback(
+        "❌ Cancel",
+        "filters_cancel",
+    )]]);
+
+    let text = "🕒 <b>Active Window</b>\n\nSend when this filter should answer. Combine any of:\n\n• Days: <code>mon-fri</code>, <code>sat,sun</code>, <code>daily</code>\n• Hours: <code>09:00-17:00</code> (overnight like <code>22:00-02:00</code> works too)\n• Dates: <code>from 2025-11-01T18:00</code>, <code>until 2025-11-02</code>\n• Timezone: <code>Europe/London</code>, <code>America/New_York</code> (default UTC)\n\n<b>Examples:</b>\n• <code>mon-fri 09:00-17:00 Europe/London</code>\n• <code>from 2025-11-01T18:00 until 2025-11-01T20:00 UTC</code>\n\nSend <code>always</code> to remove the window.";
+
+    if let Some(teloxide::types::MaybeInaccessibleMessage::Regular(message)) = &query.message {
+        bot.edit_message_text(message.chat.id, message.id, text)
+            .parse_mode(ParseMode::Html)
+            .reply_markup(keyboard)
+            .await?;
+    }
+
+    bot.answer_callback_query(query.id.clone()).await?;
+    Ok(())
+}
+
 async fn cancel_filter_wizard(
     bot: &Bot,
     query: &teloxide::types::CallbackQuery,
@@ -739,7 +1148,7 @@ pub async fn handle_message_filters(
                 send_html_message(
                     msg.clone(),
                     bot.clone(),
-                    "🔍 <b>Add New Filter - Step 2/3</b>\n\nNow send the response message that the bot should reply with when someone types your trigger.\n\n💡 <i>Use Telegram MarkdownV2 (e.g., <code>*bold*</code>, <code>_italic_</code>, <code>`code`</code>) or plain text. Double asterisks <code>**like this**</code> are not supported.</i>\n\n✨ <b>Available Placeholders:</b>\n• <code>{username}</code> → @username (creates clickable mention)\n• <code>{group_name}</code> → Group name\n• <code>{trigger}</code> → The word/phrase that triggered the filter\n\n<b>Examples:</b>\n• <code>Hello {username}! Welcome to {group_name}! 👋</code>\n• <code>*Bold text*</code> works great!\n• <code>Use `code` for inline formatting</code>\n• <code>Hey {username}, you said '{trigger}'! 🎯</code>\n• <code>Good morning {username}! ☀️</code>".to_string(),
+                    "🔍 <b>Add New Filter - Step 2/3</b>\n\nNow send the response message that the bot should reply with when someone types your trigger.\n\n💡 <i>Use Telegram MarkdownV2 (e.g., <code>*bold*</code>, <code>_italic_</code>, <code>`code`</code>) or plain text. Double asterisks <code>**like this**</code> are not supported.</i>\n\n✨ <b>Available Placeholders:</b>\n• <code>{username}</code> → @username (creates clickable mention)\n• <code>{first_name}</code> → User's first name\n• <code>{mention}</code> → Clickable mention by first name\n• <code>{user_id}</code> → User's Telegram ID\n• <code>{group_name}</code> → Group name\n• <code>{member_count}</code> → Number of group members\n• <code>{date}</code> → Today's date (UTC)\n• <code>{trigger}</code> → The word/phrase that triggered the filter\n\n🎲 <b>Random choice:</b> <code>{Hi|Hello|Hey}</code>\n🔀 <b>Conditions:</b> <code>{if username}...{else}...{end}</code> or <code>{if not username}...{end}</code>\n\n<b>Examples:</b>\n• <code>Hello {username}! Welcome to {group_name}! 👋</code>\n• <code>*Bold text*</code> works great!\n• <code>Use `code` for inline formatting</code>\n• <code>Hey {username}, you said '{trigger}'! 🎯</code>\n• <code>Good morning {username}! ☀️</code>\n\n🤖 <b>AI replies:</b> on the next step you can switch the filter to AI mode. Your text is then used as a prompt and the bot answers with the group's model and documents (billed to the group).".to_string(),
                 ).await?;
                 return Ok(true);
             }
@@ -760,18 +1169,51 @@ pub async fn handle_message_filters(
 
                 // Show confirmation with summary
                 let summary = crate::filters::helpers::summarize(&st);
-                let keyboard = teloxide::types::InlineKeyboardMarkup::new(vec![vec![
-                    teloxide::types::InlineKeyboardButton::callback(
-                        "✅ Confirm & Create",
-                        "filters_confirm",
-                    ),
-                    teloxide::types::InlineKeyboardButton::callback("❌ Cancel", "filters_cancel"),
-                ]]);
 
                 send_markdown_message_with_keyboard(
                     bot.clone(),
                     msg,
-                    KeyboardMarkupType::InlineKeyboardType(keyboard),
+                    KeyboardMarkupType::InlineKeyboardType(confirm_keyboard(&st)),
+                    &summary,
+                )
+                .await?;
+                Ok(true)
+            }
+            crate::filters::dto::PendingFilterStep::AwaitingActiveWindow => {
+                let window = match parse_active_window(&text_raw) {
+                    Ok(window) => window,
+                    Err(e) => {
+                        send_html_message(
+                            msg,
+                            bot.clone(),
+                            format!(
+                                "❌ {}\n\nTry again, e.g. <code>mon-fri 09:00-17:00 Europe/London</code>, or send <code>always</code>.",
+                                teloxide::utils::html::escape(&e)
+                            ),
+                        )
+                        .await?;
+                        return Ok(true);
+                    }
+                };
+
+                st.active_window = window;
+                st.step = crate::filters::dto::PendingFilterStep::AwaitingConfirm;
+                if let Err(e) = bot_deps.filters.put_pending_settings(filter_key, &st) {
+                    log::error!("Failed to save filter wizard state: {}", e);
+                    send_message(
+                        msg,
+                        bot.clone(),
+                        "❌ Failed to save filter progress.".to_string(),
+                    )
+                    .await?;
+                    return Ok(true);
+                }
+
+                let summary = crate::filters::helpers::summarize(&st);
+                send_markdown_message_with_keyboard(
+                    bot.clone(),
+                    msg,
+                    KeyboardMarkupType::InlineKeyboardType(confirm_keyboard(&st)),
                     &summary,
                 )
                 .await?;
@@ -787,3 +1229,214 @@ pub async fn handle_message_filters(
         return Ok(false);
     }
 }
+
+fn confirm_keyboard(state: &PendingFilterWizardState) -> InlineKeyboardMarkup {
+    let mut rows = vec![
+        vec![InlineKeyboardButton::callback(
+            "🕒 Set Active Window",
+            "filters_set_window",
+        )],
+        vec![InlineKeyboardButton::callback(
+            if state.response_type == ResponseType::Ai {
+                "🤖 AI Response: On"
+            } else {
+                "🤖 AI Response: Off"
+            },
+            "filters_toggle_ai",
+        )],
+        vec![InlineKeyboardButton::callback(
+            if state.vip_only {
+                "⭐ VIP Holders Only: On"
+            } else {
+                "⭐ VIP Holders Only: Off"
+            },
+            "filters_toggle_vip",
+        )],
+    ];
+
+    if state.response_type == ResponseType::Ai {
+        rows.push(vec![InlineKeyboardButton::callback(
+            format!(
+                "⏱️ Cache Replies: {}",
+                describe_cache_ttl(state.cache_ttl_seconds)
+            ),
+            "filters_cycle_cache",
+        )]);
+    }
+
+    rows.push(vec![
+        InlineKeyboardButton::callback("✅ Confirm & Create", "filters_confirm"),
+        InlineKeyboardButton::callback("❌ Cancel", "filters_cancel"),
+    ]);
+
+    InlineKeyboardMarkup::new(rows)
+}
+
+/// Generates, bills and sends the reply of an AI filter. Failures are only
+/// logged so a broken filter never spams the group with errors.
+async fn send_ai_filter_response(
+    bot: &Bot,
+    msg: &Message,
+    bot_deps: &BotDependencies,
+    filter: &FilterDefinition,
+) {
+    let reply = match bot_deps.filters.get_cached_ai_response(filter) {
+        Some(cached) => cached,
+        None => {
+            if !bot_deps.group.verify(msg.chat.id).await {
+                log::warn!(
+                    "Skipping AI filter {} in {}: group credentials invalid",
+                    filter.id,
+                    msg.chat.id
+                );
+                return;
+            }
+
+            let Some(group_credentials) = bot_deps.group.get_credentials(msg.chat.id) else {
+                log::warn!(
+                    "Skipping AI filter {} in {}: group credentials not found",
+                    filter.id,
+                    msg.chat.id
+                );
+                return;
+            };
+
+            let template_ctx = TemplateContext {
+                user_id: msg.from.as_ref().map(|u| u.id.0 as i64),
+                username: msg.from.as_ref().and_then(|u| u.username.clone()),
+                first_name: msg.from.as_ref().map(|u| u.first_name.clone()),
+                group_name: Some(msg.chat.title().unwrap_or("Group").to_string()),
+                trigger: Some(filter.trigger.clone()),
+                ..Default::default()
+            };
+            let prompt =
+                replace_filter_placeholders(&filter.response, &template_ctx, ResponseType::Ai);
+
+            let sender = msg
+                .from
+                .as_ref()
+                .map(|u| {
+                    u.username
+                        .as_ref()
+                        .map(|username| format!("@{}", username))
+                        .unwrap_or_else(|| u.first_name.clone())
+                })
+                .unwrap_or_else(|| "Someone".to_string());
+            let trigger_context =
+                format!("{} said: {}", sender, msg.text().unwrap_or_default());
+
+            let _ = bot
+                .send_chat_action(msg.chat.id, teloxide::types::ChatAction::Typing)
+                .await;
+
+            let model = ModelPreferences::default().chat_model.to_openai_model();
+            let ai_response = match bot_deps
+                .ai
+                .generate_filter_response(
+                    &prompt,
+                    &trigger_context,
+                    model,
+                    1000,
+                    bot_deps.clone(),
+                    msg.chat.id.0.to_string(),
+                )
+                .await
+            {
+                Ok(ai_response) => ai_response,
+                Err(e) => {
+                    log::error!("Failed to generate AI filter response {}: {}", filter.id, e);
+                    return;
+                }
+            };
+
+            let (web_search, file_search, image_gen, _) = ai_response.get_tool_usage_counts();
+            if let Err(e) = create_purchase_request(
+                file_search,
+                web_search,
+                image_gen,
+                ai_response.total_tokens,
+                ai_response.model.clone(),
+                &group_credentials.jwt,
+                Some(msg.chat.id.0.to_string()),
+                None,
+                bot_deps.clone(),
+            )
+            .await
+            {
+                log::error!("Failed to purchase AI filter response {}: {}", filter.id, e);
+                return;
+            }
+
+            if let Err(e) = bot_deps.filters.cache_ai_response(filter, &ai_response.text) {
+                log::warn!("Failed to cache AI filter response {}: {}", filter.id, e);
+            }
+
+            ai_response.text
+        }
+    };
+
+    if reply.trim().is_empty() {
+        return;
+    }
+
+    let html_text = utils::sanitize_ai_html(&utils::markdown_to_html(&reply));
+    if let Err(e) = send_html_message(msg.clone(), bot.clone(), html_text).await {
+        log::error!("Failed to send AI filter response: {}", e);
+        let _ = send_message(msg.clone(), bot.clone(), reply).await;
+    }
+}
+
+async fn update_wizard_response_options(
+    bot: &Bot,
+    query: &teloxide::types::CallbackQuery,
+    bot_deps: &BotDependencies,
+    chat_id: teloxide::types::ChatId,
+    user_id: teloxide::types::UserId,
+    update: impl FnOnce(&mut PendingFilterWizardState),
+) -> Result<()> {
+    let wizard_key = format!(
+        "filter_{}-{}:{}",
+        chat_id.0, bot_deps.filters.account_seed, user_id.0
+    );
+
+    let Some(mut wizard_state) = bot_deps.filters.get_pending_settings(&wizard_key) else {
+        bot.answer_callback_query(query.id.clone())
+            .text("❌ No active filter wizard found")
+            .await?;
+        return Ok(());
+    };
+
+    if wizard_state.step != PendingFilterStep::AwaitingConfirm {
+        bot.answer_callback_query(query.id.clone())
+            .text("❌ Invalid wizard state")
+            .await?;
+        return Ok(());
+    }
+
+    update(&mut wizard_state);
+
+    if let Err(e) = bot_deps
+        .filters
+        .put_pending_settings(wizard_key, &wizard_state)
+    {
+        log::error!("Failed to save wizard state: {}", e);
+        bot.answer_callback_query(query.id.clone())
+            .text("❌ Failed to update filter wizard")
+            .await?;
+        return Ok(());
+    }
+
+    if let Some(teloxide::types::MaybeInaccessibleMessage::Regular(message)) = &query.message {
+        bot.edit_message_text(
+            message.chat.id,
+            message.id,
+            crate::filters::helpers::summarize(&wizard_state),
+        )
+        .parse_mode(ParseMode::Html)
+        .reply_markup(confirm_keyboard(&wizard_state))
+        .await?;
+    }
+
+    bot.answer_callback_query(query.id.clone()).await?;
+    Ok(())
+}
@@ -3,8 +3,19 @@
 //   "[the contract], ca, contract" -> ["the contract", "ca", "contract"]
 //   "hello, world" -> ["hello", "world"]
 //   "[multi word] , single" -> ["multi word", "single"]
-use crate::filters::dto::{MatchType, PendingFilterWizardState, ResponseType};
-use crate::utils::{ensure_markdown_v2_reserved_chars, escape_for_markdown_v2, unescape_markdown};
+use std::collections::BTreeMap;
+
+use chrono::{
+    DateTime, Datelike, Duration, NaiveDate, NaiveDateTime, NaiveTime, TimeZone, Timelike, Utc,
+};
+use chrono_tz::Tz;
+
+use crate::filters::dto::{
+    FilterActiveWindow, FilterDefinition, FilterStats, MatchType, PendingFilterWizardState,
+    ResponseType,
+};
+use crate::template::{TemplateContext, TemplateFormat, render_template};
+use crate::utils::{csv_field, ensure_markdown_v2_reserved_chars, unescape_markdown};
 
 pub fn parse_triggers(input: &str) -> Vec<String> {
     let mut triggers: Vec<String> = Vec::new();
@@ -95,78 +106,601 @@ pub fn summarize(state: &PendingFilterWizardState) -> String {
         MatchType::StartsWith => "Message starts with",
         MatchType::EndsWith => "Message ends with",
     };
+    let active_window = state
+        .active_window
+        .as_ref()
+        .map(describe_active_window)
+        .unwrap_or_else(|| "Always".to_string());
+    let format = match state.response_type {
+        ResponseType::Ai => format!(
+            "🤖 AI-generated from this prompt (cache: {})",
+            describe_cache_ttl(state.cache_ttl_seconds)
+        ),
+        _ => "MarkdownV2 (or plain text)".to_string(),
+    };
     format!(
-        "🔍 <b>Filter Summary</b>\n\n📝 Triggers: {}\n💬 Response: <code>{}</code>\n🎯 Match type: {}\n📄 Format: MarkdownV2 (or plain text)",
-        triggers_display, response, match_type
+        "🔍 <b>Filter Summary</b>\n\n📝 Triggers: {}\n💬 Response: <code>{}</code>\n🎯 Match type: {}\n📄 Format: {}\n🕒 Active: {}\n⭐ Who: {}",
+        triggers_display,
+        response,
+        match_type,
+        format,
+        active_window,
+        if state.vip_only {
+            "VIP holders only"
+        } else {
+            "Everyone"
+        }
     )
 }
 
-/// Replace filter response placeholders with actual values
-/// 
-/// Available placeholders:
-/// - {username} -> @username (with @ prefix for Telegram mentions)
-/// - {group_name} -> actual group name
-/// - {trigger} -> actual trigger word/phrase
-/// Replace placeholders in a filter response string.
-/// For Markdown responses, `username_markup` should be a pre-escaped MarkdownV2 link entity
-/// like `[display](tg://user?id=123)`. For Text responses, pass `None` and we will use
-/// a simple `@username` fallback.
+/// Cache lifetimes offered for AI filters, cycled through from the wizard.
+pub const AI_CACHE_TTL_PRESETS: [u64; 5] = [0, 5 * 60, 60 * 60, 6 * 60 * 60, 24 * 60 * 60];
+
+pub const DEFAULT_AI_CACHE_TTL: u64 = 60 * 60;
+
+pub fn next_cache_ttl(current: u64) -> u64 {
+    let idx = AI_CACHE_TTL_PRESETS
+        .iter()
+        .position(|ttl| *ttl == current)
+        .map(|idx| (idx + 1) % AI_CACHE_TTL_PRESETS.len())
+        .unwrap_or(0);
+    AI_CACHE_TTL_PRESETS[idx]
+}
+
+pub fn describe_cache_ttl(seconds: u64) -> String {
+    match seconds {
+        0 => "off".to_string(),
+        s if s % 3600 == 0 => format!("{}h", s / 3600),
+        s if s % 60 == 0 => format!("{}m", s / 60),
+        s => format!("{}s", s),
+    }
+}
+
+/// Render a filter response template for the triggering message.
+///
+/// Markdown responses are rendered as MarkdownV2 with dynamic values escaped;
+/// Text responses are rendered without any escaping. See `crate::template`
+/// for the placeholder syntax.
 pub fn replace_filter_placeholders(
     response: &str,
-    username_markup: Option<&str>,
-    group_name: &str,
-    trigger: &str,
+    ctx: &TemplateContext,
     response_type: ResponseType,
 ) -> String {
-    let mut result = response.to_string();
-    
     match response_type {
         ResponseType::Markdown => {
-            // For markdown responses, unescape and then escape for MarkdownV2
-            result = unescape_markdown(&result);
+            // Stored responses keep the user's MarkdownV2 escapes; undo them so
+            // placeholders are visible to the template engine
+            let template = unescape_markdown(response);
+            let rendered = render_template(&template, ctx, TemplateFormat::MarkdownV2);
+            ensure_markdown_v2_reserved_chars(&rendered)
+        }
+        ResponseType::Text => render_template(response, ctx, TemplateFormat::Plain),
+        // AI prompts are captured like Markdown responses but sent to the model as plain text
+        ResponseType::Ai => {
+            render_template(&unescape_markdown(response), ctx, TemplateFormat::Plain)
+        }
+    }
+}
+
+const DAY_NAMES: [&str; 7] = ["mon", "tue", "wed", "thu", "fri", "sat", "sun"];
+const FULL_DAY_NAMES: [&str; 7] = [
+    "monday",
+    "tuesday",
+    "wednesday",
+    "thursday",
+    "friday",
+    "saturday",
+    "sunday",
+];
+
+/// Whether a filter with this window should answer at `now`.
+pub fn is_window_open(window: &FilterActiveWindow, now: DateTime<Utc>) -> bool {
+    let timestamp = now.timestamp();
+    if window.starts_at.is_some_and(|start| timestamp < start)
+        || window.ends_at.is_some_and(|end| timestamp >= end)
+    {
+        return false;
+    }
 
-            // Use prebuilt mention markup if provided; otherwise a generic label
-            let username_display = if let Some(markup) = username_markup {
-                markup.to_string()
+    let tz: Tz = window.timezone.parse().unwrap_or(Tz::UTC);
+    let local = now.with_timezone(&tz);
+    let minute = (local.hour() * 60 + local.minute()) as u16;
+    let weekday = local.weekday().num_days_from_monday() as u8;
+    let day_allowed = |day: u8| window.days.is_empty() || window.days.contains(&day);
+
+    match (window.start_minute, window.end_minute) {
+        (Some(start), Some(end)) if start <= end => {
+            day_allowed(weekday) && minute >= start && minute < end
+        }
+        (Some(start), Some(end)) => {
+            // Overnight window: the part after midnight belongs to the previous day
+            if minute >= start {
+                day_allowed(weekday)
+            } else if minute < end {
+                day_allowed((weekday + 6) % 7)
             } else {
-                escape_for_markdown_v2("User")
-            };
-
-            // Escape dynamic content for MarkdownV2 before replacement
-            let escaped_group_name = escape_for_markdown_v2(group_name);
-            let escaped_trigger = escape_for_markdown_v2(trigger);
-            
-            // Replace placeholders
-            result = result.replace("{username}", &username_display);
-            result = result.replace("{group_name}", &escaped_group_name);
-            result = result.replace("{trigger}", &escaped_trigger);
-
-            result = ensure_markdown_v2_reserved_chars(&result);
-        },
-        ResponseType::Text => {
-            // For text responses, just do simple placeholder replacement without any escaping
-            let username_display = if let Some(markup) = username_markup {
-                // If markup is given, best-effort strip to display text by removing the link part
-                // Fallback: use markup as-is
-                if let Some(start) = markup.find('[') {
-                    if let Some(end) = markup[start+1..].find(']') {
-                        markup[start+1..start+1+end].to_string()
-                    } else {
-                        markup.to_string()
-                    }
+                false
+            }
+        }
+        _ => day_allowed(weekday),
+    }
+}
+
+/// Human readable summary of a window, e.g. "Mon-Fri 09:00-17:00 (Europe/London)".
+pub fn describe_active_window(window: &FilterActiveWindow) -> String {
+    let tz: Tz = window.timezone.parse().unwrap_or(Tz::UTC);
+    let mut parts: Vec<String> = Vec::new();
+
+    if !window.days.is_empty() {
+        let mut days = window.days.clone();
+        days.sort_unstable();
+        days.dedup();
+        let contiguous = days.windows(2).all(|pair| pair[1] == pair[0] + 1);
+        let label = |day: u8| {
+            let name = DAY_NAMES[day as usize % 7];
+            format!("{}{}", name[..1].to_uppercase(), &name[1..])
+        };
+        if days.len() > 2 && contiguous {
+            parts.push(format!("{}-{}", label(days[0]), label(days[days.len() - 1])));
+        } else {
+            parts.push(days.into_iter().map(label).collect::<Vec<_>>().join(","));
+        }
+    }
+
+    if let (Some(start), Some(end)) = (window.start_minute, window.end_minute) {
+        parts.push(format!(
+            "{:02}:{:02}-{:02}:{:02}",
+            start / 60,
+            start % 60,
+            end / 60,
+            end % 60
+        ));
+    }
+
+    let format_ts = |ts: i64| {
+        DateTime::from_timestamp(ts, 0)
+            .map(|dt| dt.with_timezone(&tz).format("%Y-%m-%d %H:%M").to_string())
+            .unwrap_or_else(|| ts.to_string())
+    };
+    if let Some(start) = window.starts_at {
+        parts.push(format!("from {}", format_ts(start)));
+    }
+    if let Some(end) = window.ends_at {
+        parts.push(format!("until {}", format_ts(end)));
+    }
+
+    if parts.is_empty() {
+        return "Always".to_string();
+    }
+
+    format!("{} ({})", parts.join(" "), tz.name())
+}
+
+/// Parse an active window from wizard input.
+///
+/// Accepts any combination of:
+///   - days: `mon-fri`, `sat,sun`, `daily`
+///   - hours: `09:00-17:00` (may span midnight, e.g. `22:00-02:00`)
+///   - bounds: `from 2025-11-01T18:00`, `until 2025-11-02` (end dates are exclusive)
+///   - an IANA timezone such as `Europe/London` (defaults to UTC)
+///
+/// `always` clears the window and returns `Ok(None)`.
+pub fn parse_active_window(input: &str) -> Result<Option<FilterActiveWindow>, String> {
+    let input = input.trim();
+    if input.eq_ignore_ascii_case("always") {
+        return Ok(None);
+    }
+
+    let mut window = FilterActiveWindow {
+        days: Vec::new(),
+        start_minute: None,
+        end_minute: None,
+        timezone: "UTC".to_string(),
+        starts_at: None,
+        ends_at: None,
+    };
+    let mut from: Option<NaiveDateTime> = None;
+    let mut until: Option<NaiveDateTime> = None;
+
+    let mut tokens = input.split_whitespace();
+    while let Some(token) = tokens.next() {
+        let lower = token.to_lowercase();
+        match lower.as_str() {
+            "from" | "until" => {
+                let value = tokens
+                    .next()
+                    .ok_or_else(|| format!("Missing date after '{}'", token))?;
+                let parsed = parse_local_datetime(value)
+                    .ok_or_else(|| format!("Invalid date '{}', use YYYY-MM-DD or YYYY-MM-DDTHH:MM", value))?;
+                if lower == "from" {
+                    from = Some(parsed);
                 } else {
-                    markup.to_string()
+                    until = Some(parsed);
                 }
-            } else {
-                "User".to_string()
-            };
-            
-            // Simple placeholder replacement for text - no escaping needed
-            result = result.replace("{username}", &username_display);
-            result = result.replace("{group_name}", group_name);
-            result = result.replace("{trigger}", trigger);
+            }
+            "daily" | "everyday" => window.days.clear(),
+            _ if lower.contains(':') => {
+                let (start, end) = lower
+                    .split_once('-')
+                    .ok_or_else(|| format!("Invalid hours '{}', use HH:MM-HH:MM", token))?;
+                let start = parse_minute_of_day(start)
+                    .ok_or_else(|| format!("Invalid time '{}'", start))?;
+                let end =
+                    parse_minute_of_day(end).ok_or_else(|| format!("Invalid time '{}'", end))?;
+                if start == end {
+                    return Err("Start and end time must differ".to_string());
+                }
+                window.start_minute = Some(start);
+                window.end_minute = Some(end);
+            }
+            _ if token.contains('/') || lower == "utc" => {
+                let tz: Tz = token
+                    .parse()
+                    .map_err(|_| format!("Unknown timezone '{}'", token))?;
+                window.timezone = tz.name().to_string();
+            }
+            _ => window.days.extend(parse_days(&lower)?),
+        }
+    }
+
+    let tz: Tz = window.timezone.parse().unwrap_or(Tz::UTC);
+    let to_timestamp = |dt: NaiveDateTime| {
+        tz.from_local_datetime(&dt)
+            .earliest()
+            .map(|local| local.timestamp())
+            .ok_or_else(|| format!("{} does not exist in {}", dt, tz.name()))
+    };
+    window.starts_at = from.map(to_timestamp).transpose()?;
+    window.ends_at = until.map(to_timestamp).transpose()?;
+
+    if window
+        .starts_at
+        .zip(window.ends_at)
+        .is_some_and(|(start, end)| end <= start)
+    {
+        return Err("'until' must be after 'from'".to_string());
+    }
+
+    window.days.sort_unstable();
+    window.days.dedup();
+
+    if window.days.is_empty()
+        && window.start_minute.is_none()
+        && window.starts_at.is_none()
+        && window.ends_at.is_none()
+    {
+        return Err("No days, hours or dates given".to_string());
+    }
+
+    Ok(Some(window))
+}
+
+fn parse_local_datetime(value: &str) -> Option<NaiveDateTime> {
+    NaiveDateTime::parse_from_str(value, "%Y-%m-%dT%H:%M")
+        .ok()
+        .or_else(|| {
+            NaiveDate::parse_from_str(value, "%Y-%m-%d")
+                .ok()
+                .map(|date| date.and_time(NaiveTime::MIN))
+        })
+}
+
+fn parse_minute_of_day(value: &str) -> Option<u16> {
+    let (hour, minute) = value.split_once(':')?;
+    let hour: u16 = hour.parse().ok()?;
+    let minute: u16 = minute.parse().ok()?;
+    // 24:00 is allowed as the end of the day
+    if minute >= 60 || hour > 24 || (hour == 24 && minute != 0) {
+        return None;
+    }
+    Some(hour * 60 + minute)
+}
+
+fn parse_days(value: &str) -> Result<Vec<u8>, String> {
+    // Only exact names count, so words like "monkey" aren't read as a day
+    let day_index = |name: &str| {
+        DAY_NAMES
+            .iter()
+            .position(|day| *day == name)
+            .or_else(|| FULL_DAY_NAMES.iter().position(|day| *day == name))
+            .map(|i| i as u8)
+            .ok_or_else(|| format!("Unknown day '{}'", name))
+    };
+
+    let mut days = Vec::new();
+    for part in value.split(',').filter(|p| !p.is_empty()) {
+        if let Some((start, end)) = part.split_once('-') {
+            let (start, end) = (day_index(start)?, day_index(end)?);
+            let mut day = start;
+            loop {
+                days.push(day);
+                if day == end {
+                    break;
+                }
+                day = (day + 1) % 7;
+            }
+        } else {
+            days.push(day_index(part)?);
         }
     }
-    
-    result
+
+    Ok(days)
+}
+
+/// How many days of per-day usage buckets are kept for each filter.
+pub const DAILY_USAGE_RETENTION_DAYS: i64 = 90;
+
+pub fn day_key(date: NaiveDate) -> String {
+    date.format("%Y-%m-%d").to_string()
+}
+
+/// Counts one trigger on `today` and drops buckets older than the retention window.
+pub fn record_daily_usage(daily_usage: &mut BTreeMap<String, u64>, today: NaiveDate) {
+    *daily_usage.entry(day_key(today)).or_insert(0) += 1;
+
+    // ISO dates sort lexicographically, so a string comparison is enough
+    let cutoff = day_key(today - Duration::days(DAILY_USAGE_RETENTION_DAYS - 1));
+    daily_usage.retain(|day, _| *day >= cutoff);
+}
+
+/// Per-day counts for the last `days` days ending on `today`, oldest first.
+pub fn daily_counts(stats: &FilterStats, days: i64, today: NaiveDate) -> Vec<u64> {
+    (0..days)
+        .rev()
+        .map(|offset| {
+            let day = day_key(today - Duration::days(offset));
+            stats.daily_usage.get(&day).copied().unwrap_or(0)
+        })
+        .collect()
+}
+
+pub fn usage_in_last_days(stats: &FilterStats, days: i64, today: NaiveDate) -> u64 {
+    daily_counts(stats, days, today).iter().sum()
+}
+
+pub fn sparkline(counts: &[u64]) -> String {
+    const BARS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+    let max = counts.iter().copied().max().unwrap_or(0);
+
+    counts
+        .iter()
+        .map(|count| {
+            let level = (*count * (BARS.len() as u64 - 1)).checked_div(max);
+            BARS[level.unwrap_or(0) as usize]
+        })
+        .collect()
+}
+
+/// A filter is dead when it has not triggered in `days` days. Filters that never
+/// triggered are measured from their creation time.
+pub fn is_dead_filter(
+    filter: &FilterDefinition,
+    stats: &FilterStats,
+    days: i64,
+    now: i64,
+) -> bool {
+    let last_activity = stats.last_triggered.unwrap_or(filter.created_at);
+    now - last_activity >= days * 24 * 60 * 60
+}
+
+fn format_timestamp(timestamp: i64) -> String {
+    DateTime::from_timestamp(timestamp, 0)
+        .map(|dt| dt.format("%Y-%m-%d %H:%M:%S").to_string())
+        .unwrap_or_default()
+}
+
+/// One row per filter and day with usage, plus one dateless row for filters
+/// without any recorded buckets, so the export can be pivoted in a spreadsheet.
+pub fn filters_to_csv(rows: &[(FilterDefinition, FilterStats)]) -> String {
+    let mut csv = String::from(
+        "filter_id,trigger,match_type,response_type,created_at,total_uses,last_triggered,date,uses\n",
+    );
+
+    for (filter, stats) in rows {
+        let prefix = [
+            csv_field(&filter.id),
+            csv_field(&filter.trigger),
+            format!("{:?}", filter.match_type),
+            format!("{:?}", filter.response_type),
+            format_timestamp(filter.created_at),
+            stats.usage_count.to_string(),
+            stats.last_triggered.map(format_timestamp).unwrap_or_default(),
+        ]
+        .join(",");
+
+        if stats.daily_usage.is_empty() {
+            csv.push_str(&format!("{},,0\n", prefix));
+        }
+        for (day, uses) in &stats.daily_usage {
+            csv.push_str(&format!("{},{},{}\n", prefix, day, uses));
+        }
+    }
+
+    csv
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(rfc3339: &str) -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339(rfc3339).unwrap().with_timezone(&Utc)
+    }
+
+    #[test]
+    fn parses_days_hours_and_timezone() {
+        let window = parse_active_window("mon-fri 09:00-17:00 Europe/London")
+            .unwrap()
+            .unwrap();
+
+        assert_eq!(window.days, vec![0, 1, 2, 3, 4]);
+        assert_eq!(window.start_minute, Some(9 * 60));
+        assert_eq!(window.end_minute, Some(17 * 60));
+        assert_eq!(window.timezone, "Europe/London");
+        assert_eq!(
+            describe_active_window(&window),
+            "Mon-Fri 09:00-17:00 (Europe/London)"
+        );
+    }
+
+    #[test]
+    fn support_hours_follow_the_timezone() {
+        let window = parse_active_window("mon-fri 09:00-17:00 Europe/London")
+            .unwrap()
+            .unwrap();
+
+        // Wednesday 2025-07-16, London is UTC+1 in summer
+        assert!(is_window_open(&window, at("2025-07-16T08:30:00Z")));
+        assert!(!is_window_open(&window, at("2025-07-16T07:30:00Z")));
+        assert!(!is_window_open(&window, at("2025-07-16T16:00:00Z")));
+        // Saturday
+        assert!(!is_window_open(&window, at("2025-07-19T10:00:00Z")));
+    }
+
+    #[test]
+    fn overnight_window_belongs_to_start_day() {
+        let window = parse_active_window("fri 22:00-02:00").unwrap().unwrap();
+
+        // Friday 23:00 and Saturday 01:00 UTC are inside, Saturday 23:00 is not
+        assert!(is_window_open(&window, at("2025-07-18T23:00:00Z")));
+        assert!(is_window_open(&window, at("2025-07-19T01:00:00Z")));
+        assert!(!is_window_open(&window, at("2025-07-19T23:00:00Z")));
+        assert!(!is_window_open(&window, at("2025-07-18T21:00:00Z")));
+    }
+
+    #[test]
+    fn absolute_bounds_use_window_timezone() {
+        let window = parse_active_window("from 2025-11-01T18:00 until 2025-11-01T20:00 America/New_York")
+            .unwrap()
+            .unwrap();
+
+        // 18:00 EDT is 22:00 UTC
+        assert!(!is_window_open(&window, at("2025-11-01T21:59:00Z")));
+        assert!(is_window_open(&window, at("2025-11-01T22:00:00Z")));
+        assert!(!is_window_open(&window, at("2025-11-02T00:00:00Z")));
+    }
+
+    #[test]
+    fn rejects_invalid_input() {
+        assert!(parse_active_window("always").unwrap().is_none());
+        assert!(parse_active_window("funday").is_err());
+        assert!(parse_active_window("09:00-09:00").is_err());
+        assert!(parse_active_window("25:00-26:00").is_err());
+        assert!(parse_active_window("Mars/Olympus").is_err());
+        assert!(parse_active_window("from 2025-11-02 until 2025-11-01").is_err());
+        assert!(parse_active_window("UTC").is_err());
+    }
+
+    #[test]
+    fn day_lists_and_wrapping_ranges() {
+        let window = parse_active_window("sat,sun").unwrap().unwrap();
+        assert_eq!(window.days, vec![5, 6]);
+        assert_eq!(describe_active_window(&window), "Sat,Sun (UTC)");
+
+        let window = parse_active_window("fri-mon").unwrap().unwrap();
+        assert_eq!(window.days, vec![0, 4, 5, 6]);
+    }
+
+    #[test]
+    fn day_names_must_match_exactly() {
+        let window = parse_active_window("Monday-Wednesday").unwrap().unwrap();
+        assert_eq!(window.days, vec![0, 1, 2]);
+
+        assert!(parse_active_window("monkey").is_err());
+        assert!(parse_active_window("sunshine").is_err());
+        assert!(parse_active_window("mo").is_err());
+        assert!(parse_active_window("fri-sundays").is_err());
+    }
+
+    #[test]
+    fn cache_ttl_presets_cycle() {
+        assert_eq!(next_cache_ttl(0), 5 * 60);
+        assert_eq!(next_cache_ttl(DEFAULT_AI_CACHE_TTL), 6 * 60 * 60);
+        assert_eq!(next_cache_ttl(24 * 60 * 60), 0);
+        assert_eq!(next_cache_ttl(42), 0);
+
+        assert_eq!(describe_cache_ttl(0), "off");
+        assert_eq!(describe_cache_ttl(300), "5m");
+        assert_eq!(describe_cache_ttl(86_400), "24h");
+    }
+
+    fn stats_with(daily: &[(&str, u64)]) -> FilterStats {
+        FilterStats {
+            group_id: "-100".to_string(),
+            filter_id: "f1".to_string(),
+            usage_count: daily.iter().map(|(_, c)| c).sum(),
+            last_triggered: None,
+            last_triggered_by: None,
+            daily_usage: daily.iter().map(|(d, c)| (d.to_string(), *c)).collect(),
+        }
+    }
+
+    fn day(s: &str) -> NaiveDate {
+        NaiveDate::parse_from_str(s, "%Y-%m-%d").unwrap()
+    }
+
+    #[test]
+    fn daily_usage_is_bucketed_and_pruned() {
+        let mut daily = BTreeMap::new();
+        daily.insert("2025-01-01".to_string(), 4);
+        record_daily_usage(&mut daily, day("2025-06-01"));
+        record_daily_usage(&mut daily, day("2025-06-01"));
+
+        assert_eq!(daily.get("2025-06-01"), Some(&2));
+        assert!(!daily.contains_key("2025-01-01"));
+    }
+
+    #[test]
+    fn weekly_usage_and_sparkline() {
+        let stats = stats_with(&[("2025-05-25", 9), ("2025-05-27", 1), ("2025-06-01", 4)]);
+        let today = day("2025-06-01");
+
+        assert_eq!(daily_counts(&stats, 7, today), vec![0, 1, 0, 0, 0, 0, 4]);
+        assert_eq!(usage_in_last_days(&stats, 7, today), 5);
+        assert_eq!(sparkline(&[0, 1, 0, 0, 0, 0, 4]), "▁▂▁▁▁▁█");
+        assert_eq!(sparkline(&[0, 0]), "▁▁");
+    }
+
+    #[test]
+    fn dead_filters_use_creation_time_when_never_triggered() {
+        let mut filter = FilterDefinition::from((
+            "gm".to_string(),
+            "gm!".to_string(),
+            "-100".to_string(),
+            1,
+            MatchType::Contains,
+            ResponseType::Text,
+        ));
+        let now = 1_000 * 86_400;
+        filter.created_at = now - 40 * 86_400;
+        let mut stats = stats_with(&[]);
+
+        assert!(is_dead_filter(&filter, &stats, 30, now));
+        stats.last_triggered = Some(now - 2 * 86_400);
+        assert!(!is_dead_filter(&filter, &stats, 30, now));
+    }
+
+    #[test]
+    fn csv_export_quotes_fields_and_lists_days() {
+        let mut filter = FilterDefinition::from((
+            "hello, \"world\"".to_string(),
+            "hi".to_string(),
+            "-100".to_string(),
+            1,
+            MatchType::Exact,
+            ResponseType::Markdown,
+        ));
+        filter.id = "f1".to_string();
+        filter.created_at = 0;
+        let stats = stats_with(&[("2025-06-01", 2), ("2025-06-02", 3)]);
+
+        let csv = filters_to_csv(&[(filter, stats)]);
+        let lines: Vec<&str> = csv.lines().collect();
+        assert_eq!(lines.len(), 3);
+        assert_eq!(
+            lines[1],
+            "f1,\"hello, \"\"world\"\"\",Exact,Markdown,1970-01-01 00:00:00,5,,2025-06-01,2"
+        );
+        assert!(lines[2].ends_with(",2025-06-02,3"));
+    }
 }
@@ -0,0 +1,322 @@
+use std::collections::HashMap;
+
+use aho_corasick::AhoCorasick;
+
+use crate::filters::dto::{FilterDefinition, FilterMatch, MatchType};
+
+/// In-memory index over the active filters of a single group.
+///
+/// Every distinct trigger becomes one Aho-Corasick pattern, so a message is
+/// scanned once no matter how many filters the group has. The match type of
+/// each filter (word boundaries, anchoring) is then checked against the hits.
+pub struct GroupMatcher {
+    automaton: Option<AhoCorasick>,
+    filters: Vec<FilterDefinition>,
+    // Pattern id -> indices into `filters` that share that trigger
+    pattern_filters: Vec<Vec<usize>>,
+}
+
+impl GroupMatcher {
+    pub fn new(filters: Vec<FilterDefinition>) -> Self {
+        let mut patterns: Vec<String> = Vec::new();
+        let mut pattern_filters: Vec<Vec<usize>> = Vec::new();
+        let mut pattern_ids: HashMap<String, usize> = HashMap::new();
+
+        for (idx, filter) in filters.iter().enumerate() {
+            let trigger = filter.trigger.to_lowercase();
+            if trigger.is_empty() {
+                continue;
+            }
+
+            let pattern_id = match pattern_ids.get(&trigger) {
+                Some(id) => *id,
+                None => {
+                    patterns.push(trigger.clone());
+                    pattern_filters.push(Vec::new());
+                    pattern_ids.insert(trigger, patterns.len() - 1);
+                    patterns.len() - 1
+                }
+            };
+            pattern_filters[pattern_id].push(idx);
+        }
+
+        let automaton = if patterns.is_empty() {
+            None
+        } else {
+            match AhoCorasick::new(&patterns) {
+                Ok(automaton) => Some(automaton),
+                Err(e) => {
+                    log::error!("Failed to build filter matcher: {}", e);
+                    None
+                }
+            }
+        };
+
+        Self {
+            automaton,
+            filters,
+            pattern_filters,
+        }
+    }
+
+    /// Returns every filter that matches `text`, at most once per filter,
+    /// ordered by the position of the match in the message.
+    pub fn find_matches(&self, text: &str) -> Vec<FilterMatch> {
+        let Some(automaton) = &self.automaton else {
+            return Vec::new();
+        };
+
+        let text_lower = text.to_lowercase();
+        let mut spans: Vec<Option<(usize, usize)>> = vec![None; self.filters.len()];
+
+        // Hits for the same pattern arrive in increasing position, so the first
+        // accepted span for a filter is also its earliest one.
+        for hit in automaton.find_overlapping_iter(&text_lower) {
+            for &idx in &self.pattern_filters[hit.pattern().as_usize()] {
+                if spans[idx].is_some() {
+                    continue;
+                }
+                spans[idx] = check_hit(&self.filters[idx], &text_lower, hit.start(), hit.end());
+            }
+        }
+
+        let mut found: Vec<(usize, usize, usize)> = spans
+            .into_iter()
+            .enumerate()
+            .filter_map(|(idx, span)| span.map(|(start, end)| (start, end, idx)))
+            .collect();
+
+        // Earliest first; on ties prefer the longer (more specific) match
+        found.sort_by(|a, b| a.0.cmp(&b.0).then(b.1.cmp(&a.1)).then(a.2.cmp(&b.2)));
+
+        found
+            .into_iter()
+            .map(|(start, end, idx)| FilterMatch {
+                filter: self.filters[idx].clone(),
+                matched_text: text_lower[start..end].to_string(),
+                match_position: start,
+            })
+            .collect()
+    }
+}
+
+fn check_hit(
+    filter: &FilterDefinition,
+    text: &str,
+    start: usize,
+    end: usize,
+) -> Option<(usize, usize)> {
+    match filter.match_type {
+        MatchType::Exact => {
+            // The whole whitespace-delimited word, minus surrounding punctuation,
+            // must equal the trigger
+            let word_start = text[..start]
+                .char_indices()
+                .rev()
+                .find(|(_, c)| c.is_whitespace())
+                .map(|(i, c)| i + c.len_utf8())
+                .unwrap_or(0);
+            let word_end = text[end..]
+                .char_indices()
+                .find(|(_, c)| c.is_whitespace())
+                .map(|(i, _)| end + i)
+                .unwrap_or(text.len());
+            let word = &text[word_start..word_end];
+
+            if word.trim_matches(|c: char| !c.is_alphanumeric()) == &text[start..end] {
+                Some((word_start, word_end))
+            } else {
+                None
+            }
+        }
+        MatchType::Contains => {
+            let start_ok = text[..start]
+                .chars()
+                .next_back()
+                .is_none_or(|c| !c.is_alphanumeric());
+            let end_ok = text[end..]
+                .chars()
+                .next()
+                .is_none_or(|c| !c.is_alphanumeric());
+
+            if start_ok && end_ok {
+                Some((start, end))
+            } else {
+                None
+            }
+        }
+        MatchType::StartsWith => (start == 0).then_some((start, end)),
+        MatchType::EndsWith => (end == text.len()).then_some((start, end)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::filters::dto::ResponseType;
+    use std::time::Instant;
+
+    fn filter(id: &str, trigger: &str, match_type: MatchType) -> FilterDefinition {
+        FilterDefinition {
+            trigger: trigger.to_string(),
+            response: format!("response for {}", trigger),
+            group_id: "-100".to_string(),
+            created_by: 1,
+            created_at: 0,
+            is_active: true,
+            match_type,
+            response_type: ResponseType::Text,
+            id: id.to_string(),
+            active_window: None,
+            cache_ttl_seconds: 0,
+            vip_only: false,
+        }
+    }
+
+    fn ids(matches: &[FilterMatch]) -> Vec<&str> {
+        matches.iter().map(|m| m.filter.id.as_str()).collect()
+    }
+
+    #[test]
+    fn contains_respects_word_boundaries() {
+        let matcher = GroupMatcher::new(vec![filter("ca", "ca", MatchType::Contains)]);
+
+        assert_eq!(ids(&matcher.find_matches("what is the CA?")), vec!["ca"]);
+        assert!(matcher.find_matches("cat pictures").is_empty());
+        assert!(matcher.find_matches("africa").is_empty());
+    }
+
+    #[test]
+    fn contains_matches_multi_word_triggers() {
+        let matcher = GroupMatcher::new(vec![filter(
+            "contract",
+            "the contract",
+            MatchType::Contains,
+        )]);
+
+        let matches = matcher.find_matches("Where is The Contract address?");
+        assert_eq!(ids(&matches), vec!["contract"]);
+        assert_eq!(matches[0].matched_text, "the contract");
+        assert_eq!(matches[0].match_position, 9);
+    }
+
+    #[test]
+    fn exact_requires_whole_word() {
+        let matcher = GroupMatcher::new(vec![filter("gm", "gm", MatchType::Exact)]);
+
+        let matches = matcher.find_matches("well, gm! everyone");
+        assert_eq!(ids(&matches), vec!["gm"]);
+        assert_eq!(matches[0].matched_text, "gm!");
+        assert_eq!(matches[0].match_position, 6);
+        assert!(matcher.find_matches("gmgm all").is_empty());
+        assert!(matcher.find_matches("a-gm-b").is_empty());
+    }
+
+    #[test]
+    fn starts_and_ends_with_are_anchored() {
+        let matcher = GroupMatcher::new(vec![
+            filter("start", "hello", MatchType::StartsWith),
+            filter("end", "thanks", MatchType::EndsWith),
+        ]);
+
+        assert_eq!(
+            ids(&matcher.find_matches("Hello there, thanks")),
+            vec!["start", "end"]
+        );
+        assert!(matcher.find_matches("oh hello, thanks!").is_empty());
+        assert_eq!(ids(&matcher.find_matches("thanks and thanks")), vec!["end"]);
+        assert_eq!(
+            matcher.find_matches("thanks and thanks")[0].match_position,
+            11
+        );
+    }
+
+    #[test]
+    fn matches_are_ordered_by_position() {
+        let matcher = GroupMatcher::new(vec![
+            filter("c", "price", MatchType::Contains),
+            filter("a", "wen", MatchType::Contains),
+            filter("b", "moon", MatchType::Exact),
+        ]);
+
+        let matches = matcher.find_matches("wen moon, what price?");
+        assert_eq!(ids(&matches), vec!["a", "b", "c"]);
+    }
+
+    #[test]
+    fn overlapping_triggers_prefer_longer_match_at_same_position() {
+        let matcher = GroupMatcher::new(vec![
+            filter("short", "good", MatchType::Contains),
+            filter("long", "good morning", MatchType::Contains),
+        ]);
+
+        let matches = matcher.find_matches("good morning all");
+        assert_eq!(ids(&matches), vec!["long", "short"]);
+    }
+
+    #[test]
+    fn filters_sharing_a_trigger_all_match() {
+        let matcher = GroupMatcher::new(vec![
+            filter("one", "help", MatchType::Contains),
+            filter("two", "help", MatchType::StartsWith),
+        ]);
+
+        assert_eq!(ids(&matcher.find_matches("help me")), vec!["one", "two"]);
+        assert_eq!(ids(&matcher.find_matches("please help")), vec!["one"]);
+    }
+
+    #[test]
+    fn handles_non_ascii_text() {
+        let matcher = GroupMatcher::new(vec![filter("hola", "¿hola", MatchType::Contains)]);
+
+        assert_eq!(
+            ids(&matcher.find_matches("Ñandú dice ¿HOLA?")),
+            vec!["hola"]
+        );
+        assert!(
+            GroupMatcher::new(Vec::new())
+                .find_matches("anything")
+                .is_empty()
+        );
+    }
+
+    #[test]
+    #[ignore = "timing loop; run with --ignored"]
+    fn benchmark_thousands_of_filters() {
+        let match_types = [
+            MatchType::Exact,
+            MatchType::Contains,
+            MatchType::StartsWith,
+            MatchType::EndsWith,
+        ];
+        let filters: Vec<FilterDefinition> = (0..5_000)
+            .map(|i| {
+                filter(
+                    &format!("f{}", i),
+                    &format!("trigger{}", i),
+                    match_types[i % match_types.len()].clone(),
+                )
+            })
+            .collect();
+
+        let matcher = GroupMatcher::new(filters);
+        assert_eq!(matcher.filters.len(), 5_000);
+
+        let message =
+            "just chatting about trigger4001 and trigger17 with nothing special going on here";
+        let iterations = 10_000;
+        let match_started = Instant::now();
+        let mut total = 0;
+        for _ in 0..iterations {
+            total += matcher.find_matches(message).len();
+        }
+        let match_elapsed = match_started.elapsed();
+
+        // trigger4001 is Contains (4001 % 4 == 1); trigger17 is Contains too
+        assert_eq!(total, 2 * iterations);
+        assert_eq!(ids(&matcher.find_matches(message)), vec!["f4001", "f17"]);
+
+        // Generous bound so the test stays stable on slow CI machines
+        assert!(match_elapsed.as_secs() < 10);
+    }
+}
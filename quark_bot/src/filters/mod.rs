@@ -2,4 +2,5 @@ pub mod dto;
 pub mod filters;
 pub mod handler;
 pub mod helpers;
+pub mod matcher;
 
@@ -2,13 +2,18 @@ use std::env;
 
 use chrono::Utc;
 use reqwest::Url;
-use teloxide::{Bot, types::{ChatId, InlineKeyboardButton, InlineKeyboardMarkup}};
+use teloxide::{ApiError, Bot, RequestError, prelude::Requester, payloads::EditMessageTextSetters, types::{ChatId, InlineKeyboardButton, InlineKeyboardMarkup, MessageId, ParseMode}, utils::html};
 use tokio_cron_scheduler::Job;
 use aptos_rust_sdk_types::api_types::view::ViewRequest;
 
 use crate::{
-    dao::{dao::Dao, dto::ProposalEntry},
+    aptos::handler::Aptos,
+    credentials::handler::Auth,
+    dao::{dao::Dao, dto::{BallotType, ProposalArchive, ProposalEntry, ProposalOutcome}, helpers::{approval_weights, ballot_tally, evaluate_ballot, evaluate_outcome, format_tally, instant_runoff, verified_ballots}, payouts::prepare_payout},
+    dependencies::BotDependencies,
+    nft_gate::nft_gate::NftGate,
     panora::handler::Panora,
+    treasury::approvals::expire_requests,
     utils::{format_timestamp, send_scheduled_message, send_scheduled_message_with_keyboard},
     welcome::welcome_service::WelcomeService,
 };
@@ -17,16 +22,6 @@ use quark_core::helpers::dto::CoinVersion;
 // Retry function for handling rate limits with exponential backoff
 
 
-// Helper function to escape special characters for MarkdownV2
-fn escape_markdown_v2(text: &str) -> String {
-    text.chars()
-        .map(|c| match c {
-            '_' | '*' | '[' | ']' | '(' | ')' | '~' | '`' | '>' | '#' | '+' | '-' | '=' | '|' | '{' | '}' | '.' | '!' => format!("\\{}", c),
-            _ => c.to_string(),
-        })
-        .collect()
-}
-
 pub fn job_token_list(panora: Panora) -> Job {
     Job::new_async("0 0 * * * *", move |_uuid, _l| {
         let panora = panora.clone();
@@ -54,6 +49,11 @@ pub fn job_welcome_service_cleanup(welcome_service: WelcomeService, bot: Bot) ->
                 log::error!("Failed to cleanup expired welcome verifications: {}", e);
             }
             
+            // Lift raid lockdowns whose cool-off has passed
+            if let Err(e) = welcome_service.lift_expired_lockdowns(&bot).await {
+                log::error!("Failed to lift expired raid lockdowns: {}", e);
+            }
+
             // Cleanup expired input states
             if let Err(e) = welcome_service.cleanup_expired_input_states() {
                 log::error!("Failed to cleanup expired welcome input states: {}", e);
@@ -63,6 +63,73 @@ pub fn job_welcome_service_cleanup(welcome_service: WelcomeService, bot: Bot) ->
     .expect("Failed to create cron job")
 }
 
+pub fn job_welcome_scheduled_deletions(welcome_service: WelcomeService, bot: Bot) -> Job {
+    // Auto-delete TTLs can be as short as a few seconds, so this runs more often than the cleanup
+    Job::new_async("*/15 * * * * *", move |_uuid, _l| {
+        let welcome_service = welcome_service.clone();
+        let bot = bot.clone();
+        Box::pin(async move {
+            if let Err(e) = welcome_service.run_scheduled_deletions(&bot).await {
+                log::error!("Failed to run scheduled welcome message deletions: {}", e);
+            }
+        })
+    })
+    .expect("Failed to create cron job")
+}
+
+pub fn job_treasury_expirations(bot: Bot, bot_deps: BotDependencies) -> Job {
+    // Treasury requests stay open for a day, so a few minutes of lag is fine
+    Job::new_async("0 */5 * * * *", move |_uuid, _l| {
+        let bot = bot.clone();
+        let bot_deps = bot_deps.clone();
+        Box::pin(async move {
+            if let Err(e) = expire_requests(&bot, &bot_deps).await {
+                log::error!("Failed to expire treasury requests: {}", e);
+            }
+        })
+    })
+    .expect("Failed to create cron job")
+}
+
+pub fn job_welcome_token_holder_recheck(
+    welcome_service: WelcomeService,
+    auth: Auth,
+    aptos: Aptos,
+    bot: Bot,
+) -> Job {
+    Job::new_async("0 30 * * * *", move |_uuid, _l| {
+        let welcome_service = welcome_service.clone();
+        let auth = auth.clone();
+        let aptos = aptos.clone();
+        let bot = bot.clone();
+        Box::pin(async move {
+            log::info!("Re-checking token-gated group members");
+            if let Err(e) = welcome_service
+                .recheck_token_holders(&bot, &auth, &aptos)
+                .await
+            {
+                log::error!("Failed to re-check token holders: {}", e);
+            }
+        })
+    })
+    .expect("Failed to create cron job")
+}
+
+pub fn job_nft_role_recheck(nft_gate: NftGate, auth: Auth, aptos: Aptos) -> Job {
+    Job::new_async("0 45 * * * *", move |_uuid, _l| {
+        let nft_gate = nft_gate.clone();
+        let auth = auth.clone();
+        let aptos = aptos.clone();
+        Box::pin(async move {
+            log::info!("Re-checking NFT holder roles");
+            if let Err(e) = nft_gate.recheck_roles(&auth, &aptos).await {
+                log::error!("Failed to re-check NFT holder roles: {}", e);
+            }
+        })
+    })
+    .expect("Failed to create cron job")
+}
+
 pub fn job_token_ai_fees(panora: Panora) -> Job {
     // Run every 15 minutes instead of every minute to avoid rate limits
     Job::new_async("0 */15 * * * *", move |_uuid, _l| {
@@ -89,7 +156,7 @@ pub fn job_token_ai_fees(panora: Panora) -> Job {
     .expect("Failed to create cron job")
 }
 
-pub fn job_active_daos(dao: Dao, bot: Bot) -> Job {
+pub fn job_active_daos(panora: Panora, dao: Dao, bot: Bot) -> Job {
     let base_url = match env::var("APP_URL") {
         Ok(url) => url,
         Err(e) => {
@@ -104,6 +171,7 @@ pub fn job_active_daos(dao: Dao, bot: Bot) -> Job {
     
     Job::new_async("0 */2 * * * *", move |_uuid, _l| {
         let base_url = base_url.clone();
+        let panora = panora.clone();
         let dao = dao.clone();
         let bot = bot.clone();
         log::info!("Running active DAOs job");
@@ -158,8 +226,19 @@ pub fn job_active_daos(dao: Dao, bot: Bot) -> Job {
                 if !proposal_entry.disabled_notifications && time_since_last_notification >= interval_seconds {
                     // Create inline keyboard with voting options
                     let mut keyboard_buttons = Vec::new();
-                    
-                    for (index, option) in proposal_entry.options.iter().enumerate() {
+
+                    // Multi-select and ranked ballots are filled in from a private chat instead
+                    let vote_options: &[String] = if proposal_entry.ballot == BallotType::Single {
+                        &proposal_entry.options
+                    } else {
+                        keyboard_buttons.push(vec![InlineKeyboardButton::callback(
+                            "🗳️ Open ballot",
+                            format!("dao_ballot:{}", proposal_entry.proposal_id),
+                        )]);
+                        &[]
+                    };
+
+                    for (index, option) in vote_options.iter().enumerate() {
                         let base_url = format!(
                             "{}/dao?group_id={}&proposal_id={}&choice_id={}&coin_type={}&coin_version={}&dao_name={}&dao_description={}",
                             base_url,
@@ -186,11 +265,16 @@ pub fn job_active_daos(dao: Dao, bot: Bot) -> Job {
                         
                         // Create a row with URL button for each option
                         // Note: WebApp buttons are not supported in group chats, only in private chats
+                        // The callback button votes from Telegram for users with a linked wallet
                         let option_row = vec![
                             InlineKeyboardButton::url(
                                 format!("🗳️ Vote: {}", option),
                                 parsed_url
                             ),
+                            InlineKeyboardButton::callback(
+                                "⚡ Vote here",
+                                format!("dao_vote:{}:{}", proposal_entry.proposal_id, index)
+                            ),
                         ];
                         
                         keyboard_buttons.push(option_row);
@@ -215,14 +299,46 @@ pub fn job_active_daos(dao: Dao, bot: Bot) -> Job {
 
                     let keyboard = InlineKeyboardMarkup::new(keyboard_buttons);
 
+                    // Live tally from the contract; the reminder still goes out without it
+                    let tally_text = match fetch_tally_text(&panora, &dao, &proposal_entry).await {
+                        Ok(text) => format!("{}\n\n", text),
+                        Err(e) => {
+                            log::warn!("Failed to fetch tally for proposal {}: {}", proposal_entry.proposal_id, e);
+                            String::new()
+                        }
+                    };
+
                     // Create rich message text
                     let message_text = format!(
-                        "🏛️ {}\n\n📝 {}\n\n⏰ Voting ends: {}\n\n🗳️ Click on your preferred option below to vote:",
-                        proposal_entry.name,
-                        proposal_entry.description,
+                        "🏛️ {}\n\n📝 {}\n\n{}⏰ Voting ends: {}\n\n🗳️ Click on your preferred option below to vote:",
+                        html::escape(&proposal_entry.name),
+                        html::escape(&proposal_entry.description),
+                        tally_text,
                         format_timestamp(proposal_entry.end_date)
                     );
 
+                    // Edit the previous reminder in place; post a new one if it's gone
+                    if let Some(message_id) = proposal_entry.reminder_message_id {
+                        let edited = bot
+                            .edit_message_text(chat_group_id, MessageId(message_id), &message_text)
+                            .parse_mode(ParseMode::Html)
+                            .reply_markup(keyboard.clone())
+                            .await;
+
+                        match edited {
+                            Ok(_) | Err(RequestError::Api(ApiError::MessageNotModified)) => {
+                                log::info!("Updated active proposal reminder for: {}", proposal_entry.proposal_id);
+                                if let Err(e) = dao.update_last_active_notification(proposal_entry.proposal_id.clone()) {
+                                    log::error!("Failed to update last active notification for proposal {}: {}", proposal_entry.proposal_id, e);
+                                }
+                                continue;
+                            }
+                            Err(e) => {
+                                log::warn!("Failed to edit reminder for proposal {}, sending a new one: {}", proposal_entry.proposal_id, e);
+                            }
+                        }
+                    }
+
                     log::info!("Sending active proposals notification for: {}", proposal_entry.proposal_id);
 
                     // Send message with error handling
@@ -233,8 +349,11 @@ pub fn job_active_daos(dao: Dao, bot: Bot) -> Job {
                         proposal_entry.thread_id,
                         keyboard,
                     ).await {
-                        Ok(_) => {
+                        Ok(sent) => {
                             log::info!("Successfully sent active proposals notification for: {}", proposal_entry.proposal_id);
+                            if let Err(e) = dao.update_reminder_message_id(proposal_entry.proposal_id.clone(), Some(sent.id.0)) {
+                                log::error!("Failed to save reminder message for proposal {}: {}", proposal_entry.proposal_id, e);
+                            }
                         }
                         Err(e) => {
                             log::error!("Failed to send active proposals notification for {}: {}", proposal_entry.proposal_id, e);
@@ -260,6 +379,28 @@ pub fn job_active_daos(dao: Dao, bot: Bot) -> Job {
     .expect("Failed to create cron job")
 }
 
+async fn fetch_tally_text(panora: &Panora, dao: &Dao, proposal_entry: &ProposalEntry) -> anyhow::Result<String> {
+    let tally = match proposal_entry.ballot {
+        BallotType::Single => panora
+            .aptos
+            .get_proposal_tally(&proposal_entry.group_id, &proposal_entry.proposal_id, &proposal_entry.version)
+            .await?,
+        // Stored ballots count once the wallet's on-chain vote backs them
+        ballot => {
+            let chain_votes = panora
+                .aptos
+                .get_proposal_votes(&proposal_entry.group_id, &proposal_entry.proposal_id, &proposal_entry.version)
+                .await?;
+            let ballots = verified_ballots(&dao.get_proposal_votes(&proposal_entry.proposal_id)?, &chain_votes);
+            ballot_tally(ballot, proposal_entry.options.len(), &ballots)
+        }
+    };
+
+    let token = panora.get_token_by_address(&proposal_entry.coin_type).await?;
+
+    Ok(format_tally(&proposal_entry.options, &tally, token.decimals, &token.symbol))
+}
+
 pub fn job_daos_results(panora: Panora, bot: Bot, dao: Dao) -> Job {
     Job::new_async("0 */2 * * * *", move |_uuid, _l| {
         let panora = panora.clone();
@@ -305,7 +446,11 @@ pub fn job_daos_results(panora: Panora, bot: Bot, dao: Dao) -> Job {
                 // Check if DAO has ended and results haven't been sent
                     log::info!("Processing finished DAO: {}", proposal_entry.proposal_id);
                     
-                    match fetch_and_send_dao_results(&panora, &bot, &proposal_entry).await {
+                    // Per-proposal thresholds win over the group defaults
+                    let quorum = proposal_entry.quorum.or(admin_preferences.quorum);
+                    let approval_threshold = proposal_entry.approval_threshold.or(admin_preferences.approval_threshold);
+
+                    match fetch_and_send_dao_results(&panora, &bot, &dao, &proposal_entry, quorum, approval_threshold).await {
                         Ok(_) => {
                             log::info!("Successfully sent DAO results for: {}", proposal_entry.proposal_id);
                             if let Err(e) = dao.update_last_result_notification(proposal_entry.proposal_id.clone()) {
@@ -327,7 +472,10 @@ pub fn job_daos_results(panora: Panora, bot: Bot, dao: Dao) -> Job {
 async fn fetch_and_send_dao_results(
     panora: &Panora,
     bot: &Bot,
+    dao: &Dao,
     proposal_entry: &ProposalEntry,
+    quorum: Option<f64>,
+    approval_threshold: Option<u8>,
 ) -> anyhow::Result<()> {
     let group_id = proposal_entry.group_id.clone();
 
@@ -344,6 +492,11 @@ async fn fetch_and_send_dao_results(
         }
     };
     
+    // Multi-select and ranked ballots are tallied from the votes the bot stored
+    if proposal_entry.ballot != BallotType::Single {
+        return send_ballot_results(panora, bot, dao, chat_group_id, proposal_entry, quorum, approval_threshold).await;
+    }
+
     // Prepare view request based on DAO version
     let view_function = match proposal_entry.version {
         CoinVersion::V1 => "get_group_dao_v1",
@@ -420,11 +573,11 @@ async fn fetch_and_send_dao_results(
                 }
             }
             
-            // Create results message (using MarkdownV2 formatting)
+            // Create results message (sent with HTML formatting)
             let mut results_text = format!(
-                "🏆 *DAO VOTING RESULTS*\n\n🏛️ *{}*\n📝 {}\n\n📊 *Results:*\n",
-                escape_markdown_v2(&proposal_entry.name),
-                escape_markdown_v2(&proposal_entry.description)
+                "🏆 <b>DAO VOTING RESULTS</b>\n\n🏛️ <b>{}</b>\n📝 {}\n\n📊 <b>Results:</b>\n",
+                html::escape(&proposal_entry.name),
+                html::escape(&proposal_entry.description)
             );
             
             for (index, choice) in choices.iter().enumerate() {
@@ -439,44 +592,41 @@ async fn fetch_and_send_dao_results(
                 
                 let emoji = if index == winning_index { "🥇" } else { "📊" };
                 results_text.push_str(&format!(
-                    "{} *{}*: {} {:.2} votes \\({:.2}%\\)\n",
-                    emoji, escape_markdown_v2(choice_name), coin.symbol, votes, percentage
-                ).replace(".", "\\."));
+                    "{} <b>{}</b>: {} {:.2} votes ({:.2}%)\n",
+                    emoji, html::escape(choice_name), html::escape(&coin.symbol), votes, percentage
+                ));
             }
             
-            if total_votes > 0.0f64 {
-                let winning_choice = choices[winning_index].as_str().unwrap_or("Unknown");
-                results_text.push_str(&format!(
-                    "\n🎉 *Winner: {}* with {:.2} {} votes\\!\n📈 Total votes cast: {:.2} {}",
-                    escape_markdown_v2(winning_choice), max_votes, coin.symbol, total_votes, coin.symbol
-                ).replace(".", "\\."));
-            } else {
-                results_text.push_str("\n❌ No votes were cast for this DAO\\.");
-            }
-        
-            // Send the results message with error handling
-            match send_scheduled_message(
-                bot,
-                chat_group_id,
-                &results_text,
-                proposal_entry.thread_id,
-            ).await
-            {
-                Ok(_) => {
-                    log::info!("Sent DAO results for {} to group {}", proposal_entry.proposal_id, group_id);
-                }
-                Err(e) => {
-                    log::error!("Failed to send DAO results for {} to group {}: {}", proposal_entry.proposal_id, group_id, e);
-                    return Err(anyhow::anyhow!("Failed to send message: {}", e));
+            let weights: Vec<f64> = choices_weights
+                .iter()
+                .map(|weight| weight.as_str().unwrap_or("0").parse::<u64>().unwrap_or(0) as f64 / 10_f64.powi(decimals as i32))
+                .collect();
+            let choice_names: Vec<String> = choices
+                .iter()
+                .map(|choice| choice.as_str().unwrap_or("Unknown").to_string())
+                .collect();
+
+            let outcome = evaluate_outcome(&weights, quorum, approval_threshold);
+            results_text.push_str(&outcome_text(&outcome, &choice_names, &coin.symbol, total_votes, quorum, approval_threshold));
+
+            // Keep the final tally after the proposal itself expires
+            let mut votes = vec![0u64; weights.len()];
+            for user_choice in dao_info["user_choices"].as_array().into_iter().flatten() {
+                let choice_id = user_choice["choice_id"].as_str().and_then(|id| id.parse::<usize>().ok());
+                if let Some(count) = choice_id.and_then(|id| votes.get_mut(id)) {
+                    *count += 1;
                 }
             }
+
+            let archive = archive_entry(proposal_entry, &coin.symbol, votes, weights, outcome);
+            publish_results(bot, dao, chat_group_id, proposal_entry, &results_text, archive).await?;
         } else {
             log::warn!("No DAO data found in response for DAO: {}", proposal_entry.proposal_id);
             // Send a simple completion message with error handling
             if let Err(e) = send_scheduled_message(
                 bot,
                 chat_group_id,
-                &format!("🏛️ DAO \"{}\" has ended.", proposal_entry.name),
+                &format!("🏛️ DAO \"{}\" has ended.", html::escape(&proposal_entry.name)),
                 proposal_entry.thread_id,
             ).await {
                 log::error!("Failed to send fallback message for DAO {}: {}", proposal_entry.proposal_id, e);
@@ -488,7 +638,7 @@ async fn fetch_and_send_dao_results(
         if let Err(e) = send_scheduled_message(
             bot,
             chat_group_id,
-            &format!("🏛️ DAO \"{}\" has ended.", proposal_entry.name),
+            &format!("🏛️ DAO \"{}\" has ended.", html::escape(&proposal_entry.name)),
             proposal_entry.thread_id,
         ).await {
             log::error!("Failed to send fallback message for DAO {}: {}", proposal_entry.proposal_id, e);
@@ -498,6 +648,207 @@ async fn fetch_and_send_dao_results(
     Ok(())
 }
 
+/// Outcome lines of the results message, as HTML like the rest of it.
+fn outcome_text(
+    outcome: &ProposalOutcome,
+    options: &[String],
+    symbol: &str,
+    total_votes: f64,
+    quorum: Option<f64>,
+    approval_threshold: Option<u8>,
+) -> String {
+    let choice_name = |index: usize| html::escape(options.get(index).map(String::as_str).unwrap_or("Unknown"));
+    let amount = |value: f64| format!("{:.2} {}", value, html::escape(symbol));
+    let total_line = format!("📈 Total votes cast: {}", amount(total_votes));
+
+    match outcome {
+        ProposalOutcome::Passed { winner, approval } => format!(
+            "\n✅ <b>PASSED: {}</b> with {:.2}% approval\n{}",
+            choice_name(*winner), approval, total_line
+        ),
+        ProposalOutcome::Rejected { leader: Some(leader), approval } => format!(
+            "\n❌ <b>REJECTED</b>: {} led with {:.2}% but needed {}%\n{}",
+            choice_name(*leader),
+            approval,
+            approval_threshold.unwrap_or(0),
+            total_line
+        ),
+        ProposalOutcome::Rejected { leader: None, .. } => {
+            format!("\n❌ <b>REJECTED</b>: tied for first place\n{}", total_line)
+        }
+        ProposalOutcome::FailedQuorum { participation } => match quorum {
+            Some(quorum) => format!(
+                "\n⚠️ <b>FAILED QUORUM</b>: {} voted, {} required",
+                amount(*participation), amount(quorum)
+            ),
+            None => "\n❌ No votes were cast for this DAO.".to_string(),
+        },
+    }
+}
+
+/// Final tally kept after the proposal itself expires.
+fn archive_entry(
+    proposal_entry: &ProposalEntry,
+    symbol: &str,
+    votes: Vec<u64>,
+    weights: Vec<f64>,
+    outcome: ProposalOutcome,
+) -> ProposalArchive {
+    ProposalArchive {
+        proposal_id: proposal_entry.proposal_id.clone(),
+        group_id: proposal_entry.group_id.clone(),
+        name: proposal_entry.name.clone(),
+        description: proposal_entry.description.clone(),
+        options: proposal_entry.options.clone(),
+        start_date: proposal_entry.start_date,
+        end_date: proposal_entry.end_date,
+        symbol: symbol.to_string(),
+        votes,
+        weights,
+        outcome,
+        archived_at: Utc::now().timestamp() as u64,
+    }
+}
+
+/// Archive the final tally, post the results and prepare the payout of the
+/// winning option.
+async fn publish_results(
+    bot: &Bot,
+    dao: &Dao,
+    chat_group_id: ChatId,
+    proposal_entry: &ProposalEntry,
+    results_text: &str,
+    archive: ProposalArchive,
+) -> anyhow::Result<()> {
+    let passed_option = match archive.outcome {
+        ProposalOutcome::Passed { winner, .. } => Some(winner),
+        _ => None,
+    };
+
+    if let Err(e) = dao.archive_proposal(&archive) {
+        log::error!("Failed to archive DAO {}: {}", proposal_entry.proposal_id, e);
+    }
+
+    // Send the results message with error handling
+    let sent = send_scheduled_message(bot, chat_group_id, results_text, proposal_entry.thread_id).await;
+    match &sent {
+        Ok(_) => {
+            log::info!("Sent DAO results for {} to group {}", proposal_entry.proposal_id, proposal_entry.group_id);
+        }
+        Err(e) => {
+            log::error!("Failed to send DAO results for {} to group {}: {}", proposal_entry.proposal_id, proposal_entry.group_id, e);
+        }
+    }
+
+    // Treasury payout attached to the winning option, if any; prepared even
+    // when the announcement could not be sent
+    if let Some(winner) = passed_option {
+        let payout = prepare_payout(bot, dao, chat_group_id, proposal_entry, winner).await;
+        if let Err(e) = payout {
+            log::error!("Failed to prepare payout for DAO {}: {}", proposal_entry.proposal_id, e);
+        }
+    }
+
+    sent.map(|_| ()).map_err(|e| anyhow::anyhow!("Failed to send message: {}", e))
+}
+
+/// Results of multi-select and ranked-choice proposals, tallied from the
+/// ballots stored by the bot that are backed by the voter's on-chain vote and
+/// weighted by the balance the contract counted for it.
+async fn send_ballot_results(
+    panora: &Panora,
+    bot: &Bot,
+    dao: &Dao,
+    chat_group_id: ChatId,
+    proposal_entry: &ProposalEntry,
+    quorum: Option<f64>,
+    approval_threshold: Option<u8>,
+) -> anyhow::Result<()> {
+    let token = panora.get_token_by_address(&proposal_entry.coin_type).await?;
+    let scale = 10_f64.powi(token.decimals as i32);
+    let options = &proposal_entry.options;
+
+    let chain_votes = panora
+        .aptos
+        .get_proposal_votes(&proposal_entry.group_id, &proposal_entry.proposal_id, &proposal_entry.version)
+        .await?;
+    let ballots: Vec<(Vec<usize>, f64)> = verified_ballots(&dao.get_proposal_votes(&proposal_entry.proposal_id)?, &chain_votes)
+        .into_iter()
+        .map(|vote| (vote.choices.iter().map(|&choice| choice as usize).collect(), vote.weight as f64 / scale))
+        .collect();
+    let participation: f64 = ballots.iter().map(|(_, weight)| weight).sum();
+
+    let option_name = |index: usize| html::escape(options.get(index).map(String::as_str).unwrap_or("Unknown"));
+    let amount = |value: f64| format!("{:.2} {}", value, html::escape(&token.symbol));
+
+    let mut results_text = format!(
+        "🏆 <b>DAO VOTING RESULTS</b>\n\n🏛️ <b>{}</b>\n📝 {}\n\n",
+        html::escape(&proposal_entry.name),
+        html::escape(&proposal_entry.description)
+    );
+
+    let (votes, weights, outcome) = match proposal_entry.ballot {
+        BallotType::RankedChoice => {
+            let rounds = instant_runoff(options.len(), &ballots);
+            let mut eliminated = vec![false; options.len()];
+
+            results_text.push_str("🔁 <b>Instant-runoff rounds:</b>\n");
+
+            for (number, round) in rounds.iter().enumerate() {
+                let standings = (0..options.len())
+                    .filter(|&index| !eliminated[index])
+                    .map(|index| format!("{} {}", option_name(index), amount(round.weights[index])))
+                    .collect::<Vec<_>>()
+                    .join(" · ");
+
+                results_text.push_str(&format!("<b>Round {}</b>: {}", number + 1, standings));
+
+                if let Some(loser) = round.eliminated {
+                    eliminated[loser] = true;
+                    results_text.push_str(&format!(" ➜ {} eliminated", option_name(loser)));
+                }
+                results_text.push('\n');
+            }
+
+            let Some(last) = rounds.last() else {
+                return Err(anyhow::anyhow!("No runoff rounds for DAO {}", proposal_entry.proposal_id));
+            };
+            let final_total: f64 = last.weights.iter().sum();
+            let outcome = evaluate_ballot(&last.weights, participation, final_total, quorum, approval_threshold);
+
+            (last.votes.clone(), last.weights.clone(), outcome)
+        }
+        _ => {
+            let (votes, weights) = approval_weights(options.len(), &ballots);
+            let max = weights.iter().cloned().fold(0.0, f64::max);
+
+            results_text.push_str("📊 <b>Results:</b>\n");
+
+            for (index, weight) in weights.iter().enumerate() {
+                let share = if participation > 0.0 { weight / participation * 100.0 } else { 0.0 };
+                let emoji = if max > 0.0 && *weight == max { "🥇" } else { "📊" };
+                results_text.push_str(&format!(
+                    "{} <b>{}</b>: {} from {} voters ({:.2}% of voting power)\n",
+                    emoji,
+                    option_name(index),
+                    amount(*weight),
+                    votes[index],
+                    share
+                ));
+            }
+
+            let outcome = evaluate_ballot(&weights, participation, participation, quorum, approval_threshold);
+
+            (votes, weights, outcome)
+        }
+    };
+
+    results_text.push_str(&outcome_text(&outcome, options, &token.symbol, participation, quorum, approval_threshold));
+
+    let archive = archive_entry(proposal_entry, &token.symbol, votes, weights, outcome);
+    publish_results(bot, dao, chat_group_id, proposal_entry, &results_text, archive).await
+}
+
 pub fn job_dao_results_cleanup(dao: Dao) -> Job {
     // Run every day at 00:00
     Job::new_async("0 0 0 * * *", move |_uuid, _l| {
@@ -3,9 +3,13 @@ use crate::job::handler::{
     job_active_daos,
     job_dao_results_cleanup,
     job_daos_results,
+    job_nft_role_recheck,
     job_token_ai_fees,
     job_token_list,
+    job_treasury_expirations,
+    job_welcome_scheduled_deletions,
     job_welcome_service_cleanup,
+    job_welcome_token_holder_recheck,
 };
 use crate::scheduled_payments::runner as scheduled_payments_runner;
 use crate::scheduled_prompts::runner as scheduled_prompts_runner;
@@ -26,9 +30,23 @@ pub async fn schedule_jobs(bot: Bot, bot_deps: &BotDependencies) -> Result<()> {
     let job_token_list = job_token_list(panora.clone());
     let job_token_ai_fees = job_token_ai_fees(panora.clone());
     let job_dao_results = job_daos_results(panora.clone(), bot.clone(), dao.clone());
-    let job_active_daos = job_active_daos(dao.clone(), bot.clone());
+    let job_active_daos = job_active_daos(panora.clone(), dao.clone(), bot.clone());
     let job_dao_results_cleanup = job_dao_results_cleanup(dao.clone());
     let job_welcome_service_cleanup = job_welcome_service_cleanup(welcome_service.clone(), bot.clone());
+    let job_welcome_scheduled_deletions =
+        job_welcome_scheduled_deletions(welcome_service.clone(), bot.clone());
+    let job_welcome_token_holder_recheck = job_welcome_token_holder_recheck(
+        welcome_service.clone(),
+        bot_deps.auth.clone(),
+        panora.aptos.clone(),
+        bot.clone(),
+    );
+    let job_treasury_expirations = job_treasury_expirations(bot.clone(), bot_deps.clone());
+    let job_nft_role_recheck = job_nft_role_recheck(
+        bot_deps.nft_gate.clone(),
+        bot_deps.auth.clone(),
+        panora.aptos.clone(),
+    );
 
     if let Err(e) = scheduler.add(job_token_list).await {
         log::error!("Failed to add token list job to scheduler: {}", e);
@@ -60,6 +78,26 @@ pub async fn schedule_jobs(bot: Bot, bot_deps: &BotDependencies) -> Result<()> {
         return Err(anyhow::anyhow!("Failed to add welcome service cleanup job: {}", e));
     }
 
+    if let Err(e) = scheduler.add(job_welcome_scheduled_deletions).await {
+        log::error!("Failed to add welcome scheduled deletions job to scheduler: {}", e);
+        return Err(anyhow::anyhow!("Failed to add welcome scheduled deletions job: {}", e));
+    }
+
+    if let Err(e) = scheduler.add(job_welcome_token_holder_recheck).await {
+        log::error!("Failed to add welcome token holder recheck job to scheduler: {}", e);
+        return Err(anyhow::anyhow!("Failed to add welcome token holder recheck job: {}", e));
+    }
+
+    if let Err(e) = scheduler.add(job_nft_role_recheck).await {
+        log::error!("Failed to add NFT role recheck job to scheduler: {}", e);
+        return Err(anyhow::anyhow!("Failed to add NFT role recheck job: {}", e));
+    }
+
+    if let Err(e) = scheduler.add(job_treasury_expirations).await {
+        log::error!("Failed to add treasury expirations job to scheduler: {}", e);
+        return Err(anyhow::anyhow!("Failed to add treasury expirations job: {}", e));
+    }
+
     scheduled_prompts_runner::register_all_schedules(bot.clone(), bot_deps.clone())
         .await
         .map_err(|e| {
@@ -13,14 +13,18 @@ mod group;
 mod job;
 mod message_history;
 mod migrations;
+mod nft_gate;
 mod panora;
 mod payment;
 mod pending_transactions;
 mod scheduled_payments;
 mod scheduled_prompts;
+mod scheduling;
 mod services;
 mod sponsor;
 mod summarization_settings;
+mod template;
+mod treasury;
 mod user_conversation;
 mod user_model_preferences;
 mod utils;
@@ -46,6 +50,7 @@ use crate::{
     group::{document_library::GroupDocuments, handler::Group},
     job::job_scheduler::schedule_jobs,
     message_history::handler::MessageHistory,
+    nft_gate::nft_gate::NftGate,
     panora::handler::Panora,
     payment::{dto::PaymentPrefs, payment::Payment},
     pending_transactions::handler::PendingTransactions,
@@ -53,6 +58,7 @@ use crate::{
     scheduled_prompts::storage::ScheduledStorage,
     services::handler::Services,
     sponsor::sponsor::Sponsor,
+    treasury::handler::TreasuryApprovals,
     user_conversation::handler::UserConversations,
     user_model_preferences::handler::UserModelPreferences,
     yield_ai::yield_ai::YieldAI,
@@ -82,13 +88,14 @@ async fn main() {
     let aptos_network = env::var("APTOS_NETWORK").expect("APTOS_NETWORK not set");
     let contract_address = env::var("CONTRACT_ADDRESS").expect("CONTRACT_ADDRESS not set");
     let aptos_api_key = env::var("APTOS_API_KEY").unwrap_or_default();
+    let aptos_indexer = env::var("APTOS_INDEXER").unwrap_or_default();
     let default_symbol = env::var("DEFAULT_SYMBOL").expect("DEFAULT_SYMBOL not set");
 
     let google_cloud = GcsImageUploader::new(&gcs_creds, bucket_name)
         .await
         .expect("Failed to create GCS image uploader");
 
-    let aptos = Aptos::new(aptos_network, contract_address, aptos_api_key);
+    let aptos = Aptos::new(aptos_network, contract_address, aptos_api_key, aptos_indexer);
 
     let min_deposit = env::var("MIN_DEPOSIT")
         .expect("MIN_DEPOSIT not set")
@@ -139,14 +146,34 @@ async fn main() {
         .expect("Failed to create ModerationService");
     let sentinel = SentinelService::new(db.clone());
     let sponsor = Sponsor::new(db.clone());
+    let nft_gate = NftGate::new(db.clone());
 
     let user_convos = UserConversations::new(&db).unwrap();
     let user_model_prefs = UserModelPreferences::new(&db).unwrap();
     let group_docs = GroupDocuments::new(&db).unwrap();
     let group_file_upload_state = assets::group_file_upload_state::GroupFileUploadState::new();
     let pending_transactions = PendingTransactions::new(&db).unwrap();
+    let treasury = TreasuryApprovals::new(&db).unwrap();
     let yield_ai = YieldAI::new();
     let welcome_service = welcome::welcome_service::WelcomeService::new(db.clone());
+
+    let spammer_list_path = env::var("KNOWN_SPAMMERS_FILE")
+        .map(std::path::PathBuf::from)
+        .unwrap_or_else(|_| {
+            env::current_dir()
+                .unwrap_or_else(|_| std::path::PathBuf::from("."))
+                .join("config/known_spammers.txt")
+        });
+    if spammer_list_path.exists() {
+        match welcome_service.import_known_spammers(&spammer_list_path) {
+            Ok((added, skipped)) => log::info!(
+                "Imported known spammer list: {} new, {} entries skipped",
+                added,
+                skipped
+            ),
+            Err(e) => log::error!("Failed to import known spammer list: {}", e),
+        }
+    }
     let summarization_settings = summarization_settings::SummarizationSettings::new(&db)
         .expect("Failed to create SummarizationSettings");
     let command_settings = CommandSettingsManager::new(db.clone());
@@ -205,6 +232,19 @@ async fn main() {
             "Moderate content (reply to message) and send a report to the admin if content is found to be inappropriate, muting the user in this case.",
         ),
         BotCommand::new("rules", "Show core and custom rules for this group."),
+        BotCommand::new("roles", "Check your NFT holdings and claim group roles."),
+        BotCommand::new(
+            "manageproposals",
+            "Cancel, extend or edit the group's proposals (admins only).",
+        ),
+        BotCommand::new(
+            "proposals",
+            "Browse active and past proposals, with CSV export.",
+        ),
+        BotCommand::new(
+            "newproposal",
+            "Create a proposal step by step (admins only).",
+        ),
         BotCommand::new("balance", "Get your balance of a token."),
         BotCommand::new("groupwalletaddress", "Get the group's wallet address."),
         BotCommand::new("groupbalance", "Get the group's balance of a token."),
@@ -257,6 +297,7 @@ async fn main() {
         media_aggregator,
         history_storage,
         pending_transactions,
+        treasury,
         yield_ai,
         scheduler,
         payment,
@@ -265,6 +306,7 @@ async fn main() {
         moderation,
         sentinel,
         sponsor,
+        nft_gate,
         summarization_settings,
         welcome_service,
         summarizer,
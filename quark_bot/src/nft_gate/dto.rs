@@ -0,0 +1,72 @@
+use serde::{Deserialize, Serialize};
+use teloxide::types::{ChatId, UserId};
+
+/// Ownership requirement for an Aptos digital asset collection.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CollectionRequirement {
+    /// Collection object address as stored by the indexer.
+    pub collection_id: String,
+    pub collection_name: String,
+    pub min_count: u64,
+}
+
+/// Bot-level perks granted to holders of a collection.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum MemberRole {
+    /// Can trigger filters marked VIP only.
+    Vip,
+    /// AI requests are paid by the group without using up the sponsor quota.
+    SponsoredAi,
+}
+
+impl MemberRole {
+    pub const ALL: [MemberRole; 2] = [MemberRole::Vip, MemberRole::SponsoredAi];
+
+    /// Stable identifier used in storage keys and callback data.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            MemberRole::Vip => "vip",
+            MemberRole::SponsoredAi => "sponsored_ai",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|role| role.as_str() == value)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RoleRule {
+    pub role: MemberRole,
+    pub requirement: CollectionRequirement,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct NftRoleSettings {
+    #[serde(default)]
+    pub rules: Vec<RoleRule>,
+}
+
+pub const MAX_ROLE_RULES: usize = 5;
+
+/// A role currently held by a member, re-verified periodically.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RoleGrant {
+    pub chat_id: ChatId,
+    pub user_id: UserId,
+    pub username: String,
+    pub role: MemberRole,
+    pub granted_at: i64,   // unix timestamp
+    pub last_checked: i64, // unix timestamp
+}
+
+/// Result of checking a member's linked wallet against a collection requirement.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CollectionCheck {
+    /// No `/loginuser` credentials for this Telegram account.
+    NotLinked,
+    Insufficient { owned: u64 },
+    Qualified,
+    /// The indexer could not be reached; never treated as a failure.
+    Unavailable,
+}
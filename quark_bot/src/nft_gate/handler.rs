@@ -0,0 +1,85 @@
+use anyhow::Result;
+use teloxide::{prelude::*, types::Message};
+
+use crate::{
+    dependencies::BotDependencies,
+    nft_gate::{
+        dto::CollectionCheck,
+        helpers::{describe_requirement, describe_role},
+    },
+    utils::{send_html_message, send_message},
+};
+
+/// /roles: re-check the caller's holdings against the group's role rules and report the result.
+pub async fn handle_roles(bot: Bot, msg: Message, bot_deps: BotDependencies) -> Result<()> {
+    if !msg.chat.is_group() && !msg.chat.is_supergroup() {
+        send_message(
+            msg,
+            bot,
+            "❌ This command can only be used in a group".to_string(),
+        )
+        .await?;
+        return Ok(());
+    }
+
+    let Some(user) = msg.from.clone() else {
+        send_message(msg, bot, "❌ User not found".to_string()).await?;
+        return Ok(());
+    };
+
+    let results = bot_deps
+        .nft_gate
+        .refresh_member_roles(&bot_deps.auth, &bot_deps.panora.aptos, msg.chat.id, &user)
+        .await?;
+
+    if results.is_empty() {
+        send_message(
+            msg,
+            bot,
+            "ℹ️ This group has no NFT holder roles.".to_string(),
+        )
+        .await?;
+        return Ok(());
+    }
+
+    if results
+        .iter()
+        .all(|(_, check)| *check == CollectionCheck::NotLinked)
+    {
+        send_message(
+            msg,
+            bot,
+            "🔗 Link your wallet first: open a chat with me and send /loginuser, then try /roles again."
+                .to_string(),
+        )
+        .await?;
+        return Ok(());
+    }
+
+    let lines: Vec<String> = results
+        .iter()
+        .map(|(rule, check)| {
+            let status = match check {
+                CollectionCheck::Qualified => "✅".to_string(),
+                CollectionCheck::Insufficient { owned } => format!("❌ you hold {}", owned),
+                CollectionCheck::NotLinked => "❌ wallet not linked".to_string(),
+                CollectionCheck::Unavailable => "⚠️ couldn't check, try again later".to_string(),
+            };
+            format!(
+                "{} — {}: {}",
+                describe_role(rule.role),
+                teloxide::utils::html::escape(&describe_requirement(&rule.requirement)),
+                status
+            )
+        })
+        .collect();
+
+    let text = format!(
+        "🏷️ <b>Your roles in this group</b>\n\n{}\n\n\
+        <i>Holdings are re-checked every hour; roles are removed if you no longer qualify.</i>",
+        lines.join("\n")
+    );
+    send_html_message(msg, bot, text).await?;
+
+    Ok(())
+}
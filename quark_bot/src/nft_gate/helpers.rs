@@ -0,0 +1,116 @@
+use teloxide::types::UserId;
+
+use crate::{
+    aptos::handler::Aptos,
+    credentials::handler::Auth,
+    nft_gate::dto::{CollectionCheck, CollectionRequirement, MemberRole},
+};
+
+pub fn describe_role(role: MemberRole) -> &'static str {
+    match role {
+        MemberRole::Vip => "⭐ VIP",
+        MemberRole::SponsoredAi => "🤖 Sponsored AI",
+    }
+}
+
+/// Human readable requirement, e.g. "2 NFTs from Aptos Monkeys".
+pub fn describe_requirement(requirement: &CollectionRequirement) -> String {
+    format!(
+        "{} NFT{} from {}",
+        requirement.min_count,
+        if requirement.min_count == 1 { "" } else { "s" },
+        requirement.collection_name
+    )
+}
+
+/// Parse an admin's "<collection address> [minimum]" input; the minimum defaults to 1.
+pub fn parse_collection_input(text: &str) -> Result<(String, u64), String> {
+    let mut parts = text.split_whitespace();
+    let Some(collection_id) = parts.next() else {
+        return Err("Send the collection address, optionally followed by a minimum count.".to_string());
+    };
+
+    let hex = collection_id
+        .strip_prefix("0x")
+        .ok_or_else(|| "The collection address must start with 0x.".to_string())?;
+    if hex.is_empty() || hex.len() > 64 || !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+        return Err(format!("\"{}\" is not a valid collection address.", collection_id));
+    }
+
+    let min_count = match parts.next() {
+        Some(count) => match count.parse::<u64>() {
+            Ok(count) if count > 0 => count,
+            _ => return Err(format!("\"{}\" is not a valid minimum count.", count)),
+        },
+        None => 1,
+    };
+    if parts.next().is_some() {
+        return Err("Send only the collection address and the minimum count.".to_string());
+    }
+
+    Ok((collection_id.to_lowercase(), min_count))
+}
+
+/// Look up the member's linked wallet and count their tokens from the collection.
+pub async fn check_collection_holder(
+    auth: &Auth,
+    aptos: &Aptos,
+    requirement: &CollectionRequirement,
+    user_id: UserId,
+    username: Option<&str>,
+) -> CollectionCheck {
+    let Some(address) = auth.get_linked_address(user_id, username) else {
+        return CollectionCheck::NotLinked;
+    };
+
+    match aptos
+        .count_collection_tokens(&address, &requirement.collection_id, requirement.min_count)
+        .await
+    {
+        Ok(owned) if owned >= requirement.min_count => CollectionCheck::Qualified,
+        Ok(owned) => CollectionCheck::Insufficient { owned },
+        Err(e) => {
+            log::warn!(
+                "Failed to check {} ownership for user {}: {}",
+                requirement.collection_name,
+                user_id.0,
+                e
+            );
+            CollectionCheck::Unavailable
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn collection_input_parsing() {
+        assert_eq!(
+            parse_collection_input("0xABC123"),
+            Ok(("0xabc123".to_string(), 1))
+        );
+        assert_eq!(
+            parse_collection_input(" 0xabc 3 "),
+            Ok(("0xabc".to_string(), 3))
+        );
+        assert!(parse_collection_input("").is_err());
+        assert!(parse_collection_input("abc").is_err());
+        assert!(parse_collection_input("0xnothex").is_err());
+        assert!(parse_collection_input("0xabc 0").is_err());
+        assert!(parse_collection_input("0xabc 1 2").is_err());
+    }
+
+    #[test]
+    fn requirement_description_pluralizes() {
+        let mut requirement = CollectionRequirement {
+            collection_id: "0x1".to_string(),
+            collection_name: "Aptos Monkeys".to_string(),
+            min_count: 1,
+        };
+        assert_eq!(describe_requirement(&requirement), "1 NFT from Aptos Monkeys");
+        requirement.min_count = 2;
+        assert_eq!(describe_requirement(&requirement), "2 NFTs from Aptos Monkeys");
+    }
+}
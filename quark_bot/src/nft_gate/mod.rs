@@ -0,0 +1,4 @@
+pub mod dto;
+pub mod handler;
+pub mod helpers;
+pub mod nft_gate;
@@ -0,0 +1,208 @@
+use std::env;
+
+use anyhow::Result;
+use sled::{Db, Tree};
+use teloxide::types::{ChatId, User, UserId};
+
+use crate::{
+    aptos::handler::Aptos,
+    credentials::handler::Auth,
+    nft_gate::{
+        dto::{CollectionCheck, MemberRole, NftRoleSettings, RoleGrant, RoleRule},
+        helpers::check_collection_holder,
+    },
+};
+
+#[derive(Clone)]
+pub struct NftGate {
+    settings_tree: Tree,
+    grants_tree: Tree,
+    account_seed: String,
+}
+
+impl NftGate {
+    pub fn new(db: Db) -> Self {
+        let account_seed: String =
+            env::var("ACCOUNT_SEED").expect("ACCOUNT_SEED environment variable not found");
+
+        let settings_tree = db
+            .open_tree("nft_role_settings")
+            .expect("Failed to open NFT role settings tree");
+        let grants_tree = db
+            .open_tree("nft_role_grants")
+            .expect("Failed to open NFT role grants tree");
+
+        Self {
+            settings_tree,
+            grants_tree,
+            account_seed,
+        }
+    }
+
+    pub fn get_settings(&self, chat_id: ChatId) -> NftRoleSettings {
+        let key = format!("{}-{}", chat_id.0, self.account_seed);
+
+        self.settings_tree
+            .get(key.as_bytes())
+            .ok()
+            .flatten()
+            .and_then(|bytes| serde_json::from_slice(&bytes).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn save_settings(&self, chat_id: ChatId, settings: &NftRoleSettings) -> Result<()> {
+        let key = format!("{}-{}", chat_id.0, self.account_seed);
+        self.settings_tree
+            .insert(key.as_bytes(), serde_json::to_vec(settings)?)?;
+        Ok(())
+    }
+
+    fn grant_key(&self, chat_id: ChatId, user_id: UserId, role: MemberRole) -> String {
+        format!(
+            "{}-{}:{}:{}",
+            chat_id.0,
+            self.account_seed,
+            user_id.0,
+            role.as_str()
+        )
+    }
+
+    /// Whether the member currently holds `role` in the group. Roles whose
+    /// rules were all removed stop counting right away.
+    pub fn has_role(&self, chat_id: ChatId, user_id: UserId, role: MemberRole) -> bool {
+        let role_configured = self
+            .get_settings(chat_id)
+            .rules
+            .iter()
+            .any(|rule| rule.role == role);
+
+        role_configured
+            && self
+                .grants_tree
+                .contains_key(self.grant_key(chat_id, user_id, role).as_bytes())
+                .unwrap_or(false)
+    }
+
+    /// Check the member's holdings against every rule of the group, granting
+    /// or revoking roles to match. Returns each rule with its result.
+    pub async fn refresh_member_roles(
+        &self,
+        auth: &Auth,
+        aptos: &Aptos,
+        chat_id: ChatId,
+        user: &User,
+    ) -> Result<Vec<(RoleRule, CollectionCheck)>> {
+        let settings = self.get_settings(chat_id);
+        let mut results = Vec::new();
+        for rule in settings.rules {
+            let check = check_collection_holder(
+                auth,
+                aptos,
+                &rule.requirement,
+                user.id,
+                user.username.as_deref(),
+            )
+            .await;
+            results.push((rule, check));
+        }
+
+        for role in MemberRole::ALL {
+            let checks: Vec<CollectionCheck> = results
+                .iter()
+                .filter(|(rule, _)| rule.role == role)
+                .map(|(_, check)| *check)
+                .collect();
+            let key = self.grant_key(chat_id, user.id, role);
+
+            if checks.contains(&CollectionCheck::Qualified) {
+                let now = chrono::Utc::now().timestamp();
+                let granted_at = self
+                    .get_grant(&key)
+                    .map(|grant| grant.granted_at)
+                    .unwrap_or(now);
+                let grant = RoleGrant {
+                    chat_id,
+                    user_id: user.id,
+                    username: user.username.clone().unwrap_or_default(),
+                    role,
+                    granted_at,
+                    last_checked: now,
+                };
+                self.grants_tree
+                    .insert(key.as_bytes(), serde_json::to_vec(&grant)?)?;
+            } else if !checks.contains(&CollectionCheck::Unavailable) {
+                self.grants_tree.remove(key.as_bytes())?;
+            }
+        }
+
+        Ok(results)
+    }
+
+    fn get_grant(&self, key: &str) -> Option<RoleGrant> {
+        self.grants_tree
+            .get(key.as_bytes())
+            .ok()
+            .flatten()
+            .and_then(|bytes| serde_json::from_slice(&bytes).ok())
+    }
+
+    /// Re-verify every granted role and revoke those whose holder no longer
+    /// owns enough of any collection granting it.
+    pub async fn recheck_roles(&self, auth: &Auth, aptos: &Aptos) -> Result<()> {
+        let grants: Vec<(sled::IVec, RoleGrant)> = self
+            .grants_tree
+            .iter()
+            .filter_map(|result| result.ok())
+            .filter_map(|(key, value)| {
+                serde_json::from_slice::<RoleGrant>(&value)
+                    .ok()
+                    .map(|grant| (key, grant))
+            })
+            .collect();
+
+        for (key, mut grant) in grants {
+            let rules: Vec<RoleRule> = self
+                .get_settings(grant.chat_id)
+                .rules
+                .into_iter()
+                .filter(|rule| rule.role == grant.role)
+                .collect();
+
+            let mut unavailable = false;
+            let mut qualified = false;
+            for rule in &rules {
+                match check_collection_holder(
+                    auth,
+                    aptos,
+                    &rule.requirement,
+                    grant.user_id,
+                    Some(&grant.username),
+                )
+                .await
+                {
+                    CollectionCheck::Qualified => {
+                        qualified = true;
+                        break;
+                    }
+                    CollectionCheck::Unavailable => unavailable = true,
+                    CollectionCheck::NotLinked | CollectionCheck::Insufficient { .. } => {}
+                }
+            }
+
+            if qualified {
+                grant.last_checked = chrono::Utc::now().timestamp();
+                self.grants_tree.insert(key, serde_json::to_vec(&grant)?)?;
+            } else if !unavailable {
+                log::info!(
+                    "Revoking {} role from user {} in chat {}",
+                    grant.role.as_str(),
+                    grant.user_id.0,
+                    grant.chat_id.0
+                );
+                self.grants_tree.remove(key)?;
+            }
+        }
+
+        Ok(())
+    }
+}
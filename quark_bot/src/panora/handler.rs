@@ -263,4 +263,33 @@ impl Panora {
 
         Ok(token.clone())
     }
+
+    /// Look a token up by coin type or fungible asset address, as stored on proposals.
+    pub async fn get_token_by_address(&self, address: &str) -> Result<Token> {
+        let address = if address == "0x1" {
+            "0x1::aptos_coin::AptosCoin".to_string()
+        } else {
+            address.to_lowercase()
+        };
+
+        let matches = |token: &Token| {
+            token
+                .token_address
+                .as_ref()
+                .is_some_and(|token_address| token_address.to_lowercase() == address)
+                || token.fa_address.to_lowercase() == address
+        };
+
+        let list = self.get_panora_token_list().await?;
+
+        if let Some(token) = list.into_iter().find(|token| matches(token)) {
+            return Ok(token);
+        }
+
+        self.get_panora_token_list_non_bonding()
+            .await?
+            .into_iter()
+            .find(|token| matches(token))
+            .ok_or_else(|| anyhow::anyhow!("Token not found"))
+    }
 }
@@ -108,6 +108,10 @@ async fn handle_open_group_payment_settings(
                     "💳 Choose Group Payment Token",
                     "payment_selected",
                 )],
+                vec![InlineKeyboardButton::callback(
+                    "🔐 Treasury Approvals",
+                    "trsy_policy",
+                )],
                 vec![InlineKeyboardButton::callback(
                     "↩️ Back",
                     "back_to_group_settings",
@@ -10,9 +10,15 @@ use crate::scheduled_payments::helpers::{
     build_repeat_keyboard_payments,
     build_repeat_keyboard_with_nav_payments,
     build_nav_keyboard_payments,
+    build_recipients_keyboard_payments,
+    needs_amount,
+    recipients_step_text,
     reset_from_step_payments,
+    schedule_amount_units,
     summarize,
     send_step_message,
+    AMOUNT_PROMPT,
+    DATE_PROMPT,
 };
 use crate::scheduled_prompts::dto::RepeatPolicy;
 use crate::utils::delete_message_safe;
@@ -154,6 +160,15 @@ pub async fn handle_scheduled_payments_callback(
                     .await?;
                 return Ok(());
             }
+
+            // Keep the wizard open so the amounts can be fixed with Back
+            if let Err(e) = schedule_amount_units(&st) {
+                bot.answer_callback_query(query.id)
+                    .text(format!("❌ {}", e))
+                    .show_alert(true)
+                    .await?;
+                return Ok(());
+            }
             
             // Delete the confirmation message
             if let Some(current_msg_id) = st.current_bot_message_id {
@@ -200,6 +215,85 @@ pub async fn handle_scheduled_payments_callback(
                 .text("ℹ️ No pending payment to cancel")
                 .await?;
         }
+    } else if data.starts_with("schedpay_rcpt_rm:") {
+        let index: usize = data.split(':').nth(1).unwrap_or("").parse().unwrap_or(usize::MAX);
+        if let Some(mut st) = bot_deps.scheduled_payments.get_pending(key) {
+            if st.step != PendingPaymentStep::AwaitingRecipient || index >= st.recipients.len() {
+                bot.answer_callback_query(query.id)
+                    .text("ℹ️ Recipient not found")
+                    .await?;
+                return Ok(());
+            }
+
+            // Delete current message
+            if let Some(current_msg_id) = st.current_bot_message_id {
+                delete_message_safe(&bot, message.chat.id, current_msg_id).await;
+            }
+
+            let removed = st.recipients.remove(index);
+            bot.answer_callback_query(query.id)
+                .text(format!("🗑 Removed @{}", removed.username))
+                .await?;
+
+            // Send the updated list and capture ID
+            match send_step_message(
+                bot.clone(),
+                message.chat.id,
+                &recipients_step_text(&st),
+                build_recipients_keyboard_payments(&st.recipients),
+            )
+            .await {
+                Ok(sent_msg) => {
+                    st.current_bot_message_id = Some(sent_msg.id.0);
+                    bot_deps.scheduled_payments.put_pending(key, &st)?;
+                }
+                Err(e) => {
+                    log::error!("Failed to send recipients message: {}", e);
+                }
+            }
+        } else {
+            bot.answer_callback_query(query.id)
+                .text("ℹ️ No pending payment to edit")
+                .await?;
+        }
+    } else if data == "schedpay_rcpt_done" {
+        if let Some(mut st) = bot_deps.scheduled_payments.get_pending(key) {
+            if st.step != PendingPaymentStep::AwaitingRecipient || st.recipients.is_empty() {
+                bot.answer_callback_query(query.id)
+                    .text("ℹ️ Add at least one recipient first")
+                    .await?;
+                return Ok(());
+            }
+
+            // Delete current message
+            if let Some(current_msg_id) = st.current_bot_message_id {
+                delete_message_safe(&bot, message.chat.id, current_msg_id).await;
+            }
+
+            st.step = PendingPaymentStep::AwaitingToken;
+            bot.answer_callback_query(query.id).await?;
+
+            // Send new message and capture ID
+            match send_step_message(
+                bot.clone(),
+                message.chat.id,
+                "💳 Send token symbol (e.g., APT, USDC, or emoji)",
+                build_nav_keyboard_payments(true),
+            )
+            .await {
+                Ok(sent_msg) => {
+                    st.current_bot_message_id = Some(sent_msg.id.0);
+                    bot_deps.scheduled_payments.put_pending(key, &st)?;
+                }
+                Err(e) => {
+                    log::error!("Failed to send token step message: {}", e);
+                }
+            }
+        } else {
+            bot.answer_callback_query(query.id)
+                .text("ℹ️ No pending payment to edit")
+                .await?;
+        }
     } else if data == "schedpay_back" {
         if let Some(mut st) = bot_deps.scheduled_payments.get_pending(key) {
             // Determine previous step
@@ -208,6 +302,9 @@ pub async fn handle_scheduled_payments_callback(
                 PendingPaymentStep::AwaitingRepeat => Some(PendingPaymentStep::AwaitingMinute),
                 PendingPaymentStep::AwaitingMinute => Some(PendingPaymentStep::AwaitingHour),
                 PendingPaymentStep::AwaitingHour => Some(PendingPaymentStep::AwaitingDate),
+                PendingPaymentStep::AwaitingDate if !needs_amount(&st.recipients) => {
+                    Some(PendingPaymentStep::AwaitingToken)
+                }
                 PendingPaymentStep::AwaitingDate => Some(PendingPaymentStep::AwaitingAmount),
                 PendingPaymentStep::AwaitingAmount => Some(PendingPaymentStep::AwaitingToken),
                 PendingPaymentStep::AwaitingToken => Some(PendingPaymentStep::AwaitingRecipient),
@@ -227,19 +324,19 @@ pub async fn handle_scheduled_payments_callback(
                 // Send fresh message for previous step
                 let (text, kb) = match prev_step {
                     PendingPaymentStep::AwaitingRecipient => {
-                        ("👤 Send the recipient @username to receive payment (must have a linked wallet).".to_string(),
-                         build_nav_keyboard_payments(false))
+                        (recipients_step_text(&st),
+                         build_recipients_keyboard_payments(&st.recipients))
                     }
                     PendingPaymentStep::AwaitingToken => {
                         ("💳 Send token symbol (e.g., APT, USDC, or emoji)".to_string(),
                          build_nav_keyboard_payments(true))
                     }
                     PendingPaymentStep::AwaitingAmount => {
-                        ("💰 Send amount (decimal)".to_string(),
+                        (AMOUNT_PROMPT.to_string(),
                          build_nav_keyboard_payments(true))
                     }
                     PendingPaymentStep::AwaitingDate => {
-                        ("📅 Send start date in YYYY-MM-DD (UTC)".to_string(),
+                        (DATE_PROMPT.to_string(),
                          build_nav_keyboard_payments(true))
                     }
                     PendingPaymentStep::AwaitingHour => {
@@ -344,8 +441,7 @@ pub async fn handle_scheduled_payments_callback(
                 creator_username: rec.creator_username.clone(),
                 step: crate::scheduled_payments::dto::PendingPaymentStep::AwaitingRecipient,
                 schedule_id: Some(rec.id.clone()),
-                recipient_username: rec.recipient_username.clone(),
-                recipient_address: rec.recipient_address.clone(),
+                recipients: rec.recipients(),
                 symbol: rec.symbol.clone(),
                 token_type: rec.token_type.clone(),
                 decimals: rec.decimals,
@@ -376,7 +472,7 @@ pub async fn handle_scheduled_payments_callback(
                 use teloxide::types::{InlineKeyboardButton as Btn, InlineKeyboardMarkup as Kb};
                 let kb = Kb::new(vec![
                     vec![
-                        Btn::callback("👤 Recipient", format!("schedpay_editrecipient:{}", id)),
+                        Btn::callback("👥 Recipients", format!("schedpay_editrecipient:{}", id)),
                         Btn::callback("💳 Token", format!("schedpay_edittoken:{}", id)),
                     ],
                     vec![
@@ -399,12 +495,16 @@ pub async fn handle_scheduled_payments_callback(
             let key = (&rec.group_id, &rec.creator_user_id);
             if let Some(mut st) = bot_deps.scheduled_payments.get_pending(key) {
                 st.step = crate::scheduled_payments::dto::PendingPaymentStep::AwaitingRecipient;
+                if let Some(teloxide::types::MaybeInaccessibleMessage::Regular(m)) = &query.message
+                {
+                    bot.edit_message_text(m.chat.id, m.id, recipients_step_text(&st))
+                        .parse_mode(teloxide::types::ParseMode::Html)
+                        .reply_markup(build_recipients_keyboard_payments(&st.recipients))
+                        .await?;
+                    st.current_bot_message_id = Some(m.id.0);
+                }
                 bot_deps.scheduled_payments.put_pending(key, &st)?;
             }
-            if let Some(teloxide::types::MaybeInaccessibleMessage::Regular(m)) = &query.message {
-                bot.edit_message_text(m.chat.id, m.id, "👤 Send the new @recipient username")
-                    .await?;
-            }
             bot.answer_callback_query(query.id).await?;
         }
     } else if data.starts_with("schedpay_edittoken:") {
@@ -14,14 +14,35 @@ pub enum PendingPaymentStep {
     AwaitingConfirm,
 }
 
+/// What one recipient of a schedule gets on each run.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, bincode::Encode, bincode::Decode)]
+pub enum PaymentShare {
+    /// The schedule's amount.
+    Amount,
+    /// Fixed amount in whole tokens.
+    Fixed(f64),
+    /// Percent of the schedule's amount.
+    Percent(f64),
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, bincode::Encode, bincode::Decode)]
+pub struct PaymentRecipient {
+    pub username: String,
+    pub address: String,
+    pub share: PaymentShare,
+}
+
 #[derive(Clone, Debug, Serialize, Deserialize, bincode::Encode, bincode::Decode)]
 pub struct ScheduledPaymentRecord {
     pub id: String,
     pub group_id: i64,
     pub creator_user_id: i64,
     pub creator_username: String,
+    // Single recipient of schedules created before `recipients`
     pub recipient_username: Option<String>,
     pub recipient_address: Option<String>,
+    #[serde(default)]
+    pub recipients: Vec<PaymentRecipient>,
     pub symbol: Option<String>,
     pub token_type: Option<String>,
     pub decimals: Option<u8>,
@@ -43,6 +64,25 @@ pub struct ScheduledPaymentRecord {
     pub notify_on_failure: bool,
 }
 
+impl ScheduledPaymentRecord {
+    /// Schedules from before multi-recipient payments pay their single
+    /// recipient the full amount.
+    pub fn recipients(&self) -> Vec<PaymentRecipient> {
+        if !self.recipients.is_empty() {
+            return self.recipients.clone();
+        }
+
+        match (&self.recipient_username, &self.recipient_address) {
+            (Some(username), Some(address)) => vec![PaymentRecipient {
+                username: username.clone(),
+                address: address.clone(),
+                share: PaymentShare::Amount,
+            }],
+            _ => Vec::new(),
+        }
+    }
+}
+
 #[derive(Clone, Debug, Serialize, Deserialize, bincode::Encode, bincode::Decode)]
 pub struct PendingPaymentWizardState {
     pub group_id: i64,
@@ -50,8 +90,8 @@ pub struct PendingPaymentWizardState {
     pub creator_username: String,
     pub step: PendingPaymentStep,
     pub schedule_id: Option<String>,
-    pub recipient_username: Option<String>,
-    pub recipient_address: Option<String>,
+    #[serde(default)]
+    pub recipients: Vec<PaymentRecipient>,
     pub symbol: Option<String>,
     pub token_type: Option<String>,
     pub decimals: Option<u8>,
@@ -3,13 +3,22 @@ use teloxide::{prelude::*, types::Message};
 
 use crate::dependencies::BotDependencies;
 use crate::scheduled_payments::dto::{
-    PendingPaymentStep, PendingPaymentWizardState, ScheduledPaymentRecord,
+    PaymentRecipient, PendingPaymentStep, PendingPaymentWizardState, ScheduledPaymentRecord,
 };
 use crate::scheduled_payments::helpers::{
     build_hours_keyboard_with_nav_payments,
     build_nav_keyboard_payments,
+    build_recipients_keyboard_payments,
     extract_payment_cleanup_targets,
+    needs_amount,
+    parse_recipient_line,
+    recipient_amounts,
+    recipients_step_text,
+    schedule_amount_units,
     send_step_message,
+    total_percent,
+    AMOUNT_PROMPT,
+    DATE_PROMPT,
 };
 use crate::utils::{
     cleanup_and_transition,
@@ -70,8 +79,7 @@ pub async fn handle_schedulepayment_command(
         creator_username: username,
         step: PendingPaymentStep::AwaitingRecipient,
         schedule_id: None,
-        recipient_username: None,
-        recipient_address: None,
+        recipients: Vec::new(),
         symbol: None,
         token_type: None,
         decimals: None,
@@ -86,14 +94,8 @@ pub async fn handle_schedulepayment_command(
     };
 
     // First step: show Cancel button
-    let kb = build_nav_keyboard_payments(false);
-    let sent_msg = send_step_message(
-        bot,
-        msg.chat.id,
-        "👤 Send the recipient @username to receive payment (must have a linked wallet).",
-        kb,
-    )
-    .await?;
+    let kb = build_recipients_keyboard_payments(&state.recipients);
+    let sent_msg = send_step_message(bot, msg.chat.id, &recipients_step_text(&state), kb).await?;
     
     // Store the message ID
     state.current_bot_message_id = Some(sent_msg.id.0);
@@ -140,9 +142,17 @@ pub async fn handle_listscheduledpayments_command(
     }
 
     for rec in list {
-        let smallest = rec.amount_smallest_units.unwrap_or(0);
         let decimals = rec.decimals.unwrap_or(8);
+        let recipients = rec.recipients();
+        let smallest: u64 = recipient_amounts(&recipients, rec.amount_smallest_units, decimals)
+            .map(|amounts| amounts.iter().sum())
+            .unwrap_or(0);
         let human = (smallest as f64) / 10f64.powi(decimals as i32);
+        let usernames = recipients
+            .iter()
+            .map(|recipient| recipient.username.as_str())
+            .collect::<Vec<_>>()
+            .join(", @");
         let title = format!(
             "⏰ {:>11} — @{} — {:.4} {}",
             rec.next_run_at
@@ -150,7 +160,7 @@ pub async fn handle_listscheduledpayments_command(
                     .map(|dt| dt.format("%Y-%m-%d %H:%M").to_string())
                     .unwrap_or_else(|| v.to_string()))
                 .unwrap_or_else(|| "n/a".to_string()),
-            rec.recipient_username.clone().unwrap_or_default(),
+            usernames,
             human,
             rec.symbol.clone().unwrap_or_default(),
         );
@@ -206,6 +216,15 @@ pub async fn finalize_and_register_payment(
         return Ok(());
     }
 
+    // Reject amounts that can't be paid before anything is saved
+    let amount_smallest_units = match schedule_amount_units(&state) {
+        Ok(amount) => amount,
+        Err(e) => {
+            send_message(msg, bot, format!("❌ {}.", e)).await?;
+            return Ok(());
+        }
+    };
+
     // Compute first run timestamp (UTC) from date + hour/minute
     let date = state.date.clone().unwrap_or_default();
     let hour = state.hour_utc.unwrap_or(0);
@@ -217,11 +236,6 @@ pub async fn finalize_and_register_payment(
         })
         .unwrap_or(Utc::now().timestamp());
 
-    // Convert display amount to smallest units using decimals
-    let amount_smallest_units = state
-        .amount_display
-        .and_then(|amt| state.decimals.map(|d| (amt * 10f64.powi(d as i32)) as u64));
-
     // Upsert: if editing an existing schedule, reuse its id and preserve job id if present
     let id = state
         .schedule_id
@@ -232,8 +246,9 @@ pub async fn finalize_and_register_payment(
         group_id: state.group_id,
         creator_user_id: state.creator_user_id,
         creator_username: state.creator_username.clone(),
-        recipient_username: state.recipient_username.clone(),
-        recipient_address: state.recipient_address.clone(),
+        recipient_username: None,
+        recipient_address: None,
+        recipients: state.recipients.clone(),
         symbol: state.symbol.clone(),
         token_type: state.token_type.clone(),
         decimals: state.decimals,
@@ -290,47 +305,73 @@ pub async fn handle_message_scheduled_payments(
         }
         match st.step {
             PendingPaymentStep::AwaitingRecipient => {
-                // Expect @username
-                let uname = text_raw.trim_start_matches('@').to_string();
-                if let Some(creds) = bot_deps.auth.get_credentials(&uname) {
-                    // Clean up old messages
-                    cleanup_and_transition(
-                        &bot,
-                        msg.chat.id,
-                        Some(msg.id.0),
-                        &mut st,
-                        extract_payment_cleanup_targets,
-                    )
-                    .await;
-                    
-                    st.recipient_username = Some(uname);
-                    st.recipient_address = Some(creds.resource_account_address);
-                    st.step = PendingPaymentStep::AwaitingToken;
-                    
-                    // Send next step and capture message ID
-                    let kb = build_nav_keyboard_payments(true);
-                    match send_step_message(
-                        bot.clone(),
-                        msg.chat.id,
-                        "💳 Send token symbol (e.g., APT, USDC, or emoji)",
-                        kb,
-                    )
-                    .await {
-                        Ok(sent_msg) => {
-                            st.current_bot_message_id = Some(sent_msg.id.0);
-                            bot_deps.scheduled_payments.put_pending(pay_key, &st)?;
-                        }
-                        Err(e) => {
-                            log::error!("Failed to send step message: {}", e);
-                        }
-                    }
-                } else {
+                // Expect one "@username [amount|percent%]" per line
+                let mut recipients = st.recipients.clone();
+                for line in text_raw.lines().filter(|l| !l.trim().is_empty()) {
+                    let Some((uname, share)) = parse_recipient_line(line) else {
+                        send_message(
+                            msg,
+                            bot,
+                            format!(
+                                "❌ Couldn't read \"{}\". Use @username, @username 25 or @username 40%.",
+                                line.trim()
+                            ),
+                        )
+                        .await?;
+                        return Ok(true);
+                    };
+                    let Some(creds) = bot_deps.auth.get_credentials(&uname) else {
+                        send_message(
+                            msg,
+                            bot,
+                            format!("❌ Unknown user @{}. Please send a valid @username.", uname),
+                        )
+                        .await?;
+                        return Ok(true);
+                    };
+                    // Sending a recipient again updates their share
+                    recipients.retain(|r| r.username != uname);
+                    recipients.push(PaymentRecipient {
+                        username: uname,
+                        address: creds.resource_account_address,
+                        share,
+                    });
+                }
+
+                if total_percent(&recipients) > 100.0 {
                     send_message(
                         msg,
                         bot,
-                        "❌ Unknown user. Please send a valid @username.".to_string(),
+                        "❌ Percentages add up to more than 100%.".to_string(),
                     )
                     .await?;
+                    return Ok(true);
+                }
+
+                // Clean up old messages
+                cleanup_and_transition(
+                    &bot,
+                    msg.chat.id,
+                    Some(msg.id.0),
+                    &mut st,
+                    extract_payment_cleanup_targets,
+                )
+                .await;
+
+                st.recipients = recipients;
+
+                // Show the updated list and capture message ID
+                let kb = build_recipients_keyboard_payments(&st.recipients);
+                match send_step_message(bot.clone(), msg.chat.id, &recipients_step_text(&st), kb)
+                    .await
+                {
+                    Ok(sent_msg) => {
+                        st.current_bot_message_id = Some(sent_msg.id.0);
+                        bot_deps.scheduled_payments.put_pending(pay_key, &st)?;
+                    }
+                    Err(e) => {
+                        log::error!("Failed to send step message: {}", e);
+                    }
                 }
                 return Ok(true);
             }
@@ -382,17 +423,19 @@ pub async fn handle_message_scheduled_payments(
                 st.symbol = Some(symbol);
                 st.token_type = Some(token_type);
                 st.decimals = Some(decimals);
-                st.step = PendingPaymentStep::AwaitingAmount;
-                
+
+                // The amount step is only needed when someone is paid from it
+                let (text, kb) = if needs_amount(&st.recipients) {
+                    st.step = PendingPaymentStep::AwaitingAmount;
+                    (AMOUNT_PROMPT, build_nav_keyboard_payments(true))
+                } else {
+                    st.amount_display = None;
+                    st.step = PendingPaymentStep::AwaitingDate;
+                    (DATE_PROMPT, build_nav_keyboard_payments(true))
+                };
+
                 // Send next step and capture message ID
-                let kb = build_nav_keyboard_payments(true);
-                match send_step_message(
-                    bot.clone(),
-                    msg.chat.id,
-                    "💰 Send amount (decimal)",
-                    kb,
-                )
-                .await {
+                match send_step_message(bot.clone(), msg.chat.id, text, kb).await {
                     Ok(sent_msg) => {
                         st.current_bot_message_id = Some(sent_msg.id.0);
                         bot_deps.scheduled_payments.put_pending(pay_key, &st)?;
@@ -406,7 +449,7 @@ pub async fn handle_message_scheduled_payments(
             PendingPaymentStep::AwaitingAmount => {
                 let parsed = text_raw.replace('_', "").replace(',', "");
                 match parsed.parse::<f64>() {
-                    Ok(v) if v > 0.0 => {
+                    Ok(v) if v.is_finite() && v > 0.0 => {
                         // Clean up old messages
                         cleanup_and_transition(
                             &bot,
@@ -425,7 +468,7 @@ pub async fn handle_message_scheduled_payments(
                         match send_step_message(
                             bot.clone(),
                             msg.chat.id,
-                            "📅 Send start date in YYYY-MM-DD (UTC)",
+                            DATE_PROMPT,
                             kb,
                         )
                         .await {
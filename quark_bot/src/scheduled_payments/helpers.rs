@@ -1,10 +1,16 @@
 use teloxide::types::{InlineKeyboardButton, InlineKeyboardMarkup, ChatId};
 use teloxide::{Bot, RequestError};
 
-use crate::scheduled_payments::dto::{PendingPaymentStep, PendingPaymentWizardState};
+use crate::dao::payouts::smallest_units;
+use crate::scheduled_payments::dto::{
+    PaymentRecipient, PaymentShare, PendingPaymentStep, PendingPaymentWizardState,
+};
 use crate::scheduled_prompts::dto::RepeatPolicy;
 use crate::utils::WizardCleanupTargets;
 
+pub const AMOUNT_PROMPT: &str = "💰 Send the amount (decimal). Recipients without a fixed amount get it, and percentages are taken from it.";
+pub const DATE_PROMPT: &str = "📅 Send start date in YYYY-MM-DD (UTC)";
+
 pub fn build_repeat_keyboard_payments() -> InlineKeyboardMarkup {
     let rows = vec![
         vec![InlineKeyboardButton::callback(
@@ -107,17 +113,197 @@ pub fn build_repeat_keyboard_with_nav_payments(back_enabled: bool) -> InlineKeyb
     InlineKeyboardMarkup::new(rows)
 }
 
+/// Parse `@username`, `@username 25` (fixed amount) or `@username 40%`.
+pub fn parse_recipient_line(line: &str) -> Option<(String, PaymentShare)> {
+    let mut parts = line.split_whitespace();
+    let username = parts.next()?.trim_start_matches('@').to_string();
+    if username.is_empty() {
+        return None;
+    }
+
+    let share = match parts.next() {
+        None => PaymentShare::Amount,
+        Some(value) => match value.strip_suffix('%') {
+            Some(percent) => match percent.parse::<f64>() {
+                Ok(p) if p > 0.0 && p <= 100.0 => PaymentShare::Percent(p),
+                _ => return None,
+            },
+            None => match value.replace(',', "").parse::<f64>() {
+                Ok(v) if v.is_finite() && v > 0.0 => PaymentShare::Fixed(v),
+                _ => return None,
+            },
+        },
+    };
+
+    if parts.next().is_some() {
+        return None;
+    }
+
+    Some((username, share))
+}
+
+/// Whether any recipient is paid from the schedule's amount.
+pub fn needs_amount(recipients: &[PaymentRecipient]) -> bool {
+    recipients
+        .iter()
+        .any(|recipient| !matches!(recipient.share, PaymentShare::Fixed(_)))
+}
+
+pub fn total_percent(recipients: &[PaymentRecipient]) -> f64 {
+    recipients
+        .iter()
+        .map(|recipient| match recipient.share {
+            PaymentShare::Percent(p) => p,
+            _ => 0.0,
+        })
+        .sum()
+}
+
+/// Amount each recipient gets per run, in the token's smallest units.
+pub fn recipient_amounts(
+    recipients: &[PaymentRecipient],
+    amount_smallest_units: Option<u64>,
+    decimals: u8,
+) -> Result<Vec<u64>, String> {
+    if recipients.is_empty() {
+        return Err("Scheduled payment has no recipients".to_string());
+    }
+
+    let mut amounts = Vec::with_capacity(recipients.len());
+
+    for recipient in recipients {
+        let amount = match recipient.share {
+            PaymentShare::Fixed(value) => smallest_units(value, decimals).ok_or_else(|| {
+                format!(
+                    "Scheduled payment amount for @{} is too small or not a valid amount",
+                    recipient.username
+                )
+            })?,
+            PaymentShare::Amount => amount_smallest_units
+                .ok_or_else(|| "Scheduled payment amount is missing".to_string())?,
+            PaymentShare::Percent(p) => {
+                let total = amount_smallest_units
+                    .ok_or_else(|| "Scheduled payment amount is missing".to_string())?;
+                (total as f64 * p / 100.0).floor() as u64
+            }
+        };
+
+        if amount == 0 {
+            return Err(format!(
+                "Scheduled payment amount for @{} cannot be zero",
+                recipient.username
+            ));
+        }
+
+        amounts.push(amount);
+    }
+
+    Ok(amounts)
+}
+
+/// The wizard's amount in smallest units, after checking every recipient's
+/// share of it can be paid.
+pub fn schedule_amount_units(state: &PendingPaymentWizardState) -> Result<Option<u64>, String> {
+    let decimals = state.decimals.unwrap_or(8);
+    let amount = match state.amount_display {
+        Some(amount) => Some(smallest_units(amount, decimals).ok_or_else(|| {
+            "Scheduled payment amount is too small or not a valid amount".to_string()
+        })?),
+        None => None,
+    };
+
+    recipient_amounts(&state.recipients, amount, decimals)?;
+    Ok(amount)
+}
+
+/// Recipients grouped by amount, smallest first, so equal amounts can be paid
+/// in one `pay_members` call (for the batch total, which the contract splits).
+pub fn batch_by_amount(
+    recipients: &[PaymentRecipient],
+    amounts: &[u64],
+) -> Vec<(u64, Vec<PaymentRecipient>)> {
+    let mut batches: std::collections::BTreeMap<u64, Vec<PaymentRecipient>> =
+        std::collections::BTreeMap::new();
+    for (recipient, amount) in recipients.iter().zip(amounts) {
+        batches.entry(*amount).or_default().push(recipient.clone());
+    }
+    batches.into_iter().collect()
+}
+
+fn describe_share(share: &PaymentShare, amount_display: Option<f64>, symbol: &str) -> String {
+    match (share, amount_display) {
+        (PaymentShare::Fixed(value), _) => format!("{:.4} {}", value, symbol),
+        (PaymentShare::Amount, Some(amount)) => format!("{:.4} {}", amount, symbol),
+        (PaymentShare::Amount, None) => "the amount".to_string(),
+        (PaymentShare::Percent(p), Some(amount)) => {
+            format!("{}% ({:.4} {})", p, amount * p / 100.0, symbol)
+        }
+        (PaymentShare::Percent(p), None) => format!("{}% of the amount", p),
+    }
+}
+
+pub fn recipients_text(
+    recipients: &[PaymentRecipient],
+    amount_display: Option<f64>,
+    symbol: &str,
+) -> String {
+    if recipients.is_empty() {
+        return "(no recipients yet)".to_string();
+    }
+
+    recipients
+        .iter()
+        .map(|recipient| {
+            format!(
+                "• @{} — {}",
+                recipient.username,
+                describe_share(&recipient.share, amount_display, symbol)
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+pub fn recipients_step_text(state: &PendingPaymentWizardState) -> String {
+    format!(
+        "👥 Send the recipients (they must have a linked wallet), one per line:\n@username — gets the amount you set later\n@username 25 — a fixed amount\n@username 40% — a share of the amount\n\n<b>Recipients</b>\n{}",
+        recipients_text(
+            &state.recipients,
+            state.amount_display,
+            state.symbol.as_deref().unwrap_or("tokens")
+        )
+    )
+}
+
+/// Remove buttons for each recipient, Done once there's at least one.
+pub fn build_recipients_keyboard_payments(recipients: &[PaymentRecipient]) -> InlineKeyboardMarkup {
+    let mut rows: Vec<Vec<InlineKeyboardButton>> = recipients
+        .iter()
+        .enumerate()
+        .map(|(index, recipient)| {
+            vec![InlineKeyboardButton::callback(
+                format!("🗑 @{}", recipient.username),
+                format!("schedpay_rcpt_rm:{}", index),
+            )]
+        })
+        .collect();
+    if !recipients.is_empty() {
+        rows.push(vec![InlineKeyboardButton::callback(
+            "✅ Done".to_string(),
+            "schedpay_rcpt_done".to_string(),
+        )]);
+    }
+    rows.push(nav_row(false));
+    InlineKeyboardMarkup::new(rows)
+}
+
 pub fn summarize(state: &PendingPaymentWizardState) -> String {
-    let recipient = state
-        .recipient_username
-        .as_deref()
-        .map(|u| format!("@{}", u))
-        .unwrap_or("(recipient not set)".to_string());
     let symbol = state.symbol.as_deref().unwrap_or("(symbol not set)");
+    let recipients = recipients_text(&state.recipients, state.amount_display, symbol);
     let amount = state
         .amount_display
-        .map(|v| format!("{:.4}", v))
-        .unwrap_or("(amount not set)".to_string());
+        .map(|v| format!("{:.4} {}", v, symbol))
+        .unwrap_or("(fixed amounts only)".to_string());
     let date = state.date.clone().unwrap_or("(date not set)".to_string());
     let hour = state
         .hour_utc
@@ -138,16 +324,15 @@ pub fn summarize(state: &PendingPaymentWizardState) -> String {
         (None, _) => "(not set)".to_string(),
     };
     format!(
-        "💸 Payment schedule (UTC)\nRecipient: {}\nAmount: {} {}\nFirst run: {} {}:{}\nRepeat: {}",
-        recipient, amount, symbol, date, hour, minute, repeat
+        "💸 Payment schedule (UTC)\nRecipients:\n{}\nAmount: {}\nFirst run: {} {}:{}\nRepeat: {}",
+        recipients, amount, date, hour, minute, repeat
     )
 }
 
 pub fn reset_from_step_payments(state: &mut PendingPaymentWizardState, step: PendingPaymentStep) {
     match step {
         PendingPaymentStep::AwaitingRecipient => {
-            state.recipient_username = None;
-            state.recipient_address = None;
+            state.recipients.clear();
             state.symbol = None;
             state.token_type = None;
             state.decimals = None;
@@ -232,3 +417,65 @@ pub async fn send_step_message(
         .reply_markup(keyboard)
         .await
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn recipient(username: &str, share: PaymentShare) -> PaymentRecipient {
+        PaymentRecipient {
+            username: username.to_string(),
+            address: format!("0x{}", username),
+            share,
+        }
+    }
+
+    #[test]
+    fn parses_recipient_lines() {
+        assert_eq!(
+            parse_recipient_line("@alice"),
+            Some(("alice".to_string(), PaymentShare::Amount))
+        );
+        assert_eq!(
+            parse_recipient_line("@bob 12.5"),
+            Some(("bob".to_string(), PaymentShare::Fixed(12.5)))
+        );
+        assert_eq!(
+            parse_recipient_line("carol 40%"),
+            Some(("carol".to_string(), PaymentShare::Percent(40.0)))
+        );
+        assert_eq!(parse_recipient_line("@dave 120%"), None);
+        assert_eq!(parse_recipient_line("@erin 0"), None);
+        assert_eq!(parse_recipient_line("@frank 1 2"), None);
+        assert_eq!(parse_recipient_line("@gina inf"), None);
+    }
+
+    #[test]
+    fn splits_amounts_between_recipients() {
+        let recipients = vec![
+            recipient("alice", PaymentShare::Amount),
+            recipient("bob", PaymentShare::Fixed(2.5)),
+            recipient("carol", PaymentShare::Percent(25.0)),
+        ];
+
+        assert_eq!(
+            recipient_amounts(&recipients, Some(1_000_000), 6),
+            Ok(vec![1_000_000, 2_500_000, 250_000])
+        );
+        assert!(recipient_amounts(&recipients, None, 6).is_err());
+        assert_eq!(
+            recipient_amounts(&recipients[1..2], None, 6),
+            Ok(vec![2_500_000])
+        );
+        let huge = [recipient("dan", PaymentShare::Fixed(1e30))];
+        assert!(recipient_amounts(&huge, None, 8).is_err());
+        let dust = [recipient("dan", PaymentShare::Fixed(0.0000001))];
+        assert!(recipient_amounts(&dust, None, 6).is_err());
+        let batches = batch_by_amount(&recipients, &[250_000, 2_500_000, 250_000]);
+        assert_eq!(batches.len(), 2);
+        assert_eq!(batches[0].0, 250_000);
+        assert_eq!(batches[0].1.len(), 2);
+        assert!(needs_amount(&recipients));
+        assert!(!needs_amount(&recipients[1..2]));
+    }
+}
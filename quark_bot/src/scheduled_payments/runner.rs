@@ -1,15 +1,104 @@
 use chrono::Utc;
+use quark_core::helpers::dto::CoinVersion;
 use teloxide::{prelude::*, types::ChatId};
 use tokio_cron_scheduler::Job;
 
 use crate::dependencies::BotDependencies;
+use crate::scheduled_payments::dto::PaymentRecipient;
 use crate::scheduled_payments::dto::ScheduledPaymentRecord;
+use crate::scheduled_payments::helpers::{batch_by_amount, recipient_amounts};
 use crate::scheduled_payments::storage::ScheduledPaymentsStorage;
-use crate::scheduled_prompts::dto::RepeatPolicy;
+use crate::scheduling::next_run_after;
+use crate::treasury::approvals::{approval_gate, request_approval};
+use crate::treasury::dto::{PaymentSource, TreasuryPayment, TreasuryTransfer};
 
-fn next_week_cadence(now_ts: i64, weeks: u8) -> i64 {
-    let days = (weeks as i64) * 7;
-    now_ts + days * 24 * 3600
+struct PaymentPlan {
+    token: String,
+    coin_type: String,
+    version: CoinVersion,
+    /// Recipients grouped by amount, one `pay_members` call each.
+    batches: Vec<(u64, Vec<PaymentRecipient>)>,
+}
+
+/// Validate critical payment data before proceeding
+fn payment_plan(
+    rec: &ScheduledPaymentRecord,
+    bot_deps: &BotDependencies,
+    group_chat_id: ChatId,
+) -> anyhow::Result<PaymentPlan> {
+    let group_credentials = match bot_deps.group.get_credentials(group_chat_id) {
+        Some(c) => c,
+        None => return Err(anyhow::anyhow!("Group credentials not found")),
+    };
+
+    let coin_type = match &rec.token_type {
+        Some(token) if !token.is_empty() => token.clone(),
+        Some(_) => return Err(anyhow::anyhow!("Scheduled payment token type is empty")),
+        None => return Err(anyhow::anyhow!("Scheduled payment token type is missing")),
+    };
+
+    let recipients = rec.recipients();
+    if recipients.iter().any(|r| r.address.is_empty()) {
+        return Err(anyhow::anyhow!(
+            "Scheduled payment recipient address is empty"
+        ));
+    }
+    let amounts = recipient_amounts(
+        &recipients,
+        rec.amount_smallest_units,
+        rec.decimals.unwrap_or(8),
+    )
+    .map_err(|e| anyhow::anyhow!(e))?;
+
+    let version = if coin_type.contains("::") {
+        CoinVersion::V1
+    } else {
+        CoinVersion::V2
+    };
+
+    Ok(PaymentPlan {
+        token: group_credentials.jwt,
+        coin_type,
+        version,
+        batches: batch_by_amount(&recipients, &amounts),
+    })
+}
+
+/// A run of the schedule as a treasury payment, for groups with approvals.
+fn treasury_payment(rec: &ScheduledPaymentRecord, plan: &PaymentPlan) -> TreasuryPayment {
+    TreasuryPayment {
+        source: PaymentSource::Scheduled {
+            schedule_id: rec.id.clone(),
+        },
+        symbol: rec.symbol.clone().unwrap_or_else(|| "Unknown".to_string()),
+        coin_type: plan.coin_type.clone(),
+        version: plan.version.clone(),
+        decimals: rec.decimals.unwrap_or(8),
+        transfers: plan
+            .batches
+            .iter()
+            .flat_map(|(amount, batch)| {
+                batch.iter().map(|recipient| TreasuryTransfer {
+                    username: recipient.username.clone(),
+                    address: recipient.address.clone(),
+                    amount: *amount,
+                    transaction_hash: None,
+                })
+            })
+            .collect(),
+    }
+}
+
+/// Record a run and move to the next one; one-shot schedules end here.
+fn advance_schedule(rec: &mut ScheduledPaymentRecord, now_ts: i64) {
+    rec.last_run_at = Some(now_ts);
+    rec.run_count += 1;
+    let anchor = rec.start_timestamp_utc.unwrap_or(rec.created_at);
+    rec.next_run_at = next_run_after(&rec.repeat, anchor, rec.weekly_weeks.unwrap_or(1), now_ts);
+    if rec.next_run_at.is_none() {
+        rec.active = false;
+    }
+    rec.locked_until = None;
 }
 
 pub async fn register_all_schedules(bot: Bot, bot_deps: BotDependencies) -> anyhow::Result<()> {
@@ -81,94 +170,114 @@ pub async fn register_schedule(
             rec.locked_until = Some(now_ts + 120);
             let _ = storage.put_schedule(&rec);
 
-            // Execute payment via service.pay_members
-            let result = (|| async {
-                let group_credentials = match bot_deps.group.get_credentials(group_chat_id) {
-                    Some(c) => c,
-                    None => return Err(anyhow::anyhow!("Group credentials not found")),
-                };
-
-                // Validate critical payment data before proceeding
-                let amount = match rec.amount_smallest_units {
-                    Some(amt) => {
-                        if amt > 0 {
-                            amt
-                        } else {
-                            return Err(anyhow::anyhow!("Scheduled payment amount cannot be zero"));
+            let plan = payment_plan(&rec, &bot_deps, group_chat_id);
+
+            // Runs above the group's threshold are paid once enough admins approve
+            if let Ok(plan) = &plan {
+                let payment = treasury_payment(&rec, plan);
+                if let Some(gate) = approval_gate(&bot_deps, group_chat_id, &payment).await {
+                    match request_approval(
+                        &bot,
+                        &bot_deps,
+                        group_chat_id,
+                        payment,
+                        gate,
+                        None,
+                        None,
+                    )
+                    .await
+                    {
+                        Ok(()) => {
+                            rec.last_attempt_status = Some("awaiting_approval".to_string());
+                            rec.last_error = None;
+                            advance_schedule(&mut rec, now_ts);
+                        }
+                        Err(e) => {
+                            log::error!(
+                                "Failed to request approval for payment schedule {}: {}",
+                                rec.id,
+                                e
+                            );
+                            rec.last_attempt_status = Some("failure".to_string());
+                            rec.last_error = Some(e.to_string());
+                            rec.locked_until = None;
                         }
                     }
-                    None => return Err(anyhow::anyhow!("Scheduled payment amount is missing")),
-                };
+                    let _ = storage.put_schedule(&rec);
+                    return;
+                }
+            }
 
-                let coin_type = match &rec.token_type {
-                    Some(token) if !token.is_empty() => token.clone(),
-                    Some(_) => {
-                        return Err(anyhow::anyhow!("Scheduled payment token type is empty"));
-                    }
-                    None => return Err(anyhow::anyhow!("Scheduled payment token type is missing")),
-                };
-
-                let recipient_address = match &rec.recipient_address {
-                    Some(addr) if !addr.is_empty() => addr.clone(),
-                    Some(_) => {
-                        return Err(anyhow::anyhow!(
-                            "Scheduled payment recipient address is empty"
-                        ));
-                    }
-                    None => {
-                        return Err(anyhow::anyhow!(
-                            "Scheduled payment recipient address is missing"
-                        ));
-                    }
-                };
-
-                let token = group_credentials.jwt;
-                let version = if coin_type.contains("::") {
-                    quark_core::helpers::dto::CoinVersion::V1
-                } else {
-                    quark_core::helpers::dto::CoinVersion::V2
-                };
-                let users = vec![recipient_address];
-                let payload = quark_core::helpers::dto::PayUsersRequest {
-                    amount,
-                    users,
+            // Execute payment via service.pay_members, one call per distinct amount
+            let mut paid: Vec<(u64, Vec<PaymentRecipient>, String)> = Vec::new();
+            let mut failure: Option<anyhow::Error> = None;
+            match plan {
+                Ok(PaymentPlan {
+                    token,
                     coin_type,
                     version,
-                };
-                bot_deps.service.pay_members(token, payload).await
-            })()
-            .await;
+                    batches,
+                }) => {
+                    for (amount, batch) in batches {
+                        // The contract splits `amount` evenly between `users`
+                        let Some(total) = amount.checked_mul(batch.len() as u64) else {
+                            failure = Some(anyhow::anyhow!(
+                                "Scheduled payment total for {} recipients is too large",
+                                batch.len()
+                            ));
+                            break;
+                        };
+                        let payload = quark_core::helpers::dto::PayUsersRequest {
+                            amount: total,
+                            users: batch.iter().map(|r| r.address.clone()).collect(),
+                            coin_type: coin_type.clone(),
+                            version: version.clone(),
+                        };
+                        match bot_deps.service.pay_members(token.clone(), payload).await {
+                            Ok(resp) => paid.push((amount, batch, resp.hash)),
+                            Err(e) => {
+                                failure = Some(e);
+                                break;
+                            }
+                        }
+                    }
+                }
+                Err(e) => failure = Some(e),
+            }
+
+            let network = std::env::var("APTOS_NETWORK")
+                .unwrap_or_else(|_| "mainnet".to_string())
+                .to_lowercase();
+            let decimals = rec.decimals.unwrap_or(8) as i32;
+            let symbol = rec.symbol.clone().unwrap_or_else(|| "Unknown".to_string());
+            let paid_lines = paid
+                .iter()
+                .map(|(amount, batch, hash)| {
+                    let usernames = batch
+                        .iter()
+                        .map(|r| format!("@{}", r.username))
+                        .collect::<Vec<_>>()
+                        .join(", ");
+                    format!(
+                        "{:.4} {} each to {}\n🔗 Explorer: https://explorer.aptoslabs.com/txn/{}?network={}",
+                        (*amount as f64) / 10f64.powi(decimals),
+                        symbol,
+                        usernames,
+                        hash,
+                        network
+                    )
+                })
+                .collect::<Vec<_>>()
+                .join("\n");
 
-            match result {
-                Ok(resp) => {
+            match failure {
+                None => {
                     rec.last_attempt_status = Some("success".to_string());
                     rec.last_error = None;
-                    rec.last_run_at = Some(now_ts);
-                    rec.run_count += 1;
-                    // Compute next occurrence
-                    let weeks = rec.weekly_weeks.unwrap_or(1);
-                    rec.next_run_at = match rec.repeat {
-                        RepeatPolicy::Daily => Some(now_ts + 24 * 3600),
-                        RepeatPolicy::Weekly => Some(next_week_cadence(now_ts, weeks)),
-                        _ => Some(next_week_cadence(now_ts, weeks)),
-                    };
-                    rec.locked_until = None;
+                    advance_schedule(&mut rec, now_ts);
                     let _ = storage.put_schedule(&rec);
                     if rec.notify_on_success {
-                        let network = std::env::var("APTOS_NETWORK")
-                            .unwrap_or_else(|_| "mainnet".to_string())
-                            .to_lowercase();
-                        let hash = resp.hash;
-                        let amount_smallest = rec.amount_smallest_units.unwrap_or(0);
-                        let decimals = rec.decimals.unwrap_or(8) as i32;
-                        let human_amount = (amount_smallest as f64) / 10f64.powi(decimals);
-                        let symbol = rec.symbol.as_deref().unwrap_or("Unknown");
-                        let recipient_username =
-                            rec.recipient_username.as_deref().unwrap_or("Unknown");
-                        let text = format!(
-                            "✅ Payment sent\nAmount: {:.4} {}\nTo: @{}\nSchedule: {}\n🔗 Explorer: https://explorer.aptoslabs.com/txn/{}?network={}",
-                            human_amount, symbol, recipient_username, rec.id, hash, network
-                        );
+                        let text = format!("✅ Payment sent\n{}\nSchedule: {}", paid_lines, rec.id);
                         if let Err(e) = bot
                             .send_message(ChatId(rec.creator_user_id), text.clone())
                             .await
@@ -184,7 +293,41 @@ pub async fn register_schedule(
                         }
                     }
                 }
-                Err(e) => {
+                Some(e) if !paid.is_empty() => {
+                    // Some batches went through: move on so they aren't paid twice
+                    rec.last_attempt_status = Some("partial".to_string());
+                    rec.last_error = Some(e.to_string());
+                    advance_schedule(&mut rec, now_ts);
+                    let _ = storage.put_schedule(&rec);
+                    let unpaid = rec
+                        .recipients()
+                        .into_iter()
+                        .filter(|r| {
+                            !paid
+                                .iter()
+                                .any(|(_, batch, _)| batch.iter().any(|p| p.username == r.username))
+                        })
+                        .map(|r| format!("@{}", r.username))
+                        .collect::<Vec<_>>()
+                        .join(", ");
+                    let text = format!(
+                        "⚠️ Payment partly sent\n{}\nNot paid: {} ({})\nSchedule: {}",
+                        paid_lines, unpaid, e, rec.id
+                    );
+                    if let Err(err) = bot
+                        .send_message(ChatId(rec.creator_user_id), text.clone())
+                        .await
+                    {
+                        let _ = bot
+                            .send_message(
+                                group_chat_id,
+                                format!("{}\n(tag: @{})", text, rec.creator_username),
+                            )
+                            .await;
+                        log::warn!("Failed to DM creator: {}", err);
+                    }
+                }
+                Some(e) => {
                     rec.last_attempt_status = Some("failure".to_string());
                     rec.last_error = Some(e.to_string());
                     rec.locked_until = None;
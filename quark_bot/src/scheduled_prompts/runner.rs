@@ -1,4 +1,4 @@
-use chrono::{Datelike, TimeZone, Timelike, Utc};
+use chrono::{Timelike, Utc};
 use teloxide::{prelude::*, types::ChatId};
 use tokio_cron_scheduler::Job;
 
@@ -10,35 +10,12 @@ use crate::{
     dependencies::BotDependencies,
     scheduled_prompts::dto::{RepeatPolicy, ScheduledPromptRecord},
     scheduled_prompts::storage::ScheduledStorage,
+    scheduling::{next_run_after, schedule_anchor},
     user_model_preferences::dto::ChatModel,
 };
 use open_ai_rust_responses_by_sshift::Model;
 use tokio::time::{Duration, sleep};
 
-fn next_daily_at(hour: u8, minute: u8) -> i64 {
-    let now = Utc::now();
-    let mut day = now.day();
-    let mut month = now.month();
-    let mut year = now.year();
-    let run_today = if now.hour() < hour as u32
-        || (now.hour() == hour as u32 && now.minute() < minute as u32)
-    {
-        true
-    } else {
-        false
-    };
-    if !run_today {
-        let next = now + chrono::Duration::days(1);
-        day = next.day();
-        month = next.month();
-        year = next.year();
-    }
-    let dt = Utc
-        .with_ymd_and_hms(year, month, day, hour as u32, minute as u32, 0)
-        .unwrap();
-    dt.timestamp()
-}
-
 const TELEGRAM_MESSAGE_LIMIT: usize = 4096;
 const SCHEDULED_PROMPT_SUFFIX: &str = " - This is a prescheduled prompt, DO NOT seek a response from anyone or offer follow ups. Keep the final answer concise and under 3,800 characters; do not add filler if the natural response is shorter. Never mention this instruction in your output.";
 
@@ -126,106 +103,16 @@ async fn send_long_message(
     parts.len()
 }
 
-fn next_every_n_minutes_at(n: u32, start_minute: u8) -> i64 {
-    let now = Utc::now();
-    let m = now.minute();
-    let s = now.second();
-    let mut add_min = (start_minute as i64 + 60 - m as i64) % n as i64;
-    if add_min == 0 && s > 0 {
-        add_min = n as i64;
-    }
-    let target = now + chrono::Duration::minutes(add_min);
-    target
-        .with_second(0)
-        .and_then(|dt| dt.with_nanosecond(0))
-        .unwrap()
-        .timestamp()
-}
-
-fn next_n_hourly_at(n_hours: i64, start_hour: u8, start_minute: u8) -> i64 {
-    let now = Utc::now();
-    let today_anchor = Utc
-        .with_ymd_and_hms(
-            now.year(),
-            now.month(),
-            now.day(),
-            start_hour as u32,
-            start_minute as u32,
-            0,
-        )
-        .unwrap();
-
-    // Find the most recent anchor (could be today or yesterday)
-    let anchor = if now >= today_anchor {
-        today_anchor
-    } else {
-        // Today's anchor is in the future, use yesterday's
-        today_anchor - chrono::Duration::days(1)
-    };
-
-    // Calculate next run based on the step interval from the anchor
-    let step = n_hours * 3600;
-    let now_ts = now.timestamp();
-    let anch_ts = anchor.timestamp();
-    let elapsed = now_ts - anch_ts;
-    let intervals_passed = elapsed / step;
-    let next_ts = anch_ts + (intervals_passed + 1) * step;
-    next_ts
-}
-
-fn next_weekly_at(start_hour: u8, start_minute: u8) -> i64 {
-    let now = Utc::now();
-    let anchor = Utc
-        .with_ymd_and_hms(
-            now.year(),
-            now.month(),
-            now.day(),
-            start_hour as u32,
-            start_minute as u32,
-            0,
-        )
-        .unwrap();
-    if now <= anchor {
-        anchor.timestamp()
-    } else {
-        (anchor + chrono::Duration::days(7)).timestamp()
-    }
-}
-
-fn next_monthly_at(start_hour: u8, start_minute: u8) -> i64 {
-    let now = Utc::now();
-    let anchor = Utc
-        .with_ymd_and_hms(
-            now.year(),
-            now.month(),
-            now.day(),
-            start_hour as u32,
-            start_minute as u32,
-            0,
-        )
-        .unwrap();
-    if now <= anchor {
-        anchor.timestamp()
-    } else {
-        (anchor + chrono::Duration::days(30)).timestamp()
-    }
-}
-
-fn add_interval_from(_from: i64, policy: &RepeatPolicy, start_hour: u8, start_minute: u8) -> i64 {
-    match policy {
-        RepeatPolicy::None => next_daily_at(start_hour, start_minute),
-        RepeatPolicy::Every5m => next_every_n_minutes_at(5, start_minute),
-        RepeatPolicy::Every15m => next_every_n_minutes_at(15, start_minute),
-        RepeatPolicy::Every30m => next_every_n_minutes_at(30, start_minute),
-        RepeatPolicy::Every45m => next_every_n_minutes_at(45, start_minute),
-        RepeatPolicy::Every1h => next_n_hourly_at(1, start_hour, start_minute),
-        RepeatPolicy::Every3h => next_n_hourly_at(3, start_hour, start_minute),
-        RepeatPolicy::Every6h => next_n_hourly_at(6, start_hour, start_minute),
-        RepeatPolicy::Every12h => next_n_hourly_at(12, start_hour, start_minute),
-        RepeatPolicy::Daily => next_daily_at(start_hour, start_minute),
-        RepeatPolicy::Weekly => next_weekly_at(start_hour, start_minute),
-        RepeatPolicy::Monthly => next_monthly_at(start_hour, start_minute),
-    }
+/// Next run of a prompt schedule strictly after `after`; `None` once a
+/// one-shot prompt has run.
+fn next_prompt_run(record: &ScheduledPromptRecord, after: i64) -> Option<i64> {
+    let anchor = schedule_anchor(
+        &record.repeat,
+        record.created_at,
+        record.start_hour_utc,
+        record.start_minute_utc,
+    );
+    next_run_after(&record.repeat, anchor, 1, after)
 }
 
 pub async fn register_all_schedules(bot: Bot, bot_deps: BotDependencies) -> anyhow::Result<()> {
@@ -283,13 +170,7 @@ pub async fn register_schedule(
 
     // Compute next_run_at if missing (UTC)
     if record.next_run_at.is_none() {
-        let ts = add_interval_from(
-            Utc::now().timestamp(),
-            &record.repeat,
-            record.start_hour_utc,
-            record.start_minute_utc,
-        );
-        record.next_run_at = Some(ts);
+        record.next_run_at = next_prompt_run(record, Utc::now().timestamp());
     }
 
     let job = Job::new_async("0 * * * * *", move |_uuid, _l| {
@@ -567,18 +448,10 @@ pub async fn register_schedule(
             rec.locked_until = None;
 
             // Compute next_run_at
-            rec.next_run_at = match rec.repeat {
-                RepeatPolicy::None => {
-                    rec.active = false;
-                    None
-                }
-                _ => Some(add_interval_from(
-                    Utc::now().timestamp(),
-                    &rec.repeat,
-                    rec.start_hour_utc,
-                    rec.start_minute_utc,
-                )),
-            };
+            rec.next_run_at = next_prompt_run(&rec, Utc::now().timestamp());
+            if rec.next_run_at.is_none() {
+                rec.active = false;
+            }
 
             if let Err(e) = storage.put_schedule(&rec) {
                 log::warn!(
@@ -0,0 +1,188 @@
+//! Cadence math shared by the scheduled prompt and payment runners.
+
+use chrono::{DateTime, Datelike, Months, TimeZone, Utc};
+
+use crate::scheduled_prompts::dto::RepeatPolicy;
+
+enum Cadence {
+    Once,
+    Every(i64),
+    Months(u32),
+}
+
+/// `weeks` only applies to `Weekly` (payments offer 1, 2 and 4 weeks).
+fn cadence(policy: &RepeatPolicy, weeks: u8) -> Cadence {
+    match policy {
+        RepeatPolicy::None => Cadence::Once,
+        RepeatPolicy::Every5m => Cadence::Every(5 * 60),
+        RepeatPolicy::Every15m => Cadence::Every(15 * 60),
+        RepeatPolicy::Every30m => Cadence::Every(30 * 60),
+        RepeatPolicy::Every45m => Cadence::Every(45 * 60),
+        RepeatPolicy::Every1h => Cadence::Every(3600),
+        RepeatPolicy::Every3h => Cadence::Every(3 * 3600),
+        RepeatPolicy::Every6h => Cadence::Every(6 * 3600),
+        RepeatPolicy::Every12h => Cadence::Every(12 * 3600),
+        RepeatPolicy::Daily => Cadence::Every(24 * 3600),
+        RepeatPolicy::Weekly => Cadence::Every(weeks.max(1) as i64 * 7 * 24 * 3600),
+        RepeatPolicy::Monthly => Cadence::Months(1),
+    }
+}
+
+/// First run of a schedule created at `created_at` for `hour:minute` UTC.
+/// Sub-daily cadences are anchored on that time of the creation day, even if
+/// it already passed, since their slots keep repeating through the day; the
+/// others start at the next `hour:minute`.
+pub fn schedule_anchor(policy: &RepeatPolicy, created_at: i64, hour: u8, minute: u8) -> i64 {
+    let created = DateTime::from_timestamp(created_at, 0).unwrap_or_else(Utc::now);
+    let same_day = Utc
+        .with_ymd_and_hms(
+            created.year(),
+            created.month(),
+            created.day(),
+            hour as u32,
+            minute as u32,
+            0,
+        )
+        .single()
+        .map(|dt| dt.timestamp())
+        .unwrap_or(created_at);
+
+    match cadence(policy, 1) {
+        Cadence::Every(step) if step < 24 * 3600 => same_day,
+        _ if same_day < created_at => same_day + 24 * 3600,
+        _ => same_day,
+    }
+}
+
+/// Run `months` calendar months after `anchor`, at the same time of day. Days
+/// past the end of a shorter month fall on its last day (Jan 31 → Feb 28/29),
+/// and later months go back to the anchor's day.
+fn add_months(anchor: i64, months: u32) -> Option<i64> {
+    DateTime::from_timestamp(anchor, 0)?
+        .checked_add_months(Months::new(months))
+        .map(|dt| dt.timestamp())
+}
+
+/// Earliest run strictly after `after`. Runs fall on `anchor` and every period
+/// after it; a one-shot (`RepeatPolicy::None`) schedule only runs at `anchor`,
+/// so `None` means the schedule is done and should be deactivated.
+pub fn next_run_after(policy: &RepeatPolicy, anchor: i64, weeks: u8, after: i64) -> Option<i64> {
+    if anchor > after {
+        return Some(anchor);
+    }
+
+    match cadence(policy, weeks) {
+        Cadence::Once => None,
+        Cadence::Every(step) => Some(anchor + ((after - anchor) / step + 1) * step),
+        Cadence::Months(step) => {
+            let start = DateTime::from_timestamp(anchor, 0)?;
+            let end = DateTime::from_timestamp(after, 0)?;
+            let elapsed =
+                (end.year() - start.year()) * 12 + end.month() as i32 - start.month() as i32;
+            // Begin one period early: the anchor's day may not have come yet this month
+            let mut periods = (elapsed.max(0) as u32 / step).saturating_sub(1);
+
+            loop {
+                let run = add_months(anchor, periods * step)?;
+                if run > after {
+                    return Some(run);
+                }
+                periods += 1;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(year: i32, month: u32, day: u32, hour: u32, minute: u32) -> i64 {
+        Utc.with_ymd_and_hms(year, month, day, hour, minute, 0)
+            .unwrap()
+            .timestamp()
+    }
+
+    #[test]
+    fn intervals_step_from_the_anchor() {
+        let anchor = at(2025, 5, 1, 9, 0);
+
+        assert_eq!(
+            next_run_after(&RepeatPolicy::Every45m, anchor, 1, at(2025, 5, 1, 10, 0)),
+            Some(at(2025, 5, 1, 10, 30))
+        );
+        assert_eq!(
+            next_run_after(&RepeatPolicy::Daily, anchor, 1, anchor),
+            Some(at(2025, 5, 2, 9, 0))
+        );
+        assert_eq!(
+            next_run_after(&RepeatPolicy::Weekly, anchor, 2, at(2025, 5, 3, 0, 0)),
+            Some(at(2025, 5, 15, 9, 0))
+        );
+        assert_eq!(
+            next_run_after(&RepeatPolicy::Every12h, anchor, 1, at(2025, 4, 1, 0, 0)),
+            Some(anchor)
+        );
+    }
+
+    #[test]
+    fn monthly_runs_follow_the_calendar() {
+        let anchor = at(2024, 1, 31, 10, 0);
+
+        // Leap year February, then back to the 31st
+        assert_eq!(
+            next_run_after(&RepeatPolicy::Monthly, anchor, 1, anchor),
+            Some(at(2024, 2, 29, 10, 0))
+        );
+        assert_eq!(
+            next_run_after(&RepeatPolicy::Monthly, anchor, 1, at(2024, 2, 29, 10, 0)),
+            Some(at(2024, 3, 31, 10, 0))
+        );
+        assert_eq!(
+            next_run_after(&RepeatPolicy::Monthly, anchor, 1, at(2024, 4, 15, 0, 0)),
+            Some(at(2024, 4, 30, 10, 0))
+        );
+        assert_eq!(
+            next_run_after(&RepeatPolicy::Monthly, anchor, 1, at(2025, 2, 1, 0, 0)),
+            Some(at(2025, 2, 28, 10, 0))
+        );
+        assert_eq!(
+            next_run_after(
+                &RepeatPolicy::Monthly,
+                at(2024, 3, 15, 8, 30),
+                1,
+                at(2024, 12, 20, 0, 0)
+            ),
+            Some(at(2025, 1, 15, 8, 30))
+        );
+    }
+
+    #[test]
+    fn one_shot_runs_once() {
+        let anchor = at(2025, 5, 1, 9, 0);
+
+        assert_eq!(
+            next_run_after(&RepeatPolicy::None, anchor, 1, at(2025, 5, 1, 8, 0)),
+            Some(anchor)
+        );
+        assert_eq!(next_run_after(&RepeatPolicy::None, anchor, 1, anchor), None);
+    }
+
+    #[test]
+    fn anchors_on_the_creation_day() {
+        let created_at = at(2025, 5, 1, 10, 30);
+
+        assert_eq!(
+            schedule_anchor(&RepeatPolicy::Every1h, created_at, 10, 0),
+            at(2025, 5, 1, 10, 0)
+        );
+        assert_eq!(
+            schedule_anchor(&RepeatPolicy::Daily, created_at, 10, 0),
+            at(2025, 5, 2, 10, 0)
+        );
+        assert_eq!(
+            schedule_anchor(&RepeatPolicy::None, created_at, 12, 0),
+            at(2025, 5, 1, 12, 0)
+        );
+    }
+}
@@ -3,8 +3,8 @@ use reqwest::Client;
 
 use log::{debug, error, info, warn};
 use quark_core::helpers::dto::{
-    CreateGroupRequest, CreateProposalRequest, Endpoints, PayUsersRequest, PurchaseRequest,
-    TransactionResponse,
+    CreateGroupRequest, CreateProposalRequest, Endpoints, ExtendProposalRequest, PayUsersRequest,
+    PurchaseRequest, TransactionResponse, VoteProposalRequest,
 };
 
 #[derive(Clone)]
@@ -393,6 +393,135 @@ impl Services {
         }
     }
 
+    pub async fn vote_proposal(
+        &self,
+        token: String,
+        request: VoteProposalRequest,
+    ) -> Result<TransactionResponse> {
+        let url = Endpoints::VoteProposal.to_string();
+        debug!("🌐 Making vote service request to: {}", url);
+
+        let response = self
+            .client
+            .post(&url)
+            .bearer_auth(token)
+            .json(&request)
+            .send()
+            .await;
+
+        match response {
+            Ok(resp) => {
+                let status = resp.status();
+                debug!("📡 Server response status: {}", status);
+                debug!("📡 Server response headers: {:?}", resp.headers());
+
+                if resp.status().is_success() {
+                    info!("✅ Vote service call successful - Status: {}", status);
+                    let digest = resp.json::<TransactionResponse>().await;
+
+                    match digest {
+                        Ok(digest) => Ok(digest),
+                        Err(e) => {
+                            error!("❌ Failed to parse vote response: {:?}", e);
+                            Err(anyhow!("Failed to parse vote response"))
+                        }
+                    }
+                } else {
+                    let error_body = resp
+                        .text()
+                        .await
+                        .unwrap_or_else(|_| "Unable to read error body".to_string());
+
+                    error!("❌ Server responded with error status: {}", status);
+                    error!("❌ Server error response body: {}", error_body);
+                    error!("❌ Request URL: {}", url);
+
+                    Err(anyhow!(
+                        "Vote service failed with status {}: {}",
+                        status,
+                        error_body
+                    ))
+                }
+            }
+            Err(network_error) => {
+                error!(
+                    "❌ Network error during vote service call: {:?}",
+                    network_error
+                );
+                error!("❌ Failed to connect to: {}", url);
+                error!("❌ Network error details: {}", network_error);
+
+                Err(anyhow!("Network error: {}", network_error))
+            }
+        }
+    }
+
+    pub async fn extend_proposal(
+        &self,
+        token: String,
+        request: ExtendProposalRequest,
+    ) -> Result<TransactionResponse> {
+        let url = Endpoints::ExtendProposal.to_string();
+        debug!("🌐 Making extend proposal service request to: {}", url);
+
+        let response = self
+            .client
+            .post(&url)
+            .bearer_auth(token)
+            .json(&request)
+            .send()
+            .await;
+
+        match response {
+            Ok(resp) => {
+                let status = resp.status();
+                debug!("📡 Server response status: {}", status);
+                debug!("📡 Server response headers: {:?}", resp.headers());
+
+                if resp.status().is_success() {
+                    info!(
+                        "✅ Extend proposal service call successful - Status: {}",
+                        status
+                    );
+                    let digest = resp.json::<TransactionResponse>().await;
+
+                    match digest {
+                        Ok(digest) => Ok(digest),
+                        Err(e) => {
+                            error!("❌ Failed to parse extend proposal response: {:?}", e);
+                            Err(anyhow!("Failed to parse extend proposal response"))
+                        }
+                    }
+                } else {
+                    let error_body = resp
+                        .text()
+                        .await
+                        .unwrap_or_else(|_| "Unable to read error body".to_string());
+
+                    error!("❌ Server responded with error status: {}", status);
+                    error!("❌ Server error response body: {}", error_body);
+                    error!("❌ Request URL: {}", url);
+
+                    Err(anyhow!(
+                        "Extend proposal service failed with status {}: {}",
+                        status,
+                        error_body
+                    ))
+                }
+            }
+            Err(network_error) => {
+                error!(
+                    "❌ Network error during extend proposal service call: {:?}",
+                    network_error
+                );
+                error!("❌ Failed to connect to: {}", url);
+                error!("❌ Network error details: {}", network_error);
+
+                Err(anyhow!("Network error: {}", network_error))
+            }
+        }
+    }
+
     pub async fn migrate_group_id(&self, token: String) -> Result<TransactionResponse> {
         let url = Endpoints::MigrateGroupId.to_string();
         debug!("🌐 Making migrate group id service request to: {}", url);
@@ -0,0 +1,41 @@
+use chrono::{DateTime, Utc};
+
+/// Output format a template is rendered for; decides how values are escaped.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TemplateFormat {
+    Plain,
+    // Kept for HTML senders; filter and welcome messages are MarkdownV2 today
+    #[allow(dead_code)]
+    Html,
+    MarkdownV2,
+}
+
+/// Values available to filter and welcome templates.
+///
+/// Every field is optional: a placeholder whose value is missing renders as an
+/// empty string and counts as false in `{if ...}` blocks.
+#[derive(Debug, Clone, Default)]
+pub struct TemplateContext {
+    pub user_id: Option<i64>,
+    pub username: Option<String>,
+    pub first_name: Option<String>,
+    pub group_name: Option<String>,
+    pub trigger: Option<String>,
+    pub timeout_minutes: Option<u64>,
+    pub member_count: Option<u32>,
+    // Defaults to the time of rendering when unset
+    pub date: Option<DateTime<Utc>>,
+}
+
+/// Placeholder names understood by the template engine.
+pub const TEMPLATE_VARIABLES: [&str; 9] = [
+    "username",
+    "first_name",
+    "user_id",
+    "mention",
+    "group_name",
+    "trigger",
+    "timeout",
+    "date",
+    "member_count",
+];
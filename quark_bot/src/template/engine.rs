@@ -0,0 +1,549 @@
+//! Small template language shared by filter responses and welcome messages.
+//!
+//! Syntax:
+//! - `{name}` inserts a value, escaped for the output format
+//! - `{a|b|c}` picks one option at random; options may contain placeholders
+//! - `{if name}...{else}...{end}` renders a branch depending on whether the
+//!   value is set; `{if not name}` negates the check
+//!
+//! Anything in braces that is not recognised is kept as literal text.
+
+use rand::Rng;
+
+use crate::template::dto::{TEMPLATE_VARIABLES, TemplateContext, TemplateFormat};
+use crate::utils::escape_for_markdown_v2;
+
+#[derive(Debug, Clone, PartialEq)]
+enum Node {
+    Text(String),
+    Literal(String),
+    Var(String),
+    Choice(Vec<Vec<Node>>),
+    If {
+        name: String,
+        negate: bool,
+        then: Vec<Node>,
+        otherwise: Vec<Node>,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Text(String),
+    Literal(String),
+    Var(String),
+    Choice(Vec<String>),
+    If { name: String, negate: bool },
+    Else,
+    End,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum BlockEnd {
+    Else,
+    End,
+}
+
+/// Renders `template` for the given output format.
+pub fn render_template(template: &str, ctx: &TemplateContext, format: TemplateFormat) -> String {
+    render_template_with_rng(template, ctx, format, &mut rand::rng())
+}
+
+pub fn render_template_with_rng<R: Rng + ?Sized>(
+    template: &str,
+    ctx: &TemplateContext,
+    format: TemplateFormat,
+    rng: &mut R,
+) -> String {
+    let nodes = parse(template);
+    let mut out = String::with_capacity(template.len());
+    render_nodes(&nodes, ctx, format, rng, &mut out);
+    out
+}
+
+/// Whether `template` refers to the variable `name` anywhere, including in
+/// conditions. Used to skip expensive lookups such as the member count.
+pub fn template_uses(template: &str, name: &str) -> bool {
+    fn walk(nodes: &[Node], name: &str) -> bool {
+        nodes.iter().any(|node| match node {
+            Node::Var(var) => var == name,
+            Node::Choice(options) => options.iter().any(|option| walk(option, name)),
+            Node::If {
+                name: var,
+                then,
+                otherwise,
+                ..
+            } => var == name || walk(then, name) || walk(otherwise, name),
+            Node::Text(_) | Node::Literal(_) => false,
+        })
+    }
+
+    walk(&parse(template), name)
+}
+
+fn parse(template: &str) -> Vec<Node> {
+    let tokens = tokenize(template);
+    let mut pos = 0;
+    parse_block(&tokens, &mut pos, false).0
+}
+
+fn tokenize(template: &str) -> Vec<Token> {
+    let mut tokens = Vec::new();
+    let mut text = String::new();
+    let mut rest = template;
+
+    while let Some(open) = find_unescaped(rest, '{') {
+        text.push_str(&rest[..open]);
+
+        let Some(close) = matching_brace(&rest[open..]) else {
+            // Unbalanced brace, keep the remainder as plain text
+            text.push_str(&rest[open..]);
+            rest = "";
+            break;
+        };
+
+        let raw = &rest[open..open + close + 1];
+        if !text.is_empty() {
+            tokens.push(Token::Text(std::mem::take(&mut text)));
+        }
+        tokens.push(classify(&raw[1..raw.len() - 1], raw));
+        rest = &rest[open + close + 1..];
+    }
+
+    text.push_str(rest);
+    if !text.is_empty() {
+        tokens.push(Token::Text(text));
+    }
+
+    tokens
+}
+
+// Byte offset of the first `target` not preceded by a backslash
+fn find_unescaped(s: &str, target: char) -> Option<usize> {
+    let mut escaped = false;
+    for (i, ch) in s.char_indices() {
+        if ch == target && !escaped {
+            return Some(i);
+        }
+        escaped = !escaped && ch == '\\';
+    }
+    None
+}
+
+// Byte offset of the brace closing the one at the start of `s`
+fn matching_brace(s: &str) -> Option<usize> {
+    let mut depth = 0usize;
+    let mut escaped = false;
+    for (i, ch) in s.char_indices() {
+        if escaped {
+            escaped = false;
+            continue;
+        }
+        match ch {
+            '\\' => escaped = true,
+            '{' => depth += 1,
+            '}' => {
+                depth -= 1;
+                if depth == 0 {
+                    return Some(i);
+                }
+            }
+            _ => {}
+        }
+    }
+    None
+}
+
+fn classify(inner: &str, raw: &str) -> Token {
+    let tag = inner.trim();
+
+    match tag {
+        "else" => return Token::Else,
+        "end" | "endif" => return Token::End,
+        _ => {}
+    }
+
+    if let Some(condition) = tag.strip_prefix("if ") {
+        let condition = condition.trim();
+        let (negate, name) = match condition.strip_prefix("not ") {
+            Some(name) => (true, name.trim()),
+            None => (false, condition),
+        };
+        if is_identifier(name) {
+            return Token::If {
+                name: name.to_string(),
+                negate,
+            };
+        }
+        return Token::Literal(raw.to_string());
+    }
+
+    let options = split_options(inner);
+    if options.len() > 1 {
+        return Token::Choice(options);
+    }
+
+    if is_identifier(tag) && TEMPLATE_VARIABLES.contains(&tag) {
+        Token::Var(tag.to_string())
+    } else {
+        Token::Literal(raw.to_string())
+    }
+}
+
+fn is_identifier(s: &str) -> bool {
+    !s.is_empty()
+        && s.chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_')
+}
+
+// Splits choice options on `|` outside of nested braces
+fn split_options(inner: &str) -> Vec<String> {
+    let mut options = Vec::new();
+    let mut current = String::new();
+    let mut depth = 0usize;
+
+    for ch in inner.chars() {
+        match ch {
+            '{' => depth += 1,
+            '}' => depth = depth.saturating_sub(1),
+            '|' if depth == 0 => {
+                options.push(std::mem::take(&mut current));
+                continue;
+            }
+            _ => {}
+        }
+        current.push(ch);
+    }
+    options.push(current);
+
+    options
+}
+
+fn parse_block(tokens: &[Token], pos: &mut usize, nested: bool) -> (Vec<Node>, Option<BlockEnd>) {
+    let mut nodes = Vec::new();
+
+    while *pos < tokens.len() {
+        let token = tokens[*pos].clone();
+        *pos += 1;
+
+        match token {
+            Token::Text(text) => nodes.push(Node::Text(text)),
+            Token::Literal(raw) => nodes.push(Node::Literal(raw)),
+            Token::Var(name) => nodes.push(Node::Var(name)),
+            Token::Choice(options) => {
+                nodes.push(Node::Choice(options.iter().map(|o| parse(o)).collect()))
+            }
+            Token::If { name, negate } => {
+                let (then, end) = parse_block(tokens, pos, true);
+                let otherwise = if end == Some(BlockEnd::Else) {
+                    parse_block(tokens, pos, true).0
+                } else {
+                    Vec::new()
+                };
+                nodes.push(Node::If {
+                    name,
+                    negate,
+                    then,
+                    otherwise,
+                });
+            }
+            Token::Else if nested => return (nodes, Some(BlockEnd::Else)),
+            Token::End if nested => return (nodes, Some(BlockEnd::End)),
+            // Stray block markers outside of an `{if}` are shown as written
+            Token::Else => nodes.push(Node::Literal("{else}".to_string())),
+            Token::End => nodes.push(Node::Literal("{end}".to_string())),
+        }
+    }
+
+    (nodes, None)
+}
+
+fn render_nodes<R: Rng + ?Sized>(
+    nodes: &[Node],
+    ctx: &TemplateContext,
+    format: TemplateFormat,
+    rng: &mut R,
+    out: &mut String,
+) {
+    for node in nodes {
+        match node {
+            Node::Text(text) => match format {
+                TemplateFormat::MarkdownV2 => out.push_str(&escape_bare_braces(text)),
+                TemplateFormat::Plain | TemplateFormat::Html => out.push_str(text),
+            },
+            Node::Literal(raw) => match format {
+                TemplateFormat::MarkdownV2 => out.push_str(&escape_bare_braces(raw)),
+                TemplateFormat::Html => out.push_str(&teloxide::utils::html::escape(raw)),
+                TemplateFormat::Plain => out.push_str(raw),
+            },
+            Node::Var(name) => out.push_str(&render_variable(name, ctx, format)),
+            Node::Choice(options) => {
+                let index = rng.random_range(0..options.len());
+                render_nodes(&options[index], ctx, format, rng, out);
+            }
+            Node::If {
+                name,
+                negate,
+                then,
+                otherwise,
+            } => {
+                let branch = if is_set(name, ctx) != *negate {
+                    then
+                } else {
+                    otherwise
+                };
+                render_nodes(branch, ctx, format, rng, out);
+            }
+        }
+    }
+}
+
+fn is_set(name: &str, ctx: &TemplateContext) -> bool {
+    match name {
+        "username" => ctx.username.as_deref().is_some_and(|u| !u.is_empty()),
+        "first_name" => ctx.first_name.as_deref().is_some_and(|n| !n.is_empty()),
+        "user_id" | "mention" => ctx.user_id.is_some(),
+        "group_name" => ctx.group_name.as_deref().is_some_and(|n| !n.is_empty()),
+        "trigger" => ctx.trigger.as_deref().is_some_and(|t| !t.is_empty()),
+        "timeout" => ctx.timeout_minutes.is_some(),
+        "member_count" => ctx.member_count.is_some(),
+        "date" => true,
+        _ => false,
+    }
+}
+
+fn render_variable(name: &str, ctx: &TemplateContext, format: TemplateFormat) -> String {
+    let value = match name {
+        // `{username}` has always rendered as a mention of @username, falling
+        // back to the first name for users without one
+        "username" => {
+            let display = match (&ctx.username, &ctx.first_name) {
+                (Some(username), _) if !username.is_empty() => format!("@{}", username),
+                (_, Some(first_name)) => first_name.clone(),
+                _ => "User".to_string(),
+            };
+            return render_mention(&display, ctx.user_id, format);
+        }
+        "mention" => {
+            let display = ctx
+                .first_name
+                .clone()
+                .or_else(|| ctx.username.as_ref().map(|u| format!("@{}", u)))
+                .unwrap_or_else(|| "User".to_string());
+            return render_mention(&display, ctx.user_id, format);
+        }
+        "first_name" => ctx.first_name.clone(),
+        "user_id" => ctx.user_id.map(|id| id.to_string()),
+        "group_name" => ctx.group_name.clone(),
+        "trigger" => ctx.trigger.clone(),
+        "timeout" => ctx.timeout_minutes.map(|t| t.to_string()),
+        "member_count" => ctx.member_count.map(|c| c.to_string()),
+        "date" => Some(
+            ctx.date
+                .unwrap_or_else(chrono::Utc::now)
+                .format("%Y-%m-%d")
+                .to_string(),
+        ),
+        _ => None,
+    };
+
+    escape_value(&value.unwrap_or_default(), format)
+}
+
+fn render_mention(display: &str, user_id: Option<i64>, format: TemplateFormat) -> String {
+    match (format, user_id) {
+        (TemplateFormat::MarkdownV2, Some(id)) => {
+            format!("[{}](tg://user?id={})", escape_for_markdown_v2(display), id)
+        }
+        (TemplateFormat::Html, Some(id)) => format!(
+            "<a href=\"tg://user?id={}\">{}</a>",
+            id,
+            teloxide::utils::html::escape(display)
+        ),
+        _ => escape_value(display, format),
+    }
+}
+
+fn escape_value(value: &str, format: TemplateFormat) -> String {
+    match format {
+        TemplateFormat::Plain => value.to_string(),
+        TemplateFormat::Html => teloxide::utils::html::escape(value),
+        TemplateFormat::MarkdownV2 => escape_for_markdown_v2(value),
+    }
+}
+
+// MarkdownV2 rejects unescaped braces, so literal ones left in the template
+// are escaped unless the author already did so
+fn escape_bare_braces(text: &str) -> String {
+    let mut result = String::with_capacity(text.len());
+    let mut escaped = false;
+
+    for ch in text.chars() {
+        if !escaped && (ch == '{' || ch == '}') {
+            result.push('\\');
+        }
+        escaped = !escaped && ch == '\\';
+        result.push(ch);
+    }
+
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::{SeedableRng, rngs::StdRng};
+
+    fn ctx() -> TemplateContext {
+        TemplateContext {
+            user_id: Some(42),
+            username: Some("nova_fan".to_string()),
+            first_name: Some("Ada".to_string()),
+            group_name: Some("Nova (HQ)".to_string()),
+            trigger: Some("gm".to_string()),
+            timeout_minutes: Some(5),
+            member_count: Some(1234),
+            date: chrono::DateTime::from_timestamp(1_760_745_600, 0),
+        }
+    }
+
+    #[test]
+    fn renders_plain_variables() {
+        let result = render_template(
+            "{username} {first_name} {user_id} {group_name} {trigger} {timeout} {member_count} {date}",
+            &ctx(),
+            TemplateFormat::Plain,
+        );
+        assert_eq!(result, "@nova_fan Ada 42 Nova (HQ) gm 5 1234 2025-10-18");
+    }
+
+    #[test]
+    fn escapes_values_for_markdown_v2() {
+        let result = render_template(
+            "*{group_name}* welcomes {mention} ({username})",
+            &ctx(),
+            TemplateFormat::MarkdownV2,
+        );
+        assert_eq!(
+            result,
+            r"*Nova \(HQ\)* welcomes [Ada](tg://user?id=42) ([@nova\_fan](tg://user?id=42))"
+        );
+    }
+
+    #[test]
+    fn escapes_values_for_html() {
+        let mut context = ctx();
+        context.first_name = Some("<Ada & co>".to_string());
+
+        let result = render_template("Hi {mention}, {first_name}", &context, TemplateFormat::Html);
+        assert_eq!(
+            result,
+            "Hi <a href=\"tg://user?id=42\">&lt;Ada &amp; co&gt;</a>, &lt;Ada &amp; co&gt;"
+        );
+    }
+
+    #[test]
+    fn username_falls_back_to_first_name_then_user() {
+        let mut context = ctx();
+        context.username = None;
+        assert_eq!(
+            render_template("{username}", &context, TemplateFormat::Plain),
+            "Ada"
+        );
+
+        let anonymous = TemplateContext::default();
+        assert_eq!(
+            render_template("{username}", &anonymous, TemplateFormat::MarkdownV2),
+            "User"
+        );
+    }
+
+    #[test]
+    fn conditionals_check_presence() {
+        let template =
+            "{if username}Hi @{first_name}{else}Please set a username, {first_name}{end}!";
+        let mut context = ctx();
+        assert_eq!(
+            render_template(template, &context, TemplateFormat::Plain),
+            "Hi @Ada!"
+        );
+
+        context.username = None;
+        assert_eq!(
+            render_template(template, &context, TemplateFormat::Plain),
+            "Please set a username, Ada!"
+        );
+
+        assert_eq!(
+            render_template(
+                "{if not username}no handle{endif}",
+                &context,
+                TemplateFormat::Plain
+            ),
+            "no handle"
+        );
+    }
+
+    #[test]
+    fn nested_conditionals() {
+        let template = "{if username}{if member_count}#{member_count}{else}new{end}{end}";
+        assert_eq!(
+            render_template(template, &ctx(), TemplateFormat::Plain),
+            "#1234"
+        );
+    }
+
+    #[test]
+    fn random_choice_picks_one_option() {
+        let mut rng = StdRng::seed_from_u64(7);
+        let options = ["Hi Ada", "Hello Ada", "Hey Ada"];
+
+        for _ in 0..20 {
+            let result = render_template_with_rng(
+                "{Hi|Hello|Hey} {first_name}",
+                &ctx(),
+                TemplateFormat::Plain,
+                &mut rng,
+            );
+            assert!(options.contains(&result.as_str()), "unexpected {}", result);
+        }
+    }
+
+    #[test]
+    fn choice_options_can_hold_placeholders() {
+        let mut rng = StdRng::seed_from_u64(1);
+        let result = render_template_with_rng(
+            "{{first_name}|{first_name}}",
+            &ctx(),
+            TemplateFormat::MarkdownV2,
+            &mut rng,
+        );
+        assert_eq!(result, "Ada");
+    }
+
+    #[test]
+    fn unknown_tags_are_kept_and_escaped() {
+        assert_eq!(
+            render_template("{unknown} {x", &ctx(), TemplateFormat::Plain),
+            "{unknown} {x"
+        );
+        assert_eq!(
+            render_template("{unknown} {end}", &ctx(), TemplateFormat::MarkdownV2),
+            r"\{unknown\} \{end\}"
+        );
+        assert_eq!(
+            render_template(r"already \{escaped\}", &ctx(), TemplateFormat::MarkdownV2),
+            r"already \{escaped\}"
+        );
+    }
+
+    #[test]
+    fn detects_used_variables() {
+        assert!(template_uses(
+            "{if member_count}We are {member_count}{end}",
+            "member_count"
+        ));
+        assert!(template_uses("{a|{member_count}}", "member_count"));
+        assert!(!template_uses("{username}", "member_count"));
+    }
+}
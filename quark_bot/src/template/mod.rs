@@ -0,0 +1,5 @@
+pub mod dto;
+pub mod engine;
+
+pub use dto::{TemplateContext, TemplateFormat};
+pub use engine::{render_template, template_uses};
@@ -0,0 +1,717 @@
+use std::collections::BTreeMap;
+
+use anyhow::Result;
+use chrono::Utc;
+use quark_core::helpers::dto::PayUsersRequest;
+use teloxide::{
+    prelude::*,
+    types::{
+        InlineKeyboardButton, InlineKeyboardMarkup, MaybeInaccessibleMessage, MessageId, ParseMode,
+        User,
+    },
+    utils::html,
+};
+
+use crate::{
+    dao::dto::PayoutStatus,
+    dependencies::BotDependencies,
+    pending_transactions::dto::PendingTransaction,
+    treasury::dto::{
+        APPROVAL_WINDOW_SECS, AdminApproval, ApprovalOutcome, ApprovalPolicy, ApprovalStatus,
+        PaymentSource, TreasuryAction, TreasuryPayment, TreasuryRequest, TreasuryTransfer,
+    },
+    utils::{self, format_timestamp},
+};
+
+fn admin_approval(user: &User) -> AdminApproval {
+    AdminApproval {
+        user_id: user.id.0,
+        name: user
+            .username
+            .clone()
+            .map(|username| format!("@{}", username))
+            .unwrap_or_else(|| user.first_name.clone()),
+        at: Utc::now().timestamp() as u64,
+    }
+}
+
+/// USD value of `amount` smallest units of `coin_type` at the Panora price.
+async fn usd_value(bot_deps: &BotDependencies, coin_type: &str, amount: u64) -> Option<f64> {
+    let token = bot_deps.panora.get_token_by_address(coin_type).await.ok()?;
+    let price = token.usd_price?.parse::<f64>().ok()?;
+
+    Some(amount as f64 / 10_f64.powi(token.decimals as i32) * price)
+}
+
+/// The group's policy and the payment's USD value when the payment needs
+/// approvals, or None when it can be sent right away.
+pub async fn approval_gate(
+    bot_deps: &BotDependencies,
+    chat_id: ChatId,
+    payment: &TreasuryPayment,
+) -> Option<(ApprovalPolicy, Option<f64>)> {
+    let policy = bot_deps.treasury.get_policy(chat_id.0)?;
+    let usd_value = usd_value(bot_deps, &payment.coin_type, payment.unpaid_amount()).await;
+
+    policy.applies(usd_value).then_some((policy, usd_value))
+}
+
+/// Treasury payment of a confirmed `/g` transfer. The contract splits the
+/// amount evenly, so every recipient gets the same share.
+pub async fn ad_hoc_payment(
+    bot_deps: &BotDependencies,
+    transaction: &PendingTransaction,
+) -> TreasuryPayment {
+    let decimals = bot_deps
+        .panora
+        .get_token_by_address(&transaction.coin_type)
+        .await
+        .map(|token| token.decimals)
+        .unwrap_or(8);
+    let share = transaction.amount / transaction.user_addresses.len().max(1) as u64;
+
+    TreasuryPayment {
+        source: PaymentSource::AdHoc,
+        symbol: transaction.symbol.clone(),
+        coin_type: transaction.coin_type.clone(),
+        version: transaction.version.clone(),
+        decimals,
+        transfers: transaction
+            .user_addresses
+            .iter()
+            .enumerate()
+            .map(|(index, address)| TreasuryTransfer {
+                username: transaction
+                    .original_usernames
+                    .get(index)
+                    .cloned()
+                    .unwrap_or_else(|| address.clone()),
+                address: address.clone(),
+                amount: share,
+                transaction_hash: None,
+            })
+            .collect(),
+    }
+}
+
+fn source_line(source: &PaymentSource) -> String {
+    match source {
+        PaymentSource::AdHoc => "💬 Payment requested with /g".to_string(),
+        PaymentSource::Scheduled { schedule_id } => format!(
+            "🗓️ Scheduled payment <code>{}</code>",
+            html::escape(schedule_id)
+        ),
+        PaymentSource::DaoPayout { proposal_name, .. } => {
+            format!("🏛️ Payout of <b>{}</b>", html::escape(proposal_name))
+        }
+    }
+}
+
+fn policy_change_line(policy: Option<&ApprovalPolicy>) -> String {
+    match policy {
+        Some(policy) => format!(
+            "⚙️ New policy: payments worth ${:.0} or more need {} admins",
+            policy.usd_threshold, policy.required_approvals
+        ),
+        None => "⚙️ Turn treasury approvals off".to_string(),
+    }
+}
+
+fn transfer_lines(payment: &TreasuryPayment) -> String {
+    payment
+        .transfers
+        .iter()
+        .map(|transfer| {
+            format!(
+                "• @{}: {:.2} {}{}",
+                html::escape(&transfer.username),
+                transfer.amount as f64 / 10_f64.powi(payment.decimals as i32),
+                html::escape(&payment.symbol),
+                if transfer.transaction_hash.is_some() {
+                    " ✅"
+                } else {
+                    ""
+                }
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// One explorer link per transaction, with the recipients it paid.
+fn transaction_lines(payment: &TreasuryPayment) -> String {
+    let network = std::env::var("APTOS_NETWORK")
+        .unwrap_or("mainnet".to_string())
+        .to_lowercase();
+
+    let mut transactions: Vec<(&str, Vec<String>)> = Vec::new();
+    for transfer in &payment.transfers {
+        let Some(hash) = transfer.transaction_hash.as_deref() else {
+            continue;
+        };
+        let recipient = format!("@{}", html::escape(&transfer.username));
+        match transactions
+            .iter_mut()
+            .find(|(existing, _)| *existing == hash)
+        {
+            Some((_, recipients)) => recipients.push(recipient),
+            None => transactions.push((hash, vec![recipient])),
+        }
+    }
+
+    transactions
+        .into_iter()
+        .map(|(hash, recipients)| {
+            format!(
+                "🔗 <a href=\"https://explorer.aptoslabs.com/txn/{}?network={}\">View transaction</a> ({})",
+                hash,
+                network,
+                recipients.join(", ")
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+pub fn request_text(request: &TreasuryRequest) -> String {
+    let payment = match &request.action {
+        TreasuryAction::Payment(payment) => Some(payment),
+        TreasuryAction::ChangePolicy(_) => None,
+    };
+
+    let title = match (&request.status, payment.is_some()) {
+        (ApprovalStatus::Pending, _) => "🔐 <b>Treasury approval needed</b>",
+        (ApprovalStatus::Executing, true) => "💸 <b>Sending treasury payment...</b>",
+        (ApprovalStatus::Executing, false) => "⚙️ <b>Changing treasury policy...</b>",
+        (ApprovalStatus::Executed, true) => "✅ <b>Treasury payment sent</b>",
+        (ApprovalStatus::Executed, false) => "✅ <b>Treasury policy changed</b>",
+        (ApprovalStatus::Failed, true) => "⚠️ <b>Treasury payment incomplete</b>",
+        (ApprovalStatus::Failed, false) => "⚠️ <b>Treasury policy change failed</b>",
+        (ApprovalStatus::Rejected, true) => "🚫 <b>Treasury payment rejected</b>",
+        (ApprovalStatus::Rejected, false) => "🚫 <b>Treasury policy change rejected</b>",
+        (ApprovalStatus::Expired, _) => "⏰ <b>Treasury approval expired</b>",
+    };
+
+    let summary = match &request.action {
+        TreasuryAction::Payment(payment) => format!(
+            "{}\n\n{}\n{}",
+            source_line(&payment.source),
+            transfer_lines(payment),
+            match request.usd_value {
+                Some(usd) => format!("💵 ≈ ${:.2}", usd),
+                None => "💵 USD value unknown".to_string(),
+            }
+        ),
+        TreasuryAction::ChangePolicy(policy) => policy_change_line(policy.as_ref()),
+    };
+    let unchanged = if payment.is_some() {
+        "Nothing was sent."
+    } else {
+        "The policy was not changed."
+    };
+
+    let approvals = request
+        .approvals
+        .iter()
+        .map(|approval| {
+            format!(
+                "• {} — {}",
+                html::escape(&approval.name),
+                format_timestamp(approval.at)
+            )
+        })
+        .collect::<Vec<_>>();
+
+    let mut text = format!(
+        "{}\n\n{}\n\n<b>Approvals ({}/{}):</b>\n{}",
+        title,
+        summary,
+        request.approvals.len(),
+        request.required_approvals,
+        if approvals.is_empty() {
+            "—".to_string()
+        } else {
+            approvals.join("\n")
+        }
+    );
+
+    match request.status {
+        ApprovalStatus::Pending => text.push_str(&format!(
+            "\n\n⏰ Admins can approve until {}.",
+            format_timestamp(request.expires_at)
+        )),
+        ApprovalStatus::Executed => match payment {
+            Some(payment) => text.push_str(&format!("\n\n{}", transaction_lines(payment))),
+            None => text.push_str("\n\nThe new policy applies from now on."),
+        },
+        ApprovalStatus::Failed => match payment {
+            Some(payment) => {
+                let sent = transaction_lines(payment);
+                if !sent.is_empty() {
+                    text.push_str(&format!("\n\n{}", sent));
+                }
+                text.push_str("\n\nRetrying only pays the recipients without a ✅.");
+            }
+            None => text.push_str("\n\nThe policy couldn't be saved. Retry to apply it."),
+        },
+        ApprovalStatus::Rejected => {
+            if let Some(admin) = &request.rejected_by {
+                text.push_str(&format!(
+                    "\n\nRejected by {} — {}. {}",
+                    html::escape(&admin.name),
+                    format_timestamp(admin.at),
+                    unchanged
+                ));
+            }
+        }
+        ApprovalStatus::Expired => text.push_str(&format!(
+            "\n\nNot enough admins approved in time. {}",
+            unchanged
+        )),
+        ApprovalStatus::Executing => {}
+    }
+
+    text
+}
+
+fn request_keyboard(request: &TreasuryRequest) -> Option<InlineKeyboardMarkup> {
+    match request.status {
+        ApprovalStatus::Pending => Some(InlineKeyboardMarkup::new(vec![vec![
+            InlineKeyboardButton::callback("✅ Approve", format!("trsy_ok:{}", request.request_id)),
+            InlineKeyboardButton::callback("🚫 Reject", format!("trsy_no:{}", request.request_id)),
+        ]])),
+        ApprovalStatus::Failed => Some(InlineKeyboardMarkup::new(vec![vec![
+            InlineKeyboardButton::callback(
+                "🔁 Retry",
+                format!("trsy_retry:{}", request.request_id),
+            ),
+        ]])),
+        _ => None,
+    }
+}
+
+/// Show the request's current state in its group message.
+async fn refresh_message(bot: &Bot, request: &TreasuryRequest) {
+    let mut edit = bot
+        .edit_message_text(
+            ChatId(request.chat_id),
+            MessageId(request.message_id),
+            request_text(request),
+        )
+        .parse_mode(ParseMode::Html);
+
+    if let Some(keyboard) = request_keyboard(request) {
+        edit = edit.reply_markup(keyboard);
+    }
+
+    if let Err(e) = edit.await {
+        log::warn!(
+            "Failed to update treasury request {} message: {}",
+            request.request_id,
+            e
+        );
+    }
+}
+
+/// Carry the outcome of a request back to the schedule or proposal payout it
+/// came from.
+fn settle_source(bot_deps: &BotDependencies, request: &TreasuryRequest, error: Option<&str>) {
+    let TreasuryAction::Payment(payment) = &request.action else {
+        return;
+    };
+
+    match &payment.source {
+        PaymentSource::AdHoc => {}
+        PaymentSource::DaoPayout { proposal_id, .. } => {
+            let mut payout = match bot_deps.dao.get_payout(proposal_id) {
+                Ok(Some(payout)) => payout,
+                Ok(None) => return,
+                Err(e) => {
+                    log::error!("Failed to get payout of proposal {}: {}", proposal_id, e);
+                    return;
+                }
+            };
+
+            payout.status = match request.status {
+                ApprovalStatus::Executed => PayoutStatus::Paid,
+                ApprovalStatus::Failed => PayoutStatus::Failed,
+                ApprovalStatus::Rejected | ApprovalStatus::Expired => PayoutStatus::Declined,
+                ApprovalStatus::Pending | ApprovalStatus::Executing => return,
+            };
+
+            for (recipient, transfer) in payout.action.recipients.iter_mut().zip(&payment.transfers)
+            {
+                if transfer.transaction_hash.is_some() {
+                    recipient.transaction_hash = transfer.transaction_hash.clone();
+                }
+            }
+
+            if let Err(e) = bot_deps.dao.save_payout(&payout) {
+                log::error!("Failed to save payout of proposal {}: {}", proposal_id, e);
+            }
+        }
+        PaymentSource::Scheduled { schedule_id } => {
+            let Some(mut rec) = bot_deps.scheduled_payments.get_schedule(schedule_id) else {
+                return;
+            };
+
+            let paid_any = payment
+                .transfers
+                .iter()
+                .any(|transfer| transfer.transaction_hash.is_some());
+
+            let status = match request.status {
+                ApprovalStatus::Executed => "success",
+                ApprovalStatus::Failed if paid_any => "partial",
+                ApprovalStatus::Failed => "failure",
+                ApprovalStatus::Rejected => "rejected",
+                ApprovalStatus::Expired => "expired",
+                ApprovalStatus::Pending | ApprovalStatus::Executing => return,
+            };
+
+            rec.last_attempt_status = Some(status.to_string());
+            rec.last_error = error.map(str::to_string);
+
+            if let Err(e) = bot_deps.scheduled_payments.put_schedule(&rec) {
+                log::warn!("Failed to update payment schedule {}: {}", schedule_id, e);
+            }
+        }
+    }
+}
+
+/// Send the unpaid transfers of an approved payment from the group wallet,
+/// one `pay_members` call per distinct amount. Returns the last error.
+async fn send_transfers(
+    bot_deps: &BotDependencies,
+    chat_id: i64,
+    request_id: &str,
+    payment: &mut TreasuryPayment,
+) -> Option<String> {
+    let Some(credentials) = bot_deps.group.get_credentials(ChatId(chat_id)) else {
+        return Some("Group not logged in".to_string());
+    };
+
+    let mut batches: BTreeMap<u64, Vec<usize>> = BTreeMap::new();
+    for (index, transfer) in payment.transfers.iter().enumerate() {
+        if transfer.transaction_hash.is_none() {
+            batches.entry(transfer.amount).or_default().push(index);
+        }
+    }
+
+    let mut error = None;
+
+    for (amount, indexes) in batches {
+        // The contract splits `amount` evenly between `users`
+        let pay_request = PayUsersRequest {
+            amount: amount * indexes.len() as u64,
+            users: indexes
+                .iter()
+                .map(|&index| payment.transfers[index].address.clone())
+                .collect(),
+            coin_type: payment.coin_type.clone(),
+            version: payment.version.clone(),
+        };
+
+        match bot_deps
+            .service
+            .pay_members(credentials.jwt.clone(), pay_request)
+            .await
+        {
+            Ok(response) => {
+                for index in indexes {
+                    payment.transfers[index].transaction_hash = Some(response.hash.clone());
+                }
+            }
+            Err(e) => {
+                log::error!("Failed to send treasury request {}: {}", request_id, e);
+                error = Some(e.to_string());
+            }
+        }
+    }
+
+    error
+}
+
+/// Carry out an approved request: send the payment or apply the new policy.
+async fn execute_request(
+    bot: &Bot,
+    bot_deps: &BotDependencies,
+    mut request: TreasuryRequest,
+) -> Result<()> {
+    refresh_message(bot, &request).await;
+
+    let error = match &mut request.action {
+        TreasuryAction::Payment(payment) => {
+            send_transfers(bot_deps, request.chat_id, &request.request_id, payment).await
+        }
+        TreasuryAction::ChangePolicy(policy) => {
+            let saved = match policy {
+                Some(policy) => bot_deps.treasury.set_policy(request.chat_id, policy),
+                None => bot_deps.treasury.remove_policy(request.chat_id),
+            };
+            saved.err().map(|e| {
+                log::error!(
+                    "Failed to apply treasury request {}: {}",
+                    request.request_id,
+                    e
+                );
+                e.to_string()
+            })
+        }
+    };
+
+    request.status = if error.is_none() {
+        ApprovalStatus::Executed
+    } else {
+        ApprovalStatus::Failed
+    };
+    bot_deps.treasury.save_request(&request)?;
+
+    settle_source(bot_deps, &request, error.as_deref());
+    refresh_message(bot, &request).await;
+
+    Ok(())
+}
+
+fn new_request(
+    chat_id: ChatId,
+    action: TreasuryAction,
+    usd_value: Option<f64>,
+    required_approvals: u8,
+) -> TreasuryRequest {
+    let now = Utc::now().timestamp() as u64;
+
+    TreasuryRequest {
+        request_id: uuid::Uuid::new_v4().simple().to_string(),
+        chat_id: chat_id.0,
+        message_id: 0,
+        action,
+        usd_value,
+        required_approvals,
+        approvals: Vec::new(),
+        rejected_by: None,
+        status: ApprovalStatus::Pending,
+        created_at: now,
+        expires_at: now + APPROVAL_WINDOW_SECS,
+    }
+}
+
+/// Count the requester's approval, show the request and save it. The request
+/// replaces the message `message_id` when given, otherwise it's posted to
+/// the group.
+async fn open_request(
+    bot: &Bot,
+    bot_deps: &BotDependencies,
+    mut request: TreasuryRequest,
+    requester: Option<&User>,
+    message_id: Option<MessageId>,
+) -> Result<()> {
+    if let Some(user) = requester {
+        request.approve(admin_approval(user));
+    }
+
+    let chat_id = ChatId(request.chat_id);
+    let text = request_text(&request);
+    let keyboard = request_keyboard(&request).unwrap_or_default();
+
+    let message = match message_id {
+        Some(message_id) => {
+            bot.edit_message_text(chat_id, message_id, text)
+                .parse_mode(ParseMode::Html)
+                .reply_markup(keyboard)
+                .await?
+        }
+        None => {
+            bot.send_message(chat_id, text)
+                .parse_mode(ParseMode::Html)
+                .reply_markup(keyboard)
+                .await?
+        }
+    };
+
+    request.message_id = message.id.0;
+    bot_deps.treasury.save_request(&request)?;
+
+    if request.status == ApprovalStatus::Executing {
+        execute_request(bot, bot_deps, request).await?;
+    }
+
+    Ok(())
+}
+
+/// Hold `payment` until the group's policy is met. The request replaces the
+/// message `message_id` when given, otherwise it's posted to the group.
+/// `requester` is the admin who asked for the payment and counts as the
+/// first approval.
+pub async fn request_approval(
+    bot: &Bot,
+    bot_deps: &BotDependencies,
+    chat_id: ChatId,
+    payment: TreasuryPayment,
+    (policy, usd_value): (ApprovalPolicy, Option<f64>),
+    requester: Option<&User>,
+    message_id: Option<MessageId>,
+) -> Result<()> {
+    let request = new_request(
+        chat_id,
+        TreasuryAction::Payment(payment),
+        usd_value,
+        policy.required_approvals,
+    );
+
+    open_request(bot, bot_deps, request, requester, message_id).await
+}
+
+/// Post a request to loosen the group's policy to `policy`. It needs as many
+/// admins as the `current` policy, so one admin can't lift it alone.
+pub async fn request_policy_change(
+    bot: &Bot,
+    bot_deps: &BotDependencies,
+    chat_id: ChatId,
+    current: &ApprovalPolicy,
+    policy: Option<ApprovalPolicy>,
+    requester: &User,
+) -> Result<()> {
+    let request = new_request(
+        chat_id,
+        TreasuryAction::ChangePolicy(policy),
+        None,
+        current.required_approvals,
+    );
+
+    open_request(bot, bot_deps, request, Some(requester), None).await
+}
+
+/// Close the requests nobody finished approving in time.
+pub async fn expire_requests(bot: &Bot, bot_deps: &BotDependencies) -> Result<()> {
+    let now = Utc::now().timestamp() as u64;
+
+    for request in bot_deps.treasury.expire_due(now)? {
+        log::info!("Treasury request {} expired", request.request_id);
+        settle_source(bot_deps, &request, None);
+        refresh_message(bot, &request).await;
+    }
+
+    Ok(())
+}
+
+/// Admin answers to a treasury request (`trsy_ok`, `trsy_no` and `trsy_retry`
+/// followed by `:{request_id}`).
+pub async fn handle_treasury_approval_callback(
+    bot: Bot,
+    query: CallbackQuery,
+    bot_deps: BotDependencies,
+) -> Result<()> {
+    let data = query.data.clone().unwrap_or_default();
+    let msg = match &query.message {
+        Some(MaybeInaccessibleMessage::Regular(message)) => message.clone(),
+        _ => return Ok(()),
+    };
+
+    let Some((action, request_id)) = data.split_once(':') else {
+        bot.answer_callback_query(query.id).await?;
+        return Ok(());
+    };
+
+    let in_this_group = bot_deps
+        .treasury
+        .get_request(request_id)
+        .is_some_and(|request| request.chat_id == msg.chat.id.0);
+
+    if !in_this_group {
+        bot.answer_callback_query(query.id)
+            .text("❌ Treasury request not found")
+            .await?;
+        return Ok(());
+    }
+
+    if !utils::is_admin(&bot, msg.chat.id, query.from.id).await {
+        bot.answer_callback_query(query.id)
+            .text("❌ Only group admins can answer treasury requests")
+            .await?;
+        return Ok(());
+    }
+
+    let admin = admin_approval(&query.from);
+
+    match action {
+        "trsy_ok" => {
+            let Some((outcome, request)) = bot_deps.treasury.approve(request_id, admin)? else {
+                bot.answer_callback_query(query.id).await?;
+                return Ok(());
+            };
+
+            match outcome {
+                ApprovalOutcome::Recorded => {
+                    refresh_message(&bot, &request).await;
+                    bot.answer_callback_query(query.id)
+                        .text(format!(
+                            "✅ Approval recorded ({}/{})",
+                            request.approvals.len(),
+                            request.required_approvals
+                        ))
+                        .await?;
+                }
+                ApprovalOutcome::Ready => {
+                    let text = match request.action {
+                        TreasuryAction::Payment(_) => "💸 Approved, sending payment...",
+                        TreasuryAction::ChangePolicy(_) => "⚙️ Approved, changing the policy...",
+                    };
+                    bot.answer_callback_query(query.id).text(text).await?;
+                    execute_request(&bot, &bot_deps, request).await?;
+                }
+                ApprovalOutcome::Duplicate => {
+                    bot.answer_callback_query(query.id)
+                        .text("ℹ️ You already approved this request")
+                        .await?;
+                }
+                ApprovalOutcome::Expired => {
+                    settle_source(&bot_deps, &request, None);
+                    refresh_message(&bot, &request).await;
+                    bot.answer_callback_query(query.id)
+                        .text("⏰ This request has expired")
+                        .await?;
+                }
+                ApprovalOutcome::Closed => {
+                    bot.answer_callback_query(query.id)
+                        .text("ℹ️ This request was already handled")
+                        .await?;
+                }
+            }
+        }
+        "trsy_no" => match bot_deps.treasury.reject(request_id, admin)? {
+            Some(request) => {
+                settle_source(&bot_deps, &request, None);
+                refresh_message(&bot, &request).await;
+                bot.answer_callback_query(query.id)
+                    .text("🚫 Request rejected")
+                    .await?;
+            }
+            None => {
+                bot.answer_callback_query(query.id)
+                    .text("ℹ️ This request was already handled")
+                    .await?;
+            }
+        },
+        "trsy_retry" => match bot_deps.treasury.claim_retry(request_id)? {
+            Some(request) => {
+                bot.answer_callback_query(query.id)
+                    .text("🔁 Retrying...")
+                    .await?;
+                execute_request(&bot, &bot_deps, request).await?;
+            }
+            None => {
+                bot.answer_callback_query(query.id)
+                    .text("ℹ️ This request was already handled")
+                    .await?;
+            }
+        },
+        _ => {
+            bot.answer_callback_query(query.id)
+                .text("❌ Unknown action")
+                .await?;
+        }
+    }
+
+    Ok(())
+}
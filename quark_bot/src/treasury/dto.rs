@@ -0,0 +1,255 @@
+use quark_core::helpers::dto::CoinVersion;
+use serde::{Deserialize, Serialize};
+
+/// How long admins have to approve a treasury payment.
+pub const APPROVAL_WINDOW_SECS: u64 = 24 * 60 * 60;
+
+/// Per-group rule for payments from the group wallet.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct ApprovalPolicy {
+    /// Payments worth at least this many USD need approvals.
+    pub usd_threshold: f64,
+    /// Distinct admins that must approve, including the one who asked for it.
+    pub required_approvals: u8,
+}
+
+impl ApprovalPolicy {
+    /// Payments without a known USD price always need approvals.
+    pub fn applies(&self, usd_value: Option<f64>) -> bool {
+        usd_value.is_none_or(|usd| usd >= self.usd_threshold)
+    }
+
+    /// Whether moving to `new` lets more payments through with fewer admins.
+    /// `None` turns approvals off.
+    pub fn is_loosened_by(&self, new: Option<&ApprovalPolicy>) -> bool {
+        new.is_none_or(|new| {
+            new.usd_threshold > self.usd_threshold
+                || new.required_approvals < self.required_approvals
+        })
+    }
+}
+
+/// Where a treasury payment came from.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub enum PaymentSource {
+    /// `/g` with the get_pay_users tool.
+    AdHoc,
+    Scheduled {
+        schedule_id: String,
+    },
+    DaoPayout {
+        proposal_id: String,
+        proposal_name: String,
+    },
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct TreasuryTransfer {
+    pub username: String,
+    pub address: String,
+    /// Smallest units.
+    pub amount: u64,
+    pub transaction_hash: Option<String>,
+}
+
+/// What would leave the group wallet.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct TreasuryPayment {
+    pub source: PaymentSource,
+    pub symbol: String,
+    pub coin_type: String,
+    pub version: CoinVersion,
+    pub decimals: u8,
+    pub transfers: Vec<TreasuryTransfer>,
+}
+
+impl TreasuryPayment {
+    /// Smallest units still to be sent.
+    pub fn unpaid_amount(&self) -> u64 {
+        self.transfers
+            .iter()
+            .filter(|transfer| transfer.transaction_hash.is_none())
+            .map(|transfer| transfer.amount)
+            .sum()
+    }
+}
+
+/// What an approved request does.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub enum TreasuryAction {
+    Payment(TreasuryPayment),
+    /// Loosen the group's policy; `None` turns approvals off.
+    ChangePolicy(Option<ApprovalPolicy>),
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub enum ApprovalStatus {
+    Pending,
+    Executing,
+    Executed,
+    /// Some transfers failed or the policy couldn't be saved; an admin can retry.
+    Failed,
+    Rejected,
+    Expired,
+}
+
+/// One admin's answer, kept as the request's audit trail.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct AdminApproval {
+    pub user_id: u64,
+    pub name: String,
+    pub at: u64,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum ApprovalOutcome {
+    /// Counted, more approvals are needed.
+    Recorded,
+    /// Enough admins approved; the request moved to `Executing`.
+    Ready,
+    Duplicate,
+    Expired,
+    /// Already executed, rejected or expired.
+    Closed,
+}
+
+/// Group wallet payment or policy change waiting for `required_approvals` admins.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct TreasuryRequest {
+    pub request_id: String,
+    pub chat_id: i64,
+    pub message_id: i32,
+    pub action: TreasuryAction,
+    pub usd_value: Option<f64>,
+    pub required_approvals: u8,
+    pub approvals: Vec<AdminApproval>,
+    pub rejected_by: Option<AdminApproval>,
+    pub status: ApprovalStatus,
+    pub created_at: u64,
+    pub expires_at: u64,
+}
+
+impl TreasuryRequest {
+    pub fn is_approved(&self) -> bool {
+        self.approvals.len() >= self.required_approvals as usize
+    }
+
+    pub fn approve(&mut self, approval: AdminApproval) -> ApprovalOutcome {
+        if self.status != ApprovalStatus::Pending {
+            return ApprovalOutcome::Closed;
+        }
+
+        if approval.at > self.expires_at {
+            self.status = ApprovalStatus::Expired;
+            return ApprovalOutcome::Expired;
+        }
+
+        if self
+            .approvals
+            .iter()
+            .any(|existing| existing.user_id == approval.user_id)
+        {
+            return ApprovalOutcome::Duplicate;
+        }
+
+        self.approvals.push(approval);
+
+        if self.is_approved() {
+            self.status = ApprovalStatus::Executing;
+            ApprovalOutcome::Ready
+        } else {
+            ApprovalOutcome::Recorded
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn request(required_approvals: u8) -> TreasuryRequest {
+        TreasuryRequest {
+            request_id: "r1".to_string(),
+            chat_id: -100,
+            message_id: 1,
+            action: TreasuryAction::Payment(TreasuryPayment {
+                source: PaymentSource::AdHoc,
+                symbol: "APT".to_string(),
+                coin_type: "0x1::aptos_coin::AptosCoin".to_string(),
+                version: CoinVersion::V1,
+                decimals: 8,
+                transfers: Vec::new(),
+            }),
+            usd_value: Some(2_500.0),
+            required_approvals,
+            approvals: Vec::new(),
+            rejected_by: None,
+            status: ApprovalStatus::Pending,
+            created_at: 1_000,
+            expires_at: 1_000 + APPROVAL_WINDOW_SECS,
+        }
+    }
+
+    fn admin(user_id: u64, at: u64) -> AdminApproval {
+        AdminApproval {
+            user_id,
+            name: format!("admin{}", user_id),
+            at,
+        }
+    }
+
+    #[test]
+    fn needs_distinct_admins() {
+        let mut request = request(2);
+
+        assert_eq!(request.approve(admin(1, 1_100)), ApprovalOutcome::Recorded);
+        assert_eq!(request.approve(admin(1, 1_200)), ApprovalOutcome::Duplicate);
+        assert_eq!(request.approve(admin(2, 1_300)), ApprovalOutcome::Ready);
+        assert_eq!(request.status, ApprovalStatus::Executing);
+        assert_eq!(request.approve(admin(3, 1_400)), ApprovalOutcome::Closed);
+        assert_eq!(request.approvals.len(), 2);
+    }
+
+    #[test]
+    fn late_approvals_expire_the_request() {
+        let mut request = request(2);
+        let late = request.expires_at + 1;
+
+        assert_eq!(request.approve(admin(1, late)), ApprovalOutcome::Expired);
+        assert_eq!(request.status, ApprovalStatus::Expired);
+        assert!(request.approvals.is_empty());
+    }
+
+    #[test]
+    fn policy_applies_from_the_threshold_or_without_a_price() {
+        let policy = ApprovalPolicy {
+            usd_threshold: 1_000.0,
+            required_approvals: 2,
+        };
+
+        assert!(!policy.applies(Some(999.99)));
+        assert!(policy.applies(Some(1_000.0)));
+        assert!(policy.applies(None));
+    }
+
+    #[test]
+    fn turning_off_raising_the_threshold_or_dropping_admins_loosens() {
+        let policy = ApprovalPolicy {
+            usd_threshold: 1_000.0,
+            required_approvals: 3,
+        };
+        let with = |usd_threshold: f64, required_approvals: u8| ApprovalPolicy {
+            usd_threshold,
+            required_approvals,
+        };
+
+        assert!(policy.is_loosened_by(None));
+        assert!(policy.is_loosened_by(Some(&with(5_000.0, 3))));
+        assert!(policy.is_loosened_by(Some(&with(1_000.0, 2))));
+        assert!(policy.is_loosened_by(Some(&with(500.0, 2))));
+
+        assert!(!policy.is_loosened_by(Some(&policy)));
+        assert!(!policy.is_loosened_by(Some(&with(500.0, 3))));
+        assert!(!policy.is_loosened_by(Some(&with(1_000.0, 4))));
+    }
+}
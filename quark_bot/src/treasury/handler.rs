@@ -0,0 +1,174 @@
+use anyhow::Result;
+use sled::{Db, Tree};
+
+use super::dto::{AdminApproval, ApprovalOutcome, ApprovalPolicy, ApprovalStatus, TreasuryRequest};
+
+const POLICIES_TREE: &str = "treasury_policies";
+const REQUESTS_TREE: &str = "treasury_requests";
+
+#[derive(Clone)]
+pub struct TreasuryApprovals {
+    policies: Tree,
+    requests: Tree,
+}
+
+impl TreasuryApprovals {
+    pub fn new(db: &Db) -> sled::Result<Self> {
+        Ok(Self {
+            policies: db.open_tree(POLICIES_TREE)?,
+            requests: db.open_tree(REQUESTS_TREE)?,
+        })
+    }
+
+    pub fn get_policy(&self, chat_id: i64) -> Option<ApprovalPolicy> {
+        self.policies
+            .get(chat_id.to_string().as_bytes())
+            .ok()
+            .flatten()
+            .and_then(|value| serde_json::from_slice(&value).ok())
+    }
+
+    pub fn set_policy(&self, chat_id: i64, policy: &ApprovalPolicy) -> Result<()> {
+        self.policies
+            .insert(chat_id.to_string().as_bytes(), serde_json::to_vec(policy)?)?;
+
+        Ok(())
+    }
+
+    pub fn remove_policy(&self, chat_id: i64) -> Result<()> {
+        self.policies.remove(chat_id.to_string().as_bytes())?;
+
+        Ok(())
+    }
+
+    pub fn get_request(&self, request_id: &str) -> Option<TreasuryRequest> {
+        self.requests
+            .get(request_id.as_bytes())
+            .ok()
+            .flatten()
+            .and_then(|value| serde_json::from_slice(&value).ok())
+    }
+
+    pub fn save_request(&self, request: &TreasuryRequest) -> Result<()> {
+        self.requests
+            .insert(request.request_id.as_bytes(), serde_json::to_vec(request)?)?;
+
+        Ok(())
+    }
+
+    /// Apply `change` to a stored request with compare-and-swap, so admins
+    /// answering at the same time can't overwrite each other.
+    fn update<T>(
+        &self,
+        request_id: &str,
+        change: impl Fn(&mut TreasuryRequest) -> T,
+    ) -> Result<Option<(T, TreasuryRequest)>> {
+        loop {
+            let Some(current) = self.requests.get(request_id.as_bytes())? else {
+                return Ok(None);
+            };
+
+            let mut request: TreasuryRequest = serde_json::from_slice(&current)?;
+            let result = change(&mut request);
+
+            let swapped = self
+                .requests
+                .compare_and_swap(
+                    request_id.as_bytes(),
+                    Some(current),
+                    Some(serde_json::to_vec(&request)?),
+                )?
+                .is_ok();
+
+            if swapped {
+                return Ok(Some((result, request)));
+            }
+        }
+    }
+
+    pub fn approve(
+        &self,
+        request_id: &str,
+        approval: AdminApproval,
+    ) -> Result<Option<(ApprovalOutcome, TreasuryRequest)>> {
+        self.update(request_id, |request| request.approve(approval.clone()))
+    }
+
+    /// Reject a pending request. Returns None when it isn't pending anymore.
+    pub fn reject(
+        &self,
+        request_id: &str,
+        admin: AdminApproval,
+    ) -> Result<Option<TreasuryRequest>> {
+        let updated = self.update(request_id, |request| {
+            if request.status != ApprovalStatus::Pending {
+                return false;
+            }
+            request.status = ApprovalStatus::Rejected;
+            request.rejected_by = Some(admin.clone());
+            true
+        })?;
+
+        Ok(updated.and_then(|(rejected, request)| rejected.then_some(request)))
+    }
+
+    /// Claim a failed request for another attempt at its unpaid transfers.
+    pub fn claim_retry(&self, request_id: &str) -> Result<Option<TreasuryRequest>> {
+        let updated = self.update(request_id, |request| {
+            if request.status != ApprovalStatus::Failed {
+                return false;
+            }
+            request.status = ApprovalStatus::Executing;
+            true
+        })?;
+
+        Ok(updated.and_then(|(claimed, request)| claimed.then_some(request)))
+    }
+
+    /// Mark pending requests past their deadline as expired and return them.
+    pub fn expire_due(&self, now: u64) -> Result<Vec<TreasuryRequest>> {
+        let due = self
+            .requests
+            .iter()
+            .values()
+            .filter_map(|value| value.ok())
+            .filter_map(|value| serde_json::from_slice::<TreasuryRequest>(&value).ok())
+            .filter(|request| request.status == ApprovalStatus::Pending && request.expires_at < now)
+            .map(|request| request.request_id)
+            .collect::<Vec<_>>();
+
+        let mut expired = Vec::new();
+
+        for request_id in due {
+            let updated = self.update(&request_id, |request| {
+                if request.status != ApprovalStatus::Pending {
+                    return false;
+                }
+                request.status = ApprovalStatus::Expired;
+                true
+            })?;
+
+            if let Some((true, request)) = updated {
+                expired.push(request);
+            }
+        }
+
+        Ok(expired)
+    }
+
+    /// Latest requests of a group, newest first.
+    pub fn recent_requests(&self, chat_id: i64, limit: usize) -> Vec<TreasuryRequest> {
+        let mut requests = self
+            .requests
+            .iter()
+            .values()
+            .filter_map(|value| value.ok())
+            .filter_map(|value| serde_json::from_slice::<TreasuryRequest>(&value).ok())
+            .filter(|request| request.chat_id == chat_id)
+            .collect::<Vec<_>>();
+
+        requests.sort_by_key(|request| std::cmp::Reverse(request.created_at));
+        requests.truncate(limit);
+        requests
+    }
+}
@@ -0,0 +1,4 @@
+pub mod approvals;
+pub mod dto;
+pub mod handler;
+pub mod settings;
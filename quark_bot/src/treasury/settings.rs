@@ -0,0 +1,288 @@
+use anyhow::Result;
+use teloxide::{
+    prelude::*,
+    types::{InlineKeyboardButton, InlineKeyboardMarkup, MaybeInaccessibleMessage, ParseMode},
+    utils::html,
+};
+
+use crate::{
+    dependencies::BotDependencies,
+    treasury::{
+        approvals::request_policy_change,
+        dto::{ApprovalPolicy, ApprovalStatus, TreasuryAction},
+    },
+    utils::{self, format_timestamp},
+};
+
+const THRESHOLDS: [(u64, &str); 5] = [
+    (100, "$100"),
+    (500, "$500"),
+    (1_000, "$1K"),
+    (5_000, "$5K"),
+    (10_000, "$10K"),
+];
+const DEFAULT_THRESHOLD: f64 = 1_000.0;
+const DEFAULT_APPROVALS: u8 = 2;
+const MAX_APPROVALS: u8 = 5;
+const LOG_SIZE: usize = 10;
+
+fn policy_message(policy: Option<&ApprovalPolicy>) -> (String, InlineKeyboardMarkup) {
+    let status = match policy {
+        Some(policy) => format!(
+            "<b>On</b> — payments worth ${:.0} or more need {} admins",
+            policy.usd_threshold, policy.required_approvals
+        ),
+        None => "<b>Off</b> — any admin can send group funds alone".to_string(),
+    };
+
+    let text = format!(
+        "🔐 <b>Treasury Approvals</b>\n\nPayments from the group wallet above the threshold wait until enough admins approve them. This covers /g payments, scheduled payments and DAO payouts. The admin who asks for a payment counts as its first approval, and requests expire after 24 hours.\n\nStatus: {}\n\n💡 Payments of tokens without a USD price always need approvals.\n🔒 Stricter settings apply right away. Raising the threshold, needing fewer admins or turning approvals off must first be approved by as many admins as the current policy needs.",
+        status
+    );
+
+    let threshold_row = THRESHOLDS
+        .iter()
+        .map(|(usd, label)| {
+            let selected = policy.is_some_and(|policy| policy.usd_threshold == *usd as f64);
+            InlineKeyboardButton::callback(
+                if selected {
+                    format!("✅ {}", label)
+                } else {
+                    label.to_string()
+                },
+                format!("trsy_thr:{}", usd),
+            )
+        })
+        .collect();
+
+    let approvals_row = (2..=MAX_APPROVALS)
+        .map(|count| {
+            let selected = policy.is_some_and(|policy| policy.required_approvals == count);
+            InlineKeyboardButton::callback(
+                if selected {
+                    format!("✅ {} admins", count)
+                } else {
+                    format!("{} admins", count)
+                },
+                format!("trsy_req:{}", count),
+            )
+        })
+        .collect();
+
+    let mut rows = vec![threshold_row, approvals_row];
+
+    if policy.is_some() {
+        rows.push(vec![InlineKeyboardButton::callback(
+            "🚫 Turn off",
+            "trsy_off",
+        )]);
+    }
+
+    rows.push(vec![InlineKeyboardButton::callback(
+        "📜 Recent requests",
+        "trsy_log",
+    )]);
+    rows.push(vec![InlineKeyboardButton::callback(
+        "↩️ Back",
+        "open_group_payment_settings",
+    )]);
+
+    (text, InlineKeyboardMarkup::new(rows))
+}
+
+fn status_label(status: &ApprovalStatus) -> &'static str {
+    match status {
+        ApprovalStatus::Pending => "🔐 Waiting",
+        ApprovalStatus::Executing => "💸 Sending",
+        ApprovalStatus::Executed => "✅ Sent",
+        ApprovalStatus::Failed => "⚠️ Incomplete",
+        ApprovalStatus::Rejected => "🚫 Rejected",
+        ApprovalStatus::Expired => "⏰ Expired",
+    }
+}
+
+fn log_text(bot_deps: &BotDependencies, chat_id: ChatId) -> String {
+    let requests = bot_deps.treasury.recent_requests(chat_id.0, LOG_SIZE);
+
+    if requests.is_empty() {
+        return "📜 <b>Recent Treasury Requests</b>\n\nNo payment has needed approvals yet."
+            .to_string();
+    }
+
+    let entries = requests
+        .iter()
+        .map(|request| {
+            let summary = match &request.action {
+                TreasuryAction::Payment(payment) => format!(
+                    "{:.2} {} to {} recipient(s)",
+                    payment
+                        .transfers
+                        .iter()
+                        .map(|transfer| transfer.amount)
+                        .sum::<u64>() as f64
+                        / 10_f64.powi(payment.decimals as i32),
+                    html::escape(&payment.symbol),
+                    payment.transfers.len()
+                ),
+                TreasuryAction::ChangePolicy(Some(policy)) => format!(
+                    "policy change to ${:.0} with {} admins",
+                    policy.usd_threshold, policy.required_approvals
+                ),
+                TreasuryAction::ChangePolicy(None) => "turning approvals off".to_string(),
+            };
+            let approvers = request
+                .approvals
+                .iter()
+                .map(|approval| html::escape(&approval.name))
+                .collect::<Vec<_>>()
+                .join(", ");
+
+            let mut entry = format!(
+                "{} — {}\n🕒 {}\n👥 Approved by: {}",
+                status_label(&request.status),
+                summary,
+                format_timestamp(request.created_at),
+                if approvers.is_empty() {
+                    "—".to_string()
+                } else {
+                    approvers
+                }
+            );
+            if let Some(admin) = &request.rejected_by {
+                entry.push_str(&format!("\n🚫 Rejected by: {}", html::escape(&admin.name)));
+            }
+            entry
+        })
+        .collect::<Vec<_>>()
+        .join("\n\n");
+
+    format!("📜 <b>Recent Treasury Requests</b>\n\n{}", entries)
+}
+
+/// Group settings for treasury approvals (`trsy_policy`, `trsy_thr:{usd}`,
+/// `trsy_req:{count}`, `trsy_off` and `trsy_log`).
+pub async fn handle_treasury_settings_callback(
+    bot: Bot,
+    query: CallbackQuery,
+    bot_deps: BotDependencies,
+) -> Result<()> {
+    let data = query.data.clone().unwrap_or_default();
+    let msg = match &query.message {
+        Some(MaybeInaccessibleMessage::Regular(message)) => message.clone(),
+        _ => return Ok(()),
+    };
+
+    if !utils::is_admin(&bot, msg.chat.id, query.from.id).await {
+        bot.answer_callback_query(query.id)
+            .text("❌ Only administrators can manage treasury approvals")
+            .await?;
+        return Ok(());
+    }
+
+    let chat_id = msg.chat.id;
+    let current = bot_deps.treasury.get_policy(chat_id.0);
+
+    if data == "trsy_log" {
+        let keyboard = InlineKeyboardMarkup::new(vec![vec![InlineKeyboardButton::callback(
+            "↩️ Back",
+            "trsy_policy",
+        )]]);
+        bot.edit_message_text(chat_id, msg.id, log_text(&bot_deps, chat_id))
+            .parse_mode(ParseMode::Html)
+            .reply_markup(keyboard)
+            .await?;
+        bot.answer_callback_query(query.id).await?;
+        return Ok(());
+    }
+
+    let policy = if let Some(usd) = data.strip_prefix("trsy_thr:") {
+        let Ok(usd) = usd.parse::<u64>() else {
+            bot.answer_callback_query(query.id).await?;
+            return Ok(());
+        };
+        Some(ApprovalPolicy {
+            usd_threshold: usd as f64,
+            required_approvals: current
+                .as_ref()
+                .map(|policy| policy.required_approvals)
+                .unwrap_or(DEFAULT_APPROVALS),
+        })
+    } else if let Some(count) = data.strip_prefix("trsy_req:") {
+        let Some(count) = count
+            .parse::<u8>()
+            .ok()
+            .filter(|count| (2..=MAX_APPROVALS).contains(count))
+        else {
+            bot.answer_callback_query(query.id).await?;
+            return Ok(());
+        };
+
+        let admins = bot
+            .get_chat_administrators(chat_id)
+            .await?
+            .iter()
+            .filter(|admin| !admin.user.is_bot)
+            .count();
+
+        if admins < count as usize {
+            bot.answer_callback_query(query.id)
+                .text(format!(
+                    "❌ This group has {} admins, so {} approvals could never be reached",
+                    admins, count
+                ))
+                .show_alert(true)
+                .await?;
+            return Ok(());
+        }
+
+        Some(ApprovalPolicy {
+            usd_threshold: current
+                .as_ref()
+                .map(|policy| policy.usd_threshold)
+                .unwrap_or(DEFAULT_THRESHOLD),
+            required_approvals: count,
+        })
+    } else if data == "trsy_off" {
+        None
+    } else {
+        current.clone()
+    };
+
+    // Telegram refuses edits that leave the message unchanged
+    if policy == current && data != "trsy_policy" {
+        bot.answer_callback_query(query.id).await?;
+        return Ok(());
+    }
+
+    // Loosening waits for the admins the current policy requires
+    if let Some(current) = current
+        .as_ref()
+        .filter(|c| c.is_loosened_by(policy.as_ref()))
+    {
+        request_policy_change(&bot, &bot_deps, chat_id, current, policy, &query.from).await?;
+        bot.answer_callback_query(query.id)
+            .text(format!(
+                "🔐 Loosening the policy needs {} admins. Approval request posted.",
+                current.required_approvals
+            ))
+            .show_alert(true)
+            .await?;
+        return Ok(());
+    }
+
+    if policy != current {
+        match &policy {
+            Some(policy) => bot_deps.treasury.set_policy(chat_id.0, policy)?,
+            None => bot_deps.treasury.remove_policy(chat_id.0)?,
+        }
+    }
+
+    let (text, keyboard) = policy_message(policy.as_ref());
+    bot.edit_message_text(chat_id, msg.id, text)
+        .parse_mode(ParseMode::Html)
+        .reply_markup(keyboard)
+        .await?;
+    bot.answer_callback_query(query.id).await?;
+
+    Ok(())
+}
@@ -112,6 +112,22 @@ pub fn format_time_duration(seconds: u64) -> String {
     }
 }
 
+/// Quotes a value for a CSV cell, prefixing `'` to anything a spreadsheet would
+/// run as a formula
+pub fn csv_field(value: &str) -> String {
+    let value = if value.starts_with(['=', '+', '-', '@', '\t', '\r']) {
+        format!("'{}", value)
+    } else {
+        value.to_string()
+    };
+
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value
+    }
+}
+
 /// Get emoji icon based on file extension
 pub fn get_file_icon(filename: &str) -> &'static str {
     let extension = filename.split('.').last().unwrap_or("").to_lowercase();
@@ -397,7 +413,7 @@ pub fn sanitize_ai_html(input: &str) -> String {
 
 #[cfg(test)]
 mod tests {
-    use super::{sanitize_ai_html, unescape_markdown};
+    use super::{csv_field, sanitize_ai_html, unescape_markdown};
 
     #[test]
     fn strips_unsupported_tags() {
@@ -483,6 +499,15 @@ mod tests {
         assert_eq!(unescape_markdown(input), r"\%keep\!");
     }
 
+    #[test]
+    fn csv_field_defuses_formulas() {
+        assert_eq!(csv_field("=HYPERLINK(\"x\")"), "\"'=HYPERLINK(\"\"x\"\")\"");
+        assert_eq!(csv_field("+1, -1"), "\"'+1, -1\"");
+        assert_eq!(csv_field("@sum"), "'@sum");
+        assert_eq!(csv_field("\t=1+1"), "'\t=1+1");
+        assert_eq!(csv_field("\r=1+1"), "\"'\r=1+1\"");
+        assert_eq!(csv_field("Budget"), "Budget");
+    }
 }
 
 pub fn normalize_image_url_anchor(text: &str) -> String {
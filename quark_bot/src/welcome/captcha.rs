@@ -0,0 +1,349 @@
+use std::io::Write;
+
+use anyhow::Result;
+use flate2::{Compression, write::ZlibEncoder};
+use rand::prelude::*;
+
+use crate::welcome::dto::{CaptchaChallenge, CaptchaType, QuizQuestion};
+
+/// Number of answer buttons shown for button-based challenges.
+pub const CHALLENGE_OPTIONS: usize = 4;
+
+const EMOJI_POOL: &[(&str, &str)] = &[
+    ("dog", "🐶"),
+    ("cat", "🐱"),
+    ("apple", "🍎"),
+    ("car", "🚗"),
+    ("rocket", "🚀"),
+    ("pizza", "🍕"),
+    ("tree", "🌳"),
+    ("star", "⭐"),
+    ("fish", "🐟"),
+    ("house", "🏠"),
+    ("banana", "🍌"),
+    ("football", "⚽"),
+    ("moon", "🌙"),
+    ("key", "🔑"),
+    ("bell", "🔔"),
+    ("umbrella", "☂️"),
+];
+
+const WORD_POOL: &[&str] = &[
+    "APPLE", "BRAVE", "CLOUD", "DREAM", "EAGLE", "FLAME", "GRAPE", "HONEY", "LEMON", "MANGO",
+    "NORTH", "OCEAN", "PIANO", "QUEEN", "RIVER", "STONE", "TIGER", "WATER", "ZEBRA", "PLANT",
+];
+
+pub fn describe_captcha_type(captcha_type: CaptchaType) -> &'static str {
+    match captcha_type {
+        CaptchaType::Button => "Button",
+        CaptchaType::Arithmetic => "Arithmetic",
+        CaptchaType::Emoji => "Pick the emoji",
+        CaptchaType::ImageWord => "Word from image",
+    }
+}
+
+/// Build a challenge with shuffled options. `Button` has no challenge and
+/// `ImageWord` has no options: the member types the word instead.
+pub fn generate_challenge<R: Rng + ?Sized>(
+    captcha_type: CaptchaType,
+    rng: &mut R,
+) -> Option<CaptchaChallenge> {
+    let mut challenge = match captcha_type {
+        CaptchaType::Button => return None,
+        CaptchaType::Arithmetic => arithmetic_challenge(rng),
+        CaptchaType::Emoji => {
+            let picked: Vec<&(&str, &str)> =
+                EMOJI_POOL.choose_multiple(rng, CHALLENGE_OPTIONS).collect();
+            let (name, emoji) = *picked[0];
+            CaptchaChallenge {
+                prompt: format!("Tap the {}", name),
+                options: picked.iter().map(|(_, e)| e.to_string()).collect(),
+                answer: emoji.to_string(),
+            }
+        }
+        CaptchaType::ImageWord => CaptchaChallenge {
+            prompt: "Type the word shown in the image".to_string(),
+            options: Vec::new(),
+            answer: WORD_POOL.choose(rng).unwrap_or(&WORD_POOL[0]).to_string(),
+        },
+    };
+
+    challenge.options.shuffle(rng);
+    Some(challenge)
+}
+
+fn arithmetic_challenge<R: Rng + ?Sized>(rng: &mut R) -> CaptchaChallenge {
+    let a: i32 = rng.random_range(10..=20);
+    let b: i32 = rng.random_range(1..=9);
+    let (prompt, answer) = if rng.random_bool(0.5) {
+        (format!("What is {} + {}?", a, b), a + b)
+    } else {
+        (format!("What is {} - {}?", a, b), a - b)
+    };
+
+    let mut options = vec![answer];
+    while options.len() < CHALLENGE_OPTIONS {
+        let decoy = answer + rng.random_range(-5..=5);
+        if decoy >= 0 && !options.contains(&decoy) {
+            options.push(decoy);
+        }
+    }
+
+    CaptchaChallenge {
+        prompt,
+        options: options.iter().map(|n| n.to_string()).collect(),
+        answer: answer.to_string(),
+    }
+}
+
+/// Turn an admin-written quiz question into a challenge with shuffled options.
+pub fn quiz_challenge<R: Rng + ?Sized>(question: &QuizQuestion, rng: &mut R) -> CaptchaChallenge {
+    let mut options = vec![question.correct_answer.clone()];
+    options.extend(question.wrong_answers.iter().cloned());
+    options.shuffle(rng);
+
+    CaptchaChallenge {
+        prompt: question.question.clone(),
+        options,
+        answer: question.correct_answer.clone(),
+    }
+}
+
+/// Shuffle the buttons again, e.g. after a wrong answer.
+pub fn reshuffle_options<R: Rng + ?Sized>(challenge: &mut CaptchaChallenge, rng: &mut R) {
+    challenge.options.shuffle(rng);
+}
+
+/// Whether a typed reply matches a word challenge, ignoring case and spacing.
+pub fn typed_answer_matches(challenge: &CaptchaChallenge, text: &str) -> bool {
+    challenge.options.is_empty() && text.trim().eq_ignore_ascii_case(&challenge.answer)
+}
+
+/// Chance that tapping a random button passes within `attempts` tries.
+pub fn random_pass_chance(options: usize, attempts: u8) -> f64 {
+    1.0 - (1.0 - 1.0 / options as f64).powi(attempts as i32)
+}
+
+// 5x7 bitmap glyphs for A-Z, one byte per row, low five bits used.
+const GLYPHS: [[u8; 7]; 26] = [
+    [0x0E, 0x11, 0x11, 0x1F, 0x11, 0x11, 0x11],
+    [0x1E, 0x11, 0x11, 0x1E, 0x11, 0x11, 0x1E],
+    [0x0E, 0x11, 0x10, 0x10, 0x10, 0x11, 0x0E],
+    [0x1E, 0x11, 0x11, 0x11, 0x11, 0x11, 0x1E],
+    [0x1F, 0x10, 0x10, 0x1E, 0x10, 0x10, 0x1F],
+    [0x1F, 0x10, 0x10, 0x1E, 0x10, 0x10, 0x10],
+    [0x0E, 0x11, 0x10, 0x17, 0x11, 0x11, 0x0F],
+    [0x11, 0x11, 0x11, 0x1F, 0x11, 0x11, 0x11],
+    [0x0E, 0x04, 0x04, 0x04, 0x04, 0x04, 0x0E],
+    [0x07, 0x02, 0x02, 0x02, 0x02, 0x12, 0x0C],
+    [0x11, 0x12, 0x14, 0x18, 0x14, 0x12, 0x11],
+    [0x10, 0x10, 0x10, 0x10, 0x10, 0x10, 0x1F],
+    [0x11, 0x1B, 0x15, 0x15, 0x11, 0x11, 0x11],
+    [0x11, 0x11, 0x19, 0x15, 0x13, 0x11, 0x11],
+    [0x0E, 0x11, 0x11, 0x11, 0x11, 0x11, 0x0E],
+    [0x1E, 0x11, 0x11, 0x1E, 0x10, 0x10, 0x10],
+    [0x0E, 0x11, 0x11, 0x11, 0x15, 0x12, 0x0D],
+    [0x1E, 0x11, 0x11, 0x1E, 0x14, 0x12, 0x11],
+    [0x0F, 0x10, 0x10, 0x0E, 0x01, 0x01, 0x1E],
+    [0x1F, 0x04, 0x04, 0x04, 0x04, 0x04, 0x04],
+    [0x11, 0x11, 0x11, 0x11, 0x11, 0x11, 0x0E],
+    [0x11, 0x11, 0x11, 0x11, 0x11, 0x0A, 0x04],
+    [0x11, 0x11, 0x11, 0x15, 0x15, 0x15, 0x0A],
+    [0x11, 0x11, 0x0A, 0x04, 0x0A, 0x11, 0x11],
+    [0x11, 0x11, 0x0A, 0x04, 0x04, 0x04, 0x04],
+    [0x1F, 0x01, 0x02, 0x04, 0x08, 0x10, 0x1F],
+];
+
+const SCALE: usize = 5;
+const MARGIN: usize = 20;
+const LETTER_STEP: usize = 5 * SCALE + 10;
+
+/// Draw `word` (A-Z only) as a noisy grayscale PNG.
+pub fn render_word_image<R: Rng + ?Sized>(word: &str, rng: &mut R) -> Result<Vec<u8>> {
+    let letters: Vec<usize> = word
+        .chars()
+        .filter(|c| c.is_ascii_alphabetic())
+        .map(|c| (c.to_ascii_uppercase() as u8 - b'A') as usize)
+        .collect();
+
+    let width = MARGIN * 2 + letters.len().max(1) * LETTER_STEP;
+    let height = MARGIN * 2 + 7 * SCALE;
+    let mut pixels = vec![235u8; width * height];
+
+    for (i, glyph) in letters.iter().map(|&l| &GLYPHS[l]).enumerate() {
+        let x0 = (MARGIN + i * LETTER_STEP) as i32 + rng.random_range(-3..=3);
+        let y0 = MARGIN as i32 + rng.random_range(-8..=8);
+        let ink: u8 = rng.random_range(20..=90);
+        for (row, bits) in glyph.iter().enumerate() {
+            for col in 0..5 {
+                if bits & (0x10 >> col) == 0 {
+                    continue;
+                }
+                for dy in 0..SCALE {
+                    for dx in 0..SCALE {
+                        let x = x0 + (col * SCALE + dx) as i32;
+                        let y = y0 + (row * SCALE + dy) as i32;
+                        if x >= 0 && y >= 0 && (x as usize) < width && (y as usize) < height {
+                            pixels[y as usize * width + x as usize] = ink;
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    // Crossing lines and speckles make plain OCR less reliable
+    for _ in 0..3 {
+        let (x1, y1) = (0.0, rng.random_range(0..height) as f32);
+        let (x2, y2) = (width as f32, rng.random_range(0..height) as f32);
+        let shade: u8 = rng.random_range(60..=140);
+        for step in 0..width * 2 {
+            let t = step as f32 / (width * 2) as f32;
+            let x = (x1 + (x2 - x1) * t) as usize;
+            let y = (y1 + (y2 - y1) * t) as usize;
+            if x < width && y < height {
+                pixels[y * width + x] = shade;
+            }
+        }
+    }
+    for pixel in pixels.iter_mut() {
+        if rng.random_bool(0.08) {
+            *pixel = rng.random_range(0..=255);
+        }
+    }
+
+    encode_grayscale_png(width as u32, height as u32, &pixels)
+}
+
+fn encode_grayscale_png(width: u32, height: u32, pixels: &[u8]) -> Result<Vec<u8>> {
+    let mut raw = Vec::with_capacity(pixels.len() + height as usize);
+    for row in pixels.chunks(width as usize) {
+        raw.push(0); // filter: none
+        raw.extend_from_slice(row);
+    }
+    let mut encoder = ZlibEncoder::new(Vec::new(), Compression::default());
+    encoder.write_all(&raw)?;
+    let idat = encoder.finish()?;
+
+    let mut ihdr = Vec::with_capacity(13);
+    ihdr.extend_from_slice(&width.to_be_bytes());
+    ihdr.extend_from_slice(&height.to_be_bytes());
+    ihdr.extend_from_slice(&[8, 0, 0, 0, 0]); // 8-bit grayscale, no interlace
+
+    let mut png = b"\x89PNG\r\n\x1a\n".to_vec();
+    write_png_chunk(&mut png, b"IHDR", &ihdr);
+    write_png_chunk(&mut png, b"IDAT", &idat);
+    write_png_chunk(&mut png, b"IEND", &[]);
+    Ok(png)
+}
+
+fn write_png_chunk(out: &mut Vec<u8>, kind: &[u8; 4], data: &[u8]) {
+    out.extend_from_slice(&(data.len() as u32).to_be_bytes());
+    out.extend_from_slice(kind);
+    out.extend_from_slice(data);
+    let mut hasher = crc32fast::Hasher::new();
+    hasher.update(kind);
+    hasher.update(data);
+    out.extend_from_slice(&hasher.finalize().to_be_bytes());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::{SeedableRng, rngs::StdRng};
+
+    #[test]
+    fn button_type_has_no_challenge() {
+        let mut rng = StdRng::seed_from_u64(1);
+        assert!(generate_challenge(CaptchaType::Button, &mut rng).is_none());
+    }
+
+    #[test]
+    fn challenges_offer_unique_options_including_answer() {
+        let mut rng = StdRng::seed_from_u64(7);
+        for captcha_type in [CaptchaType::Arithmetic, CaptchaType::Emoji] {
+            for _ in 0..50 {
+                let challenge = generate_challenge(captcha_type, &mut rng).unwrap();
+                assert_eq!(challenge.options.len(), CHALLENGE_OPTIONS);
+                assert!(challenge.options.contains(&challenge.answer));
+                let mut unique = challenge.options.clone();
+                unique.sort();
+                unique.dedup();
+                assert_eq!(unique.len(), CHALLENGE_OPTIONS);
+            }
+        }
+    }
+
+    #[test]
+    fn word_challenge_is_typed_not_tapped() {
+        let mut rng = StdRng::seed_from_u64(9);
+        let challenge = generate_challenge(CaptchaType::ImageWord, &mut rng).unwrap();
+        assert!(challenge.options.is_empty());
+        assert!(WORD_POOL.contains(&challenge.answer.as_str()));
+
+        let typed = format!("  {} ", challenge.answer.to_lowercase());
+        assert!(typed_answer_matches(&challenge, &typed));
+        assert!(!typed_answer_matches(&challenge, "0"));
+
+        // Button challenges never accept typed replies
+        let emoji = generate_challenge(CaptchaType::Emoji, &mut rng).unwrap();
+        assert!(!typed_answer_matches(&emoji, &emoji.answer));
+    }
+
+    #[test]
+    fn random_taps_pass_the_default_challenge_a_quarter_of_the_time() {
+        use crate::welcome::dto::DEFAULT_MAX_ATTEMPTS;
+
+        let chance = random_pass_chance(CHALLENGE_OPTIONS, DEFAULT_MAX_ATTEMPTS);
+        assert!((chance - 0.25).abs() < 1e-9);
+        assert!((random_pass_chance(CHALLENGE_OPTIONS, 3) - 0.578125).abs() < 1e-9);
+    }
+
+    #[test]
+    fn arithmetic_answer_matches_prompt() {
+        let mut rng = StdRng::seed_from_u64(3);
+        for _ in 0..50 {
+            let challenge = generate_challenge(CaptchaType::Arithmetic, &mut rng).unwrap();
+            let expr = challenge
+                .prompt
+                .trim_start_matches("What is ")
+                .trim_end_matches('?');
+            let parts: Vec<&str> = expr.split(' ').collect();
+            let (a, b): (i32, i32) = (parts[0].parse().unwrap(), parts[2].parse().unwrap());
+            let expected = if parts[1] == "+" { a + b } else { a - b };
+            assert_eq!(challenge.answer, expected.to_string());
+        }
+    }
+
+    #[test]
+    fn quiz_challenge_contains_every_answer() {
+        let mut rng = StdRng::seed_from_u64(5);
+        let question = QuizQuestion {
+            question: "Where do we share links?".to_string(),
+            correct_answer: "#links".to_string(),
+            wrong_answers: vec!["DMs".to_string(), "Anywhere".to_string()],
+        };
+
+        let challenge = quiz_challenge(&question, &mut rng);
+
+        assert_eq!(challenge.prompt, question.question);
+        assert_eq!(challenge.answer, "#links");
+        let mut options = challenge.options.clone();
+        options.sort();
+        assert_eq!(options, vec!["#links", "Anywhere", "DMs"]);
+    }
+
+    #[test]
+    fn word_image_is_a_png_with_expected_size() {
+        let mut rng = StdRng::seed_from_u64(11);
+        let png = render_word_image("TIGER", &mut rng).unwrap();
+
+        assert_eq!(&png[..8], b"\x89PNG\r\n\x1a\n");
+        assert_eq!(&png[12..16], b"IHDR");
+        let width = u32::from_be_bytes(png[16..20].try_into().unwrap()) as usize;
+        let height = u32::from_be_bytes(png[20..24].try_into().unwrap()) as usize;
+        assert_eq!(width, MARGIN * 2 + 5 * LETTER_STEP);
+        assert_eq!(height, MARGIN * 2 + 7 * SCALE);
+        assert!(png.ends_with(&[0xAE, 0x42, 0x60, 0x82]));
+    }
+}
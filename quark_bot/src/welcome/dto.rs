@@ -1,6 +1,24 @@
+use std::collections::BTreeMap;
+
 use serde::{Deserialize, Serialize};
 use teloxide::types::{ChatId, UserId};
 
+use crate::nft_gate::dto::CollectionRequirement;
+
+/// Challenge a new member has to solve before being unmuted.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum CaptchaType {
+    /// Single "Prove You're Human" button.
+    #[default]
+    Button,
+    /// Pick the result of a small sum.
+    Arithmetic,
+    /// Pick the emoji matching a name.
+    Emoji,
+    /// Pick the word drawn in a generated image.
+    ImageWord,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct WelcomeSettings {
     pub enabled: bool,
@@ -9,6 +27,178 @@ pub struct WelcomeSettings {
     pub verification_success_count: u64,
     pub verification_failure_count: u64,
     pub last_updated: i64, // unix timestamp
+    #[serde(default)]
+    pub captcha_type: CaptchaType,
+    #[serde(default = "default_max_attempts")]
+    pub max_attempts: u8,
+    /// Show the group rules and require an "I agree" tap after the challenge.
+    #[serde(default)]
+    pub require_rules: bool,
+    #[serde(default)]
+    pub quiz: Vec<QuizQuestion>,
+    #[serde(default)]
+    pub media: Option<WelcomeMedia>,
+    #[serde(default)]
+    pub url_buttons: Vec<WelcomeUrlButton>,
+    /// Forum topic (message thread id) welcome messages are posted into.
+    #[serde(default)]
+    pub topic_id: Option<i32>,
+    /// Post a message when members leave or are removed.
+    #[serde(default)]
+    pub goodbye_enabled: bool,
+    /// MarkdownV2 template; falls back to the default goodbye when unset.
+    #[serde(default)]
+    pub goodbye_message: Option<String>,
+    #[serde(default)]
+    pub raid_protection_enabled: bool,
+    /// Joins within `RAID_WINDOW_SECONDS` that trigger a lockdown.
+    #[serde(default = "default_raid_join_threshold")]
+    pub raid_join_threshold: u32,
+    #[serde(default = "default_raid_lockdown_minutes")]
+    pub raid_lockdown_minutes: u64,
+    /// Seconds after which the bot deletes its own finished welcome, verification
+    /// and goodbye messages. `None` keeps them.
+    #[serde(default)]
+    pub bot_message_ttl: Option<u64>,
+    /// Seconds after which Telegram's join/leave service messages are deleted.
+    #[serde(default)]
+    pub service_message_ttl: Option<u64>,
+    /// Only members holding this token may stay; checked as the last verification step.
+    #[serde(default)]
+    pub token_gate: Option<TokenGate>,
+    /// Digital asset collection members must hold, checked together with `token_gate`.
+    #[serde(default)]
+    pub nft_gate: Option<CollectionRequirement>,
+    /// What happens when someone on the known-spammer list joins.
+    #[serde(default)]
+    pub spammer_action: SpammerAction,
+    /// Apply list entries loaded from the bot's spammer list file.
+    #[serde(default)]
+    pub trust_imported_spammers: bool,
+    /// Apply bans made in other groups using this bot.
+    #[serde(default)]
+    pub trust_other_group_bans: bool,
+}
+
+/// Handling of joining users found on the known-spammer list.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum SpammerAction {
+    /// Don't check the list.
+    #[default]
+    Off,
+    /// Ban right away.
+    Ban,
+    /// Mute and ask the admins to allow or ban.
+    Review,
+}
+
+/// How a user ended up on the known-spammer list.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SpammerSource {
+    /// Loaded from the spammer list file.
+    Imported,
+    /// Banned by an admin through the bot in this group.
+    Banned { chat_id: ChatId },
+}
+
+/// Entry of the bot-wide known-spammer list. Groups only act on the sources
+/// they opted in to.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct KnownSpammer {
+    pub user_id: UserId,
+    pub sources: Vec<SpammerSource>,
+    pub added_at: i64, // unix timestamp
+}
+
+/// Minimum balance of a token new members must hold in their linked wallet.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TokenGate {
+    /// Coin type or fungible asset address passed to `get_account_balance`.
+    pub token_type: String,
+    pub symbol: String,
+    pub decimals: u8,
+    pub min_amount: f64,
+}
+
+/// A member admitted through the token gate, re-checked periodically.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TokenHolder {
+    pub chat_id: ChatId,
+    pub user_id: UserId,
+    pub username: String,
+    pub verified_at: i64,  // unix timestamp
+    pub last_checked: i64, // unix timestamp
+}
+
+pub const RAID_WINDOW_SECONDS: i64 = 60;
+pub const DEFAULT_RAID_JOIN_THRESHOLD: u32 = 10;
+pub const DEFAULT_RAID_LOCKDOWN_MINUTES: u64 = 30;
+
+fn default_raid_join_threshold() -> u32 {
+    DEFAULT_RAID_JOIN_THRESHOLD
+}
+
+fn default_raid_lockdown_minutes() -> u64 {
+    DEFAULT_RAID_LOCKDOWN_MINUTES
+}
+
+/// A message queued for deletion; persisted so restarts don't lose it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScheduledDeletion {
+    pub chat_id: ChatId,
+    pub message_id: i32,
+    pub delete_at: i64, // unix timestamp
+}
+
+/// Active raid lockdown for a group.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RaidLockdown {
+    pub chat_id: ChatId,
+    pub started_at: i64, // unix timestamp
+    pub until: i64,      // unix timestamp
+    pub joins_detected: usize,
+    /// Members muted on arrival while verification was off; unmuted when the lockdown lifts.
+    pub restricted_users: Vec<UserId>,
+    pub alert_message_id: Option<i32>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum WelcomeMediaKind {
+    Photo,
+    Animation,
+}
+
+/// Image or GIF sent just before the welcome text, stored by Telegram file id.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WelcomeMedia {
+    pub kind: WelcomeMediaKind,
+    pub file_id: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WelcomeUrlButton {
+    pub text: String,
+    pub url: String,
+}
+
+pub const MAX_URL_BUTTONS: usize = 6;
+
+/// Multiple-choice question asked after the rules step.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct QuizQuestion {
+    pub question: String,
+    pub correct_answer: String,
+    pub wrong_answers: Vec<String>,
+}
+
+pub const MAX_QUIZ_QUESTIONS: usize = 5;
+
+// Options are reshuffled after a miss, so every extra attempt is another
+// 1 in CHALLENGE_OPTIONS guess for a bot tapping at random
+pub const DEFAULT_MAX_ATTEMPTS: u8 = 1;
+
+fn default_max_attempts() -> u8 {
+    DEFAULT_MAX_ATTEMPTS
 }
 
 impl Default for WelcomeSettings {
@@ -20,10 +210,50 @@ impl Default for WelcomeSettings {
             verification_success_count: 0,
             verification_failure_count: 0,
             last_updated: chrono::Utc::now().timestamp(),
+            captcha_type: CaptchaType::default(),
+            max_attempts: DEFAULT_MAX_ATTEMPTS,
+            require_rules: false,
+            quiz: Vec::new(),
+            media: None,
+            url_buttons: Vec::new(),
+            topic_id: None,
+            goodbye_enabled: false,
+            goodbye_message: None,
+            raid_protection_enabled: false,
+            raid_join_threshold: DEFAULT_RAID_JOIN_THRESHOLD,
+            raid_lockdown_minutes: DEFAULT_RAID_LOCKDOWN_MINUTES,
+            bot_message_ttl: None,
+            service_message_ttl: None,
+            token_gate: None,
+            nft_gate: None,
+            spammer_action: SpammerAction::default(),
+            trust_imported_spammers: false,
+            trust_other_group_bans: false,
         }
     }
 }
 
+/// Step of the welcome flow a pending member is currently on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum VerificationStage {
+    #[default]
+    Challenge,
+    Rules,
+    /// Index into `WelcomeSettings::quiz`.
+    Quiz(usize),
+    /// Waiting for the member to link a wallet holding the gated token or NFTs.
+    TokenGate,
+}
+
+/// A generated challenge. `options` is the order the buttons are shown in,
+/// empty for challenges the member answers by typing.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CaptchaChallenge {
+    pub prompt: String,
+    pub options: Vec<String>,
+    pub answer: String,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PendingVerification {
     pub user_id: UserId,
@@ -33,6 +263,38 @@ pub struct PendingVerification {
     pub joined_at: i64, // unix timestamp
     pub expires_at: i64, // unix timestamp
     pub verification_message_id: i32,
+    #[serde(default)]
+    pub challenge: Option<CaptchaChallenge>,
+    #[serde(default)]
+    pub attempts_left: u8,
+    /// Separate message carrying the challenge image, deleted once resolved.
+    #[serde(default)]
+    pub challenge_message_id: Option<i32>,
+    #[serde(default)]
+    pub stage: VerificationStage,
+    /// Welcome media message, removed again if the member fails verification.
+    #[serde(default)]
+    pub media_message_id: Option<i32>,
+}
+
+/// What a pending member sent back for their challenge.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CaptchaAnswer {
+    /// Index of the pressed option, `None` for the single verify button.
+    Button(Option<usize>),
+    /// Group message typed by the member for a word challenge.
+    Typed(String),
+}
+
+/// Result of a member answering a verification step.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VerificationOutcome {
+    Verified,
+    /// Step passed, the member was moved on to the rules or the quiz.
+    NextStep,
+    WrongAnswer { attempts_left: u8 },
+    /// Out of attempts; the member was removed from the group.
+    Rejected,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -56,6 +318,20 @@ impl Default for WelcomeStats {
     }
 }
 
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DailyMemberActivity {
+    pub joins: u64,
+    pub leaves: u64,
+}
+
+/// Per-group join/leave counters, with daily buckets keyed by `YYYY-MM-DD` (UTC).
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct MemberActivity {
+    pub total_joins: u64,
+    pub total_leaves: u64,
+    pub daily: BTreeMap<String, DailyMemberActivity>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct WelcomeMessageTemplate {
     pub message: String,
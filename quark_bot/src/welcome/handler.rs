@@ -7,8 +7,25 @@ use teloxide::{
 
 use crate::{
     dependencies::BotDependencies,
+    nft_gate::{
+        dto::{CollectionRequirement, MAX_ROLE_RULES, MemberRole, RoleRule},
+        helpers::{describe_requirement, describe_role, parse_collection_input},
+    },
     utils::{self, send_html_message, send_message},
-    welcome::{helpers::format_timeout_display, welcome_service::WelcomeService},
+    welcome::{
+        captcha::{CHALLENGE_OPTIONS, describe_captcha_type, random_pass_chance},
+        dto::{
+            CaptchaAnswer, CaptchaType, MAX_QUIZ_QUESTIONS, MAX_URL_BUTTONS, RAID_WINDOW_SECONDS,
+            SpammerAction, TokenGate, VerificationOutcome, VerificationStage, WelcomeMedia,
+            WelcomeMediaKind,
+        },
+        helpers::{
+            describe_spammer_action, format_timeout_display, format_ttl_display,
+            member_activity_in_last_days,
+            parse_quiz_question, parse_token_gate_input, parse_url_buttons,
+        },
+        welcome_service::WelcomeService,
+    },
 };
 
 pub async fn handle_welcome_settings_callback(
@@ -49,6 +66,220 @@ pub async fn handle_welcome_settings_callback(
         "welcome_timeout" => {
             show_timeout_menu(bot.clone(), msg, welcome_service).await?;
         }
+        "welcome_captcha" => {
+            show_captcha_menu(bot.clone(), msg, welcome_service).await?;
+        }
+        "welcome_rules" => {
+            // Also reached via Back from the quiz question prompt
+            welcome_service.clear_input_state(msg.chat.id)?;
+            show_rules_quiz_menu(bot.clone(), msg, welcome_service).await?;
+        }
+        "welcome_rules_toggle" => {
+            let mut settings = welcome_service.get_settings(msg.chat.id);
+            settings.require_rules = !settings.require_rules;
+            settings.last_updated = chrono::Utc::now().timestamp();
+            welcome_service.save_settings(msg.chat.id, settings)?;
+            show_rules_quiz_menu(bot.clone(), msg, welcome_service).await?;
+        }
+        "welcome_rich" => {
+            // Also reached via Back from the media and link prompts
+            welcome_service.clear_input_state(msg.chat.id)?;
+            show_rich_welcome_menu(bot.clone(), msg, welcome_service).await?;
+        }
+        "welcome_media_set" => {
+            start_rich_input(
+                bot.clone(),
+                msg,
+                welcome_service,
+                "media_input",
+                "🖼️ <b>Welcome Media</b>\n\n\
+                Send the photo or GIF new members should see above the welcome message.\n\n\
+                <i>Send /cancel to cancel.</i>",
+            )
+            .await?;
+        }
+        "welcome_media_remove" => {
+            let mut settings = welcome_service.get_settings(msg.chat.id);
+            settings.media = None;
+            settings.last_updated = chrono::Utc::now().timestamp();
+            welcome_service.save_settings(msg.chat.id, settings)?;
+            show_rich_welcome_menu(bot.clone(), msg, welcome_service).await?;
+        }
+        "welcome_links_set" => {
+            start_rich_input(
+                bot.clone(),
+                msg,
+                welcome_service,
+                "url_buttons_input",
+                "🔗 <b>Welcome Link Buttons</b>\n\n\
+                Send one button per line as <code>Text - https://link</code>. \
+                This replaces the current buttons.\n\n\
+                <b>Example:</b>\n\
+                <code>🌐 Website - https://example.com\n\
+                📚 Docs - https://docs.example.com\n\
+                🐦 X - https://x.com/example</code>\n\n\
+                <i>Send /cancel to cancel.</i>",
+            )
+            .await?;
+        }
+        "welcome_links_clear" => {
+            let mut settings = welcome_service.get_settings(msg.chat.id);
+            settings.url_buttons.clear();
+            settings.last_updated = chrono::Utc::now().timestamp();
+            welcome_service.save_settings(msg.chat.id, settings)?;
+            show_rich_welcome_menu(bot.clone(), msg, welcome_service).await?;
+        }
+        "welcome_topic_here" => {
+            let mut settings = welcome_service.get_settings(msg.chat.id);
+            settings.topic_id = if msg.is_topic_message {
+                msg.thread_id.map(|thread_id| thread_id.0.0)
+            } else {
+                None
+            };
+            settings.last_updated = chrono::Utc::now().timestamp();
+            welcome_service.save_settings(msg.chat.id, settings)?;
+            show_rich_welcome_menu(bot.clone(), msg, welcome_service).await?;
+        }
+        "welcome_goodbye" => {
+            // Also reached via Back from the goodbye message prompt
+            welcome_service.clear_input_state(msg.chat.id)?;
+            show_goodbye_menu(bot.clone(), msg, welcome_service).await?;
+        }
+        "welcome_goodbye_toggle" => {
+            let mut settings = welcome_service.get_settings(msg.chat.id);
+            settings.goodbye_enabled = !settings.goodbye_enabled;
+            settings.last_updated = chrono::Utc::now().timestamp();
+            welcome_service.save_settings(msg.chat.id, settings)?;
+            show_goodbye_menu(bot.clone(), msg, welcome_service).await?;
+        }
+        "welcome_goodbye_reset" => {
+            let mut settings = welcome_service.get_settings(msg.chat.id);
+            settings.goodbye_message = None;
+            settings.last_updated = chrono::Utc::now().timestamp();
+            welcome_service.save_settings(msg.chat.id, settings)?;
+            show_goodbye_menu(bot.clone(), msg, welcome_service).await?;
+        }
+        "welcome_goodbye_set" => {
+            let keyboard = InlineKeyboardMarkup::new(vec![vec![InlineKeyboardButton::callback(
+                "↩️ Back",
+                "welcome_goodbye",
+            )]]);
+            bot.edit_message_text(
+                msg.chat.id,
+                msg.id,
+                "✏️ <b>Goodbye Message</b>\n\n\
+                Reply with the message to post when someone leaves. \
+                The same formatting and placeholders as the welcome message are supported, \
+                e.g. <code>👋 {first_name} has left {group_name}.</code>\n\n\
+                <i>Send /cancel to cancel.</i>",
+            )
+            .parse_mode(ParseMode::Html)
+            .reply_markup(keyboard)
+            .await?;
+            welcome_service
+                .store_input_state(msg.chat.id, "goodbye_message_input")
+                .await?;
+        }
+        "welcome_raid" => {
+            show_raid_menu(bot.clone(), msg, welcome_service).await?;
+        }
+        "welcome_raid_toggle" => {
+            let mut settings = welcome_service.get_settings(msg.chat.id);
+            settings.raid_protection_enabled = !settings.raid_protection_enabled;
+            settings.last_updated = chrono::Utc::now().timestamp();
+            welcome_service.save_settings(msg.chat.id, settings)?;
+            show_raid_menu(bot.clone(), msg, welcome_service).await?;
+        }
+        "welcome_raid_lift" => {
+            welcome_service.lift_lockdown(&bot, msg.chat.id).await?;
+            show_raid_menu(bot.clone(), msg, welcome_service).await?;
+        }
+        "welcome_lockdown_lift" => {
+            // Pressed on the raid alert itself; lift_lockdown edits the alert in place
+            let lifted = welcome_service.lift_lockdown(&bot, msg.chat.id).await?;
+            bot.answer_callback_query(query.id)
+                .text(if lifted {
+                    "✅ Lockdown lifted."
+                } else {
+                    "ℹ️ No lockdown is active."
+                })
+                .await?;
+            return Ok(());
+        }
+        "welcome_spammers" => {
+            show_spammer_menu(bot.clone(), msg, welcome_service).await?;
+        }
+        "welcome_spammer_trust_imported" => {
+            let mut settings = welcome_service.get_settings(msg.chat.id);
+            settings.trust_imported_spammers = !settings.trust_imported_spammers;
+            settings.last_updated = chrono::Utc::now().timestamp();
+            welcome_service.save_settings(msg.chat.id, settings)?;
+            show_spammer_menu(bot.clone(), msg, welcome_service).await?;
+        }
+        "welcome_spammer_trust_groups" => {
+            let mut settings = welcome_service.get_settings(msg.chat.id);
+            settings.trust_other_group_bans = !settings.trust_other_group_bans;
+            settings.last_updated = chrono::Utc::now().timestamp();
+            welcome_service.save_settings(msg.chat.id, settings)?;
+            show_spammer_menu(bot.clone(), msg, welcome_service).await?;
+        }
+        "welcome_tokengate" => {
+            // Also reached via Back from the token and collection prompts
+            welcome_service.clear_input_state(msg.chat.id)?;
+            show_token_gate_menu(bot.clone(), msg, &bot_deps).await?;
+        }
+        "welcome_tokengate_remove" => {
+            let mut settings = welcome_service.get_settings(msg.chat.id);
+            settings.token_gate = None;
+            settings.last_updated = chrono::Utc::now().timestamp();
+            welcome_service.save_settings(msg.chat.id, settings)?;
+            show_token_gate_menu(bot.clone(), msg, &bot_deps).await?;
+        }
+        "welcome_nftgate_remove" => {
+            let mut settings = welcome_service.get_settings(msg.chat.id);
+            settings.nft_gate = None;
+            settings.last_updated = chrono::Utc::now().timestamp();
+            welcome_service.save_settings(msg.chat.id, settings)?;
+            show_token_gate_menu(bot.clone(), msg, &bot_deps).await?;
+        }
+        "welcome_nftgate_set" => {
+            start_collection_input(
+                &bot,
+                msg,
+                &welcome_service,
+                "🖼️ <b>NFT Gate</b>\n\n\
+                Reply with the collection address and how many NFTs new members must hold, \
+                e.g. <code>0x1a2b... 2</code>. The count defaults to 1.",
+                "nft_gate_input",
+            )
+            .await?;
+        }
+        "welcome_tokengate_set" => {
+            let keyboard = InlineKeyboardMarkup::new(vec![vec![InlineKeyboardButton::callback(
+                "↩️ Back",
+                "welcome_tokengate",
+            )]]);
+            bot.edit_message_text(
+                msg.chat.id,
+                msg.id,
+                "🪙 <b>Token Gate</b>\n\n\
+                Reply with the token symbol and the minimum amount new members must hold, \
+                e.g. <code>APT 10</code>.\n\n\
+                <i>Send /cancel to cancel.</i>",
+            )
+            .parse_mode(ParseMode::Html)
+            .reply_markup(keyboard)
+            .await?;
+            welcome_service
+                .store_input_state(msg.chat.id, "token_gate_input")
+                .await?;
+        }
+        "welcome_autodelete" => {
+            show_auto_delete_menu(bot.clone(), msg, welcome_service).await?;
+        }
+        "welcome_quiz_add" => {
+            start_quiz_question_input(bot.clone(), msg, welcome_service).await?;
+        }
         "welcome_stats" => {
             show_welcome_stats(bot.clone(), msg, welcome_service).await?;
         }
@@ -61,12 +292,162 @@ pub async fn handle_welcome_settings_callback(
         "welcome_set_custom_message" => {
             start_custom_message_input(bot.clone(), msg, welcome_service).await?;
         }
+        _ if data.starts_with("welcome_nftrole_add_") => {
+            let role = data.strip_prefix("welcome_nftrole_add_").unwrap();
+            if let Some(role) = MemberRole::parse(role) {
+                let prompt = format!(
+                    "{} <b>Role</b>\n\n\
+                    Reply with the collection address and how many NFTs members must hold \
+                    to get this role, e.g. <code>0x1a2b... 2</code>. The count defaults to 1.",
+                    describe_role(role)
+                );
+                start_collection_input(
+                    &bot,
+                    msg,
+                    &welcome_service,
+                    &prompt,
+                    &format!("nft_role_input:{}", role.as_str()),
+                )
+                .await?;
+            }
+        }
+        _ if data.starts_with("welcome_nftrole_remove_") => {
+            let index = data.strip_prefix("welcome_nftrole_remove_").unwrap();
+            if let Ok(index) = index.parse::<usize>() {
+                let mut role_settings = bot_deps.nft_gate.get_settings(msg.chat.id);
+                if index < role_settings.rules.len() {
+                    role_settings.rules.remove(index);
+                    bot_deps.nft_gate.save_settings(msg.chat.id, &role_settings)?;
+                }
+            }
+            show_token_gate_menu(bot.clone(), msg, &bot_deps).await?;
+        }
         _ if data.starts_with("welcome_timeout_set_") => {
             let timeout = data.strip_prefix("welcome_timeout_set_").unwrap();
             if let Ok(timeout_seconds) = timeout.parse::<u64>() {
                 set_welcome_timeout(bot.clone(), msg, welcome_service, timeout_seconds).await?;
             }
         }
+        _ if data.starts_with("welcome_captcha_set_") => {
+            let captcha_type = match data.strip_prefix("welcome_captcha_set_").unwrap() {
+                "button" => Some(CaptchaType::Button),
+                "arithmetic" => Some(CaptchaType::Arithmetic),
+                "emoji" => Some(CaptchaType::Emoji),
+                "image" => Some(CaptchaType::ImageWord),
+                _ => None,
+            };
+            if let Some(captcha_type) = captcha_type {
+                let mut settings = welcome_service.get_settings(msg.chat.id);
+                settings.captcha_type = captcha_type;
+                settings.last_updated = chrono::Utc::now().timestamp();
+                welcome_service.save_settings(msg.chat.id, settings)?;
+                show_captcha_menu(bot.clone(), msg, welcome_service).await?;
+            }
+        }
+        _ if data.starts_with("welcome_attempts_set_") => {
+            let attempts = data.strip_prefix("welcome_attempts_set_").unwrap();
+            if let Ok(attempts) = attempts.parse::<u8>() {
+                let mut settings = welcome_service.get_settings(msg.chat.id);
+                settings.max_attempts = attempts.max(1);
+                settings.last_updated = chrono::Utc::now().timestamp();
+                welcome_service.save_settings(msg.chat.id, settings)?;
+                show_captcha_menu(bot.clone(), msg, welcome_service).await?;
+            }
+        }
+        _ if data.starts_with("welcome_spammer_action_") => {
+            let action = match data.strip_prefix("welcome_spammer_action_").unwrap() {
+                "off" => Some(SpammerAction::Off),
+                "ban" => Some(SpammerAction::Ban),
+                "review" => Some(SpammerAction::Review),
+                _ => None,
+            };
+            if let Some(action) = action {
+                let mut settings = welcome_service.get_settings(msg.chat.id);
+                settings.spammer_action = action;
+                settings.last_updated = chrono::Utc::now().timestamp();
+                welcome_service.save_settings(msg.chat.id, settings)?;
+                show_spammer_menu(bot.clone(), msg, welcome_service).await?;
+            }
+        }
+        _ if data.starts_with("welcome_spammer_allow:")
+            || data.starts_with("welcome_spammer_ban:") =>
+        {
+            // Pressed on the review request itself
+            let (ban, user_id) = match data.strip_prefix("welcome_spammer_ban:") {
+                Some(user_id) => (true, user_id),
+                None => (false, data.strip_prefix("welcome_spammer_allow:").unwrap()),
+            };
+            let Ok(user_id) = user_id.parse::<u64>() else {
+                bot.answer_callback_query(query.id)
+                    .text("❌ Invalid user")
+                    .await?;
+                return Ok(());
+            };
+            let result = welcome_service
+                .resolve_spammer_review(&bot, msg.chat.id, UserId(user_id), ban, msg.id)
+                .await;
+            let answer = match (result, ban) {
+                (Ok(()), true) => "✅ User banned.",
+                (Ok(()), false) => "✅ User allowed.",
+                (Err(e), _) => {
+                    log::error!("Failed to resolve spammer review in {}: {}", msg.chat.id, e);
+                    "❌ Failed to apply the decision."
+                }
+            };
+            bot.answer_callback_query(query.id).text(answer).await?;
+            return Ok(());
+        }
+        _ if data.starts_with("welcome_raid_threshold_") => {
+            let threshold = data.strip_prefix("welcome_raid_threshold_").unwrap();
+            if let Ok(threshold) = threshold.parse::<u32>() {
+                let mut settings = welcome_service.get_settings(msg.chat.id);
+                settings.raid_join_threshold = threshold.max(2);
+                settings.last_updated = chrono::Utc::now().timestamp();
+                welcome_service.save_settings(msg.chat.id, settings)?;
+                show_raid_menu(bot.clone(), msg, welcome_service).await?;
+            }
+        }
+        _ if data.starts_with("welcome_raid_duration_") => {
+            let minutes = data.strip_prefix("welcome_raid_duration_").unwrap();
+            if let Ok(minutes) = minutes.parse::<u64>() {
+                let mut settings = welcome_service.get_settings(msg.chat.id);
+                settings.raid_lockdown_minutes = minutes.max(1);
+                settings.last_updated = chrono::Utc::now().timestamp();
+                welcome_service.save_settings(msg.chat.id, settings)?;
+                show_raid_menu(bot.clone(), msg, welcome_service).await?;
+            }
+        }
+        _ if data.starts_with("welcome_ttl_bot_") || data.starts_with("welcome_ttl_service_") => {
+            let (is_service, seconds) = match data.strip_prefix("welcome_ttl_service_") {
+                Some(seconds) => (true, seconds),
+                None => (false, data.strip_prefix("welcome_ttl_bot_").unwrap()),
+            };
+            if let Ok(seconds) = seconds.parse::<u64>() {
+                // 0 turns auto-delete off
+                let ttl = (seconds > 0).then_some(seconds);
+                let mut settings = welcome_service.get_settings(msg.chat.id);
+                if is_service {
+                    settings.service_message_ttl = ttl;
+                } else {
+                    settings.bot_message_ttl = ttl;
+                }
+                settings.last_updated = chrono::Utc::now().timestamp();
+                welcome_service.save_settings(msg.chat.id, settings)?;
+                show_auto_delete_menu(bot.clone(), msg, welcome_service).await?;
+            }
+        }
+        _ if data.starts_with("welcome_quiz_remove_") => {
+            let index = data.strip_prefix("welcome_quiz_remove_").unwrap();
+            if let Ok(index) = index.parse::<usize>() {
+                let mut settings = welcome_service.get_settings(msg.chat.id);
+                if index < settings.quiz.len() {
+                    settings.quiz.remove(index);
+                    settings.last_updated = chrono::Utc::now().timestamp();
+                    welcome_service.save_settings(msg.chat.id, settings)?;
+                }
+                show_rules_quiz_menu(bot.clone(), msg, welcome_service).await?;
+            }
+        }
         _ if data.starts_with("welcome_back_to_") => {
             let target = data.strip_prefix("welcome_back_to_").unwrap();
             match target {
@@ -78,71 +459,999 @@ pub async fn handle_welcome_settings_callback(
         _ => {}
     }
 
-    // Answer callback query for all welcome callbacks to prevent retries
-    if data.starts_with("welcome_") {
-        bot.answer_callback_query(query.id).await?;
-    }
+    // Answer callback query for all welcome callbacks to prevent retries
+    if data.starts_with("welcome_") {
+        bot.answer_callback_query(query.id).await?;
+    }
+    Ok(())
+}
+
+async fn show_welcome_settings_menu(
+    bot: Bot,
+    msg: &Message,
+    welcome_service: WelcomeService,
+) -> Result<()> {
+    let settings = welcome_service.get_settings(msg.chat.id);
+    let stats = welcome_service.get_stats(msg.chat.id);
+
+    let status_text = if settings.enabled {
+        "🟢 Enabled"
+    } else {
+        "🔴 Disabled"
+    };
+    let timeout_text = format_timeout_display(settings.verification_timeout);
+
+    let text = format!(
+        "👋 <b>Welcome Settings</b>\n\n\
+        📊 Status: {}\n\
+        ⏰ Verification Timeout: {}\n\
+        🧩 Challenge: {}\n\
+        📈 Success Rate: {:.1}%\n\
+        ✅ Total Verifications: {}\n\
+        ❌ Failed Verifications: {}\n\n\
+        Configure anti-spam protection for new group members.",
+        status_text,
+        timeout_text,
+        describe_captcha_type(settings.captcha_type),
+        stats.success_rate,
+        stats.total_verifications,
+        stats.failed_verifications
+    );
+
+    let keyboard = InlineKeyboardMarkup::new(vec![
+        vec![InlineKeyboardButton::callback(
+            if settings.enabled {
+                "🔴 Disable Welcome"
+            } else {
+                "🟢 Enable Welcome"
+            },
+            "welcome_toggle",
+        )],
+        vec![InlineKeyboardButton::callback(
+            "✏️ Custom Message",
+            "welcome_custom_message",
+        )],
+        vec![InlineKeyboardButton::callback(
+            "⏰ Set Timeout",
+            "welcome_timeout",
+        )],
+        vec![InlineKeyboardButton::callback(
+            "🧩 Challenge Type",
+            "welcome_captcha",
+        )],
+        vec![InlineKeyboardButton::callback(
+            "📜 Rules & Quiz",
+            "welcome_rules",
+        )],
+        vec![InlineKeyboardButton::callback(
+            "🎨 Media, Buttons & Topic",
+            "welcome_rich",
+        )],
+        vec![InlineKeyboardButton::callback(
+            "👋 Goodbye Message",
+            "welcome_goodbye",
+        )],
+        vec![InlineKeyboardButton::callback(
+            "🚨 Raid Protection",
+            "welcome_raid",
+        )],
+        vec![InlineKeyboardButton::callback(
+            "🚫 Known Spammers",
+            "welcome_spammers",
+        )],
+        vec![InlineKeyboardButton::callback(
+            "🪙 Token & NFT Gate",
+            "welcome_tokengate",
+        )],
+        vec![InlineKeyboardButton::callback(
+            "🧹 Auto-Delete",
+            "welcome_autodelete",
+        )],
+        vec![InlineKeyboardButton::callback(
+            "📊 View Statistics",
+            "welcome_stats",
+        )],
+        vec![InlineKeyboardButton::callback(
+            "🔄 Reset Statistics",
+            "welcome_reset_stats",
+        )],
+        vec![InlineKeyboardButton::callback(
+            "↩️ Back to Group Settings",
+            "welcome_back_to_groupsettings",
+        )],
+    ]);
+
+    match bot
+        .edit_message_text(msg.chat.id, msg.id, text)
+        .parse_mode(ParseMode::Html)
+        .reply_markup(keyboard)
+        .await
+    {
+        Ok(_) => log::info!("Welcome settings menu updated successfully"),
+        Err(e) => {
+            if e.to_string().contains("message is not modified") {
+                log::info!("Welcome settings menu unchanged, skipping update");
+            } else {
+                return Err(anyhow::anyhow!("Failed to edit message: {}", e));
+            }
+        }
+    }
+
+    Ok(())
+}
+
+async fn toggle_welcome_feature(
+    bot: Bot,
+    msg: &Message,
+    welcome_service: WelcomeService,
+) -> Result<()> {
+    let mut settings = welcome_service.get_settings(msg.chat.id);
+    settings.enabled = !settings.enabled;
+    settings.last_updated = chrono::Utc::now().timestamp();
+
+    welcome_service.save_settings(msg.chat.id, settings.clone())?;
+
+    // Always refresh the menu to show the new state
+    show_welcome_settings_menu(bot, msg, welcome_service).await?;
+
+    Ok(())
+}
+
+async fn show_custom_message_menu(
+    bot: Bot,
+    msg: &Message,
+    welcome_service: WelcomeService,
+) -> Result<()> {
+    let settings = welcome_service.get_settings(msg.chat.id);
+    let current_message = if let Some(ref msg) = settings.custom_message {
+        msg
+    } else {
+        "Use default welcome message"
+    };
+
+    let text = format!(
+        "✏️ <b>Custom Welcome Message</b>\n\n\
+            Current message:\n\
+            <code>{}</code>\n\n\
+            💡 <i>Use Telegram MarkdownV2 (e.g., <code>*bold*</code>, <code>_italic_</code>, <code>`code`</code>) or plain text. Double asterisks <code>**like this**</code> are not supported.</i>\n\n\
+            Available placeholders:\n\
+            • {{username}} - @username (creates clickable mention)\n\
+            • {{first_name}} / {{mention}} - First name, plain or as a clickable mention\n\
+            • {{user_id}} - Telegram user ID\n\
+            • {{group_name}} - Group name\n\
+            • {{member_count}} - Number of group members\n\
+            • {{date}} - Today's date (UTC)\n\
+            • {{timeout}} - Verification timeout in minutes\n\
+            • {{Hi|Hello|Hey}} - Random choice\n\
+            • {{if username}}...{{else}}...{{end}} - Condition (also <code>if not username</code>)\n\n\
+            <b>Examples:</b>\n\
+            • <code>Hello {{username}}! Welcome to {{group_name}}! 👋</code>\n\
+            • <code>*Bold welcome*</code> to <code>{{group_name}}</code>, <code>{{username}}</code>!\n\
+            • <code>Use `code` for inline formatting</code>\n\n\
+            To set a custom message, reply to this message with your text.\n\
+            To use the default message, click 'Reset to Default'.",
+        teloxide::utils::html::escape(current_message)
+    );
+
+    let keyboard = InlineKeyboardMarkup::new(vec![
+        vec![InlineKeyboardButton::callback(
+            "✏️ Set Custom Message",
+            "welcome_set_custom_message",
+        )],
+        vec![InlineKeyboardButton::callback(
+            "🔄 Reset to Default",
+            "welcome_reset_message",
+        )],
+        vec![InlineKeyboardButton::callback(
+            "↩️ Back",
+            "welcome_back_to_main",
+        )],
+    ]);
+
+    bot.edit_message_text(msg.chat.id, msg.id, text)
+        .parse_mode(ParseMode::Html)
+        .reply_markup(keyboard)
+        .await?;
+
+    Ok(())
+}
+
+async fn show_timeout_menu(bot: Bot, msg: &Message, welcome_service: WelcomeService) -> Result<()> {
+    let settings = welcome_service.get_settings(msg.chat.id);
+    let current_timeout = settings.verification_timeout;
+
+    let text = format!(
+        "⏰ <b>Verification Timeout</b>\n\n\
+        Current timeout: {}\n\n\
+        Select a new timeout value:",
+        format_timeout_display(current_timeout)
+    );
+
+    let keyboard = InlineKeyboardMarkup::new(vec![
+        vec![
+            InlineKeyboardButton::callback("30s", "welcome_timeout_set_30"),
+            InlineKeyboardButton::callback("1m", "welcome_timeout_set_60"),
+            InlineKeyboardButton::callback("2m", "welcome_timeout_set_120"),
+        ],
+        vec![
+            InlineKeyboardButton::callback("3m", "welcome_timeout_set_180"),
+            InlineKeyboardButton::callback("4m", "welcome_timeout_set_240"),
+            InlineKeyboardButton::callback("5m", "welcome_timeout_set_300"),
+        ],
+        // Longer options leave time to link a wallet in token-gated groups
+        vec![
+            InlineKeyboardButton::callback("10m", "welcome_timeout_set_600"),
+            InlineKeyboardButton::callback("15m", "welcome_timeout_set_900"),
+            InlineKeyboardButton::callback("30m", "welcome_timeout_set_1800"),
+        ],
+        vec![InlineKeyboardButton::callback(
+            "↩️ Back",
+            "welcome_back_to_main",
+        )],
+    ]);
+
+    bot.edit_message_text(msg.chat.id, msg.id, text)
+        .parse_mode(ParseMode::Html)
+        .reply_markup(keyboard)
+        .await?;
+
+    Ok(())
+}
+
+/// Odds of a bot passing by tapping at random; word challenges have no buttons.
+fn random_tap_note(captcha_type: CaptchaType, max_attempts: u8) -> String {
+    match captcha_type {
+        CaptchaType::Arithmetic | CaptchaType::Emoji => format!(
+            "\n\nA bot tapping at random passes {:.0}% of the time with {} attempt(s).",
+            random_pass_chance(CHALLENGE_OPTIONS, max_attempts) * 100.0,
+            max_attempts
+        ),
+        CaptchaType::Button | CaptchaType::ImageWord => String::new(),
+    }
+}
+
+async fn show_captcha_menu(bot: Bot, msg: &Message, welcome_service: WelcomeService) -> Result<()> {
+    let settings = welcome_service.get_settings(msg.chat.id);
+
+    let text = format!(
+        "🧩 <b>Verification Challenge</b>\n\n\
+        Current challenge: {}\n\
+        Wrong answers allowed: {}\n\n\
+        • <b>Button</b> - a single \"Prove You're Human\" button\n\
+        • <b>Arithmetic</b> - pick the result of a small sum\n\
+        • <b>Pick the emoji</b> - tap the emoji matching a word\n\
+        • <b>Word from image</b> - type the word drawn in a generated image\n\n\
+        Answer buttons are shuffled for every member and after each wrong answer. \
+        Members who run out of attempts are removed and counted as failed verifications.{}",
+        describe_captcha_type(settings.captcha_type),
+        settings.max_attempts,
+        random_tap_note(settings.captcha_type, settings.max_attempts)
+    );
+
+    let type_button = |captcha_type: CaptchaType, data: &str| {
+        let label = describe_captcha_type(captcha_type);
+        let label = if settings.captcha_type == captcha_type {
+            format!("✅ {}", label)
+        } else {
+            label.to_string()
+        };
+        InlineKeyboardButton::callback(label, format!("welcome_captcha_set_{}", data))
+    };
+    let attempts_button = |attempts: u8| {
+        let label = if settings.max_attempts == attempts {
+            format!("✅ {}", attempts)
+        } else {
+            attempts.to_string()
+        };
+        InlineKeyboardButton::callback(label, format!("welcome_attempts_set_{}", attempts))
+    };
+
+    let keyboard = InlineKeyboardMarkup::new(vec![
+        vec![
+            type_button(CaptchaType::Button, "button"),
+            type_button(CaptchaType::Arithmetic, "arithmetic"),
+        ],
+        vec![
+            type_button(CaptchaType::Emoji, "emoji"),
+            type_button(CaptchaType::ImageWord, "image"),
+        ],
+        vec![attempts_button(1), attempts_button(3), attempts_button(5)],
+        vec![InlineKeyboardButton::callback(
+            "↩️ Back",
+            "welcome_back_to_main",
+        )],
+    ]);
+
+    match bot
+        .edit_message_text(msg.chat.id, msg.id, text)
+        .parse_mode(ParseMode::Html)
+        .reply_markup(keyboard)
+        .await
+    {
+        Ok(_) => {}
+        Err(e) if e.to_string().contains("message is not modified") => {}
+        Err(e) => return Err(anyhow::anyhow!("Failed to edit message: {}", e)),
+    }
+
+    Ok(())
+}
+
+async fn show_goodbye_menu(
+    bot: Bot,
+    msg: &Message,
+    welcome_service: WelcomeService,
+) -> Result<()> {
+    let settings = welcome_service.get_settings(msg.chat.id);
+
+    let text = format!(
+        "👋 <b>Goodbye Message</b>\n\n\
+        📊 Status: {}\n\n\
+        Current message:\n\
+        <code>{}</code>\n\n\
+        Posted when a member leaves or is removed by an admin. \
+        Members who leave before verifying get no goodbye; their pending verification is cleaned up right away.",
+        if settings.goodbye_enabled {
+            "🟢 Enabled"
+        } else {
+            "🔴 Disabled"
+        },
+        teloxide::utils::html::escape(
+            settings
+                .goodbye_message
+                .as_deref()
+                .unwrap_or("Use default goodbye message")
+        )
+    );
+
+    let keyboard = InlineKeyboardMarkup::new(vec![
+        vec![InlineKeyboardButton::callback(
+            if settings.goodbye_enabled {
+                "🔴 Disable Goodbye"
+            } else {
+                "🟢 Enable Goodbye"
+            },
+            "welcome_goodbye_toggle",
+        )],
+        vec![InlineKeyboardButton::callback(
+            "✏️ Set Goodbye Message",
+            "welcome_goodbye_set",
+        )],
+        vec![InlineKeyboardButton::callback(
+            "🔄 Reset to Default",
+            "welcome_goodbye_reset",
+        )],
+        vec![InlineKeyboardButton::callback(
+            "↩️ Back",
+            "welcome_back_to_main",
+        )],
+    ]);
+
+    match bot
+        .edit_message_text(msg.chat.id, msg.id, text)
+        .parse_mode(ParseMode::Html)
+        .reply_markup(keyboard)
+        .await
+    {
+        Ok(_) => {}
+        Err(e) if e.to_string().contains("message is not modified") => {}
+        Err(e) => return Err(anyhow::anyhow!("Failed to edit message: {}", e)),
+    }
+
+    Ok(())
+}
+
+async fn show_raid_menu(bot: Bot, msg: &Message, welcome_service: WelcomeService) -> Result<()> {
+    let settings = welcome_service.get_settings(msg.chat.id);
+    let lockdown = welcome_service.get_active_lockdown(msg.chat.id);
+
+    let lockdown_line = match &lockdown {
+        Some(lockdown) => format!(
+            "🔒 <b>Lockdown active</b> until {} UTC ({} joins detected, {} members held)",
+            chrono::DateTime::from_timestamp(lockdown.until, 0)
+                .map(|time| time.format("%H:%M").to_string())
+                .unwrap_or_else(|| "unknown".to_string()),
+            lockdown.joins_detected,
+            lockdown.restricted_users.len()
+        ),
+        None => "🔓 No lockdown active".to_string(),
+    };
+
+    let text = format!(
+        "🚨 <b>Raid Protection</b>\n\n\
+        📊 Status: {}\n\
+        👥 Trigger: <b>{}</b> joins within {}s\n\
+        ⏱️ Lockdown length: <b>{}</b>\n\n\
+        {}\n\n\
+        During a lockdown new members get a stricter challenge with a single attempt and a shorter timeout. \
+        If verification is off they stay muted until the lockdown ends. \
+        Admins are alerted in the group and by DM.",
+        if settings.raid_protection_enabled {
+            "🟢 Enabled"
+        } else {
+            "🔴 Disabled"
+        },
+        settings.raid_join_threshold,
+        RAID_WINDOW_SECONDS,
+        format_timeout_display(settings.raid_lockdown_minutes * 60),
+        lockdown_line
+    );
+
+    let threshold_button = |threshold: u32| {
+        let label = if settings.raid_join_threshold == threshold {
+            format!("✅ {}", threshold)
+        } else {
+            threshold.to_string()
+        };
+        InlineKeyboardButton::callback(label, format!("welcome_raid_threshold_{}", threshold))
+    };
+    let duration_button = |label: &str, minutes: u64| {
+        let label = if settings.raid_lockdown_minutes == minutes {
+            format!("✅ {}", label)
+        } else {
+            label.to_string()
+        };
+        InlineKeyboardButton::callback(label, format!("welcome_raid_duration_{}", minutes))
+    };
+
+    let mut rows = vec![
+        vec![InlineKeyboardButton::callback(
+            if settings.raid_protection_enabled {
+                "🔴 Disable Raid Protection"
+            } else {
+                "🟢 Enable Raid Protection"
+            },
+            "welcome_raid_toggle",
+        )],
+        vec![
+            threshold_button(5),
+            threshold_button(10),
+            threshold_button(20),
+            threshold_button(50),
+        ],
+        vec![
+            duration_button("10m", 10),
+            duration_button("30m", 30),
+            duration_button("1h", 60),
+            duration_button("6h", 360),
+        ],
+    ];
+    if lockdown.is_some() {
+        rows.push(vec![InlineKeyboardButton::callback(
+            "🔓 Lift Lockdown",
+            "welcome_raid_lift",
+        )]);
+    }
+    rows.push(vec![InlineKeyboardButton::callback(
+        "↩️ Back",
+        "welcome_back_to_main",
+    )]);
+
+    match bot
+        .edit_message_text(msg.chat.id, msg.id, text)
+        .parse_mode(ParseMode::Html)
+        .reply_markup(InlineKeyboardMarkup::new(rows))
+        .await
+    {
+        Ok(_) => {}
+        Err(e) if e.to_string().contains("message is not modified") => {}
+        Err(e) => return Err(anyhow::anyhow!("Failed to edit message: {}", e)),
+    }
+
+    Ok(())
+}
+
+async fn show_spammer_menu(
+    bot: Bot,
+    msg: &Message,
+    welcome_service: WelcomeService,
+) -> Result<()> {
+    let settings = welcome_service.get_settings(msg.chat.id);
+
+    let text = format!(
+        "🚫 <b>Known Spammers</b>\n\n\
+        📊 When a listed user joins: <b>{}</b>\n\
+        📋 Users on the list: <b>{}</b>\n\
+        📥 Imported list: <b>{}</b>\n\
+        🌐 Bans from other groups: <b>{}</b>\n\n\
+        The list is shared by every group using this bot. It is loaded from the bot's spammer \
+        list file and grows whenever an admin bans someone through a moderation report or a \
+        spammer review. Bans made in this group always count; the imported list and bans from \
+        other groups only count once you trust them below.\n\n\
+        <i>Held users stay muted until an admin taps Allow or Ban.</i>",
+        describe_spammer_action(settings.spammer_action),
+        welcome_service.known_spammer_count(),
+        if settings.trust_imported_spammers { "Trusted" } else { "Ignored" },
+        if settings.trust_other_group_bans { "Trusted" } else { "Ignored" }
+    );
+
+    let action_button = |action: SpammerAction, value: &str| {
+        let label = describe_spammer_action(action);
+        let label = if settings.spammer_action == action {
+            format!("✅ {}", label)
+        } else {
+            label.to_string()
+        };
+        vec![InlineKeyboardButton::callback(
+            label,
+            format!("welcome_spammer_action_{}", value),
+        )]
+    };
+
+    let rows = vec![
+        action_button(SpammerAction::Review, "review"),
+        action_button(SpammerAction::Ban, "ban"),
+        action_button(SpammerAction::Off, "off"),
+        vec![InlineKeyboardButton::callback(
+            if settings.trust_imported_spammers {
+                "🔴 Ignore Imported List"
+            } else {
+                "🟢 Trust Imported List"
+            },
+            "welcome_spammer_trust_imported",
+        )],
+        vec![InlineKeyboardButton::callback(
+            if settings.trust_other_group_bans {
+                "🔴 Ignore Other Groups' Bans"
+            } else {
+                "🟢 Trust Other Groups' Bans"
+            },
+            "welcome_spammer_trust_groups",
+        )],
+        vec![InlineKeyboardButton::callback(
+            "↩️ Back",
+            "welcome_back_to_main",
+        )],
+    ];
+
+    match bot
+        .edit_message_text(msg.chat.id, msg.id, text)
+        .parse_mode(ParseMode::Html)
+        .reply_markup(InlineKeyboardMarkup::new(rows))
+        .await
+    {
+        Ok(_) => {}
+        Err(e) if e.to_string().contains("message is not modified") => {}
+        Err(e) => return Err(anyhow::anyhow!("Failed to edit message: {}", e)),
+    }
+
+    Ok(())
+}
+
+async fn show_token_gate_menu(bot: Bot, msg: &Message, bot_deps: &BotDependencies) -> Result<()> {
+    let settings = bot_deps.welcome_service.get_settings(msg.chat.id);
+    let role_settings = bot_deps.nft_gate.get_settings(msg.chat.id);
+
+    let token_requirement = match &settings.token_gate {
+        Some(gate) => format!(
+            "🟢 Hold at least <b>{} {}</b>",
+            gate.min_amount,
+            teloxide::utils::html::escape(&gate.symbol)
+        ),
+        None => "🔴 Disabled".to_string(),
+    };
+    let nft_requirement = match &settings.nft_gate {
+        Some(requirement) => format!(
+            "🟢 Hold at least <b>{}</b>",
+            teloxide::utils::html::escape(&describe_requirement(requirement))
+        ),
+        None => "🔴 Disabled".to_string(),
+    };
+    let roles = if role_settings.rules.is_empty() {
+        "<i>No roles configured.</i>".to_string()
+    } else {
+        role_settings
+            .rules
+            .iter()
+            .enumerate()
+            .map(|(i, rule)| {
+                format!(
+                    "{}. {} — {}",
+                    i + 1,
+                    describe_role(rule.role),
+                    teloxide::utils::html::escape(&describe_requirement(&rule.requirement))
+                )
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    };
+    let text = format!(
+        "🪙 <b>Token &amp; NFT Gate</b>\n\n\
+        🪙 Token: {}\n\
+        🖼️ NFT: {}\n\n\
+        After the other verification steps, new members link their wallet with /loginuser \
+        and the bot checks their holdings before unmuting. Members who don't qualify within \
+        the verification timeout ({}) are removed, and holders are re-checked every hour.\n\n\
+        <b>Holder roles</b>\n{}\n\n\
+        ⭐ VIP members can trigger VIP-only filters. 🤖 Sponsored AI members can use /g \
+        without using up the sponsor quota. Members claim roles with /roles.\n\n\
+        <i>Gates require the welcome feature to be enabled.</i>",
+        token_requirement,
+        nft_requirement,
+        format_timeout_display(settings.verification_timeout),
+        roles
+    );
+
+    let mut rows = vec![vec![InlineKeyboardButton::callback(
+        "✏️ Set Token & Minimum",
+        "welcome_tokengate_set",
+    )]];
+    if settings.token_gate.is_some() {
+        rows.push(vec![InlineKeyboardButton::callback(
+            "🗑️ Remove Token Gate",
+            "welcome_tokengate_remove",
+        )]);
+    }
+    rows.push(vec![InlineKeyboardButton::callback(
+        "🖼️ Set NFT Collection",
+        "welcome_nftgate_set",
+    )]);
+    if settings.nft_gate.is_some() {
+        rows.push(vec![InlineKeyboardButton::callback(
+            "🗑️ Remove NFT Gate",
+            "welcome_nftgate_remove",
+        )]);
+    }
+    if role_settings.rules.len() < MAX_ROLE_RULES {
+        rows.push(
+            MemberRole::ALL
+                .into_iter()
+                .map(|role| {
+                    InlineKeyboardButton::callback(
+                        format!("➕ {}", describe_role(role)),
+                        format!("welcome_nftrole_add_{}", role.as_str()),
+                    )
+                })
+                .collect(),
+        );
+    }
+    for i in 0..role_settings.rules.len() {
+        rows.push(vec![InlineKeyboardButton::callback(
+            format!("🗑️ Remove role {}", i + 1),
+            format!("welcome_nftrole_remove_{}", i),
+        )]);
+    }
+    rows.push(vec![InlineKeyboardButton::callback(
+        "↩️ Back",
+        "welcome_back_to_main",
+    )]);
+
+    match bot
+        .edit_message_text(msg.chat.id, msg.id, text)
+        .parse_mode(ParseMode::Html)
+        .reply_markup(InlineKeyboardMarkup::new(rows))
+        .await
+    {
+        Ok(_) => {}
+        Err(e) if e.to_string().contains("message is not modified") => {}
+        Err(e) => return Err(anyhow::anyhow!("Failed to edit message: {}", e)),
+    }
+
+    Ok(())
+}
+
+async fn start_collection_input(
+    bot: &Bot,
+    msg: &Message,
+    welcome_service: &WelcomeService,
+    prompt: &str,
+    input_type: &str,
+) -> Result<()> {
+    let keyboard = InlineKeyboardMarkup::new(vec![vec![InlineKeyboardButton::callback(
+        "↩️ Back",
+        "welcome_tokengate",
+    )]]);
+    bot.edit_message_text(
+        msg.chat.id,
+        msg.id,
+        format!("{}\n\n<i>Send /cancel to cancel.</i>", prompt),
+    )
+    .parse_mode(ParseMode::Html)
+    .reply_markup(keyboard)
+    .await?;
+    welcome_service
+        .store_input_state(msg.chat.id, input_type)
+        .await?;
+    Ok(())
+}
+
+/// Handle a collection reply for the NFT entry gate (`role` is None) or a holder role.
+async fn handle_collection_input(
+    bot: Bot,
+    bot_deps: BotDependencies,
+    msg: &Message,
+    group_id: ChatId,
+    text: &str,
+    role: Option<MemberRole>,
+) -> Result<bool> {
+    if text == "/cancel" {
+        bot_deps.welcome_service.clear_input_state(group_id)?;
+        send_message(msg.clone(), bot, "❌ Collection input cancelled.".to_string()).await?;
+        return Ok(true);
+    }
+
+    let (collection_id, min_count) = match parse_collection_input(text) {
+        Ok(parsed) => parsed,
+        Err(e) => {
+            send_message(msg.clone(), bot, format!("❌ {} Use /cancel to cancel.", e)).await?;
+            return Ok(true);
+        }
+    };
+
+    let collection_name = match bot_deps.panora.aptos.get_collection_name(&collection_id).await {
+        Ok(Some(name)) => name,
+        Ok(None) => {
+            send_message(
+                msg.clone(),
+                bot,
+                "❌ No collection found at that address. Use /cancel to cancel.".to_string(),
+            )
+            .await?;
+            return Ok(true);
+        }
+        Err(e) => {
+            log::error!("Failed to look up collection {}: {}", collection_id, e);
+            send_message(
+                msg.clone(),
+                bot,
+                "❌ Couldn't reach the Aptos indexer, please try again. Use /cancel to cancel."
+                    .to_string(),
+            )
+            .await?;
+            return Ok(true);
+        }
+    };
+
+    let requirement = CollectionRequirement {
+        collection_id,
+        collection_name,
+        min_count,
+    };
+    let described = teloxide::utils::html::escape(&describe_requirement(&requirement));
+
+    let summary = match role {
+        Some(role) => {
+            let mut role_settings = bot_deps.nft_gate.get_settings(group_id);
+            if role_settings.rules.len() >= MAX_ROLE_RULES {
+                bot_deps.welcome_service.clear_input_state(group_id)?;
+                send_message(
+                    msg.clone(),
+                    bot,
+                    format!("❌ A group can have at most {} holder roles.", MAX_ROLE_RULES),
+                )
+                .await?;
+                return Ok(true);
+            }
+            role_settings.rules.push(RoleRule { role, requirement });
+            bot_deps.nft_gate.save_settings(group_id, &role_settings)?;
+            format!(
+                "✅ <b>{} role added</b>\n\nMembers holding at least <b>{}</b> can claim it with /roles.",
+                describe_role(role),
+                described
+            )
+        }
+        None => {
+            let mut settings = bot_deps.welcome_service.get_settings(group_id);
+            settings.nft_gate = Some(requirement);
+            settings.last_updated = chrono::Utc::now().timestamp();
+            bot_deps.welcome_service.save_settings(group_id, settings)?;
+            format!(
+                "✅ <b>NFT gate set</b>\n\nNew members must hold at least <b>{}</b>.",
+                described
+            )
+        }
+    };
+    bot_deps.welcome_service.clear_input_state(group_id)?;
+
+    send_html_message(msg.clone(), bot, summary).await?;
+
+    Ok(true)
+}
+
+async fn handle_token_gate_input(
+    bot: Bot,
+    bot_deps: BotDependencies,
+    msg: &Message,
+    group_id: ChatId,
+    text: &str,
+) -> Result<bool> {
+    if text == "/cancel" {
+        bot_deps.welcome_service.clear_input_state(group_id)?;
+        send_message(msg.clone(), bot, "❌ Token gate input cancelled.".to_string()).await?;
+        return Ok(true);
+    }
+
+    let (symbol, min_amount) = match parse_token_gate_input(text) {
+        Ok(parsed) => parsed,
+        Err(e) => {
+            send_message(msg.clone(), bot, format!("❌ {} Use /cancel to cancel.", e)).await?;
+            return Ok(true);
+        }
+    };
+
+    let gate = if symbol == "APT" || symbol == "APTOS" {
+        TokenGate {
+            token_type: "0x1::aptos_coin::AptosCoin".to_string(),
+            symbol: "APT".to_string(),
+            decimals: 8,
+            min_amount,
+        }
+    } else {
+        let token = match bot_deps.panora.get_token_by_symbol(&symbol).await {
+            Ok(token) => token,
+            Err(e) => {
+                log::error!("Failed to look up token {} for token gate: {}", symbol, e);
+                send_message(
+                    msg.clone(),
+                    bot,
+                    format!("❌ Token {} not found. Use /cancel to cancel.", symbol),
+                )
+                .await?;
+                return Ok(true);
+            }
+        };
+        TokenGate {
+            token_type: token
+                .token_address
+                .clone()
+                .unwrap_or_else(|| token.fa_address.clone()),
+            symbol: token.symbol.clone(),
+            decimals: token.decimals,
+            min_amount,
+        }
+    };
+
+    let summary = format!(
+        "✅ <b>Token gate set</b>\n\nNew members must hold at least <b>{} {}</b>.",
+        gate.min_amount,
+        teloxide::utils::html::escape(&gate.symbol)
+    );
+    let mut settings = bot_deps.welcome_service.get_settings(group_id);
+    settings.token_gate = Some(gate);
+    settings.last_updated = chrono::Utc::now().timestamp();
+    bot_deps.welcome_service.save_settings(group_id, settings)?;
+    bot_deps.welcome_service.clear_input_state(group_id)?;
+
+    send_html_message(msg.clone(), bot, summary).await?;
+
+    Ok(true)
+}
+
+async fn show_auto_delete_menu(
+    bot: Bot,
+    msg: &Message,
+    welcome_service: WelcomeService,
+) -> Result<()> {
+    let settings = welcome_service.get_settings(msg.chat.id);
+
+    let text = format!(
+        "🧹 <b>Auto-Delete</b>\n\n\
+        🤖 Bot messages: deleted <b>{}</b>\n\
+        🔔 Join/leave notices: deleted <b>{}</b>\n\n\
+        Bot messages are the welcome once verification finishes (passed or failed) and goodbye messages. \
+        Join/leave notices are Telegram's own \"X joined the group\" messages. \
+        The bot needs the <i>Delete messages</i> admin right.",
+        format_ttl_display(settings.bot_message_ttl),
+        format_ttl_display(settings.service_message_ttl)
+    );
+
+    let ttl_button = |prefix: &str, current: Option<u64>, label: &str, seconds: u64| {
+        let selected = current.unwrap_or(0) == seconds;
+        let label = if selected {
+            format!("✅ {}", label)
+        } else {
+            label.to_string()
+        };
+        InlineKeyboardButton::callback(label, format!("{}{}", prefix, seconds))
+    };
+    let bot_ttl = settings.bot_message_ttl;
+    let service_ttl = settings.service_message_ttl;
+
+    let keyboard = InlineKeyboardMarkup::new(vec![
+        vec![
+            ttl_button("welcome_ttl_bot_", bot_ttl, "🤖 Keep", 0),
+            ttl_button("welcome_ttl_bot_", bot_ttl, "1m", 60),
+            ttl_button("welcome_ttl_bot_", bot_ttl, "5m", 300),
+            ttl_button("welcome_ttl_bot_", bot_ttl, "1h", 3600),
+        ],
+        vec![
+            ttl_button("welcome_ttl_service_", service_ttl, "🔔 Keep", 0),
+            ttl_button("welcome_ttl_service_", service_ttl, "15s", 15),
+            ttl_button("welcome_ttl_service_", service_ttl, "1m", 60),
+            ttl_button("welcome_ttl_service_", service_ttl, "5m", 300),
+        ],
+        vec![InlineKeyboardButton::callback(
+            "↩️ Back",
+            "welcome_back_to_main",
+        )],
+    ]);
+
+    match bot
+        .edit_message_text(msg.chat.id, msg.id, text)
+        .parse_mode(ParseMode::Html)
+        .reply_markup(keyboard)
+        .await
+    {
+        Ok(_) => {}
+        Err(e) if e.to_string().contains("message is not modified") => {}
+        Err(e) => return Err(anyhow::anyhow!("Failed to edit message: {}", e)),
+    }
+
     Ok(())
 }
 
-async fn show_welcome_settings_menu(
+async fn show_rich_welcome_menu(
     bot: Bot,
     msg: &Message,
     welcome_service: WelcomeService,
 ) -> Result<()> {
     let settings = welcome_service.get_settings(msg.chat.id);
-    let stats = welcome_service.get_stats(msg.chat.id);
 
-    let status_text = if settings.enabled {
-        "🟢 Enabled"
-    } else {
-        "🔴 Disabled"
+    let media_text = match settings.media.as_ref().map(|media| media.kind) {
+        Some(WelcomeMediaKind::Photo) => "🖼️ Photo",
+        Some(WelcomeMediaKind::Animation) => "🎞️ GIF",
+        None => "None",
+    };
+    let mut links_text = String::new();
+    if settings.url_buttons.is_empty() {
+        links_text.push_str("<i>No link buttons.</i>\n");
+    }
+    for button in &settings.url_buttons {
+        links_text.push_str(&format!(
+            "• {} → {}\n",
+            teloxide::utils::html::escape(&button.text),
+            teloxide::utils::html::escape(&button.url)
+        ));
+    }
+    let topic_text = match settings.topic_id {
+        Some(topic_id) => format!("Topic #{}", topic_id),
+        None => "General".to_string(),
     };
-    let timeout_text = format_timeout_display(settings.verification_timeout);
 
     let text = format!(
-        "👋 <b>Welcome Settings</b>\n\n\
-        📊 Status: {}\n\
-        ⏰ Verification Timeout: {}\n\
-        📈 Success Rate: {:.1}%\n\
-        ✅ Total Verifications: {}\n\
-        ❌ Failed Verifications: {}\n\n\
-        Configure anti-spam protection for new group members.",
-        status_text,
-        timeout_text,
-        stats.success_rate,
-        stats.total_verifications,
-        stats.failed_verifications
+        "🎨 <b>Media, Buttons &amp; Topic</b>\n\n\
+        🖼️ Media: {}\n\
+        📍 Posted in: {}\n\n\
+        🔗 <b>Link buttons</b> ({}/{}):\n{}\n\
+        Media is sent just above the welcome message. Link buttons are shown under it and stay after verification. \
+        In forum groups, open these settings inside a topic and tap <b>Post in This Topic</b> to move welcomes there.",
+        media_text,
+        topic_text,
+        settings.url_buttons.len(),
+        MAX_URL_BUTTONS,
+        links_text
     );
 
+    let mut media_row = vec![InlineKeyboardButton::callback(
+        "🖼️ Set Media",
+        "welcome_media_set",
+    )];
+    if settings.media.is_some() {
+        media_row.push(InlineKeyboardButton::callback(
+            "🗑️ Remove Media",
+            "welcome_media_remove",
+        ));
+    }
+    let mut links_row = vec![InlineKeyboardButton::callback(
+        "🔗 Set Link Buttons",
+        "welcome_links_set",
+    )];
+    if !settings.url_buttons.is_empty() {
+        links_row.push(InlineKeyboardButton::callback(
+            "🗑️ Clear Links",
+            "welcome_links_clear",
+        ));
+    }
+
     let keyboard = InlineKeyboardMarkup::new(vec![
+        media_row,
+        links_row,
         vec![InlineKeyboardButton::callback(
-            if settings.enabled {
-                "🔴 Disable Welcome"
-            } else {
-                "🟢 Enable Welcome"
-            },
-            "welcome_toggle",
-        )],
-        vec![InlineKeyboardButton::callback(
-            "✏️ Custom Message",
-            "welcome_custom_message",
-        )],
-        vec![InlineKeyboardButton::callback(
-            "⏰ Set Timeout",
-            "welcome_timeout",
-        )],
-        vec![InlineKeyboardButton::callback(
-            "📊 View Statistics",
-            "welcome_stats",
-        )],
-        vec![InlineKeyboardButton::callback(
-            "🔄 Reset Statistics",
-            "welcome_reset_stats",
+            "📍 Post in This Topic",
+            "welcome_topic_here",
         )],
         vec![InlineKeyboardButton::callback(
-            "↩️ Back to Group Settings",
-            "welcome_back_to_groupsettings",
+            "↩️ Back",
+            "welcome_back_to_main",
         )],
     ]);
 
@@ -152,125 +1461,297 @@ async fn show_welcome_settings_menu(
         .reply_markup(keyboard)
         .await
     {
-        Ok(_) => log::info!("Welcome settings menu updated successfully"),
-        Err(e) => {
-            if e.to_string().contains("message is not modified") {
-                log::info!("Welcome settings menu unchanged, skipping update");
-            } else {
-                return Err(anyhow::anyhow!("Failed to edit message: {}", e));
-            }
-        }
+        Ok(_) => {}
+        Err(e) if e.to_string().contains("message is not modified") => {}
+        Err(e) => return Err(anyhow::anyhow!("Failed to edit message: {}", e)),
     }
 
     Ok(())
 }
 
-async fn toggle_welcome_feature(
+async fn start_rich_input(
     bot: Bot,
     msg: &Message,
     welcome_service: WelcomeService,
+    input_type: &str,
+    prompt: &str,
 ) -> Result<()> {
-    let mut settings = welcome_service.get_settings(msg.chat.id);
-    settings.enabled = !settings.enabled;
-    settings.last_updated = chrono::Utc::now().timestamp();
+    let keyboard = InlineKeyboardMarkup::new(vec![vec![InlineKeyboardButton::callback(
+        "↩️ Back",
+        "welcome_rich",
+    )]]);
 
-    welcome_service.save_settings(msg.chat.id, settings.clone())?;
+    bot.edit_message_text(msg.chat.id, msg.id, prompt)
+        .parse_mode(ParseMode::Html)
+        .reply_markup(keyboard)
+        .await?;
 
-    // Always refresh the menu to show the new state
-    show_welcome_settings_menu(bot, msg, welcome_service).await?;
+    welcome_service
+        .store_input_state(msg.chat.id, input_type)
+        .await?;
 
     Ok(())
 }
 
-async fn show_custom_message_menu(
+async fn handle_media_input(
+    bot: Bot,
+    bot_deps: BotDependencies,
+    msg: &Message,
+    group_id: ChatId,
+) -> Result<bool> {
+    if msg.text().map(str::trim) == Some("/cancel") {
+        bot_deps.welcome_service.clear_input_state(group_id)?;
+        send_message(msg.clone(), bot, "❌ Welcome media input cancelled.".to_string()).await?;
+        return Ok(true);
+    }
+
+    // Animations also carry a document, so check them before photos
+    let media = if let Some(animation) = msg.animation() {
+        WelcomeMedia {
+            kind: WelcomeMediaKind::Animation,
+            file_id: animation.file.id.0.clone(),
+        }
+    } else if let Some(photo) = msg.photo().and_then(|sizes| sizes.last()) {
+        WelcomeMedia {
+            kind: WelcomeMediaKind::Photo,
+            file_id: photo.file.id.0.clone(),
+        }
+    } else {
+        send_message(
+            msg.clone(),
+            bot,
+            "❌ Please send a photo or GIF. Use /cancel to cancel.".to_string(),
+        )
+        .await?;
+        return Ok(true);
+    };
+
+    let mut settings = bot_deps.welcome_service.get_settings(group_id);
+    settings.media = Some(media);
+    settings.last_updated = chrono::Utc::now().timestamp();
+    bot_deps.welcome_service.save_settings(group_id, settings)?;
+    bot_deps.welcome_service.clear_input_state(group_id)?;
+
+    send_html_message(
+        msg.clone(),
+        bot,
+        "✅ <b>Welcome media saved</b>\n\nIt will be posted with every new welcome message.".to_string(),
+    )
+    .await?;
+
+    Ok(true)
+}
+
+async fn handle_url_buttons_input(
+    bot: Bot,
+    bot_deps: BotDependencies,
+    msg: &Message,
+    group_id: ChatId,
+    text: &str,
+) -> Result<bool> {
+    if text == "/cancel" {
+        bot_deps.welcome_service.clear_input_state(group_id)?;
+        send_message(msg.clone(), bot, "❌ Link button input cancelled.".to_string()).await?;
+        return Ok(true);
+    }
+
+    let buttons = match parse_url_buttons(text) {
+        Ok(buttons) => buttons,
+        Err(e) => {
+            send_message(msg.clone(), bot, format!("❌ {} Use /cancel to cancel.", e)).await?;
+            return Ok(true);
+        }
+    };
+
+    let count = buttons.len();
+    let mut settings = bot_deps.welcome_service.get_settings(group_id);
+    settings.url_buttons = buttons;
+    settings.last_updated = chrono::Utc::now().timestamp();
+    bot_deps.welcome_service.save_settings(group_id, settings)?;
+    bot_deps.welcome_service.clear_input_state(group_id)?;
+
+    send_html_message(
+        msg.clone(),
+        bot,
+        format!(
+            "✅ <b>{} link button(s) saved</b>\n\nThey will appear under every new welcome message.",
+            count
+        ),
+    )
+    .await?;
+
+    Ok(true)
+}
+
+async fn show_rules_quiz_menu(
     bot: Bot,
     msg: &Message,
     welcome_service: WelcomeService,
 ) -> Result<()> {
     let settings = welcome_service.get_settings(msg.chat.id);
-    let current_message = if let Some(ref msg) = settings.custom_message {
-        msg
-    } else {
-        "Use default welcome message"
-    };
+
+    let mut questions = String::new();
+    if settings.quiz.is_empty() {
+        questions.push_str("<i>No quiz questions.</i>");
+    }
+    for (i, question) in settings.quiz.iter().enumerate() {
+        questions.push_str(&format!(
+            "{}. {}\n   ✅ {}\n   ❌ {}\n",
+            i + 1,
+            teloxide::utils::html::escape(&question.question),
+            teloxide::utils::html::escape(&question.correct_answer),
+            teloxide::utils::html::escape(&question.wrong_answers.join(", "))
+        ));
+    }
 
     let text = format!(
-        "✏️ <b>Custom Welcome Message</b>\n\n\
-            Current message:\n\
-            <code>{}</code>\n\n\
-            💡 <i>Use Telegram MarkdownV2 (e.g., <code>*bold*</code>, <code>_italic_</code>, <code>`code`</code>) or plain text. Double asterisks <code>**like this**</code> are not supported.</i>\n\n\
-            Available placeholders:\n\
-            • {{username}} - @username (creates clickable mention)\n\
-            • {{group_name}} - Group name\n\
-            • {{timeout}} - Verification timeout in minutes\n\n\
-            <b>Examples:</b>\n\
-            • <code>Hello {{username}}! Welcome to {{group_name}}! 👋</code>\n\
-            • <code>*Bold welcome*</code> to <code>{{group_name}}</code>, <code>{{username}}</code>!\n\
-            • <code>Use `code` for inline formatting</code>\n\n\
-            To set a custom message, reply to this message with your text.\n\
-            To use the default message, click 'Reset to Default'.",
-        teloxide::utils::html::escape(current_message)
+        "📜 <b>Rules &amp; Entry Quiz</b>\n\n\
+        Rules acceptance: {}\n\n\
+        After the challenge, new members are shown the same rules as /rules and must tap \
+        <b>I agree</b>. If quiz questions are set, they must then answer each one. \
+        Members stay muted until they pass, and the verification timeout still applies.\n\n\
+        <b>Quiz questions</b> ({}/{}):\n{}",
+        if settings.require_rules {
+            "🟢 Required"
+        } else {
+            "🔴 Off"
+        },
+        settings.quiz.len(),
+        MAX_QUIZ_QUESTIONS,
+        questions
     );
 
-    let keyboard = InlineKeyboardMarkup::new(vec![
-        vec![InlineKeyboardButton::callback(
-            "✏️ Set Custom Message",
-            "welcome_set_custom_message",
-        )],
-        vec![InlineKeyboardButton::callback(
-            "🔄 Reset to Default",
-            "welcome_reset_message",
-        )],
-        vec![InlineKeyboardButton::callback(
-            "↩️ Back",
-            "welcome_back_to_main",
-        )],
-    ]);
+    let mut rows = vec![vec![InlineKeyboardButton::callback(
+        if settings.require_rules {
+            "🔴 Don't Require Rules"
+        } else {
+            "🟢 Require Rules"
+        },
+        "welcome_rules_toggle",
+    )]];
+    if settings.quiz.len() < MAX_QUIZ_QUESTIONS {
+        rows.push(vec![InlineKeyboardButton::callback(
+            "➕ Add Question",
+            "welcome_quiz_add",
+        )]);
+    }
+    for i in 0..settings.quiz.len() {
+        rows.push(vec![InlineKeyboardButton::callback(
+            format!("🗑️ Remove Question {}", i + 1),
+            format!("welcome_quiz_remove_{}", i),
+        )]);
+    }
+    rows.push(vec![InlineKeyboardButton::callback(
+        "↩️ Back",
+        "welcome_back_to_main",
+    )]);
 
-    bot.edit_message_text(msg.chat.id, msg.id, text)
+    match bot
+        .edit_message_text(msg.chat.id, msg.id, text)
         .parse_mode(ParseMode::Html)
-        .reply_markup(keyboard)
-        .await?;
+        .reply_markup(InlineKeyboardMarkup::new(rows))
+        .await
+    {
+        Ok(_) => {}
+        Err(e) if e.to_string().contains("message is not modified") => {}
+        Err(e) => return Err(anyhow::anyhow!("Failed to edit message: {}", e)),
+    }
 
     Ok(())
 }
 
-async fn show_timeout_menu(bot: Bot, msg: &Message, welcome_service: WelcomeService) -> Result<()> {
-    let settings = welcome_service.get_settings(msg.chat.id);
-    let current_timeout = settings.verification_timeout;
-
-    let text = format!(
-        "⏰ <b>Verification Timeout</b>\n\n\
-        Current timeout: {}\n\n\
-        Select a new timeout value:",
-        format_timeout_display(current_timeout)
-    );
+async fn start_quiz_question_input(
+    bot: Bot,
+    msg: &Message,
+    welcome_service: WelcomeService,
+) -> Result<()> {
+    let text = "📝 <b>Add Quiz Question</b>\n\n\
+        Reply with the question and its answers, each on its own line:\n\
+        1. The question\n\
+        2. The correct answer\n\
+        3. One to three wrong answers\n\n\
+        <b>Example:</b>\n\
+        <code>Where should links be shared?\n\
+        In the #links topic\n\
+        In members' DMs\n\
+        Anywhere</code>\n\n\
+        Answer buttons are shuffled for every member.\n\n\
+        <i>Send /cancel to cancel.</i>";
 
-    let keyboard = InlineKeyboardMarkup::new(vec![
-        vec![
-            InlineKeyboardButton::callback("30s", "welcome_timeout_set_30"),
-            InlineKeyboardButton::callback("1m", "welcome_timeout_set_60"),
-            InlineKeyboardButton::callback("2m", "welcome_timeout_set_120"),
-        ],
-        vec![
-            InlineKeyboardButton::callback("3m", "welcome_timeout_set_180"),
-            InlineKeyboardButton::callback("4m", "welcome_timeout_set_240"),
-            InlineKeyboardButton::callback("5m", "welcome_timeout_set_300"),
-        ],
-        vec![InlineKeyboardButton::callback(
-            "↩️ Back",
-            "welcome_back_to_main",
-        )],
-    ]);
+    let keyboard = InlineKeyboardMarkup::new(vec![vec![InlineKeyboardButton::callback(
+        "↩️ Back",
+        "welcome_rules",
+    )]]);
 
     bot.edit_message_text(msg.chat.id, msg.id, text)
         .parse_mode(ParseMode::Html)
         .reply_markup(keyboard)
         .await?;
 
+    welcome_service
+        .store_input_state(msg.chat.id, "quiz_question_input")
+        .await?;
+
     Ok(())
 }
 
+async fn handle_quiz_question_input(
+    bot: Bot,
+    bot_deps: BotDependencies,
+    msg: &Message,
+    group_id: ChatId,
+    text: &str,
+) -> Result<bool> {
+    if text == "/cancel" {
+        bot_deps.welcome_service.clear_input_state(group_id)?;
+        send_message(
+            msg.clone(),
+            bot,
+            "❌ Quiz question input cancelled.".to_string(),
+        )
+        .await?;
+        return Ok(true);
+    }
+
+    let question = match parse_quiz_question(text) {
+        Ok(question) => question,
+        Err(e) => {
+            send_message(msg.clone(), bot, format!("❌ {} Use /cancel to cancel.", e)).await?;
+            return Ok(true);
+        }
+    };
+
+    let mut settings = bot_deps.welcome_service.get_settings(group_id);
+    if settings.quiz.len() >= MAX_QUIZ_QUESTIONS {
+        bot_deps.welcome_service.clear_input_state(group_id)?;
+        send_message(
+            msg.clone(),
+            bot,
+            format!("❌ The quiz already has {} questions.", MAX_QUIZ_QUESTIONS),
+        )
+        .await?;
+        return Ok(true);
+    }
+
+    settings.quiz.push(question);
+    settings.last_updated = chrono::Utc::now().timestamp();
+    let count = settings.quiz.len();
+    bot_deps.welcome_service.save_settings(group_id, settings)?;
+    bot_deps.welcome_service.clear_input_state(group_id)?;
+
+    send_html_message(
+        msg.clone(),
+        bot,
+        format!(
+            "✅ <b>Quiz question added</b> ({}/{})\n\nNew members will answer it after accepting the rules.",
+            count, MAX_QUIZ_QUESTIONS
+        ),
+    )
+    .await?;
+
+    Ok(true)
+}
+
 async fn set_welcome_timeout(
     bot: Bot,
     msg: &Message,
@@ -296,6 +1777,10 @@ async fn show_welcome_stats(
 ) -> Result<()> {
     let stats = welcome_service.get_stats(msg.chat.id);
     let settings = welcome_service.get_settings(msg.chat.id);
+    let activity = welcome_service.get_member_activity(msg.chat.id);
+    let today = chrono::Utc::now().date_naive();
+    let (joins_7d, leaves_7d) = member_activity_in_last_days(&activity, 7, today);
+    let (joins_30d, leaves_30d) = member_activity_in_last_days(&activity, 30, today);
 
     let last_verification = if let Some(timestamp) = stats.last_verification {
         let dt = chrono::DateTime::from_timestamp(timestamp, 0).unwrap_or_default();
@@ -313,6 +1798,9 @@ async fn show_welcome_stats(
         🕐 Last Verification: {}\n\
         ⏰ Current Timeout: {}\n\
         🕐 Last Updated: {}\n\n\
+        👥 <b>Members</b> (7d / 30d / all time)\n\
+        ➕ Joined: {} / {} / {}\n\
+        ➖ Left: {} / {} / {}\n\n\
         These statistics help you monitor the effectiveness of your anti-spam protection.",
         stats.total_verifications,
         stats.successful_verifications,
@@ -322,7 +1810,13 @@ async fn show_welcome_stats(
         format_timeout_display(settings.verification_timeout),
         chrono::DateTime::from_timestamp(settings.last_updated, 0)
             .unwrap_or_default()
-            .format("%Y-%m-%d %H:%M:%S UTC")
+            .format("%Y-%m-%d %H:%M:%S UTC"),
+        joins_7d,
+        joins_30d,
+        activity.total_joins,
+        leaves_7d,
+        leaves_30d,
+        activity.total_leaves
     );
 
     let keyboard = InlineKeyboardMarkup::new(vec![
@@ -385,8 +1879,14 @@ async fn start_custom_message_input(
         💡 <i>Use Telegram MarkdownV2 (e.g., <code>*bold*</code>, <code>_italic_</code>, <code>`code`</code>) or plain text. Double asterisks <code>**like this**</code> are not supported.</i>\n\n\
         Available placeholders:\n\
         • {username} - @username (creates clickable mention)\n\
+        • {first_name} / {mention} - First name, plain or as a clickable mention\n\
+        • {user_id} - Telegram user ID\n\
         • {group_name} - Group name\n\
-        • {timeout} - Verification timeout in minutes\n\n\
+        • {member_count} - Number of group members\n\
+        • {date} - Today's date (UTC)\n\
+        • {timeout} - Verification timeout in minutes\n\
+        • {Hi|Hello|Hey} - Random choice\n\
+        • {if username}...{else}...{end} - Condition (also <code>if not username</code>)\n\n\
         <b>Examples:</b>\n\
         • <code>Hello {username}! Welcome to {group_name}! 👋</code>\n\
         • <code>*Bold welcome*</code> to <code>{group_name}</code>, <code>{username}</code>!\n\
@@ -409,7 +1909,10 @@ async fn start_custom_message_input(
     }
 
     // Store the state that we're waiting for custom message input
-    match welcome_service.store_input_state(msg.chat.id).await {
+    match welcome_service
+        .store_input_state(msg.chat.id, "custom_message_input")
+        .await
+    {
         Ok(_) => log::info!("Successfully stored welcome input state"),
         Err(e) => log::error!("Failed to store welcome input state: {}", e),
     }
@@ -475,6 +1978,73 @@ async fn show_main_group_settings(bot: Bot, msg: &Message) -> Result<()> {
     Ok(())
 }
 
+/// Group message from a member who is still verifying. Only the typed answer to
+/// a word challenge counts; everything they send is deleted.
+pub async fn handle_pending_member_message(
+    bot: Bot,
+    msg: Message,
+    bot_deps: BotDependencies,
+) -> Result<()> {
+    let Some(user) = msg.from.as_ref() else {
+        return Ok(());
+    };
+    let chat_id = msg.chat.id;
+    let welcome_service = bot_deps.welcome_service.clone();
+
+    if let Err(e) = bot.delete_message(chat_id, msg.id).await {
+        log::warn!("Failed to delete message from unverified member: {}", e);
+    }
+
+    let Some(verification) = welcome_service.get_pending_verification(chat_id, user.id) else {
+        return Ok(());
+    };
+    let is_word_challenge = verification.stage == VerificationStage::Challenge
+        && verification
+            .challenge
+            .as_ref()
+            .is_some_and(|challenge| challenge.options.is_empty());
+    let Some(text) = msg.text().filter(|_| is_word_challenge) else {
+        return Ok(());
+    };
+
+    let rules_html = crate::bot::handler::group_rules_html(&bot_deps, chat_id);
+    let outcome = welcome_service
+        .handle_verification(
+            &bot,
+            chat_id,
+            user.id,
+            user.id,
+            CaptchaAnswer::Typed(text.to_string()),
+            &rules_html,
+        )
+        .await;
+
+    if let Ok(VerificationOutcome::WrongAnswer { attempts_left }) = outcome {
+        let mut request = bot.send_message(
+            chat_id,
+            format!(
+                "❌ {}, that is not the word in the image. {} attempt(s) left.",
+                teloxide::utils::html::escape(&user.first_name),
+                attempts_left
+            ),
+        );
+        if let Some(thread_id) = msg.thread_id {
+            request = request.message_thread_id(thread_id);
+        }
+        let reply = request.parse_mode(ParseMode::Html).await?;
+        welcome_service.schedule_service_message_deletion(chat_id, reply.id);
+    } else if let Err(e) = outcome {
+        log::error!(
+            "Typed verification failed for user {} in chat {}: {}",
+            user.id.0,
+            chat_id.0,
+            e
+        );
+    }
+
+    Ok(())
+}
+
 pub async fn handle_welcome_message(
     bot: Bot,
     bot_deps: BotDependencies,
@@ -500,7 +2070,7 @@ pub async fn handle_welcome_message(
 
     let user_id = UserId(user_id.unwrap());
 
-    if let Some(_input_state) = bot_deps.welcome_service.get_input_state(group_id) {
+    if let Some(input_state) = bot_deps.welcome_service.get_input_state(group_id) {
         log::info!("Found welcome input state for group: {}", group_id);
         // Only process if the user is an admin
         let is_admin = utils::is_admin(&bot, group_id, user_id).await;
@@ -509,9 +2079,46 @@ pub async fn handle_welcome_message(
             return Ok(false);
         }
 
+        if input_state["type"].as_str() == Some("media_input") {
+            return handle_media_input(bot, bot_deps, msg, group_id).await;
+        }
+
         if let Some(text) = msg.text() {
             let text = text.trim();
             if !text.is_empty() {
+                match input_state["type"].as_str() {
+                    Some("quiz_question_input") => {
+                        return handle_quiz_question_input(bot, bot_deps, msg, group_id, text)
+                            .await;
+                    }
+                    Some("url_buttons_input") => {
+                        return handle_url_buttons_input(bot, bot_deps, msg, group_id, text)
+                            .await;
+                    }
+                    Some("token_gate_input") => {
+                        return handle_token_gate_input(bot, bot_deps, msg, group_id, text)
+                            .await;
+                    }
+                    Some("nft_gate_input") => {
+                        return handle_collection_input(
+                            bot, bot_deps, msg, group_id, text, None,
+                        )
+                        .await;
+                    }
+                    Some(input_type) if input_type.starts_with("nft_role_input:") => {
+                        let role = input_type
+                            .strip_prefix("nft_role_input:")
+                            .and_then(MemberRole::parse);
+                        if role.is_some() {
+                            return handle_collection_input(
+                                bot, bot_deps, msg, group_id, text, role,
+                            )
+                            .await;
+                        }
+                    }
+                    _ => {}
+                }
+
                 if text == "/cancel" {
                     // Cancel the custom message input
                     bot_deps.welcome_service.clear_input_state(group_id)?;
@@ -535,6 +2142,22 @@ pub async fn handle_welcome_message(
                     .trim()
                     .to_string();
 
+                if input_state["type"].as_str() == Some("goodbye_message_input") {
+                    let mut settings = bot_deps.welcome_service.get_settings(msg.chat.id);
+                    settings.goodbye_message = Some(message_text);
+                    settings.last_updated = chrono::Utc::now().timestamp();
+                    bot_deps.welcome_service.save_settings(msg.chat.id, settings)?;
+                    bot_deps.welcome_service.clear_input_state(group_id)?;
+
+                    send_html_message(
+                        msg.clone(),
+                        bot,
+                        "✅ <b>Goodbye message updated successfully!</b>".to_string(),
+                    )
+                    .await?;
+                    return Ok(true);
+                }
+
                 // Update the welcome settings with custom message
                 let mut settings = bot_deps.welcome_service.get_settings(msg.chat.id);
                 settings.custom_message = Some(message_text);
@@ -1,5 +1,18 @@
+use crate::template::{TemplateContext, TemplateFormat, dto::TEMPLATE_VARIABLES, render_template};
 use crate::utils::{escape_for_markdown_v2, unescape_markdown};
-use crate::welcome::dto::WelcomeSettings;
+use std::collections::VecDeque;
+
+use chrono::NaiveDate;
+use teloxide::types::{ChatId, UserId};
+
+use crate::nft_gate::helpers::describe_requirement;
+use crate::welcome::dto::{
+    CaptchaType, KnownSpammer, MAX_URL_BUTTONS, MemberActivity, QuizQuestion, SpammerAction,
+    SpammerSource, TokenGate, VerificationStage, WelcomeSettings, WelcomeUrlButton,
+};
+
+/// Daily join/leave buckets older than this are dropped.
+pub const MEMBER_ACTIVITY_RETENTION_DAYS: i64 = 365;
 
 pub fn get_default_welcome_message(
     username_markup: &str,
@@ -10,47 +23,117 @@ pub fn get_default_welcome_message(
     let escaped_timeout = escape_for_markdown_v2(&timeout_minutes.to_string());
 
     format!(
-        "👋 Welcome to {}, {}\\!\n\n🔒 Please verify you're human by clicking the button below within {} minutes\\.\n\n⚠️ You'll be automatically removed if you don't verify in time\\.",
+        "👋 Welcome to {}, {}\\!\n\n🔒 Please verify you're human using the buttons below within {} minutes\\.\n\n⚠️ You'll be automatically removed if you don't verify in time\\.",
         escaped_group_name, username_markup, escaped_timeout
     )
 }
 
-pub fn get_custom_welcome_message(
-    settings: &WelcomeSettings,
-    username_markup: &str,
-    group_name: &str,
-) -> String {
+/// Render the group's welcome message as MarkdownV2. The verification
+/// timeout is taken from `settings` unless the context already sets one.
+pub fn get_custom_welcome_message(settings: &WelcomeSettings, ctx: &TemplateContext) -> String {
+    let mut ctx = ctx.clone();
+    ctx.timeout_minutes.get_or_insert(settings.verification_timeout / 60);
+
     if let Some(ref custom_msg) = settings.custom_message {
         let mut message = unescape_markdown(custom_msg);
 
         // Remove inline code wrappers around placeholders so replacements render correctly
-        for placeholder in ["{username}", "{group_name}", "{timeout}"] {
+        for name in TEMPLATE_VARIABLES {
+            let placeholder = format!("{{{}}}", name);
             let code_wrapped = format!("`{}`", placeholder);
             if message.contains(&code_wrapped) {
-                message = message.replace(&code_wrapped, placeholder);
+                message = message.replace(&code_wrapped, &placeholder);
             }
         }
 
-        // username_markup is already MarkdownV2 link entity like
-        // [@username](tg://user?id=123) or [First Name](tg://user?id=123)
-        let escaped_group_name = escape_for_markdown_v2(group_name);
-        let timeout_minutes = (settings.verification_timeout / 60).to_string();
-        let escaped_timeout = escape_for_markdown_v2(&timeout_minutes);
-
-        message = message.replace("{username}", username_markup);
-        message = message.replace("{group_name}", &escaped_group_name);
-        message = message.replace("{timeout}", &escaped_timeout);
-
-        message
+        render_template(&message, &ctx, TemplateFormat::MarkdownV2)
     } else {
         get_default_welcome_message(
-            username_markup,
-            group_name,
+            &render_template("{username}", &ctx, TemplateFormat::MarkdownV2),
+            ctx.group_name.as_deref().unwrap_or("this group"),
             settings.verification_timeout / 60,
         )
     }
 }
 
+pub fn get_default_goodbye_message(first_name_markup: &str) -> String {
+    format!("👋 {} has left the group\\.", first_name_markup)
+}
+
+/// Render the group's goodbye message as MarkdownV2.
+pub fn get_goodbye_message(settings: &WelcomeSettings, ctx: &TemplateContext) -> String {
+    match settings.goodbye_message {
+        Some(ref template) => {
+            render_template(&unescape_markdown(template), ctx, TemplateFormat::MarkdownV2)
+        }
+        None => get_default_goodbye_message(&render_template(
+            "{first_name}",
+            ctx,
+            TemplateFormat::MarkdownV2,
+        )),
+    }
+}
+
+/// Count a join or leave on `date`, dropping buckets past the retention window.
+pub fn record_member_activity(activity: &mut MemberActivity, joined: bool, date: NaiveDate) {
+    let bucket = activity
+        .daily
+        .entry(date.format("%Y-%m-%d").to_string())
+        .or_default();
+    if joined {
+        bucket.joins += 1;
+        activity.total_joins += 1;
+    } else {
+        bucket.leaves += 1;
+        activity.total_leaves += 1;
+    }
+
+    let cutoff = (date - chrono::Duration::days(MEMBER_ACTIVITY_RETENTION_DAYS))
+        .format("%Y-%m-%d")
+        .to_string();
+    activity.daily.retain(|day, _| *day > cutoff);
+}
+
+/// Joins and leaves over the last `days` days, including `today`.
+pub fn member_activity_in_last_days(
+    activity: &MemberActivity,
+    days: i64,
+    today: NaiveDate,
+) -> (u64, u64) {
+    let from = (today - chrono::Duration::days(days - 1))
+        .format("%Y-%m-%d")
+        .to_string();
+    activity
+        .daily
+        .range(from..)
+        .fold((0, 0), |(joins, leaves), (_, day)| {
+            (joins + day.joins, leaves + day.leaves)
+        })
+}
+
+/// Add a join at `now` and return how many joins fall within the last `window_seconds`.
+pub fn count_recent_joins(joins: &mut VecDeque<i64>, now: i64, window_seconds: i64) -> usize {
+    joins.push_back(now);
+    while joins.front().is_some_and(|&joined_at| now - joined_at >= window_seconds) {
+        joins.pop_front();
+    }
+    joins.len()
+}
+
+/// Verification settings used while the group is in raid lockdown: a real
+/// challenge instead of the plain button, a single attempt, half the timeout
+/// (at least 30 seconds) and no welcome media.
+pub fn lockdown_verification_settings(settings: &WelcomeSettings) -> WelcomeSettings {
+    let mut strict = settings.clone();
+    if strict.captcha_type == CaptchaType::Button {
+        strict.captcha_type = CaptchaType::Arithmetic;
+    }
+    strict.max_attempts = 1;
+    strict.verification_timeout = (settings.verification_timeout / 2).max(30);
+    strict.media = None;
+    strict
+}
+
 pub fn format_timeout_display(seconds: u64) -> String {
     if seconds < 60 {
         format!("{} seconds", seconds)
@@ -61,6 +144,14 @@ pub fn format_timeout_display(seconds: u64) -> String {
     }
 }
 
+/// Auto-delete setting for display; `None` means messages are kept.
+pub fn format_ttl_display(ttl_seconds: Option<u64>) -> String {
+    match ttl_seconds {
+        Some(seconds) => format!("after {}", format_timeout_display(seconds)),
+        None => "never".to_string(),
+    }
+}
+
 pub fn is_verification_expired(timestamp: i64) -> bool {
     chrono::Utc::now().timestamp() > timestamp
 }
@@ -69,11 +160,189 @@ pub fn get_verification_expiry_time(timeout_seconds: u64) -> i64 {
     chrono::Utc::now().timestamp() + timeout_seconds as i64
 }
 
+/// Step that follows `current`, or `None` once the member has passed everything.
+pub fn next_verification_stage(
+    current: VerificationStage,
+    settings: &WelcomeSettings,
+) -> Option<VerificationStage> {
+    let first_question = (!settings.quiz.is_empty()).then_some(VerificationStage::Quiz(0));
+    let next = match current {
+        VerificationStage::Challenge if settings.require_rules => Some(VerificationStage::Rules),
+        VerificationStage::Challenge | VerificationStage::Rules => first_question,
+        VerificationStage::Quiz(index) => {
+            (index + 1 < settings.quiz.len()).then_some(VerificationStage::Quiz(index + 1))
+        }
+        VerificationStage::TokenGate => return None,
+    };
+    // The holdings check always comes last
+    next.or_else(|| {
+        (settings.token_gate.is_some() || settings.nft_gate.is_some())
+            .then_some(VerificationStage::TokenGate)
+    })
+}
+
+/// Plain-text summary of what members must hold, e.g. "10 APT and 1 NFT from
+/// Aptos Monkeys". `None` when the group has no holder requirements.
+pub fn describe_holdings_requirement(settings: &WelcomeSettings) -> Option<String> {
+    let mut parts = Vec::new();
+    if let Some(gate) = &settings.token_gate {
+        parts.push(format!("{} {}", gate.min_amount, gate.symbol));
+    }
+    if let Some(requirement) = &settings.nft_gate {
+        parts.push(describe_requirement(requirement));
+    }
+    (!parts.is_empty()).then(|| parts.join(" and "))
+}
+
+/// Smallest raw on-chain balance that satisfies the gate.
+pub fn token_gate_min_raw(gate: &TokenGate) -> i64 {
+    (gate.min_amount * 10_f64.powi(gate.decimals as i32)).ceil() as i64
+}
+
+/// Parse the admin's "SYMBOL AMOUNT" token gate input, e.g. `APT 10`.
+pub fn parse_token_gate_input(text: &str) -> Result<(String, f64), String> {
+    let mut parts = text.split_whitespace();
+    let (Some(symbol), Some(amount), None) = (parts.next(), parts.next(), parts.next()) else {
+        return Err("Send the token symbol and the minimum amount, e.g. APT 10.".to_string());
+    };
+
+    match amount.replace(',', "").parse::<f64>() {
+        Ok(amount) if amount.is_finite() && amount > 0.0 => {
+            Ok((symbol.trim_start_matches('$').to_uppercase(), amount))
+        }
+        _ => Err(format!("\"{}\" is not a valid minimum amount.", amount)),
+    }
+}
+
+/// Parse an admin message into a quiz question: the first line is the
+/// question, the second the correct answer and the rest (1-3) wrong answers.
+pub fn parse_quiz_question(text: &str) -> Result<QuizQuestion, String> {
+    let lines: Vec<&str> = text
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .collect();
+
+    if lines.len() < 3 {
+        return Err(
+            "Send a question, the correct answer and at least one wrong answer, each on its own line."
+                .to_string(),
+        );
+    }
+    if lines.len() > 5 {
+        return Err("A question can have at most 4 answers.".to_string());
+    }
+
+    let answers = &lines[1..];
+    if answers
+        .iter()
+        .enumerate()
+        .any(|(i, answer)| answers[..i].contains(answer))
+    {
+        return Err("Answers must all be different.".to_string());
+    }
+
+    Ok(QuizQuestion {
+        question: lines[0].to_string(),
+        correct_answer: answers[0].to_string(),
+        wrong_answers: answers[1..].iter().map(|a| a.to_string()).collect(),
+    })
+}
+
+/// Parse one `Text - https://link` button per line.
+pub fn parse_url_buttons(text: &str) -> Result<Vec<WelcomeUrlButton>, String> {
+    let mut buttons = Vec::new();
+
+    for line in text.lines().map(str::trim).filter(|line| !line.is_empty()) {
+        let Some((label, url)) = line.rsplit_once(" - ") else {
+            return Err(format!("\"{}\" is not in the form Text - https://link.", line));
+        };
+        let (label, url) = (label.trim(), url.trim());
+
+        let valid_scheme = reqwest::Url::parse(url)
+            .map(|parsed| matches!(parsed.scheme(), "http" | "https" | "tg"))
+            .unwrap_or(false);
+        if label.is_empty() || !valid_scheme {
+            return Err(format!("\"{}\" needs a label and an http(s) or tg:// link.", line));
+        }
+
+        buttons.push(WelcomeUrlButton {
+            text: label.to_string(),
+            url: url.to_string(),
+        });
+    }
+
+    if buttons.is_empty() {
+        return Err("Send at least one button.".to_string());
+    }
+    if buttons.len() > MAX_URL_BUTTONS {
+        return Err(format!("At most {} buttons are allowed.", MAX_URL_BUTTONS));
+    }
+
+    Ok(buttons)
+}
+
+pub fn describe_spammer_action(action: SpammerAction) -> &'static str {
+    match action {
+        SpammerAction::Off => "Off",
+        SpammerAction::Ban => "Ban automatically",
+        SpammerAction::Review => "Hold for admin review",
+    }
+}
+
+/// First source of a list entry this group opted in to. Bans made in the
+/// group itself always count.
+pub fn trusted_spammer_source(
+    spammer: &KnownSpammer,
+    chat_id: ChatId,
+    settings: &WelcomeSettings,
+) -> Option<SpammerSource> {
+    spammer.sources.iter().copied().find(|source| match source {
+        SpammerSource::Imported => settings.trust_imported_spammers,
+        SpammerSource::Banned { chat_id: banned_in } => {
+            *banned_in == chat_id || settings.trust_other_group_bans
+        }
+    })
+}
+
+/// Parse a spammer list file: user ids separated by whitespace or commas, with
+/// `#` starting a comment. Returns the ids and the number of entries skipped.
+pub fn parse_spammer_ids(text: &str) -> (Vec<UserId>, usize) {
+    let mut ids = Vec::new();
+    let mut skipped = 0;
+
+    for line in text.lines() {
+        let line = line.split('#').next().unwrap_or_default();
+        for entry in line
+            .split(|c: char| c == ',' || c.is_whitespace())
+            .filter(|entry| !entry.is_empty())
+        {
+            match entry.parse::<u64>() {
+                Ok(id) if id > 0 => ids.push(UserId(id)),
+                _ => skipped += 1,
+            }
+        }
+    }
+
+    (ids, skipped)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::nft_gate::dto::CollectionRequirement;
     use crate::welcome::dto::WelcomeSettings;
 
+    fn build_ctx(username: &str, user_id: i64, group_name: &str) -> TemplateContext {
+        TemplateContext {
+            user_id: Some(user_id),
+            username: Some(username.to_string()),
+            first_name: Some("Nova".to_string()),
+            group_name: Some(group_name.to_string()),
+            ..Default::default()
+        }
+    }
+
     fn build_settings(message: &str, timeout_seconds: u64) -> WelcomeSettings {
         WelcomeSettings {
             enabled: true,
@@ -90,8 +359,7 @@ mod tests {
 
         let result = get_custom_welcome_message(
             &settings,
-            "[@nova](tg://user?id=42)",
-            "Inferenco Inner Circle",
+            &build_ctx("nova", 42, "Inferenco Inner Circle"),
         );
 
         let expected = "Welcome to *Inferenco Inner Circle*, [@nova](tg://user?id=42)!\n\n1. Connect\n• Enjoy [demo](https://example.com)";
@@ -103,11 +371,7 @@ mod tests {
         let template = "Hi {username}! Welcome to {group_name}. Timeout: {timeout}";
         let settings = build_settings(template, 180);
 
-        let result = get_custom_welcome_message(
-            &settings,
-            "[@nova](tg://user?id=42)",
-            "Group (v2)!",
-        );
+        let result = get_custom_welcome_message(&settings, &build_ctx("nova", 42, "Group (v2)!"));
 
         let expected = r"Hi [@nova](tg://user?id=42)! Welcome to Group \(v2\)\!. Timeout: 3";
         assert_eq!(result, expected);
@@ -120,8 +384,7 @@ mod tests {
 
         let result = get_custom_welcome_message(
             &settings,
-            "[@username](tg://user?id=123)",
-            "Inferenco Inner Circle",
+            &build_ctx("username", 123, "Inferenco Inner Circle"),
         );
 
         assert!(result.contains(r"\!"));
@@ -135,15 +398,257 @@ mod tests {
         let template = "`{username}` joins `{group_name}` in `{timeout}` minutes";
         let settings = build_settings(template, 600);
 
-        let result = get_custom_welcome_message(
-            &settings,
-            "[@nova](tg://user?id=42)",
-            "Awesome Group",
-        );
+        let result =
+            get_custom_welcome_message(&settings, &build_ctx("nova", 42, "Awesome Group"));
 
         assert_eq!(
             result,
             "[@nova](tg://user?id=42) joins Awesome Group in 10 minutes"
         );
     }
+
+    #[test]
+    fn custom_message_supports_conditionals_and_new_placeholders() {
+        let template = "Hi {first_name}, member {user_id}{if not username}, please set a username{end}";
+        let settings = build_settings(template, 600);
+        let mut ctx = build_ctx("nova", 42, "Awesome Group");
+        ctx.username = None;
+
+        let result = get_custom_welcome_message(&settings, &ctx);
+
+        assert_eq!(result, "Hi Nova, member 42, please set a username");
+    }
+
+    #[test]
+    fn stages_follow_rules_then_quiz() {
+        let question = parse_quiz_question("Q?\nyes\nno").unwrap();
+        let mut settings = WelcomeSettings::default();
+
+        assert_eq!(next_verification_stage(VerificationStage::Challenge, &settings), None);
+
+        settings.require_rules = true;
+        settings.quiz = vec![question.clone(), question];
+        assert_eq!(
+            next_verification_stage(VerificationStage::Challenge, &settings),
+            Some(VerificationStage::Rules)
+        );
+        assert_eq!(
+            next_verification_stage(VerificationStage::Rules, &settings),
+            Some(VerificationStage::Quiz(0))
+        );
+        assert_eq!(
+            next_verification_stage(VerificationStage::Quiz(0), &settings),
+            Some(VerificationStage::Quiz(1))
+        );
+        assert_eq!(next_verification_stage(VerificationStage::Quiz(1), &settings), None);
+
+        settings.require_rules = false;
+        assert_eq!(
+            next_verification_stage(VerificationStage::Challenge, &settings),
+            Some(VerificationStage::Quiz(0))
+        );
+    }
+
+    #[test]
+    fn token_gate_comes_last() {
+        let mut settings = WelcomeSettings {
+            token_gate: Some(TokenGate {
+                token_type: "0x1::aptos_coin::AptosCoin".to_string(),
+                symbol: "APT".to_string(),
+                decimals: 8,
+                min_amount: 1.5,
+            }),
+            ..Default::default()
+        };
+        assert_eq!(
+            next_verification_stage(VerificationStage::Challenge, &settings),
+            Some(VerificationStage::TokenGate)
+        );
+        assert_eq!(next_verification_stage(VerificationStage::TokenGate, &settings), None);
+
+        settings.quiz = vec![parse_quiz_question("Q?\nyes\nno").unwrap()];
+        assert_eq!(
+            next_verification_stage(VerificationStage::Quiz(0), &settings),
+            Some(VerificationStage::TokenGate)
+        );
+        assert_eq!(token_gate_min_raw(settings.token_gate.as_ref().unwrap()), 150_000_000);
+        assert_eq!(
+            describe_holdings_requirement(&settings).as_deref(),
+            Some("1.5 APT")
+        );
+
+        settings.token_gate = None;
+        assert_eq!(describe_holdings_requirement(&settings), None);
+        settings.nft_gate = Some(CollectionRequirement {
+            collection_id: "0x1".to_string(),
+            collection_name: "Aptos Monkeys".to_string(),
+            min_count: 2,
+        });
+        assert_eq!(
+            next_verification_stage(VerificationStage::Quiz(0), &settings),
+            Some(VerificationStage::TokenGate)
+        );
+        assert_eq!(
+            describe_holdings_requirement(&settings).as_deref(),
+            Some("2 NFTs from Aptos Monkeys")
+        );
+    }
+
+    #[test]
+    fn token_gate_input_parsing() {
+        assert_eq!(parse_token_gate_input("apt 10"), Ok(("APT".to_string(), 10.0)));
+        assert_eq!(
+            parse_token_gate_input(" $nova 1,000.5 "),
+            Ok(("NOVA".to_string(), 1000.5))
+        );
+        assert!(parse_token_gate_input("APT").is_err());
+        assert!(parse_token_gate_input("APT 0").is_err());
+        assert!(parse_token_gate_input("APT ten").is_err());
+        assert!(parse_token_gate_input("APT 1 2").is_err());
+    }
+
+    #[test]
+    fn quiz_question_parsing() {
+        let question =
+            parse_quiz_question("Where do links go?\n #links \n\nDMs\nAnywhere").unwrap();
+        assert_eq!(question.question, "Where do links go?");
+        assert_eq!(question.correct_answer, "#links");
+        assert_eq!(question.wrong_answers, vec!["DMs", "Anywhere"]);
+
+        assert!(parse_quiz_question("Only a question\nanswer").is_err());
+        assert!(parse_quiz_question("Q\na\nb\nc\nd\ne").is_err());
+        assert!(parse_quiz_question("Q\nsame\nsame").is_err());
+    }
+
+    #[test]
+    fn url_button_parsing() {
+        let buttons =
+            parse_url_buttons("🌐 Website - https://example.com\n\nDocs - Guide - https://docs.example.com/a?b=c")
+                .unwrap();
+        assert_eq!(buttons.len(), 2);
+        assert_eq!(buttons[0].text, "🌐 Website");
+        assert_eq!(buttons[1].text, "Docs - Guide");
+        assert_eq!(buttons[1].url, "https://docs.example.com/a?b=c");
+
+        assert!(parse_url_buttons("No link here").is_err());
+        assert!(parse_url_buttons("Bad - javascript:alert(1)").is_err());
+        assert!(parse_url_buttons(" - https://example.com").is_err());
+        assert!(parse_url_buttons(&"A - https://a.io\n".repeat(7)).is_err());
+    }
+
+    #[test]
+    fn member_activity_counts_and_windows() {
+        let today = NaiveDate::from_ymd_opt(2025, 3, 10).unwrap();
+        let mut activity = MemberActivity::default();
+
+        record_member_activity(&mut activity, true, today);
+        record_member_activity(&mut activity, true, today - chrono::Duration::days(3));
+        record_member_activity(&mut activity, false, today - chrono::Duration::days(10));
+        record_member_activity(&mut activity, false, today - chrono::Duration::days(400));
+
+        assert_eq!(activity.total_joins, 2);
+        assert_eq!(activity.total_leaves, 2);
+        assert_eq!(member_activity_in_last_days(&activity, 1, today), (1, 0));
+        assert_eq!(member_activity_in_last_days(&activity, 7, today), (2, 0));
+        assert_eq!(member_activity_in_last_days(&activity, 30, today), (2, 1));
+
+        // A new event prunes buckets past the retention window
+        record_member_activity(&mut activity, true, today);
+        assert!(!activity.daily.contains_key("2024-02-04"));
+        assert_eq!(activity.daily.len(), 3);
+    }
+
+    #[test]
+    fn goodbye_message_uses_template_or_default() {
+        let ctx = build_ctx("nova", 42, "Group");
+        let mut settings = WelcomeSettings::default();
+
+        assert_eq!(get_goodbye_message(&settings, &ctx), r"👋 Nova has left the group\.");
+
+        settings.goodbye_message = Some("Bye {first_name}, see you in {group_name}!".to_string());
+        assert_eq!(get_goodbye_message(&settings, &ctx), "Bye Nova, see you in Group!");
+    }
+
+    #[test]
+    fn join_window_drops_old_joins() {
+        let mut joins = VecDeque::new();
+
+        assert_eq!(count_recent_joins(&mut joins, 100, 60), 1);
+        assert_eq!(count_recent_joins(&mut joins, 130, 60), 2);
+        assert_eq!(count_recent_joins(&mut joins, 159, 60), 3);
+        assert_eq!(count_recent_joins(&mut joins, 160, 60), 3);
+        assert_eq!(count_recent_joins(&mut joins, 300, 60), 1);
+    }
+
+    #[test]
+    fn lockdown_makes_verification_stricter() {
+        let settings = WelcomeSettings {
+            verification_timeout: 300,
+            max_attempts: 3,
+            ..Default::default()
+        };
+
+        let strict = lockdown_verification_settings(&settings);
+        assert_eq!(strict.captcha_type, CaptchaType::Arithmetic);
+        assert_eq!(strict.max_attempts, 1);
+        assert_eq!(strict.verification_timeout, 150);
+
+        let short = WelcomeSettings {
+            verification_timeout: 30,
+            captcha_type: CaptchaType::Emoji,
+            ..Default::default()
+        };
+        let strict = lockdown_verification_settings(&short);
+        assert_eq!(strict.captcha_type, CaptchaType::Emoji);
+        assert_eq!(strict.verification_timeout, 30);
+    }
+
+    #[test]
+    fn ttl_display_covers_off_and_durations() {
+        assert_eq!(format_ttl_display(None), "never");
+        assert_eq!(format_ttl_display(Some(15)), "after 15 seconds");
+        assert_eq!(format_ttl_display(Some(3600)), "after 1 hours");
+    }
+
+    #[test]
+    fn spammer_ids_skip_comments_and_junk() {
+        let text = "# exported 2025-10-01\n12345\n  67890, 111 # reported twice\n\n@spammer 0\n";
+        let (ids, skipped) = parse_spammer_ids(text);
+
+        assert_eq!(ids, vec![UserId(12345), UserId(67890), UserId(111)]);
+        assert_eq!(skipped, 2);
+    }
+
+    #[test]
+    fn spammer_sources_apply_only_when_opted_in() {
+        let (here, other) = (ChatId(-100), ChatId(-200));
+        let spammer = KnownSpammer {
+            user_id: UserId(42),
+            sources: vec![
+                SpammerSource::Imported,
+                SpammerSource::Banned { chat_id: other },
+            ],
+            added_at: 0,
+        };
+        let mut settings = WelcomeSettings::default();
+        assert_eq!(settings.spammer_action, SpammerAction::Off);
+        assert_eq!(trusted_spammer_source(&spammer, here, &settings), None);
+
+        settings.trust_other_group_bans = true;
+        assert_eq!(
+            trusted_spammer_source(&spammer, here, &settings),
+            Some(SpammerSource::Banned { chat_id: other })
+        );
+
+        // Bans made in the group itself always count
+        settings.trust_other_group_bans = false;
+        let banned_here = KnownSpammer {
+            sources: vec![SpammerSource::Banned { chat_id: here }],
+            ..spammer
+        };
+        assert_eq!(
+            trusted_spammer_source(&banned_here, here, &settings),
+            Some(SpammerSource::Banned { chat_id: here })
+        );
+    }
 }
@@ -1,4 +1,6 @@
+pub mod captcha;
 pub mod dto;
 pub mod handler;
 pub mod welcome_service;
 pub mod helpers;
+pub mod token_gate;
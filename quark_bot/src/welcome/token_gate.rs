@@ -0,0 +1,97 @@
+use teloxide::types::UserId;
+
+use crate::{
+    aptos::handler::Aptos,
+    credentials::handler::Auth,
+    nft_gate::{
+        dto::CollectionCheck,
+        helpers::{check_collection_holder, describe_requirement},
+    },
+    welcome::{
+        dto::{TokenGate, WelcomeSettings},
+        helpers::token_gate_min_raw,
+    },
+};
+
+/// Result of checking a member's linked wallet against the group's holder requirements.
+#[derive(Debug, Clone, PartialEq)]
+pub enum TokenGateStatus {
+    /// No `/loginuser` credentials for this Telegram account.
+    NotLinked,
+    /// Short explanation of the requirement that was missed.
+    Insufficient { detail: String },
+    Qualified,
+    /// The balance could not be read; never treated as a failure.
+    Unavailable,
+}
+
+/// Check the member's linked wallet against the group's token gate and NFT
+/// gate, whichever are set. Both must be met.
+pub async fn check_holdings(
+    auth: &Auth,
+    aptos: &Aptos,
+    settings: &WelcomeSettings,
+    user_id: UserId,
+    username: Option<&str>,
+) -> TokenGateStatus {
+    if let Some(gate) = &settings.token_gate {
+        let status = check_token_holder(auth, aptos, gate, user_id, username).await;
+        if status != TokenGateStatus::Qualified {
+            return status;
+        }
+    }
+
+    if let Some(requirement) = &settings.nft_gate {
+        match check_collection_holder(auth, aptos, requirement, user_id, username).await {
+            CollectionCheck::Qualified => {}
+            CollectionCheck::NotLinked => return TokenGateStatus::NotLinked,
+            CollectionCheck::Unavailable => return TokenGateStatus::Unavailable,
+            CollectionCheck::Insufficient { owned } => {
+                return TokenGateStatus::Insufficient {
+                    detail: format!(
+                        "Your wallet holds {} of the {} required.",
+                        owned,
+                        describe_requirement(requirement)
+                    ),
+                };
+            }
+        }
+    }
+
+    TokenGateStatus::Qualified
+}
+
+/// Look up the member's linked wallet and compare its balance with the gate's minimum.
+async fn check_token_holder(
+    auth: &Auth,
+    aptos: &Aptos,
+    gate: &TokenGate,
+    user_id: UserId,
+    username: Option<&str>,
+) -> TokenGateStatus {
+    let Some(address) = auth.get_linked_address(user_id, username) else {
+        return TokenGateStatus::NotLinked;
+    };
+
+    match aptos.get_account_balance(&address, &gate.token_type).await {
+        Ok(raw) if raw >= token_gate_min_raw(gate) => TokenGateStatus::Qualified,
+        Ok(raw) => TokenGateStatus::Insufficient {
+            detail: format!(
+                "Your wallet holds {} {}, but at least {} {} is required.",
+                raw as f64 / 10_f64.powi(gate.decimals as i32),
+                gate.symbol,
+                gate.min_amount,
+                gate.symbol
+            ),
+        },
+        Err(e) => {
+            log::warn!(
+                "Failed to read {} balance for user {}: {}",
+                gate.symbol,
+                user_id.0,
+                e
+            );
+            TokenGateStatus::Unavailable
+        }
+    }
+}
@@ -1,17 +1,39 @@
-use std::env;
+use std::{collections::VecDeque, env, path::Path, sync::Arc};
 
 use anyhow::Result;
+use dashmap::{DashMap, mapref::entry::Entry};
 use sled::Tree;
 use teloxide::{
     prelude::*,
-    types::{ChatId, ChatPermissions, InlineKeyboardButton, InlineKeyboardMarkup, UserId},
+    types::{
+        ChatId, ChatPermissions, FileId, InlineKeyboardButton, InlineKeyboardMarkup, InputFile,
+        MessageId, ThreadId, User, UserId,
+    },
 };
 
 use crate::welcome::{
-    dto::{PendingVerification, WelcomeSettings, WelcomeStats},
-    helpers::{get_custom_welcome_message, get_verification_expiry_time, is_verification_expired},
+    captcha::{
+        generate_challenge, quiz_challenge, render_word_image, reshuffle_options,
+        typed_answer_matches,
+    },
+    dto::{
+        CaptchaAnswer, CaptchaChallenge, CaptchaType, KnownSpammer, MemberActivity, PendingVerification,
+        RAID_WINDOW_SECONDS, RaidLockdown, ScheduledDeletion, SpammerAction, SpammerSource,
+        TokenHolder, VerificationOutcome, VerificationStage, WelcomeMedia, WelcomeMediaKind,
+        WelcomeSettings, WelcomeStats, WelcomeUrlButton,
+    },
+    helpers::{
+        count_recent_joins, describe_holdings_requirement, get_custom_welcome_message,
+        get_goodbye_message,
+        get_verification_expiry_time, is_verification_expired, lockdown_verification_settings,
+        next_verification_stage, parse_spammer_ids, record_member_activity,
+        trusted_spammer_source,
+    },
+    token_gate::{TokenGateStatus, check_holdings},
 };
-use crate::utils::{escape_for_markdown_v2};
+use crate::template::{TemplateContext, template_uses};
+use crate::{aptos::handler::Aptos, credentials::handler::Auth};
+use crate::utils::{escape_for_markdown_v2, unescape_markdown};
 
 use rand::{SeedableRng, prelude::*, rngs::StdRng};
 
@@ -20,9 +42,21 @@ pub struct WelcomeService {
     settings_db: Tree,
     verifications_db: Tree,
     stats_db: Tree,
+    activity_db: Tree,
+    lockdown_db: Tree,
+    deletions_db: Tree,
+    holders_db: Tree,
+    spammers_db: Tree,
     account_seed: String,
+    // Recent join timestamps per chat, for raid detection
+    join_windows: Arc<DashMap<i64, VecDeque<i64>>>,
+    // Joins/leaves arrive both as chat_member updates and as service messages
+    recent_member_events: Arc<DashMap<(i64, u64, bool), i64>>,
 }
 
+/// Window in which a repeated join/leave for the same user is treated as a duplicate.
+const MEMBER_EVENT_DEDUP_SECONDS: i64 = 60;
+
 impl WelcomeService {
     pub fn new(db: sled::Db) -> Self {
         let settings_db = db
@@ -34,6 +68,21 @@ impl WelcomeService {
         let stats_db = db
             .open_tree("welcome_stats")
             .expect("Failed to open welcome stats tree");
+        let activity_db = db
+            .open_tree("welcome_member_activity")
+            .expect("Failed to open welcome member activity tree");
+        let lockdown_db = db
+            .open_tree("welcome_lockdowns")
+            .expect("Failed to open welcome lockdowns tree");
+        let deletions_db = db
+            .open_tree("welcome_scheduled_deletions")
+            .expect("Failed to open welcome scheduled deletions tree");
+        let holders_db = db
+            .open_tree("welcome_token_holders")
+            .expect("Failed to open welcome token holders tree");
+        let spammers_db = db
+            .open_tree("welcome_known_spammers")
+            .expect("Failed to open welcome known spammers tree");
 
         let account_seed: String =
             env::var("ACCOUNT_SEED").expect("ACCOUNT_SEED environment variable not found");
@@ -42,7 +91,14 @@ impl WelcomeService {
             settings_db,
             verifications_db,
             stats_db,
+            activity_db,
+            lockdown_db,
+            deletions_db,
+            holders_db,
+            spammers_db,
             account_seed,
+            join_windows: Arc::new(DashMap::new()),
+            recent_member_events: Arc::new(DashMap::new()),
         }
     }
 
@@ -70,6 +126,35 @@ impl WelcomeService {
         self.get_settings(chat_id).enabled
     }
 
+    /// Entry point for a member joining: counts the join once, watches the
+    /// join rate for raids and then starts verification.
+    pub async fn handle_member_joined(&self, bot: &Bot, chat_id: ChatId, user: &User) -> Result<()> {
+        if !self.record_member_event(chat_id, user.id, true) {
+            return Ok(());
+        }
+
+        let settings = self.get_settings(chat_id);
+        if let Err(e) = self.track_join_rate(bot, chat_id, &settings).await {
+            log::error!("Failed to track join rate for chat {}: {}", chat_id.0, e);
+        }
+
+        if self
+            .screen_known_spammer(bot, chat_id, user, &settings)
+            .await?
+        {
+            return Ok(());
+        }
+
+        self.handle_new_member(
+            bot,
+            chat_id,
+            user.id,
+            user.username.clone(),
+            user.first_name.clone(),
+        )
+        .await
+    }
+
     pub async fn handle_new_member(
         &self,
         bot: &Bot,
@@ -78,7 +163,13 @@ impl WelcomeService {
         username: Option<String>,
         first_name: String,
     ) -> Result<()> {
+        let lockdown = self.get_active_lockdown(chat_id);
+
         if !self.is_enabled(chat_id) {
+            // Without verification, lockdown simply mutes newcomers until it lifts
+            if lockdown.is_some() {
+                self.restrict_during_lockdown(bot, chat_id, user_id).await?;
+            }
             return Ok(());
         }
 
@@ -98,10 +189,17 @@ impl WelcomeService {
             return Ok(());
         }
 
-        let settings = self.get_settings(chat_id);
+        let settings = match lockdown {
+            Some(_) => lockdown_verification_settings(&self.get_settings(chat_id)),
+            None => self.get_settings(chat_id),
+        };
 
-        // Mute the new member immediately
-        let restricted_permissions = ChatPermissions::empty();
+        // Mute the new member immediately; word challenges are answered by typing
+        let restricted_permissions = if settings.captcha_type == CaptchaType::ImageWord {
+            ChatPermissions::SEND_MESSAGES
+        } else {
+            ChatPermissions::empty()
+        };
         bot.restrict_chat_member(chat_id, user_id, restricted_permissions)
             .await?;
 
@@ -109,32 +207,71 @@ impl WelcomeService {
         let chat = bot.get_chat(chat_id).await?;
         let group_name = chat.title().unwrap_or("this group").to_string();
 
-        // Create verification button
-        let keyboard = InlineKeyboardMarkup::new(vec![vec![InlineKeyboardButton::callback(
-            "✅ Prove You're Human",
-            format!(
-                "welcome_verify:{}:{}",
-                chat_id.to_string(),
-                user_id.to_string()
-            ),
-        )]]);
+        // In forum groups everything is posted into the configured topic
+        let thread_id = settings.topic_id.map(|id| ThreadId(MessageId(id)));
+
+        // Optional welcome image or animation goes first
+        let media_message_id = match settings.media.as_ref() {
+            Some(media) => match send_welcome_media(bot, chat_id, thread_id, media).await {
+                Ok(message_id) => Some(message_id),
+                Err(e) => {
+                    log::warn!("Failed to send welcome media in chat {}: {}", chat_id.0, e);
+                    None
+                }
+            },
+            None => None,
+        };
+
+        // Create the challenge (if any) and its answer buttons
+        let challenge = generate_challenge(settings.captcha_type, &mut rand::rng());
+        let keyboard =
+            verification_keyboard(chat_id, user_id, challenge.as_ref(), &settings.url_buttons);
+
+        // Word challenges are drawn on an image sent just before the welcome message
+        let challenge_message_id = match challenge.as_ref() {
+            Some(challenge) if settings.captcha_type == CaptchaType::ImageWord => {
+                let png = render_word_image(&challenge.answer, &mut rand::rng())?;
+                let mut request =
+                    bot.send_photo(chat_id, InputFile::memory(png).file_name("captcha.png"));
+                if let Some(thread_id) = thread_id {
+                    request = request.message_thread_id(thread_id);
+                }
+                Some(request.await?.id.0)
+            }
+            _ => None,
+        };
 
         // Send welcome message with verification button
-        // Prefer the user's actual @username in a safe clickable mention; fall back to first name
-        let display_name = if let Some(ref u) = username {
-            format!("@{}", u)
-        } else {
-            first_name.clone()
+        // {username} renders as a clickable mention of @username, falling back to first name
+        let member_count = match settings.custom_message.as_deref() {
+            Some(template) if template_uses(&unescape_markdown(template), "member_count") => {
+                bot.get_chat_member_count(chat_id).await.ok()
+            }
+            _ => None,
+        };
+        let template_ctx = TemplateContext {
+            user_id: Some(user_id.0 as i64),
+            username: username.clone(),
+            first_name: Some(first_name.clone()),
+            group_name: Some(group_name),
+            member_count,
+            ..Default::default()
         };
-        // Escape display text for MarkdownV2, then create a tg:// user mention link
-        let escaped_display = escape_for_markdown_v2(&display_name);
-        let mention_markup = format!("[{}](tg://user?id={})", escaped_display, user_id.0);
-        let welcome_text = get_custom_welcome_message(&settings, &mention_markup, &group_name);
-        let message = bot
+        let mut welcome_text = get_custom_welcome_message(&settings, &template_ctx);
+        if let Some(ref challenge) = challenge {
+            welcome_text.push_str(&format!(
+                "\n\n🧩 *{}*",
+                escape_for_markdown_v2(&challenge.prompt)
+            ));
+        }
+        let mut request = bot
             .send_message(chat_id, welcome_text)
             .parse_mode(teloxide::types::ParseMode::MarkdownV2)
-            .reply_markup(keyboard)
-            .await?;
+            .reply_markup(keyboard);
+        if let Some(thread_id) = thread_id {
+            request = request.message_thread_id(thread_id);
+        }
+        let message = request.await?;
 
         // Store pending verification
         let verification = PendingVerification {
@@ -145,6 +282,11 @@ impl WelcomeService {
             joined_at: chrono::Utc::now().timestamp(),
             expires_at: get_verification_expiry_time(settings.verification_timeout),
             verification_message_id: message.id.0,
+            challenge,
+            attempts_left: settings.max_attempts.max(1),
+            challenge_message_id,
+            stage: VerificationStage::Challenge,
+            media_message_id,
         };
 
         let key = format!("{}-{}:{}", chat_id.0, self.account_seed, user_id.0);
@@ -160,7 +302,9 @@ impl WelcomeService {
         chat_id: ChatId,
         user_id: UserId,
         requester_id: UserId, // Add the ID of the user who clicked the button
-    ) -> Result<()> {
+        answer: CaptchaAnswer, // Pressed option or typed word for challenge captchas
+        rules_html: &str,      // Shown when the group requires rules acceptance
+    ) -> Result<VerificationOutcome> {
         // Verify that the user clicking the button is the same user who joined
         if requester_id != user_id {
             log::warn!(
@@ -180,7 +324,7 @@ impl WelcomeService {
         );
 
         // Get verification record
-        let verification = if let Ok(Some(bytes)) = self.verifications_db.get(key.as_bytes()) {
+        let mut verification = if let Ok(Some(bytes)) = self.verifications_db.get(key.as_bytes()) {
             if let Ok(verification) = serde_json::from_slice::<PendingVerification>(&bytes) {
                 log::info!(
                     "Found verification record for user {}: expires at {}",
@@ -214,6 +358,145 @@ impl WelcomeService {
             return Err(anyhow::anyhow!("Verification expired"));
         }
 
+        // Token-gated members finish through pass_token_gate once their balance is confirmed
+        if verification.stage == VerificationStage::TokenGate {
+            return Err(anyhow::anyhow!("Token balance has not been confirmed"));
+        }
+
+        let settings = self.get_settings(chat_id);
+
+        if let Some(mut challenge) = verification.challenge.clone() {
+            let correct = match &answer {
+                CaptchaAnswer::Button(index) => index
+                    .and_then(|index| challenge.options.get(index))
+                    .is_some_and(|option| *option == challenge.answer),
+                CaptchaAnswer::Typed(text) => typed_answer_matches(&challenge, text),
+            };
+
+            if !correct {
+                let attempts_left = verification.attempts_left.saturating_sub(1);
+                log::info!(
+                    "Wrong captcha answer from user {} in chat {}, {} attempts left",
+                    user_id.0,
+                    chat_id.0,
+                    attempts_left
+                );
+
+                if attempts_left == 0 {
+                    let reason = format!(
+                        "❌ Verification failed for {}",
+                        escape_for_markdown_v2(&verification.first_name)
+                    );
+                    self.remove_unverified_member(bot, key.as_bytes(), &verification, &reason)
+                        .await;
+                    return Ok(VerificationOutcome::Rejected);
+                }
+
+                reshuffle_options(&mut challenge, &mut rand::rng());
+                let keyboard = verification_keyboard(
+                    chat_id,
+                    user_id,
+                    Some(&challenge),
+                    &settings.url_buttons,
+                );
+                let has_buttons = !challenge.options.is_empty();
+                verification.challenge = Some(challenge);
+                verification.attempts_left = attempts_left;
+                self.verifications_db
+                    .insert(key.as_bytes(), serde_json::to_vec(&verification)?)?;
+
+                if !has_buttons {
+                    return Ok(VerificationOutcome::WrongAnswer { attempts_left });
+                }
+                if let Err(e) = bot
+                    .edit_message_reply_markup(
+                        chat_id,
+                        MessageId(verification.verification_message_id),
+                    )
+                    .reply_markup(keyboard)
+                    .await
+                {
+                    log::warn!("Failed to reshuffle captcha buttons: {}", e);
+                }
+
+                return Ok(VerificationOutcome::WrongAnswer { attempts_left });
+            }
+        }
+
+        // Move on to the rules or the quiz before unmuting
+        if let Some(stage) = next_verification_stage(verification.stage, &settings) {
+            self.show_verification_stage(bot, &key, verification, stage, &settings, rules_html)
+                .await?;
+            return Ok(VerificationOutcome::NextStep);
+        }
+
+        self.complete_verification(bot, &key, verification, &settings)
+            .await
+    }
+
+    /// Final step for token-gated groups, called once the member's balance has
+    /// been confirmed: remembers them for re-checks and unmutes them.
+    pub async fn pass_token_gate(
+        &self,
+        bot: &Bot,
+        chat_id: ChatId,
+        user_id: UserId,
+        requester_id: UserId,
+    ) -> Result<VerificationOutcome> {
+        if requester_id != user_id {
+            return Err(anyhow::anyhow!("You can only verify yourself"));
+        }
+
+        let verification = self
+            .get_pending_verification(chat_id, user_id)
+            .ok_or_else(|| anyhow::anyhow!("Verification not found"))?;
+        if is_verification_expired(verification.expires_at) {
+            return Err(anyhow::anyhow!("Verification expired"));
+        }
+        if verification.stage != VerificationStage::TokenGate {
+            return Err(anyhow::anyhow!("Not at the token check step"));
+        }
+
+        let now = chrono::Utc::now().timestamp();
+        self.save_token_holder(&TokenHolder {
+            chat_id,
+            user_id,
+            username: verification.username.clone().unwrap_or_default(),
+            verified_at: now,
+            last_checked: now,
+        })?;
+
+        let key = format!("{}-{}:{}", chat_id.0, self.account_seed, user_id.0);
+        let settings = self.get_settings(chat_id);
+        self.complete_verification(bot, &key, verification, &settings)
+            .await
+    }
+
+    pub fn get_pending_verification(
+        &self,
+        chat_id: ChatId,
+        user_id: UserId,
+    ) -> Option<PendingVerification> {
+        let key = format!("{}-{}:{}", chat_id.0, self.account_seed, user_id.0);
+
+        self.verifications_db
+            .get(key.as_bytes())
+            .ok()
+            .flatten()
+            .and_then(|bytes| serde_json::from_slice(&bytes).ok())
+    }
+
+    /// Unmute a member who passed every step, then update the welcome message and stats.
+    async fn complete_verification(
+        &self,
+        bot: &Bot,
+        key: &str,
+        verification: PendingVerification,
+        settings: &WelcomeSettings,
+    ) -> Result<VerificationOutcome> {
+        let chat_id = verification.chat_id;
+        let user_id = verification.user_id;
+
         log::info!(
             "Attempting to unmute user {} in chat {}",
             user_id.to_string(),
@@ -266,15 +549,19 @@ impl WelcomeService {
             escape_for_markdown_v2(&verification.first_name)
         );
 
-        match bot
+        // Keep the group's link buttons under the welcome once verified
+        let mut request = bot
             .edit_message_text(
                 chat_id,
                 teloxide::types::MessageId(verification.verification_message_id),
                 success_text,
             )
-            .parse_mode(teloxide::types::ParseMode::MarkdownV2)
-            .await
-        {
+            .parse_mode(teloxide::types::ParseMode::MarkdownV2);
+        if !settings.url_buttons.is_empty() {
+            request =
+                request.reply_markup(InlineKeyboardMarkup::new(url_button_rows(&settings.url_buttons)));
+        }
+        match request.await {
             Ok(_) => log::info!(
                 "Successfully updated verification message for user {} in chat {}",
                 user_id.to_string(),
@@ -291,6 +578,15 @@ impl WelcomeService {
             }
         }
 
+        delete_message_if_any(bot, chat_id, verification.challenge_message_id).await;
+
+        self.schedule_deletion(
+            chat_id,
+            Some(verification.verification_message_id),
+            settings.bot_message_ttl,
+        );
+        self.schedule_deletion(chat_id, verification.media_message_id, settings.bot_message_ttl);
+
         log::info!(
             "Removing verification record for user {} in chat {}",
             user_id.to_string(),
@@ -333,6 +629,109 @@ impl WelcomeService {
             user_id.to_string(),
             chat_id.to_string()
         );
+        Ok(VerificationOutcome::Verified)
+    }
+
+    async fn show_verification_stage(
+        &self,
+        bot: &Bot,
+        key: &str,
+        mut verification: PendingVerification,
+        stage: VerificationStage,
+        settings: &WelcomeSettings,
+        rules_html: &str,
+    ) -> Result<()> {
+        let chat_id = verification.chat_id;
+        let user_id = verification.user_id;
+        let first_name = teloxide::utils::html::escape(&verification.first_name);
+
+        let (text, challenge) = match stage {
+            VerificationStage::Rules => (
+                format!(
+                    "📜 <b>{}, please read the group rules before posting.</b>\n\n{}\n\nTap <b>I agree</b> to continue.",
+                    first_name, rules_html
+                ),
+                None,
+            ),
+            VerificationStage::Quiz(index) => {
+                let question = settings
+                    .quiz
+                    .get(index)
+                    .ok_or_else(|| anyhow::anyhow!("Quiz question {} not found", index))?;
+                let challenge = quiz_challenge(question, &mut rand::rng());
+                (
+                    format!(
+                        "📝 <b>Entry quiz for {}</b> ({}/{})\n\n{}",
+                        first_name,
+                        index + 1,
+                        settings.quiz.len(),
+                        teloxide::utils::html::escape(&challenge.prompt)
+                    ),
+                    Some(challenge),
+                )
+            }
+            VerificationStage::TokenGate => {
+                let requirement = describe_holdings_requirement(settings)
+                    .ok_or_else(|| anyhow::anyhow!("Holder gate not configured"))?;
+                (
+                    format!(
+                        "🪙 <b>{}, this group is for holders only.</b>\n\n\
+                        Hold at least <b>{}</b> in your Nova wallet to join.\n\n\
+                        1. Open a chat with me and send /loginuser to link your wallet\n\
+                        2. Come back and tap <b>Check my balance</b>",
+                        first_name,
+                        teloxide::utils::html::escape(&requirement)
+                    ),
+                    None,
+                )
+            }
+            VerificationStage::Challenge => {
+                return Err(anyhow::anyhow!("Cannot go back to the challenge step"));
+            }
+        };
+
+        let keyboard = match challenge.as_ref() {
+            Some(challenge) => {
+                verification_keyboard(chat_id, user_id, Some(challenge), &settings.url_buttons)
+            }
+            None if stage == VerificationStage::TokenGate => {
+                let mut rows = vec![vec![InlineKeyboardButton::callback(
+                    "🔍 Check my balance",
+                    format!("welcome_verify:{}:{}", chat_id.0, user_id.0),
+                )]];
+                let me = bot.get_me().await?;
+                if let Ok(url) = reqwest::Url::parse(&format!("https://t.me/{}", me.username())) {
+                    rows.push(vec![InlineKeyboardButton::url("🔗 Link wallet", url)]);
+                }
+                rows.extend(url_button_rows(&settings.url_buttons));
+                InlineKeyboardMarkup::new(rows)
+            }
+            None => {
+                let mut rows = vec![vec![InlineKeyboardButton::callback(
+                    "✅ I agree",
+                    format!("welcome_verify:{}:{}", chat_id.0, user_id.0),
+                )]];
+                rows.extend(url_button_rows(&settings.url_buttons));
+                InlineKeyboardMarkup::new(rows)
+            }
+        };
+
+        delete_message_if_any(bot, chat_id, verification.challenge_message_id).await;
+        verification.challenge_message_id = None;
+        verification.challenge = challenge;
+        verification.stage = stage;
+        self.verifications_db
+            .insert(key.as_bytes(), serde_json::to_vec(&verification)?)?;
+
+        bot.edit_message_text(
+            chat_id,
+            MessageId(verification.verification_message_id),
+            text,
+        )
+        .parse_mode(teloxide::types::ParseMode::Html)
+        .reply_markup(keyboard)
+        .await?;
+
         Ok(())
     }
 
@@ -396,79 +795,793 @@ impl WelcomeService {
 
         // Process each expired verification
         for (key, verification) in expired_verifications {
-            let mut rng = StdRng::from_seed([0; 32]);
             log::info!(
                 "Cleaning up expired verification for user {} in chat {}",
                 verification.user_id.to_string(),
                 verification.chat_id.to_string()
             );
 
-            let mut range: Vec<i64> = (5..60).collect();
-            range.shuffle(&mut rng);
+            let reason = format!(
+                "⏰ Verification expired for {}",
+                escape_for_markdown_v2(&verification.first_name)
+            );
+            self.remove_unverified_member(bot, &key, &verification, &reason)
+                .await;
+        }
 
-            let mut time_option = range.choose(&mut rng);
+        if count > 0 {
+            log::info!("Cleaned up {} expired verifications", count);
+        }
 
-            let time = loop {
-                if let Some(time) = time_option {
-                    break time;
-                }
-                time_option = range.choose(&mut rng);
-            };
+        Ok(())
+    }
 
-            let until_date = chrono::Utc::now() + chrono::Duration::minutes(*time);
+    /// Kick a member who did not pass verification for a short random period,
+    /// replace the welcome message with `reason` (MarkdownV2) and count the failure.
+    async fn remove_unverified_member(
+        &self,
+        bot: &Bot,
+        key: &[u8],
+        verification: &PendingVerification,
+        reason: &str,
+    ) {
+        let mut rng = StdRng::from_seed([0; 32]);
 
-            // Remove user from group
-            let kick_result = bot
-                .kick_chat_member(verification.chat_id, verification.user_id)
-                .until_date(until_date)
-                .revoke_messages(false)
-                .await;
+        let mut range: Vec<i64> = (5..60).collect();
+        range.shuffle(&mut rng);
 
-            if let Err(e) = kick_result {
-                log::error!(
-                    "Failed to kick expired verification user {}: {}",
-                    verification.user_id.to_string(),
-                    e
-                );
+        let mut time_option = range.choose(&mut rng);
+
+        let time = loop {
+            if let Some(time) = time_option {
+                break time;
             }
+            time_option = range.choose(&mut rng);
+        };
+
+        let until_date = chrono::Utc::now() + chrono::Duration::minutes(*time);
+
+        // Remove the record first so the resulting leave event finds nothing pending
+        if let Err(e) = self.verifications_db.remove(key) {
+            log::error!("Failed to remove verification record: {}", e);
+        }
 
-            // Update verification message
-            let expired_text = format!(
-                "⏰ Verification expired for {}\\. User has been removed from the group until {}\\.",
-                escape_for_markdown_v2(&verification.first_name),
-                escape_for_markdown_v2(&until_date.format("%Y-%m-%d %H:%M:%S").to_string())
+        // Remove user from group
+        let kick_result = bot
+            .kick_chat_member(verification.chat_id, verification.user_id)
+            .until_date(until_date)
+            .revoke_messages(false)
+            .await;
+
+        if let Err(e) = kick_result {
+            log::error!(
+                "Failed to kick unverified user {}: {}",
+                verification.user_id.to_string(),
+                e
             );
+        }
+
+        // Update verification message
+        let removed_text = format!(
+            "{}\\. User has been removed from the group until {}\\.",
+            reason,
+            escape_for_markdown_v2(&until_date.format("%Y-%m-%d %H:%M:%S").to_string())
+        );
+
+        if let Err(e) = bot
+            .edit_message_text(
+                verification.chat_id,
+                MessageId(verification.verification_message_id),
+                removed_text,
+            )
+            .parse_mode(teloxide::types::ParseMode::MarkdownV2)
+            .await
+        {
+            log::error!(
+                "Failed to update verification message for user {} in chat {}: {}",
+                verification.user_id.to_string(),
+                verification.chat_id.to_string(),
+                e
+            );
+        }
+
+        delete_message_if_any(bot, verification.chat_id, verification.challenge_message_id).await;
+        delete_message_if_any(bot, verification.chat_id, verification.media_message_id).await;
+
+        self.schedule_deletion(
+            verification.chat_id,
+            Some(verification.verification_message_id),
+            self.get_settings(verification.chat_id).bot_message_ttl,
+        );
+
+        // Update statistics
+        if let Err(e) = self.update_stats(verification.chat_id, false) {
+            log::error!("Failed to update stats for failed verification: {}", e);
+        }
+    }
+
+    /// Count a join or leave once, even when Telegram reports it twice.
+    /// Returns `false` for duplicates.
+    pub fn record_member_event(&self, chat_id: ChatId, user_id: UserId, joined: bool) -> bool {
+        let now = chrono::Utc::now();
+        let event = (chat_id.0, user_id.0, joined);
+        self.recent_member_events
+            .retain(|_, seen_at| now.timestamp() - *seen_at < MEMBER_EVENT_DEDUP_SECONDS);
+        // Claim the event atomically so concurrent duplicates count once
+        match self.recent_member_events.entry(event) {
+            Entry::Occupied(_) => return false,
+            Entry::Vacant(slot) => {
+                slot.insert(now.timestamp());
+            }
+        }
+
+        // Update in place so concurrent joins and leaves don't overwrite each other
+        let key = format!("{}-{}", chat_id.0, self.account_seed);
+        let today = now.date_naive();
+        let result = self.activity_db.update_and_fetch(key.as_bytes(), |bytes| {
+            let mut activity: MemberActivity = bytes
+                .and_then(|bytes| serde_json::from_slice(bytes).ok())
+                .unwrap_or_default();
+            record_member_activity(&mut activity, joined, today);
+            match serde_json::to_vec(&activity) {
+                Ok(updated) => Some(updated),
+                Err(_) => bytes.map(|bytes| bytes.to_vec()),
+            }
+        });
+        if let Err(e) = result {
+            log::error!("Failed to store member activity for chat {}: {}", chat_id.0, e);
+        }
+
+        true
+    }
+
+    pub fn get_member_activity(&self, chat_id: ChatId) -> MemberActivity {
+        let key = format!("{}-{}", chat_id.0, self.account_seed);
+
+        self.activity_db
+            .get(key.as_bytes())
+            .ok()
+            .flatten()
+            .and_then(|bytes| serde_json::from_slice(&bytes).ok())
+            .unwrap_or_default()
+    }
+
+    /// Handle a member leaving or being removed: drop any pending verification
+    /// right away and post the goodbye message if the group enabled it.
+    /// `actor_id` is whoever removed the member (the member themselves when leaving).
+    pub async fn handle_member_left(
+        &self,
+        bot: &Bot,
+        chat_id: ChatId,
+        user: &User,
+        actor_id: Option<UserId>,
+    ) -> Result<()> {
+        if !self.record_member_event(chat_id, user.id, false) {
+            return Ok(());
+        }
+
+        let key = format!("{}-{}:{}", chat_id.0, self.account_seed, user.id.0);
+        self.holders_db.remove(key.as_bytes())?;
+        if let Some(bytes) = self.verifications_db.remove(key.as_bytes())? {
+            log::info!(
+                "User {} left chat {} before verifying, removing pending verification",
+                user.id.0,
+                chat_id.0
+            );
+            if let Ok(verification) = serde_json::from_slice::<PendingVerification>(&bytes) {
+                delete_message_if_any(bot, chat_id, Some(verification.verification_message_id))
+                    .await;
+                delete_message_if_any(bot, chat_id, verification.challenge_message_id).await;
+                delete_message_if_any(bot, chat_id, verification.media_message_id).await;
+            }
+            self.update_stats(chat_id, false)?;
+            // Never-verified members get no goodbye
+            return Ok(());
+        }
+
+        let settings = self.get_settings(chat_id);
+        if !settings.goodbye_enabled {
+            return Ok(());
+        }
+
+        // Members the bot removed itself (e.g. failed verification) get no goodbye
+        let bot_id = bot.get_me().await?.id;
+        if actor_id == Some(bot_id) || user.id == bot_id {
+            return Ok(());
+        }
+
+        let group_name = bot
+            .get_chat(chat_id)
+            .await
+            .ok()
+            .and_then(|chat| chat.title().map(|title| title.to_string()));
+        let ctx = TemplateContext {
+            user_id: Some(user.id.0 as i64),
+            username: user.username.clone(),
+            first_name: Some(user.first_name.clone()),
+            group_name,
+            ..Default::default()
+        };
+
+        let mut request = bot
+            .send_message(chat_id, get_goodbye_message(&settings, &ctx))
+            .parse_mode(teloxide::types::ParseMode::MarkdownV2);
+        if let Some(topic_id) = settings.topic_id {
+            request = request.message_thread_id(ThreadId(MessageId(topic_id)));
+        }
+        let message = request.await?;
+        self.schedule_deletion(chat_id, Some(message.id.0), settings.bot_message_ttl);
+
+        Ok(())
+    }
+
+    async fn track_join_rate(
+        &self,
+        bot: &Bot,
+        chat_id: ChatId,
+        settings: &WelcomeSettings,
+    ) -> Result<()> {
+        if !settings.raid_protection_enabled {
+            return Ok(());
+        }
 
+        let now = chrono::Utc::now().timestamp();
+        let joins = {
+            let mut window = self.join_windows.entry(chat_id.0).or_default();
+            count_recent_joins(&mut window, now, RAID_WINDOW_SECONDS)
+        };
+
+        if joins < settings.raid_join_threshold.max(1) as usize {
+            return Ok(());
+        }
+
+        let until = now + settings.raid_lockdown_minutes as i64 * 60;
+
+        // Ongoing raid: keep the lockdown going
+        if let Some(mut lockdown) = self.get_active_lockdown(chat_id) {
+            lockdown.until = lockdown.until.max(until);
+            lockdown.joins_detected = lockdown.joins_detected.max(joins);
+            return self.save_lockdown(&lockdown);
+        }
+
+        log::warn!(
+            "Raid detected in chat {}: {} joins within {}s, starting lockdown",
+            chat_id.0,
+            joins,
+            RAID_WINDOW_SECONDS
+        );
+
+        let mut lockdown = RaidLockdown {
+            chat_id,
+            started_at: now,
+            until,
+            joins_detected: joins,
+            restricted_users: Vec::new(),
+            alert_message_id: None,
+        };
+        self.save_lockdown(&lockdown)?;
+
+        let until_text = chrono::DateTime::from_timestamp(until, 0)
+            .unwrap_or_default()
+            .format("%H:%M UTC");
+        let alert = format!(
+            "🚨 <b>Raid detected</b>\n\n\
+            {} accounts joined within a minute. The group is in lockdown until {}.\n\n\
+            • New members are muted on arrival\n\
+            • Verification is stricter: a real challenge, one attempt and a shorter timeout\n\n\
+            The lockdown lifts automatically after the cool-off period.",
+            joins, until_text
+        );
+
+        let keyboard = InlineKeyboardMarkup::new(vec![vec![InlineKeyboardButton::callback(
+            "🔓 Lift Lockdown",
+            "welcome_lockdown_lift",
+        )]]);
+        let mut request = bot
+            .send_message(chat_id, format!("{}\nAdmins can also lift it now.", alert))
+            .parse_mode(teloxide::types::ParseMode::Html)
+            .reply_markup(keyboard);
+        if let Some(topic_id) = settings.topic_id {
+            request = request.message_thread_id(ThreadId(MessageId(topic_id)));
+        }
+        match request.await {
+            Ok(message) => {
+                lockdown.alert_message_id = Some(message.id.0);
+                self.save_lockdown(&lockdown)?;
+            }
+            Err(e) => log::error!("Failed to send raid alert in chat {}: {}", chat_id.0, e),
+        }
+
+        // Also alert admins directly; this only reaches admins who started the bot
+        let group_name = bot
+            .get_chat(chat_id)
+            .await
+            .ok()
+            .and_then(|chat| chat.title().map(|title| title.to_string()))
+            .unwrap_or_else(|| "your group".to_string());
+        let dm = format!(
+            "{}\n\n<b>Group:</b> {}\nUse /groupsettings → Welcome Settings → Raid Protection in the group to lift it early.",
+            alert,
+            teloxide::utils::html::escape(&group_name)
+        );
+        for admin in bot.get_chat_administrators(chat_id).await? {
+            if admin.user.is_bot {
+                continue;
+            }
             if let Err(e) = bot
-                .edit_message_text(
-                    verification.chat_id,
-                    teloxide::types::MessageId(verification.verification_message_id),
-                    expired_text,
-                )
-                .parse_mode(teloxide::types::ParseMode::MarkdownV2)
+                .send_message(admin.user.id, dm.clone())
+                .parse_mode(teloxide::types::ParseMode::Html)
+                .await
+            {
+                log::info!("Could not DM raid alert to admin {}: {}", admin.user.id.0, e);
+            }
+        }
+
+        Ok(())
+    }
+
+    async fn restrict_during_lockdown(
+        &self,
+        bot: &Bot,
+        chat_id: ChatId,
+        user_id: UserId,
+    ) -> Result<()> {
+        bot.restrict_chat_member(chat_id, user_id, ChatPermissions::empty())
+            .await?;
+
+        let Some(mut lockdown) = self.get_active_lockdown(chat_id) else {
+            return Ok(());
+        };
+        if !lockdown.restricted_users.contains(&user_id) {
+            lockdown.restricted_users.push(user_id);
+            self.save_lockdown(&lockdown)?;
+        }
+
+        Ok(())
+    }
+
+    fn save_lockdown(&self, lockdown: &RaidLockdown) -> Result<()> {
+        let key = format!("{}-{}", lockdown.chat_id.0, self.account_seed);
+        self.lockdown_db
+            .insert(key.as_bytes(), serde_json::to_vec(lockdown)?)?;
+        Ok(())
+    }
+
+    /// The group's lockdown, if one is active and has not run out yet.
+    pub fn get_active_lockdown(&self, chat_id: ChatId) -> Option<RaidLockdown> {
+        let key = format!("{}-{}", chat_id.0, self.account_seed);
+
+        self.lockdown_db
+            .get(key.as_bytes())
+            .ok()
+            .flatten()
+            .and_then(|bytes| serde_json::from_slice::<RaidLockdown>(&bytes).ok())
+            .filter(|lockdown| lockdown.until > chrono::Utc::now().timestamp())
+    }
+
+    /// End the group's lockdown, unmuting members it muted. Returns `false`
+    /// when there was no lockdown.
+    pub async fn lift_lockdown(&self, bot: &Bot, chat_id: ChatId) -> Result<bool> {
+        let key = format!("{}-{}", chat_id.0, self.account_seed);
+        let Some(bytes) = self.lockdown_db.remove(key.as_bytes())? else {
+            return Ok(false);
+        };
+        let lockdown: RaidLockdown = serde_json::from_slice(&bytes)?;
+
+        for user_id in &lockdown.restricted_users {
+            if let Err(e) = bot
+                .restrict_chat_member(chat_id, *user_id, ChatPermissions::all())
                 .await
             {
                 log::error!(
-                    "Failed to update expired verification message for user {} in chat {}: {}",
-                    verification.user_id.to_string(),
-                    verification.chat_id.to_string(),
+                    "Failed to unmute user {} after lockdown in chat {}: {}",
+                    user_id.0,
+                    chat_id.0,
                     e
                 );
             }
+        }
+
+        self.join_windows.remove(&chat_id.0);
 
-            // Remove verification record
-            if let Err(e) = self.verifications_db.remove(&key) {
-                log::error!("Failed to remove expired verification record: {}", e);
+        if let Some(message_id) = lockdown.alert_message_id {
+            let result = bot
+                .edit_message_text(
+                    chat_id,
+                    MessageId(message_id),
+                    "✅ <b>Lockdown lifted</b>\n\nNew members are verified normally again.",
+                )
+                .parse_mode(teloxide::types::ParseMode::Html)
+                .await;
+            if let Err(e) = result {
+                log::warn!("Failed to update raid alert in chat {}: {}", chat_id.0, e);
             }
+        }
 
-            // Update statistics
-            if let Err(e) = self.update_stats(verification.chat_id, false) {
-                log::error!("Failed to update stats for expired verification: {}", e);
+        log::info!("Lockdown lifted in chat {}", chat_id.0);
+        Ok(true)
+    }
+
+    pub async fn lift_expired_lockdowns(&self, bot: &Bot) -> Result<()> {
+        let now = chrono::Utc::now().timestamp();
+        let expired: Vec<ChatId> = self
+            .lockdown_db
+            .iter()
+            .filter_map(|result| result.ok())
+            .filter_map(|(_, value)| serde_json::from_slice::<RaidLockdown>(&value).ok())
+            .filter(|lockdown| lockdown.until <= now)
+            .map(|lockdown| lockdown.chat_id)
+            .collect();
+
+        for chat_id in expired {
+            self.lift_lockdown(bot, chat_id).await?;
+        }
+
+        Ok(())
+    }
+
+    fn spammer_key(&self, user_id: UserId) -> String {
+        format!("{}-{}", user_id.0, self.account_seed)
+    }
+
+    pub fn get_known_spammer(&self, user_id: UserId) -> Option<KnownSpammer> {
+        self.spammers_db
+            .get(self.spammer_key(user_id).as_bytes())
+            .ok()
+            .flatten()
+            .and_then(|bytes| serde_json::from_slice(&bytes).ok())
+    }
+
+    /// Add a user to the bot-wide known-spammer list, or record another source
+    /// for an existing entry. Returns whether the user was newly added.
+    pub fn add_known_spammer(&self, user_id: UserId, source: SpammerSource) -> Result<bool> {
+        let now = chrono::Utc::now().timestamp();
+        let previous = self
+            .spammers_db
+            .fetch_and_update(self.spammer_key(user_id).as_bytes(), |bytes| {
+                let mut spammer = bytes
+                    .and_then(|bytes| serde_json::from_slice::<KnownSpammer>(bytes).ok())
+                    .unwrap_or(KnownSpammer {
+                        user_id,
+                        sources: Vec::new(),
+                        added_at: now,
+                    });
+                if !spammer.sources.contains(&source) {
+                    spammer.sources.push(source);
+                }
+                match serde_json::to_vec(&spammer) {
+                    Ok(updated) => Some(updated),
+                    Err(_) => bytes.map(|bytes| bytes.to_vec()),
+                }
+            })?;
+        Ok(previous.is_none())
+    }
+
+    pub fn known_spammer_count(&self) -> usize {
+        let suffix = format!("-{}", self.account_seed);
+        self.spammers_db
+            .iter()
+            .keys()
+            .filter_map(|key| key.ok())
+            .filter(|key| key.ends_with(suffix.as_bytes()))
+            .count()
+    }
+
+    /// Load a spammer list file into the known-spammer list. Returns how many
+    /// users were new and how many entries could not be parsed.
+    pub fn import_known_spammers(&self, path: &Path) -> Result<(usize, usize)> {
+        let text = std::fs::read_to_string(path)?;
+        let (ids, skipped) = parse_spammer_ids(&text);
+
+        let mut added = 0;
+        for user_id in ids {
+            if self.add_known_spammer(user_id, SpammerSource::Imported)? {
+                added += 1;
             }
         }
 
-        if count > 0 {
-            log::info!("Cleaned up {} expired verifications", count);
+        Ok((added, skipped))
+    }
+
+    /// Ban or hold a joining user found on the known-spammer list. Returns
+    /// true when the user was handled here and the welcome flow is skipped.
+    async fn screen_known_spammer(
+        &self,
+        bot: &Bot,
+        chat_id: ChatId,
+        user: &User,
+        settings: &WelcomeSettings,
+    ) -> Result<bool> {
+        if settings.spammer_action == SpammerAction::Off {
+            return Ok(false);
+        }
+        let Some(source) = self
+            .get_known_spammer(user.id)
+            .and_then(|spammer| trusted_spammer_source(&spammer, chat_id, settings))
+        else {
+            return Ok(false);
+        };
+
+        log::warn!(
+            "Known spammer {} joined chat {} ({:?})",
+            user.id.0,
+            chat_id.0,
+            settings.spammer_action
+        );
+
+        let name = teloxide::utils::html::escape(&user.full_name());
+        let origin = match source {
+            SpammerSource::Imported => "imported list",
+            SpammerSource::Banned { chat_id: banned_in } if banned_in == chat_id => {
+                "banned here before"
+            }
+            SpammerSource::Banned { .. } => "banned in another group",
+        };
+
+        let (text, keyboard) = if settings.spammer_action == SpammerAction::Ban {
+            bot.ban_chat_member(chat_id, user.id).await?;
+            (
+                format!(
+                    "🚫 <b>Known spammer banned</b>\n\n{} (<code>{}</code>) is on the known-spammer list ({}) and was banned on arrival.",
+                    name, user.id.0, origin
+                ),
+                None,
+            )
+        } else {
+            bot.restrict_chat_member(chat_id, user.id, ChatPermissions::empty())
+                .await?;
+            (
+                format!(
+                    "🚩 <b>Known spammer joined</b>\n\n{} (<code>{}</code>) is on the known-spammer list ({}) and has been muted.\n\nAdmins, allow or ban them:",
+                    name, user.id.0, origin
+                ),
+                Some(InlineKeyboardMarkup::new(vec![vec![
+                    InlineKeyboardButton::callback(
+                        "✅ Allow",
+                        format!("welcome_spammer_allow:{}", user.id.0),
+                    ),
+                    InlineKeyboardButton::callback(
+                        "🔨 Ban",
+                        format!("welcome_spammer_ban:{}", user.id.0),
+                    ),
+                ]])),
+            )
+        };
+
+        let mut request = bot
+            .send_message(chat_id, text)
+            .parse_mode(teloxide::types::ParseMode::Html);
+        if let Some(topic_id) = settings.topic_id {
+            request = request.message_thread_id(ThreadId(MessageId(topic_id)));
+        }
+        let has_keyboard = keyboard.is_some();
+        if let Some(keyboard) = keyboard {
+            request = request.reply_markup(keyboard);
+        }
+        match request.await {
+            // Review requests stay until an admin acts on them
+            Ok(message) if !has_keyboard => {
+                self.schedule_deletion(chat_id, Some(message.id.0), settings.bot_message_ttl)
+            }
+            Ok(_) => {}
+            Err(e) => log::error!(
+                "Failed to post known spammer notice in chat {}: {}",
+                chat_id.0,
+                e
+            ),
+        }
+
+        Ok(true)
+    }
+
+    /// Apply an admin's decision on a held known spammer and update the review message.
+    pub async fn resolve_spammer_review(
+        &self,
+        bot: &Bot,
+        chat_id: ChatId,
+        user_id: UserId,
+        ban: bool,
+        review_message_id: MessageId,
+    ) -> Result<()> {
+        let text = if ban {
+            bot.ban_chat_member(chat_id, user_id).await?;
+            self.add_known_spammer(user_id, SpammerSource::Banned { chat_id })?;
+            format!("🔨 <b>Known spammer banned</b>\n\nUser <code>{}</code> was banned.", user_id.0)
+        } else {
+            // Allowed members go through the usual welcome flow
+            let member = bot.get_chat_member(chat_id, user_id).await?;
+            if self.is_enabled(chat_id) || self.get_active_lockdown(chat_id).is_some() {
+                self.handle_new_member(
+                    bot,
+                    chat_id,
+                    user_id,
+                    member.user.username.clone(),
+                    member.user.first_name.clone(),
+                )
+                .await?;
+            } else {
+                bot.restrict_chat_member(chat_id, user_id, ChatPermissions::all())
+                    .await?;
+            }
+            format!(
+                "✅ <b>Allowed</b>\n\n{} may stay in this group.",
+                teloxide::utils::html::escape(&member.user.full_name())
+            )
+        };
+
+        if let Err(e) = bot
+            .edit_message_text(chat_id, review_message_id, text)
+            .parse_mode(teloxide::types::ParseMode::Html)
+            .await
+        {
+            log::warn!("Failed to update spammer review in chat {}: {}", chat_id.0, e);
+        }
+        let ttl = self.get_settings(chat_id).bot_message_ttl;
+        self.schedule_deletion(chat_id, Some(review_message_id.0), ttl);
+
+        Ok(())
+    }
+
+    fn save_token_holder(&self, holder: &TokenHolder) -> Result<()> {
+        let key = format!(
+            "{}-{}:{}",
+            holder.chat_id.0, self.account_seed, holder.user_id.0
+        );
+        self.holders_db
+            .insert(key.as_bytes(), serde_json::to_vec(holder)?)?;
+        Ok(())
+    }
+
+    /// Re-check members admitted through the holder gate and remove those whose
+    /// holdings dropped below the minimum or whose wallet is no longer linked.
+    pub async fn recheck_token_holders(&self, bot: &Bot, auth: &Auth, aptos: &Aptos) -> Result<()> {
+        let holders: Vec<(sled::IVec, TokenHolder)> = self
+            .holders_db
+            .iter()
+            .filter_map(|result| result.ok())
+            .filter_map(|(key, value)| {
+                serde_json::from_slice::<TokenHolder>(&value)
+                    .ok()
+                    .map(|holder| (key, holder))
+            })
+            .collect();
+
+        for (key, mut holder) in holders {
+            let settings = self.get_settings(holder.chat_id);
+            let requirement = match describe_holdings_requirement(&settings) {
+                Some(requirement) if settings.enabled => requirement,
+                // Gate switched off: nothing left to enforce for this member
+                _ => {
+                    self.holders_db.remove(key)?;
+                    continue;
+                }
+            };
+
+            let status = check_holdings(
+                auth,
+                aptos,
+                &settings,
+                holder.user_id,
+                Some(&holder.username),
+            )
+            .await;
+            match status {
+                TokenGateStatus::Qualified => {
+                    holder.last_checked = chrono::Utc::now().timestamp();
+                    self.holders_db.insert(key, serde_json::to_vec(&holder)?)?;
+                }
+                // Try again on the next run rather than removing on a node hiccup
+                TokenGateStatus::Unavailable => {}
+                TokenGateStatus::NotLinked | TokenGateStatus::Insufficient { .. } => {
+                    self.holders_db.remove(key)?;
+                    self.remove_former_holder(bot, &holder, &requirement).await;
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    async fn remove_former_holder(&self, bot: &Bot, holder: &TokenHolder, requirement: &str) {
+        log::info!(
+            "Removing user {} from chat {}: no longer holds {}",
+            holder.user_id.0,
+            holder.chat_id.0,
+            requirement
+        );
+
+        // Ban and unban right away so they can rejoin once they top up
+        if let Err(e) = bot.ban_chat_member(holder.chat_id, holder.user_id).await {
+            log::error!(
+                "Failed to remove former holder {} from chat {}: {}",
+                holder.user_id.0,
+                holder.chat_id.0,
+                e
+            );
+            return;
+        }
+        if let Err(e) = bot.unban_chat_member(holder.chat_id, holder.user_id).await {
+            log::warn!("Failed to unban former holder {}: {}", holder.user_id.0, e);
+        }
+
+        let group_name = bot
+            .get_chat(holder.chat_id)
+            .await
+            .ok()
+            .and_then(|chat| chat.title().map(|title| title.to_string()))
+            .unwrap_or_else(|| "the group".to_string());
+        let notice = format!(
+            "🪙 You were removed from <b>{}</b> because your linked wallet no longer holds at least <b>{}</b>.\n\n\
+            Top up and you're welcome to join again.",
+            teloxide::utils::html::escape(&group_name),
+            teloxide::utils::html::escape(requirement)
+        );
+        if let Err(e) = bot
+            .send_message(holder.user_id, notice)
+            .parse_mode(teloxide::types::ParseMode::Html)
+            .await
+        {
+            log::info!("Could not DM former holder {}: {}", holder.user_id.0, e);
+        }
+    }
+
+    /// Queue a message for deletion after `ttl_seconds`. Does nothing when
+    /// either is `None`; failures are only logged.
+    pub fn schedule_deletion(
+        &self,
+        chat_id: ChatId,
+        message_id: Option<i32>,
+        ttl_seconds: Option<u64>,
+    ) {
+        let (Some(message_id), Some(ttl_seconds)) = (message_id, ttl_seconds) else {
+            return;
+        };
+
+        let deletion = ScheduledDeletion {
+            chat_id,
+            message_id,
+            delete_at: chrono::Utc::now().timestamp() + ttl_seconds as i64,
+        };
+        let key = format!("{}-{}:{}", chat_id.0, self.account_seed, message_id);
+        match serde_json::to_vec(&deletion) {
+            Ok(bytes) => {
+                if let Err(e) = self.deletions_db.insert(key.as_bytes(), bytes) {
+                    log::error!(
+                        "Failed to schedule deletion of message {} in chat {}: {}",
+                        message_id,
+                        chat_id.0,
+                        e
+                    );
+                }
+            }
+            Err(e) => log::error!("Failed to serialize scheduled deletion: {}", e),
+        }
+    }
+
+    /// Queue a join/leave service message for deletion per the group's setting.
+    pub fn schedule_service_message_deletion(&self, chat_id: ChatId, message_id: MessageId) {
+        let ttl_seconds = self.get_settings(chat_id).service_message_ttl;
+        self.schedule_deletion(chat_id, Some(message_id.0), ttl_seconds);
+    }
+
+    pub async fn run_scheduled_deletions(&self, bot: &Bot) -> Result<()> {
+        let now = chrono::Utc::now().timestamp();
+        let due: Vec<(sled::IVec, ScheduledDeletion)> = self
+            .deletions_db
+            .iter()
+            .filter_map(|result| result.ok())
+            .filter_map(|(key, value)| {
+                serde_json::from_slice::<ScheduledDeletion>(&value)
+                    .ok()
+                    .map(|deletion| (key, deletion))
+            })
+            .filter(|(_, deletion)| deletion.delete_at <= now)
+            .collect();
+
+        for (key, deletion) in due {
+            // Already deleted or missing rights: either way there is nothing to retry
+            delete_message_if_any(bot, deletion.chat_id, Some(deletion.message_id)).await;
+            self.deletions_db.remove(key)?;
         }
 
         Ok(())
@@ -480,7 +1593,10 @@ impl WelcomeService {
         Ok(())
     }
 
-    pub async fn store_input_state(&self, chat_id: ChatId) -> Result<()> {
+    /// Remember that the next admin message in `chat_id` is input of `input_type`
+    /// (`custom_message_input`, `quiz_question_input`, `media_input`, `url_buttons_input`
+    /// or `goodbye_message_input`).
+    pub async fn store_input_state(&self, chat_id: ChatId, input_type: &str) -> Result<()> {
         let key = format!(
             "welcome_custom_msg_input:{}-{}",
             chat_id.to_string(),
@@ -489,7 +1605,7 @@ impl WelcomeService {
         let input_state = serde_json::json!({
             "chat_id": chat_id.0,
             "timestamp": chrono::Utc::now().timestamp(),
-            "type": "custom_message_input"
+            "type": input_type
         });
         let bytes = serde_json::to_vec(&input_state)?;
         self.settings_db.insert(key.as_bytes(), bytes)?;
@@ -548,5 +1664,82 @@ impl WelcomeService {
     }
 }
 
+/// Delete a helper message (captcha image, welcome media) if one was sent.
+async fn delete_message_if_any(bot: &Bot, chat_id: ChatId, message_id: Option<i32>) {
+    let Some(message_id) = message_id else {
+        return;
+    };
+    if let Err(e) = bot.delete_message(chat_id, MessageId(message_id)).await {
+        log::warn!("Failed to delete message {} in chat {}: {}", message_id, chat_id.0, e);
+    }
+}
 
+/// Single "Prove You're Human" button, or the challenge options two per row,
+/// followed by the group's link buttons.
+fn verification_keyboard(
+    chat_id: ChatId,
+    user_id: UserId,
+    challenge: Option<&CaptchaChallenge>,
+    links: &[WelcomeUrlButton],
+) -> InlineKeyboardMarkup {
+    let mut rows = match challenge {
+        None => vec![vec![InlineKeyboardButton::callback(
+            "✅ Prove You're Human",
+            format!("welcome_verify:{}:{}", chat_id.0, user_id.0),
+        )]],
+        Some(challenge) => {
+            let buttons: Vec<InlineKeyboardButton> = challenge
+                .options
+                .iter()
+                .enumerate()
+                .map(|(index, option)| {
+                    InlineKeyboardButton::callback(
+                        option.clone(),
+                        format!("welcome_verify:{}:{}:{}", chat_id.0, user_id.0, index),
+                    )
+                })
+                .collect();
+            buttons.chunks(2).map(|row| row.to_vec()).collect()
+        }
+    };
 
+    rows.extend(url_button_rows(links));
+    InlineKeyboardMarkup::new(rows)
+}
+
+fn url_button_rows(links: &[WelcomeUrlButton]) -> Vec<Vec<InlineKeyboardButton>> {
+    let buttons: Vec<InlineKeyboardButton> = links
+        .iter()
+        .filter_map(|link| {
+            let url = reqwest::Url::parse(&link.url).ok()?;
+            Some(InlineKeyboardButton::url(link.text.clone(), url))
+        })
+        .collect();
+    buttons.chunks(2).map(|row| row.to_vec()).collect()
+}
+
+async fn send_welcome_media(
+    bot: &Bot,
+    chat_id: ChatId,
+    thread_id: Option<ThreadId>,
+    media: &WelcomeMedia,
+) -> Result<i32> {
+    let file = InputFile::file_id(FileId(media.file_id.clone()));
+    let message = match media.kind {
+        WelcomeMediaKind::Photo => {
+            let mut request = bot.send_photo(chat_id, file);
+            if let Some(thread_id) = thread_id {
+                request = request.message_thread_id(thread_id);
+            }
+            request.await?
+        }
+        WelcomeMediaKind::Animation => {
+            let mut request = bot.send_animation(chat_id, file);
+            if let Some(thread_id) = thread_id {
+                request = request.message_thread_id(thread_id);
+            }
+            request.await?
+        }
+    };
+    Ok(message.id.0)
+}
@@ -33,6 +33,14 @@ pub enum Command {
     Report,
     #[command(description = "Show core and custom rules for this group.")]
     Rules,
+    #[command(description = "Check your NFT holdings and claim group roles.")]
+    Roles,
+    #[command(description = "Cancel, extend or edit the group's proposals (admins only).")]
+    ManageProposals,
+    #[command(description = "Browse active and past proposals, with CSV export.")]
+    Proposals,
+    #[command(description = "Create a proposal step by step (admins only).")]
+    NewProposal,
     #[command(description = "Get your wallet address.")]
     WalletAddress,
     #[command(description = "Get your balance of a token.")]
@@ -12,6 +12,8 @@ pub enum Endpoints {
     GroupPurchase,
     CreateProposal,
     MigrateGroupId,
+    VoteProposal,
+    ExtendProposal,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -138,6 +140,24 @@ pub struct CreateProposalRequest {
     pub thread_id: Option<i32>,
 }
 
+/// New end date for one of the calling group's proposals.
+#[derive(Deserialize, Serialize, Debug, ToSchema)]
+pub struct ExtendProposalRequest {
+    pub proposal_id: String,
+    pub end_date: u64,
+    pub version: CoinVersion,
+}
+
+/// Vote cast with the caller's resource account on a group proposal.
+#[derive(Deserialize, Serialize, Debug, ToSchema)]
+pub struct VoteProposalRequest {
+    pub group_id: String,
+    pub proposal_id: String,
+    pub choice_id: u64,
+    pub version: CoinVersion,
+    pub currency: String,
+}
+
 #[derive(Deserialize, Serialize, Debug)]
 pub struct GasPrice {
     pub deprioritized_gas_estimate: u64,
@@ -159,6 +179,8 @@ impl fmt::Display for Endpoints {
             &Endpoints::GroupPurchase => write!(f, "{}/group-purchase", backend_url),
             &Endpoints::CreateProposal => write!(f, "{}/proposal", backend_url),
             &Endpoints::MigrateGroupId => write!(f, "{}/migrate-group-id", backend_url),
+            &Endpoints::VoteProposal => write!(f, "{}/vote-proposal", backend_url),
+            &Endpoints::ExtendProposal => write!(f, "{}/extend-proposal", backend_url),
         }
     }
 }
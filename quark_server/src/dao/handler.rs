@@ -8,7 +8,8 @@ use aptos_rust_sdk_types::api_types::{
 };
 use axum::{Extension, Json, extract::State, http::StatusCode};
 use quark_core::helpers::dto::{
-    CoinVersion, CreateProposalRequest, GroupPayload, TransactionResponse,
+    CoinVersion, CreateProposalRequest, ExtendProposalRequest, GroupPayload, TransactionResponse,
+    UserPayload, VoteProposalRequest,
 };
 
 use crate::{
@@ -155,3 +156,191 @@ pub async fn create_proposal(
 
     Ok(Json(result))
 }
+
+#[utoipa::path(
+    post,
+    path = "/vote-proposal",
+    request_body = [VoteProposalRequest],
+    description = "Vote on a group proposal with the user's resource account",
+    responses(
+        (status = 200, description = "Success"),
+        (status = 400, description = "Bad Request"),
+    )
+)]
+pub async fn vote_proposal(
+    State(server_state): State<Arc<ServerState>>,
+    Extension(user): Extension<UserPayload>,
+    Json(request): Json<VoteProposalRequest>,
+) -> Result<Json<TransactionResponse>, ErrorServer> {
+    let (admin, signer) = get_admin().map_err(|e| ErrorServer {
+        status: StatusCode::INTERNAL_SERVER_ERROR.into(),
+        message: e.to_string(),
+    })?;
+
+    let (reviewer, reviewer_signer) = get_reviewer_priv_acc().map_err(|e| ErrorServer {
+        status: StatusCode::INTERNAL_SERVER_ERROR.into(),
+        message: e.to_string(),
+    })?;
+
+    let node = server_state.node();
+    let chain_id = server_state.chain_id();
+    let contract_address = server_state.contract_address();
+
+    let state = node.get_state().await.map_err(|e| ErrorServer {
+        status: StatusCode::INTERNAL_SERVER_ERROR.into(),
+        message: e.to_string(),
+    })?;
+
+    let account_address =
+        AccountAddress::from_str(&user.account_address).map_err(|e| ErrorServer {
+            status: StatusCode::BAD_REQUEST.into(),
+            message: e.to_string(),
+        })?;
+
+    let group_id = request.group_id;
+    let proposal_id = request.proposal_id;
+    let choice_id = request.choice_id;
+    let currency = request.currency;
+
+    let payload = match request.version {
+        CoinVersion::V1 => {
+            let coin_type = TypeTag::from_str(&currency).map_err(|e| ErrorServer {
+                status: StatusCode::BAD_REQUEST.into(),
+                message: e.to_string(),
+            })?;
+
+            TransactionPayload::EntryFunction(EntryFunction::new(
+                ModuleId::new(contract_address, "group".to_string()),
+                "vote_group_dao_for_user_v1".to_string(),
+                vec![coin_type],
+                vec![
+                    account_address.to_vec(),
+                    bcs::to_bytes(&group_id).map_err(|e| ErrorServer {
+                        status: StatusCode::INTERNAL_SERVER_ERROR.into(),
+                        message: e.to_string(),
+                    })?,
+                    bcs::to_bytes(&proposal_id).map_err(|e| ErrorServer {
+                        status: StatusCode::INTERNAL_SERVER_ERROR.into(),
+                        message: e.to_string(),
+                    })?,
+                    choice_id.to_le_bytes().to_vec(),
+                ],
+            ))
+        }
+        CoinVersion::V2 => TransactionPayload::EntryFunction(EntryFunction::new(
+            ModuleId::new(contract_address, "group".to_string()),
+            "vote_group_dao_for_user_v2".to_string(),
+            vec![],
+            vec![
+                account_address.to_vec(),
+                bcs::to_bytes(&group_id).map_err(|e| ErrorServer {
+                    status: StatusCode::INTERNAL_SERVER_ERROR.into(),
+                    message: e.to_string(),
+                })?,
+                bcs::to_bytes(&proposal_id).map_err(|e| ErrorServer {
+                    status: StatusCode::INTERNAL_SERVER_ERROR.into(),
+                    message: e.to_string(),
+                })?,
+                choice_id.to_le_bytes().to_vec(),
+                AccountAddress::from_str(&currency)
+                    .map_err(|e| ErrorServer {
+                        status: StatusCode::BAD_REQUEST.into(),
+                        message: e.to_string(),
+                    })?
+                    .to_vec(),
+            ],
+        )),
+    };
+
+    let result = execute_transaction(
+        node,
+        admin,
+        reviewer,
+        &signer,
+        &reviewer_signer,
+        payload,
+        &state,
+        chain_id,
+    )
+    .await?;
+
+    Ok(Json(result))
+}
+
+#[utoipa::path(
+    post,
+    path = "/extend-proposal",
+    request_body = [ExtendProposalRequest],
+    description = "Move the end date of a group proposal",
+    responses(
+        (status = 200, description = "Success"),
+        (status = 400, description = "Bad Request"),
+    )
+)]
+pub async fn extend_proposal(
+    State(server_state): State<Arc<ServerState>>,
+    Extension(group): Extension<GroupPayload>,
+    Json(request): Json<ExtendProposalRequest>,
+) -> Result<Json<TransactionResponse>, ErrorServer> {
+    let (admin, signer) = get_admin().map_err(|e| ErrorServer {
+        status: StatusCode::INTERNAL_SERVER_ERROR.into(),
+        message: e.to_string(),
+    })?;
+
+    let (reviewer, reviewer_signer) = get_reviewer_priv_acc().map_err(|e| ErrorServer {
+        status: StatusCode::INTERNAL_SERVER_ERROR.into(),
+        message: e.to_string(),
+    })?;
+
+    let node = server_state.node();
+    let chain_id = server_state.chain_id();
+    let contract_address = server_state.contract_address();
+
+    let state = node.get_state().await.map_err(|e| ErrorServer {
+        status: StatusCode::INTERNAL_SERVER_ERROR.into(),
+        message: e.to_string(),
+    })?;
+
+    if request.end_date < Utc::now().timestamp() as u64 {
+        return Err(ErrorServer {
+            status: StatusCode::BAD_REQUEST.into(),
+            message: "End date must be in the future".to_string(),
+        });
+    }
+
+    let function = match request.version {
+        CoinVersion::V1 => "update_group_dao_end_v1",
+        CoinVersion::V2 => "update_group_dao_end_v2",
+    };
+
+    let payload = TransactionPayload::EntryFunction(EntryFunction::new(
+        ModuleId::new(contract_address, "group".to_string()),
+        function.to_string(),
+        vec![],
+        vec![
+            bcs::to_bytes(&group.group_id).map_err(|e| ErrorServer {
+                status: StatusCode::INTERNAL_SERVER_ERROR.into(),
+                message: e.to_string(),
+            })?,
+            bcs::to_bytes(&request.proposal_id).map_err(|e| ErrorServer {
+                status: StatusCode::INTERNAL_SERVER_ERROR.into(),
+                message: e.to_string(),
+            })?,
+            request.end_date.to_le_bytes().to_vec(),
+        ],
+    ));
+
+    let result = execute_transaction(
+        node,
+        admin,
+        reviewer,
+        &signer,
+        &reviewer_signer,
+        payload,
+        &state,
+        chain_id,
+    )
+    .await?;
+
+    Ok(Json(result))
+}
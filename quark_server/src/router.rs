@@ -12,7 +12,7 @@ use utoipa_redoc::{Redoc, Servable};
 
 use crate::{
     create_group::handler::create_group,
-    dao::handler::create_proposal,
+    dao::handler::{create_proposal, extend_proposal, vote_proposal},
     docs::{dto::ApiDoc, handler::api_docs},
     info::handler::info,
     middlewares::handler::{auth, auth_group},
@@ -118,12 +118,14 @@ pub async fn router() -> Router {
     let auth_router = Router::new()
         .route("/pay-users", post(pay_users))
         .route("/purchase", post(purchase))
+        .route("/vote-proposal", post(vote_proposal))
         .route_layer(middleware::from_fn(auth));
 
     let auth_group_router = Router::new()
         .route("/pay-members", post(pay_members))
         .route("/group-purchase", post(group_purchase))
         .route("/proposal", post(create_proposal))
+        .route("/extend-proposal", post(extend_proposal))
         .route("/migrate-group-id", post(migrate_group_id))
         .route_layer(middleware::from_fn(auth_group));
 